@@ -0,0 +1,68 @@
+//! 剪贴板模糊搜索的基准：在 1000 条历史上对比三元组相似度与 LCS 相似度的排序耗时
+//!
+//! 运行：cargo bench --bench fuzzy_search
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fuyun_tools_lib::utils::utils_helpers::{calculate_text_similarity, rank_fuzzy_matches};
+
+/// 历史记录条数，与常见的历史上限相当
+const HISTORY_SIZE: usize = 1000;
+/// 模糊搜索默认的相似度下限
+const MIN_SCORE: f64 = 0.6;
+
+/// 确定性的伪随机历史（xorshift）：由常见词拼成的中英文短句，长度不一
+fn sample_history(seed: u64, count: usize) -> Vec<String> {
+    const WORDS: &[&str] = &[
+        "clipboard", "history", "search", "translate", "window", "shortcut", "settings", "paste",
+        "selection", "result", "剪贴板", "历史", "搜索", "翻译", "快捷键", "设置", "粘贴", "划词",
+    ];
+    let mut state = seed.max(1);
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as usize
+    };
+    (0..count)
+        .map(|_| {
+            let words = 3 + next() % 30;
+            (0..words)
+                .map(|_| WORDS[next() % WORDS.len()])
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect()
+}
+
+/// 与 rank_fuzzy_matches 输出一致的 LCS 版本，作为对照
+fn rank_lcs_matches(items: &[String], query: &str, min_score: f64) -> Vec<(usize, f64)> {
+    let mut matches: Vec<(usize, f64)> = items
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            let score = calculate_text_similarity(query, item);
+            (score > 0.0 && score >= min_score).then_some((index, score))
+        })
+        .collect();
+    matches.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    matches
+}
+
+fn bench_fuzzy_search(c: &mut Criterion) {
+    let history = sample_history(42, HISTORY_SIZE);
+    let mut group = c.benchmark_group("fuzzy_search_1000");
+    // 短查询带错字；长查询为某条历史的前半段
+    let long_query: String = history[HISTORY_SIZE / 2].chars().take(60).collect();
+    for (name, query) in [("short", "clipbaord histroy".to_string()), ("long", long_query)] {
+        group.bench_with_input(BenchmarkId::new("trigram", name), &query, |b, query| {
+            b.iter(|| rank_fuzzy_matches(&history, black_box(query), MIN_SCORE))
+        });
+        group.bench_with_input(BenchmarkId::new("lcs", name), &query, |b, query| {
+            b.iter(|| rank_lcs_matches(&history, black_box(query), MIN_SCORE))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_fuzzy_search);
+criterion_main!(benches);
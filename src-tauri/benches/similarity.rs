@@ -0,0 +1,94 @@
+//! 剪贴板去重相似度计算的基准：对比完整 LCS 与带提前退出的相似度计算，
+//! 以及在历史记录中查找替换候选的整体耗时
+//!
+//! 运行：cargo bench --bench similarity
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use fuyun_tools_lib::utils::utils_helpers::{
+    bounded_text_similarity, calculate_text_similarity, find_best_replacement_candidate,
+};
+
+/// 去重时使用的相似度阈值
+const SIMILARITY_THRESHOLD: f64 = 0.8;
+
+/// 确定性的伪随机文本（xorshift），中英文混合，长度在 min_len..=max_len 之间
+fn sample_texts(seed: u64, count: usize, min_len: usize, max_len: usize) -> Vec<String> {
+    const ALPHABET: &[char] = &[
+        'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'k', 'l', 'm', 'n', 'o', 'p', 'r', 's', 't',
+        'u', ' ', ' ', '.', ',', '的', '是', '在', '了', '和', '文', '本', '复', '制',
+    ];
+    let mut state = seed.max(1);
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    (0..count)
+        .map(|_| {
+            let len = min_len + (next() as usize) % (max_len - min_len + 1);
+            (0..len)
+                .map(|_| ALPHABET[(next() as usize) % ALPHABET.len()])
+                .collect()
+        })
+        .collect()
+}
+
+/// 在文本中间改动几个字符，得到与原文高度相似的新版本
+fn edited(text: &str) -> String {
+    let mut chars: Vec<char> = text.chars().collect();
+    let mid = chars.len() / 2;
+    chars.splice(mid..mid, "（已修改）".chars());
+    chars.into_iter().collect()
+}
+
+fn bench_pairwise(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pairwise_similarity");
+    for len in [100, 500, 1000] {
+        let texts = sample_texts(len as u64, 2, len, len);
+        let (base, unrelated) = (&texts[0], &texts[1]);
+        let similar = edited(base);
+
+        group.bench_with_input(BenchmarkId::new("lcs_unrelated", len), &len, |b, _| {
+            b.iter(|| calculate_text_similarity(black_box(base), black_box(unrelated)))
+        });
+        group.bench_with_input(BenchmarkId::new("bounded_unrelated", len), &len, |b, _| {
+            b.iter(|| {
+                bounded_text_similarity(black_box(base), black_box(unrelated), SIMILARITY_THRESHOLD)
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("lcs_similar", len), &len, |b, _| {
+            b.iter(|| calculate_text_similarity(black_box(base), black_box(&similar)))
+        });
+        group.bench_with_input(BenchmarkId::new("bounded_similar", len), &len, |b, _| {
+            b.iter(|| {
+                bounded_text_similarity(black_box(base), black_box(&similar), SIMILARITY_THRESHOLD)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_replacement_candidate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("find_best_replacement_candidate");
+    for size in [100, 500] {
+        let history = sample_texts(size as u64, size, 20, 300);
+        let unrelated = sample_texts(7, 1, 150, 150).remove(0);
+        let similar = edited(&history[size / 2]);
+
+        group.bench_with_input(BenchmarkId::new("no_match", size), &history, |b, history| {
+            b.iter(|| {
+                find_best_replacement_candidate(black_box(&unrelated), history, SIMILARITY_THRESHOLD)
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("edited_entry", size), &history, |b, history| {
+            b.iter(|| {
+                find_best_replacement_candidate(black_box(&similar), history, SIMILARITY_THRESHOLD)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_pairwise, bench_replacement_candidate);
+criterion_main!(benches);
@@ -1,30 +1,228 @@
+use crate::core::config::{ExplainDepth, RESULT_HISTORY_MAX_ITEMS, SELECTION_HISTORY_MAX_ITEMS};
+use crate::services::ai_client::{AIClient, Message};
 use crate::utils::clipboard::ClipboardManager;
 use crate::utils::image_clipboard::ImageClipboardManager;
 use crate::utils::utils_helpers::{load_settings, AppSettingsData};
-use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::{Notify, Semaphore};
+
+/// 应用是否正在退出，后台监听线程据此结束循环
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// 标记应用开始退出，返回是否为首次标记
+pub fn request_shutdown() -> bool {
+    !SHUTTING_DOWN.swap(true, Ordering::SeqCst)
+}
+
+/// 应用是否正在退出
+pub fn is_shutting_down() -> bool {
+    SHUTTING_DOWN.load(Ordering::SeqCst)
+}
+
+/// 调试构建下等待单个锁的最长时间，超过即视为死锁
+#[cfg(debug_assertions)]
+const LOCK_DEADLOCK_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+// 锁顺序约定：AppState → ClipboardManager / ImageClipboardManager → 管理器内部的锁。
+// 需要使用管理器时，先通过 clipboard_manager_of / image_clipboard_manager_of 克隆出 Arc，
+// 释放 AppState 锁后再对管理器加锁，避免在 AppState 锁内读写系统剪贴板或磁盘。
+// 持有管理器锁时不得再获取 AppState 锁。
+
+/// 克隆文本剪贴板管理器的 Arc，返回时 AppState 锁已释放
+pub fn clipboard_manager_of(state: &Arc<Mutex<AppState>>) -> Arc<Mutex<ClipboardManager>> {
+    state.lock().unwrap().clipboard_manager.clone()
+}
+
+/// 克隆图片剪贴板管理器的 Arc，返回时 AppState 锁已释放
+pub fn image_clipboard_manager_of(
+    state: &Arc<Mutex<AppState>>,
+) -> Arc<Mutex<ImageClipboardManager>> {
+    state.lock().unwrap().image_clipboard_manager.clone()
+}
+
+/// 获取锁；调试构建下等待超时即 panic 并给出锁名，便于定位违反锁顺序的调用
+pub fn lock_checked<'a, T>(mutex: &'a Mutex<T>, name: &str) -> MutexGuard<'a, T> {
+    #[cfg(debug_assertions)]
+    {
+        let started = std::time::Instant::now();
+        loop {
+            match mutex.try_lock() {
+                Ok(guard) => return guard,
+                Err(std::sync::TryLockError::Poisoned(e)) => panic!("{} 锁已中毒: {}", name, e),
+                Err(std::sync::TryLockError::WouldBlock) => {
+                    if started.elapsed() >= LOCK_DEADLOCK_TIMEOUT {
+                        panic!(
+                            "疑似死锁：等待 {} 锁超过 {:?}，请检查锁顺序",
+                            name, LOCK_DEADLOCK_TIMEOUT
+                        );
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(1));
+                }
+            }
+        }
+    }
+    #[cfg(not(debug_assertions))]
+    {
+        let _ = name;
+        mutex.lock().unwrap()
+    }
+}
+
+/// 窗口可见性、选中位置与回填状态等高频读写的标志。
+/// 剪贴板监听、鼠标监听和命令都会频繁读取，使用原子变量避免为此获取 AppState 锁
+pub struct UiFlags {
+    pub is_visible: AtomicBool,
+    pub is_image_visible: AtomicBool,
+    pub selected_index: AtomicUsize,
+    pub image_selected_index: AtomicUsize,
+    pub is_updating_clipboard: AtomicBool,
+    pub is_processing_selection: AtomicBool,
+    pub do_not_disturb: AtomicBool,
+    pub auto_dnd_fullscreen: AtomicBool,
+}
+
+/// 全局界面标志，与 SHUTTING_DOWN 一样在进程内唯一
+pub static UI_FLAGS: UiFlags = UiFlags {
+    is_visible: AtomicBool::new(false),
+    is_image_visible: AtomicBool::new(false),
+    selected_index: AtomicUsize::new(0),
+    image_selected_index: AtomicUsize::new(0),
+    is_updating_clipboard: AtomicBool::new(false),
+    is_processing_selection: AtomicBool::new(false),
+    do_not_disturb: AtomicBool::new(false),
+    auto_dnd_fullscreen: AtomicBool::new(false),
+};
+
+impl UiFlags {
+    pub fn is_visible(&self) -> bool {
+        self.is_visible.load(Ordering::SeqCst)
+    }
+
+    pub fn is_image_visible(&self) -> bool {
+        self.is_image_visible.load(Ordering::SeqCst)
+    }
+
+    pub fn is_updating_clipboard(&self) -> bool {
+        self.is_updating_clipboard.load(Ordering::SeqCst)
+    }
+
+    pub fn is_processing_selection(&self) -> bool {
+        self.is_processing_selection.load(Ordering::SeqCst)
+    }
+
+    pub fn set_updating_clipboard(&self, updating: bool) {
+        self.is_updating_clipboard.store(updating, Ordering::SeqCst);
+    }
+
+    pub fn set_processing_selection(&self, processing: bool) {
+        self.is_processing_selection.store(processing, Ordering::SeqCst);
+    }
+
+    /// 是否手动开启了免打扰，是否生效见 features::do_not_disturb::is_active
+    pub fn is_do_not_disturb(&self) -> bool {
+        self.do_not_disturb.load(Ordering::SeqCst)
+    }
+
+    pub fn set_do_not_disturb(&self, enabled: bool) {
+        self.do_not_disturb.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn is_auto_dnd_fullscreen(&self) -> bool {
+        self.auto_dnd_fullscreen.load(Ordering::SeqCst)
+    }
+
+    pub fn set_auto_dnd_fullscreen(&self, enabled: bool) {
+        self.auto_dnd_fullscreen.store(enabled, Ordering::SeqCst);
+    }
+
+    /// 任一剪贴板窗口正在显示
+    pub fn any_window_visible(&self) -> bool {
+        self.is_visible() || self.is_image_visible()
+    }
+
+    /// 正在回填、划词或显示剪贴板窗口，此时剪贴板变化不应计入历史
+    pub fn is_busy(&self) -> bool {
+        self.is_updating_clipboard() || self.is_processing_selection() || self.any_window_visible()
+    }
+}
 
 /// 托盘菜单项
 #[derive(Clone)]
 pub struct TrayMenuItems {
     pub autostart_item: tauri::menu::CheckMenuItem<tauri::Wry>,
+    pub dnd_item: tauri::menu::CheckMenuItem<tauri::Wry>,
+    pub selection_item: tauri::menu::CheckMenuItem<tauri::Wry>,
+}
+
+/// 划词记录条目
+#[derive(Clone, Debug, Serialize)]
+pub struct SelectionHistoryEntry {
+    pub text: String,
+    pub timestamp_ms: u64,
+}
+
+/// AI结果窗口当前展示的原文与累计结果
+#[derive(Clone, Debug, Default)]
+pub struct AiResultRecord {
+    pub original: String,
+    pub content: String,
+}
+
+/// 最近一次AI请求的参数，用于重试
+#[derive(Clone, Debug, Default)]
+pub struct AiRequestContext {
+    pub text: String,
+    pub source_language: Option<String>,
+    pub target_language: String,
+    pub scene_hint: Option<String>,
+    pub explain_depth: ExplainDepth,
 }
 
 /// 应用程序全局状态
 pub struct AppState {
     pub clipboard_manager: Arc<Mutex<ClipboardManager>>,
     pub image_clipboard_manager: Arc<Mutex<ImageClipboardManager>>,
-    pub is_visible: bool,
-    pub is_image_visible: bool,
-    pub selected_index: usize,
-    pub image_selected_index: usize,
     pub settings: AppSettingsData,
-    pub is_updating_clipboard: bool,
-    pub is_processing_selection: bool,
     pub text_fill_seq: u64,
     pub image_fill_seq: u64,
     pub ai_request_seq: u64,
-    pub active_translation_op_id: u64,
-    pub active_explanation_op_id: u64,
+    /// 各结果窗口当前有效的AI操作ID，键为窗口类型
+    pub active_ai_op_ids: HashMap<String, u64>,
+    /// 各结果窗口当前AI操作的中止信号，新请求接管窗口时通知旧操作立即结束
+    pub ai_operation_aborts: HashMap<String, Arc<Notify>>,
+    /// 划词对话会话，键为会话ID
+    pub chat_sessions: HashMap<String, Vec<Message>>,
+    /// 最近的划词记录（仅内存，最新在前）
+    pub selection_history: VecDeque<SelectionHistoryEntry>,
+    /// 各结果窗口的最新结果，键为窗口类型
+    pub ai_results: HashMap<String, AiResultRecord>,
+    /// 各结果窗口被新请求替换下来的旧结果（最旧在前），键为窗口类型
+    pub result_history: HashMap<String, VecDeque<AiResultRecord>>,
+    /// 正在翻看旧结果的窗口及其查看位置，不在其中的窗口显示最新结果
+    pub result_view_index: HashMap<String, usize>,
+    /// 从旧结果切回最新结果时已整体推送的结果长度（字节），流式推送据此跳过重复内容
+    pub result_synced_len: HashMap<String, usize>,
+    /// 剪贴板窗口输入过滤后匹配的记录内容（按匹配度排序），为 None 时不过滤；
+    /// 存内容而非索引，历史在过滤期间变动后仍能对应到正确的记录
+    pub history_filter: Option<Vec<String>>,
+    /// 各结果窗口最近一次请求的参数，键为窗口类型
+    pub last_ai_requests: HashMap<String, AiRequestContext>,
+    /// 本次运行中最近使用的解释深度
+    pub last_explain_depth: ExplainDepth,
+    /// 缓存的AI客户端，配置（密钥/地址/模型）不变时复用
+    pub ai_client: Option<AIClient>,
+    /// 各结果窗口类型的AI请求排队信号量，键为窗口类型
+    pub ai_request_semaphores: HashMap<String, Arc<Semaphore>>,
+    /// 全局AI请求并发限制，名额数为 ai_request_limit
+    pub ai_request_semaphore: Arc<Semaphore>,
+    /// 当前全局信号量对应的并发上限
+    pub ai_request_limit: u8,
+    /// 发生崩溃的子系统（线程名）及崩溃信息
+    pub subsystem_failures: HashMap<String, String>,
     pub tray_menu_items: Option<TrayMenuItems>,
 }
 
@@ -34,18 +232,26 @@ impl Clone for AppState {
         Self {
             clipboard_manager: self.clipboard_manager.clone(),
             image_clipboard_manager: self.image_clipboard_manager.clone(),
-            is_visible: self.is_visible,
-            is_image_visible: self.is_image_visible,
-            selected_index: self.selected_index,
-            image_selected_index: self.image_selected_index,
             settings: self.settings.clone(),
-            is_updating_clipboard: self.is_updating_clipboard,
-            is_processing_selection: self.is_processing_selection,
             text_fill_seq: self.text_fill_seq,
             image_fill_seq: self.image_fill_seq,
             ai_request_seq: self.ai_request_seq,
-            active_translation_op_id: self.active_translation_op_id,
-            active_explanation_op_id: self.active_explanation_op_id,
+            active_ai_op_ids: self.active_ai_op_ids.clone(),
+            ai_operation_aborts: self.ai_operation_aborts.clone(),
+            chat_sessions: self.chat_sessions.clone(),
+            selection_history: self.selection_history.clone(),
+            ai_results: self.ai_results.clone(),
+            result_history: self.result_history.clone(),
+            result_view_index: self.result_view_index.clone(),
+            result_synced_len: self.result_synced_len.clone(),
+            history_filter: self.history_filter.clone(),
+            last_ai_requests: self.last_ai_requests.clone(),
+            last_explain_depth: self.last_explain_depth,
+            ai_client: self.ai_client.clone(),
+            ai_request_semaphores: self.ai_request_semaphores.clone(),
+            ai_request_semaphore: self.ai_request_semaphore.clone(),
+            ai_request_limit: self.ai_request_limit,
+            subsystem_failures: self.subsystem_failures.clone(),
             tray_menu_items: None,
         }
     }
@@ -54,33 +260,137 @@ impl Clone for AppState {
 impl Default for AppState {
     /// 默认状态初始化
     fn default() -> Self {
-        let saved_settings = load_settings().unwrap_or_default();
+        let saved_settings = load_settings().unwrap_or_else(|e| {
+            log::error!("加载设置失败: {}，使用默认设置", e);
+            AppSettingsData::default()
+        });
+
+        let ai_request_limit = saved_settings.max_concurrent_ai_requests.max(1);
 
         Self {
             clipboard_manager: Arc::new(Mutex::new(ClipboardManager::new(
                 saved_settings.max_items,
                 saved_settings.grouped_items_protected_from_limit,
+                saved_settings.dedup_mode,
             ))),
             image_clipboard_manager: Arc::new(Mutex::new(ImageClipboardManager::new(
                 saved_settings.max_items,
                 saved_settings.grouped_items_protected_from_limit,
             ))),
-            is_visible: false,
-            is_image_visible: false,
-            selected_index: 0,
-            image_selected_index: 0,
             settings: saved_settings,
-            is_updating_clipboard: false,
-            is_processing_selection: false,
             text_fill_seq: 0,
             image_fill_seq: 0,
             ai_request_seq: 0,
-            active_translation_op_id: 0,
-            active_explanation_op_id: 0,
+            active_ai_op_ids: HashMap::new(),
+            ai_operation_aborts: HashMap::new(),
+            chat_sessions: HashMap::new(),
+            selection_history: VecDeque::new(),
+            ai_results: HashMap::new(),
+            result_history: HashMap::new(),
+            result_view_index: HashMap::new(),
+            result_synced_len: HashMap::new(),
+            history_filter: None,
+            last_ai_requests: HashMap::new(),
+            last_explain_depth: ExplainDepth::default(),
+            ai_client: None,
+            ai_request_semaphores: HashMap::new(),
+            ai_request_semaphore: Arc::new(Semaphore::new(ai_request_limit as usize)),
+            ai_request_limit,
+            subsystem_failures: HashMap::new(),
             tray_menu_items: None,
         }
     }
 }
 
+impl AppState {
+    /// 获取已缓存的AI客户端
+    pub fn try_get_cached_ai_client(&self) -> Option<AIClient> {
+        self.ai_client.clone()
+    }
+
+    /// 缓存新创建的AI客户端，替换旧客户端
+    pub fn update_ai_client_cache(&mut self, client: AIClient) {
+        self.ai_client = Some(client);
+    }
+
+    /// 记录划词文本，重复文本移到最前
+    pub fn record_selection(&mut self, text: &str) {
+        if !self.settings.record_selection_history {
+            return;
+        }
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        self.selection_history.retain(|entry| entry.text != text);
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        self.selection_history.push_front(SelectionHistoryEntry {
+            text: text.to_string(),
+            timestamp_ms,
+        });
+        self.selection_history.truncate(SELECTION_HISTORY_MAX_ITEMS);
+    }
+
+    /// 为新请求开启结果位置：原最新结果（非空时）转入历史，窗口跳到新位置
+    pub fn begin_result_slot(&mut self, window_type: &str, original: String) {
+        let previous = self.ai_results.insert(
+            window_type.to_string(),
+            AiResultRecord {
+                original,
+                content: String::new(),
+            },
+        );
+        if let Some(previous) = previous.filter(|record| !record.content.trim().is_empty()) {
+            let history = self.result_history.entry(window_type.to_string()).or_default();
+            history.push_back(previous);
+            while history.len() >= RESULT_HISTORY_MAX_ITEMS {
+                history.pop_front();
+            }
+        }
+        self.result_view_index.remove(window_type);
+        self.result_synced_len.remove(window_type);
+    }
+
+    /// 结果窗口当前查看的位置与结果总数，没有任何结果时总数为 0
+    pub fn result_position(&self, window_type: &str) -> (usize, usize) {
+        let archived = self.result_history.get(window_type).map_or(0, VecDeque::len);
+        let total = archived + usize::from(self.ai_results.contains_key(window_type));
+        let index = self
+            .result_view_index
+            .get(window_type)
+            .copied()
+            .unwrap_or(total.saturating_sub(1));
+        (index, total)
+    }
+
+    /// 指定位置的结果，最后一个位置为最新结果
+    pub fn result_at(&self, window_type: &str, index: usize) -> Option<AiResultRecord> {
+        let history = self.result_history.get(window_type);
+        match history.and_then(|history| history.get(index)) {
+            Some(record) => Some(record.clone()),
+            None if index == history.map_or(0, VecDeque::len) => self.ai_results.get(window_type).cloned(),
+            None => None,
+        }
+    }
+
+    /// 更新全局AI请求并发上限；上限变化时换用新的信号量，进行中的请求仍持有旧信号量的名额
+    pub fn set_ai_request_limit(&mut self, limit: u8) {
+        let limit = limit.max(1);
+        if limit != self.ai_request_limit {
+            self.ai_request_semaphore = Arc::new(Semaphore::new(limit as usize));
+            self.ai_request_limit = limit;
+        }
+    }
+
+    /// 当前占用全局名额的AI请求数
+    pub fn active_ai_request_count(&self) -> u8 {
+        let available = self.ai_request_semaphore.available_permits();
+        (self.ai_request_limit as usize).saturating_sub(available) as u8
+    }
+}
+
 /// 共享应用程序状态别名
 pub type SharedAppState = AppState;
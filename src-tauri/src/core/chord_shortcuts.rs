@@ -0,0 +1,279 @@
+use crate::core::app_state::AppState;
+use crate::core::config::{CAPTURE_SELECTION_DELAY, CHORD_SHORTCUT_TIMEOUT};
+use crate::core::diagnostics::spawn_named;
+use crate::core::ui_worker::{run_on_ui_worker, run_on_ui_worker_after};
+use crate::features::mouse_listener::{capture_selected_text, capture_selection_now};
+use crate::services::ai_services::{stream_selection_action, translate_clipboard_content};
+use crate::ui::window_manager::{show_clipboard_window, show_image_clipboard_window};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+/// 支持的组合键动作
+pub const CHORD_ACTIONS: [&str; 6] = [
+    "translate",
+    "translate_clipboard",
+    "explain",
+    "capture_selection",
+    "show_clipboard",
+    "show_image_clipboard",
+];
+
+/// 两段式组合快捷键，例如先按 Ctrl+K 再按 Ctrl+T
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ChordShortcut {
+    pub first: String,
+    pub second: String,
+    pub action: String,
+}
+
+impl ChordShortcut {
+    /// 检查组合键是否有效
+    pub fn validate(&self) -> Result<(), String> {
+        if self.first.trim().is_empty() || self.second.trim().is_empty() {
+            return Err("组合快捷键的两段按键都不能为空".to_string());
+        }
+        if self.first == self.second {
+            return Err(format!("组合快捷键 {} 的两段按键不能相同", self.first));
+        }
+        if !CHORD_ACTIONS.contains(&self.action.as_str()) {
+            return Err(format!("不支持的组合快捷键动作: {}", self.action));
+        }
+        Ok(())
+    }
+}
+
+/// 等待第二段按键的状态
+struct PendingChord {
+    generation: u64,
+    started: Instant,
+    /// 临时注册的第二段按键
+    seconds: Vec<String>,
+}
+
+/// 组合快捷键监听器：记录第一段按键的触发时间，并在超时后清理临时注册
+#[derive(Default)]
+pub struct ChordShortcutListener {
+    /// 已注册的第一段按键
+    registered_firsts: Vec<String>,
+    pending: Option<PendingChord>,
+    generation: u64,
+}
+
+lazy_static::lazy_static! {
+    static ref CHORD_LISTENER: Mutex<ChordShortcutListener> =
+        Mutex::new(ChordShortcutListener::default());
+}
+
+impl ChordShortcutListener {
+    /// 开始等待第二段按键，返回本次等待的序号与需要清理的上一次临时注册
+    fn begin(&mut self) -> (u64, Vec<String>) {
+        let stale = self.take_pending();
+        self.generation += 1;
+        self.pending = Some(PendingChord {
+            generation: self.generation,
+            started: Instant::now(),
+            seconds: Vec::new(),
+        });
+        (self.generation, stale)
+    }
+
+    /// 结束等待，返回需要注销的第二段按键
+    fn take_pending(&mut self) -> Vec<String> {
+        self.pending
+            .take()
+            .map(|pending| pending.seconds)
+            .unwrap_or_default()
+    }
+
+    /// 若指定序号的等待已超时，结束等待并返回需要注销的按键
+    fn expire(&mut self, generation: u64, timeout: Duration) -> Vec<String> {
+        match &self.pending {
+            Some(pending)
+                if pending.generation == generation && pending.started.elapsed() >= timeout =>
+            {
+                self.take_pending()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// 第二段按键是否仍在有效期内
+    fn is_active(&self, generation: u64) -> bool {
+        self.pending.as_ref().is_some_and(|pending| {
+            pending.generation == generation && pending.started.elapsed() < CHORD_SHORTCUT_TIMEOUT
+        })
+    }
+}
+
+fn unregister_shortcuts(app_handle: &AppHandle, shortcuts: &[String]) {
+    for shortcut in shortcuts {
+        if let Err(e) = app_handle.global_shortcut().unregister(shortcut.as_str()) {
+            log::debug!("注销组合快捷键 {} 失败: {}", shortcut, e);
+        }
+    }
+}
+
+/// 按当前设置重新注册组合快捷键（仅注册第一段按键）
+pub fn register_chord_shortcuts(app_handle: &AppHandle, state: Arc<Mutex<AppState>>) {
+    let chords = state.lock().unwrap().settings.chord_shortcuts.clone();
+    let (old_firsts, stale) = {
+        let mut listener = CHORD_LISTENER.lock().unwrap();
+        (
+            std::mem::take(&mut listener.registered_firsts),
+            listener.take_pending(),
+        )
+    };
+    unregister_shortcuts(app_handle, &stale);
+    unregister_shortcuts(app_handle, &old_firsts);
+
+    let mut firsts: Vec<String> = chords.iter().map(|chord| chord.first.clone()).collect();
+    firsts.sort();
+    firsts.dedup();
+
+    let mut registered = Vec::new();
+    for first in firsts {
+        let app_for_chord = app_handle.clone();
+        let state_for_chord = state.clone();
+        let first_for_chord = first.clone();
+        let result = app_handle
+            .global_shortcut()
+            .on_shortcut(first.as_str(), move |_app, _shortcut, event| {
+                if let ShortcutState::Pressed = event.state {
+                    // 插件分发回调时持有快捷键表的锁，注册与注销须放到工作线程上执行
+                    let app_for_chord = app_for_chord.clone();
+                    let state_for_chord = state_for_chord.clone();
+                    let first_for_chord = first_for_chord.clone();
+                    run_on_ui_worker(move || {
+                        on_first_chord(&app_for_chord, state_for_chord, &first_for_chord);
+                    });
+                }
+            });
+        match result {
+            Ok(()) => registered.push(first),
+            Err(e) => log::error!("注册组合快捷键 {} 失败: {}", first, e),
+        }
+    }
+    log::info!("已注册组合快捷键: {:?}", registered);
+    CHORD_LISTENER.lock().unwrap().registered_firsts = registered;
+}
+
+/// 第一段按键触发（在共享工作线程上执行）：临时注册对应的第二段按键，超时后自动注销
+fn on_first_chord(app_handle: &AppHandle, state: Arc<Mutex<AppState>>, first: &str) {
+    let chords: Vec<ChordShortcut> = state
+        .lock()
+        .unwrap()
+        .settings
+        .chord_shortcuts
+        .iter()
+        .filter(|chord| chord.first == first)
+        .cloned()
+        .collect();
+    let (generation, stale) = CHORD_LISTENER.lock().unwrap().begin();
+    unregister_shortcuts(app_handle, &stale);
+
+    let mut seconds = Vec::new();
+    for chord in chords {
+        let app_for_action = app_handle.clone();
+        let state_for_action = state.clone();
+        let action = chord.action.clone();
+        let result = app_handle
+            .global_shortcut()
+            .on_shortcut(chord.second.as_str(), move |_app, _shortcut, event| {
+                if let ShortcutState::Pressed = event.state {
+                    let (active, registered) = {
+                        let mut listener = CHORD_LISTENER.lock().unwrap();
+                        let active = listener.is_active(generation);
+                        (active, if active { listener.take_pending() } else { Vec::new() })
+                    };
+                    if !active {
+                        return;
+                    }
+                    let app_for_worker = app_for_action.clone();
+                    let state_for_worker = state_for_action.clone();
+                    let action = action.clone();
+                    // 不能在快捷键回调中注销自身，放到工作线程上处理
+                    run_on_ui_worker(move || {
+                        unregister_shortcuts(&app_for_worker, &registered);
+                        dispatch_chord_action(&app_for_worker, state_for_worker, &action);
+                    });
+                }
+            });
+        match result {
+            Ok(()) => seconds.push(chord.second),
+            Err(e) => log::warn!("临时注册组合快捷键第二段 {} 失败: {}", chord.second, e),
+        }
+    }
+
+    {
+        let mut listener = CHORD_LISTENER.lock().unwrap();
+        match listener.pending.as_mut() {
+            Some(pending) if pending.generation == generation => pending.seconds = seconds,
+            _ => {
+                drop(listener);
+                unregister_shortcuts(app_handle, &seconds);
+                return;
+            }
+        }
+    }
+    log::debug!("组合快捷键 {} 已触发，等待第二段按键", first);
+
+    let app_for_expire = app_handle.clone();
+    spawn_named("chord-shortcut-expire", move || {
+        thread::sleep(CHORD_SHORTCUT_TIMEOUT);
+        let expired = CHORD_LISTENER
+            .lock()
+            .unwrap()
+            .expire(generation, CHORD_SHORTCUT_TIMEOUT);
+        if !expired.is_empty() {
+            log::debug!("组合快捷键等待超时，已注销临时按键");
+        }
+        unregister_shortcuts(&app_for_expire, &expired);
+    });
+}
+
+/// 执行组合快捷键对应的动作
+fn dispatch_chord_action(app_handle: &AppHandle, state: Arc<Mutex<AppState>>, action: &str) {
+    log::info!("执行组合快捷键动作: {}", action);
+    match action {
+        "show_clipboard" => show_clipboard_window(app_handle.clone(), state),
+        "show_image_clipboard" => show_image_clipboard_window(app_handle.clone(), state),
+        "translate_clipboard" => {
+            let app_for_action = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = translate_clipboard_content(None, None, app_for_action, state).await {
+                    log::info!("组合快捷键翻译剪贴板未完成: {}", e);
+                }
+            });
+        }
+        "capture_selection" | "translate" | "explain" => {
+            let app_for_action = app_handle.clone();
+            let action = action.to_string();
+            // 等待快捷键的修饰键释放，避免干扰模拟复制
+            run_on_ui_worker_after(CAPTURE_SELECTION_DELAY, move || {
+                if action == "capture_selection" {
+                    if let Err(e) = capture_selection_now(&app_for_action, &state) {
+                        log::info!("手动划词未完成: {}", e);
+                    }
+                    return;
+                }
+                let text = match capture_selected_text(&app_for_action, &state) {
+                    Ok(text) => text,
+                    Err(e) => {
+                        log::info!("组合快捷键未获取到选中文本: {}", e);
+                        return;
+                    }
+                };
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = stream_selection_action(&action, text, app_for_action, state).await {
+                        log::error!("组合快捷键动作执行失败: {}", e);
+                    }
+                });
+            });
+        }
+        _ => log::warn!("未知的组合快捷键动作: {}", action),
+    }
+}
@@ -0,0 +1,266 @@
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+
+use crate::core::app_state::{clipboard_manager_of, lock_checked, AppState};
+use crate::core::single_instance::send_cli_request;
+use crate::utils::utils_helpers::{load_history_data, save_history_data_with_retry};
+
+/// 命令执行成功
+pub const EXIT_OK: i32 = 0;
+/// 命令已送达但执行失败
+pub const EXIT_FAILED: i32 = 1;
+/// 参数错误
+pub const EXIT_BAD_ARGS: i32 = 2;
+/// 命令需要运行中的实例，但没有实例在运行
+pub const EXIT_NO_INSTANCE: i32 = 3;
+
+const USAGE: &str = "用法:
+  fuyun_tools --history list [--json]
+  fuyun_tools --history add <文本> [--json]
+  fuyun_tools --history paste <索引> [--json]
+  fuyun_tools --translate <文本> --to <目标语言> [--json]";
+
+/// 命令行请求，经单实例通道以 JSON 发送给运行中的实例
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum CliRequest {
+    HistoryList,
+    HistoryAdd { text: String },
+    HistoryPaste { index: usize },
+    Translate { text: String, to: String },
+}
+
+/// 历史记录条目，--json 输出的字段保持稳定
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CliHistoryItem {
+    pub index: usize,
+    pub text: String,
+}
+
+/// 命令行响应，--json 时原样输出
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CliResponse {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<Vec<CliHistoryItem>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl CliResponse {
+    fn success() -> Self {
+        Self {
+            ok: true,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn failure(error: impl Into<String>) -> Self {
+        Self {
+            ok: false,
+            error: Some(error.into()),
+            ..Default::default()
+        }
+    }
+
+    fn with_items(items: Vec<String>) -> Self {
+        Self {
+            ok: true,
+            items: Some(
+                items
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, text)| CliHistoryItem { index, text })
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+}
+
+/// 解析命令行参数；不是命令行模式（例如普通启动或 --autostart）时返回 None
+fn parse_args(args: &[String]) -> Option<Result<(CliRequest, bool), String>> {
+    let rest: Vec<&str> = args.iter().skip(1).map(String::as_str).collect();
+    if !rest.iter().any(|arg| *arg == "--history" || *arg == "--translate") {
+        return None;
+    }
+    let json = rest.contains(&"--json");
+    let rest: Vec<&str> = rest.into_iter().filter(|arg| *arg != "--json").collect();
+
+    let request = match rest.as_slice() {
+        ["--history", "list"] => Ok(CliRequest::HistoryList),
+        ["--history", "add", text] => Ok(CliRequest::HistoryAdd {
+            text: text.to_string(),
+        }),
+        ["--history", "paste", index] => index
+            .parse()
+            .map(|index| CliRequest::HistoryPaste { index })
+            .map_err(|_| format!("无效的索引: {}", index)),
+        ["--translate", text, "--to", to] | ["--to", to, "--translate", text] => {
+            Ok(CliRequest::Translate {
+                text: text.to_string(),
+                to: to.to_string(),
+            })
+        }
+        _ => Err("无法识别的参数".to_string()),
+    };
+    Some(request.map(|request| (request, json)))
+}
+
+/// 命令行模式入口：识别到命令行参数时执行并返回退出码，否则返回 None 继续正常启动
+pub fn run_from_args(args: &[String]) -> Option<i32> {
+    let parsed = parse_args(args)?;
+    attach_parent_console();
+
+    let (request, json) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return Some(EXIT_BAD_ARGS);
+        }
+    };
+
+    let payload = match serde_json::to_string(&request) {
+        Ok(payload) => payload,
+        Err(e) => {
+            print_response(&CliResponse::failure(format!("序列化命令失败: {}", e)), json);
+            return Some(EXIT_FAILED);
+        }
+    };
+    let response = match send_cli_request(&payload) {
+        Some(Ok(line)) => serde_json::from_str(&line)
+            .unwrap_or_else(|e| CliResponse::failure(format!("解析运行中实例的响应失败: {}", e))),
+        Some(Err(e)) => CliResponse::failure(e),
+        None => match run_offline(&request) {
+            Some(response) => response,
+            None => {
+                print_response(&CliResponse::failure("没有正在运行的 fuyun_tools 实例"), json);
+                return Some(EXIT_NO_INSTANCE);
+            }
+        },
+    };
+
+    print_response(&response, json);
+    Some(if response.ok { EXIT_OK } else { EXIT_FAILED })
+}
+
+/// 没有运行中的实例时直接读写历史文件；粘贴与翻译需要运行中的实例，返回 None
+fn run_offline(request: &CliRequest) -> Option<CliResponse> {
+    match request {
+        CliRequest::HistoryList => Some(match load_history_data() {
+            Ok(data) => CliResponse::with_items(data.items),
+            Err(e) => CliResponse::failure(e),
+        }),
+        CliRequest::HistoryAdd { text } => Some(add_to_history_file(text)),
+        CliRequest::HistoryPaste { .. } | CliRequest::Translate { .. } => None,
+    }
+}
+
+fn add_to_history_file(text: &str) -> CliResponse {
+    if text.trim().is_empty() {
+        return CliResponse::failure("文本为空");
+    }
+    let mut data = match load_history_data() {
+        Ok(data) => data,
+        Err(e) => return CliResponse::failure(e),
+    };
+    data.items.retain(|item| item != text);
+    data.items.insert(0, text.to_string());
+    match save_history_data_with_retry(&data, 3) {
+        Ok(()) => CliResponse::success(),
+        Err(e) => CliResponse::failure(e),
+    }
+}
+
+fn print_response(response: &CliResponse, json: bool) {
+    if json {
+        match serde_json::to_string(response) {
+            Ok(line) => println!("{}", line),
+            Err(e) => eprintln!("序列化输出失败: {}", e),
+        }
+        return;
+    }
+    if let Some(error) = &response.error {
+        eprintln!("{}", error);
+    }
+    if let Some(items) = &response.items {
+        // 每条一行，换行符转义，便于脚本逐行处理
+        for item in items {
+            println!("{}\t{}", item.index, item.text.replace('\r', "\\r").replace('\n', "\\n"));
+        }
+    }
+    if let Some(text) = &response.text {
+        println!("{}", text);
+    }
+}
+
+/// 处理单实例通道收到的命令行请求，返回 JSON 响应
+pub fn handle_cli_request(app: &AppHandle, payload: &str) -> String {
+    let response = match serde_json::from_str::<CliRequest>(payload) {
+        Ok(request) => {
+            log::info!("收到命令行请求: {:?}", request);
+            let state = app.state::<Arc<Mutex<AppState>>>().inner().clone();
+            execute_request(app, state, request)
+        }
+        Err(e) => CliResponse::failure(format!("无法解析命令行请求: {}", e)),
+    };
+    serde_json::to_string(&response).unwrap_or_else(|_| r#"{"ok":false}"#.to_string())
+}
+
+/// 在运行中的实例内执行请求，本地 HTTP 接口也复用此实现
+pub(crate) fn execute_request(
+    app: &AppHandle,
+    state: Arc<Mutex<AppState>>,
+    request: CliRequest,
+) -> CliResponse {
+    match request {
+        CliRequest::HistoryList => {
+            let clipboard_manager = clipboard_manager_of(&state);
+            let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+            CliResponse::with_items(manager.get_history())
+        }
+        CliRequest::HistoryAdd { text } => {
+            if text.trim().is_empty() {
+                return CliResponse::failure("文本为空");
+            }
+            let clipboard_manager = clipboard_manager_of(&state);
+            let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+            manager.add_to_history(text);
+            CliResponse::success()
+        }
+        CliRequest::HistoryPaste { index } => {
+            match crate::ui::commands::paste_history_item(index, state, app.clone()) {
+                Ok(_) => CliResponse::success(),
+                Err(e) => CliResponse::failure(e),
+            }
+        }
+        CliRequest::Translate { text, to } => {
+            let result = tauri::async_runtime::block_on(
+                crate::services::ai_services::translate_text_once(state, &text, &to),
+            );
+            match result {
+                Ok(translated) => CliResponse {
+                    ok: true,
+                    text: Some(translated),
+                    ..Default::default()
+                },
+                Err(e) => CliResponse::failure(e.to_string()),
+            }
+        }
+    }
+}
+
+/// 发布版使用 windows 子系统，从命令行启动时需附加到父进程控制台才能输出
+#[cfg(target_os = "windows")]
+fn attach_parent_console() {
+    use winapi::um::wincon::{AttachConsole, ATTACH_PARENT_PROCESS};
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn attach_parent_console() {}
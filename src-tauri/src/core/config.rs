@@ -2,10 +2,15 @@
 
 use enigo::Key;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 
 /// 剪贴板轮询间隔时间
 pub const CLIPBOARD_POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// 连续多少次未检测到剪贴板变化后退避轮询
+pub const CLIPBOARD_IDLE_POLLS_BEFORE_BACKOFF: u32 = 60;
+/// 空闲退避后的剪贴板轮询间隔
+pub const CLIPBOARD_POLL_BACKOFF_INTERVAL: Duration = Duration::from_millis(2000);
 pub const CLIPBOARD_POLL_MIN_INTERVAL: Duration = Duration::from_millis(50);
 pub const CLIPBOARD_POLL_WARM_INTERVAL: Duration = Duration::from_millis(200);
 pub const CLIPBOARD_POLL_IDLE_INTERVAL: Duration = Duration::from_millis(1200);
@@ -20,6 +25,55 @@ pub const DEFAULT_CLIPBOARD_POLL_METRICS_ENABLED: bool = true;
 pub const DEFAULT_CLIPBOARD_POLL_METRICS_LOG_LEVEL: &str = "info";
 /// 剪贴板窗口与任务栏之间的额外安全边距（像素）
 pub const CLIPBOARD_WINDOW_BOTTOM_EXTRA_MARGIN: i32 = 8;
+/// 结果窗口默认尺寸（逻辑像素）
+pub const DEFAULT_RESULT_WINDOW_WIDTH: u32 = 560;
+pub const DEFAULT_RESULT_WINDOW_HEIGHT: u32 = 360;
+/// 结果窗口移动或缩放停止后延迟保存位置的时间，拖动过程中不重复写盘
+pub const RESULT_WINDOW_GEOMETRY_SAVE_DELAY: Duration = Duration::from_millis(800);
+/// 划词记录保留的最大条数
+pub const SELECTION_HISTORY_MAX_ITEMS: usize = 50;
+/// 每个结果窗口可前后翻看的结果条数（含最新一条）
+pub const RESULT_HISTORY_MAX_ITEMS: usize = 10;
+/// 剪贴板窗口输入过滤时模糊匹配允许的最大差异（0~100，对应相似度下限 0.3）
+pub const HISTORY_FILTER_MAX_DISTANCE: u32 = 70;
+/// 全局搜索每个来源默认返回的条数
+pub const DEFAULT_GLOBAL_SEARCH_RESULTS_PER_SOURCE: usize = 5;
+/// 全局搜索浮动窗口的标签
+pub const GLOBAL_SEARCH_WINDOW_LABEL: &str = "global_search";
+/// 组合快捷键第一段按下后等待第二段的时长
+pub const CHORD_SHORTCUT_TIMEOUT: Duration = Duration::from_millis(1000);
+/// 回填前等待剪贴板窗口隐藏的最长时间
+pub const FILL_WINDOW_HIDE_TIMEOUT: Duration = Duration::from_millis(900);
+/// 窗口隐藏后、写入剪贴板前的等待时间，让焦点回到目标应用
+pub const FILL_AFTER_HIDE_DELAY: Duration = Duration::from_millis(40);
+/// 写入剪贴板后到模拟粘贴前的等待时间
+pub const PASTE_INITIAL_DELAY: Duration = Duration::from_millis(135);
+/// 首次粘贴失败后重试前的等待时间
+pub const PASTE_RETRY_DELAY: Duration = Duration::from_millis(140);
+/// 粘贴前重新激活打开剪贴板窗口前的前台窗口的最长时间，超时后退回固定等待 PASTE_INITIAL_DELAY
+pub const PASTE_TARGET_ACTIVATE_TIMEOUT: Duration = Duration::from_millis(500);
+/// 重新激活目标窗口的重试间隔
+pub const PASTE_TARGET_ACTIVATE_INTERVAL: Duration = Duration::from_millis(25);
+/// 托盘菜单事件回调中延迟重建菜单的时间
+pub const TRAY_MENU_REBUILD_DELAY: Duration = Duration::from_millis(100);
+/// 手动划词快捷键触发后等待修饰键释放的时间
+pub const CAPTURE_SELECTION_DELAY: Duration = Duration::from_millis(150);
+/// 设置文件写入失败后重试保存的间隔
+pub const SETTINGS_SAVE_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+/// 开机自启时附加的启动参数，自启动注册时写入
+pub const AUTOSTART_ARG: &str = "--autostart";
+/// 与 --autostart 等价的启动参数，便于手动创建的快捷方式使用
+pub const MINIMIZED_ARG: &str = "--minimized";
+/// 开机自启后延迟启动剪贴板与划词监听的默认秒数
+pub const DEFAULT_AUTOSTART_LISTENER_DELAY_SECS: u64 = 10;
+/// 划词捕获等待剪贴板更新的默认最长时间（毫秒），捕获失败时检测线程会阻塞这么久
+pub const DEFAULT_SELECTION_CAPTURE_TIMEOUT_MS: u64 = 600;
+/// 划词捕获等待时间上限（毫秒），为响应慢的应用与远程桌面预留余量
+pub const MAX_SELECTION_CAPTURE_TIMEOUT_MS: u64 = 3000;
+/// 划词捕获轮询剪贴板的默认间隔（毫秒），也用作模拟复制后的初始等待
+pub const DEFAULT_SELECTION_CAPTURE_INTERVAL_MS: u64 = 10;
+/// 划词对话会话保留的最大消息条数
+pub const CHAT_SESSION_MAX_MESSAGES: usize = 20;
 /// 默认切换快捷键（根据操作系统自动适配）
 pub const DEFAULT_TOGGLE_SHORTCUT: &str = if cfg!(target_os = "macos") {
     "Cmd+Shift+z"
@@ -44,6 +98,35 @@ pub const CTRL_KEY: Key = if cfg!(target_os = "macos") {
     Key::Control
 };
 
+/// 解释的详细程度
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ExplainDepth {
+    /// 一句话概括
+    Brief,
+    /// 默认模板的长度
+    #[default]
+    Standard,
+    /// 约500字并附示例
+    Detailed,
+    /// 正式学术语气
+    Academic,
+}
+
+impl ExplainDepth {
+    /// 附加到解释提示词中的要求，标准深度不追加
+    pub fn prompt_suffix(self) -> Option<&'static str> {
+        match self {
+            Self::Brief => Some("忽略上述字数要求，只用一句话解释核心含义。"),
+            Self::Standard => None,
+            Self::Detailed => Some("忽略上述字数要求，详细解释，控制在500字以内，并给出1-2个具体示例。"),
+            Self::Academic => Some(
+                "忽略上述字数要求，使用正式的学术语气解释，给出准确定义与相关概念，并在涉及公认理论或来源时以引用格式注明。",
+            ),
+        }
+    }
+}
+
 /// AI服务提供商枚举
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum AIProvider {
@@ -94,11 +177,93 @@ impl AIProvider {
     }
 }
 
-/// 单个AI提供商的配置
+/// 稳定版更新地址
+pub const UPDATE_ENDPOINT_STABLE: &str =
+    "https://github.com/zRq1351/fuyun_tools/releases/latest/download/latest.json";
+/// 测试版更新地址
+pub const UPDATE_ENDPOINT_BETA: &str =
+    "https://github.com/zRq1351/fuyun_tools/releases/download/beta/latest.json";
+/// 启动后首次后台检查更新的延迟（秒）
+pub const UPDATE_CHECK_INITIAL_DELAY_SECS: u64 = 60;
+/// 后台检查更新间隔（秒）
+pub const UPDATE_CHECK_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+/// AI 请求默认超时时间（秒）
+pub const DEFAULT_AI_REQUEST_TIMEOUT_SECS: u64 = 120;
+/// 每种结果窗口同时进行的AI请求数，超出时排队等待
+pub const AI_REQUESTS_PER_WINDOW: usize = 1;
+/// 全局默认允许同时进行的AI请求数
+pub const DEFAULT_MAX_CONCURRENT_AI_REQUESTS: u8 = 3;
+/// AI请求排队等待空闲名额的最长时间
+pub const AI_REQUEST_QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+/// 流式结果累积到该字符数后推送到结果窗口
+pub const STREAM_FLUSH_CHARS: usize = 50;
+/// 长文本分段翻译时每段的默认最大字符数
+pub const DEFAULT_TRANSLATION_CHUNK_CHARS: usize = 1500;
+/// UI Automation 兜底读取选中文本时的默认最大字符数
+pub const DEFAULT_SELECTION_UIA_MAX_CHARS: usize = 5000;
+/// 距上次推送超过该时长时推送流式结果
+pub const STREAM_FLUSH_INTERVAL: Duration = Duration::from_millis(200);
+/// 输入估算超过该 token 数时前端应先请用户确认再发送
+pub const DEFAULT_LARGE_INPUT_CONFIRM_TOKENS: usize = 2000;
+/// 单次流式请求的输出 token 上限
+pub const STREAM_MAX_TOKENS: u32 = 1000;
+/// 估算生成进度时每个 token 对应的字符数
+pub const STREAM_PROGRESS_CHARS_PER_TOKEN: usize = 4;
+
+/// 单个AI提供商的配置，缺失字段使用默认值，未知字段保存在 extra 中
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
 pub struct ProviderConfig {
     pub api_url: String,
     pub model_name: String,
     #[serde(default)]
     pub encrypted_api_key: String,
+    /// 单次请求（含完整流式输出）的超时时间，未配置时使用默认值
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// 附加到每个请求的自定义请求头，用于企业网关等需要额外认证信息的场景
+    #[serde(default)]
+    pub custom_headers: HashMap<String, String>,
+    /// 每百万输入 token 的价格（币种由用户自定），未配置时不估算费用
+    #[serde(default)]
+    pub price_per_million_tokens: Option<f64>,
+    /// 当前版本无法识别的字段，原样保留
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ProviderConfig {
+    /// 获取生效的请求超时时间（秒）
+    pub fn request_timeout_secs(&self) -> u64 {
+        self.timeout_secs.unwrap_or(DEFAULT_AI_REQUEST_TIMEOUT_SECS)
+    }
+}
+
+/// 校验自定义请求头：名称须为非空 ASCII 且不含冒号、空白，值不能包含换行等控制字符
+pub fn validate_custom_headers(headers: &HashMap<String, String>) -> Result<(), String> {
+    for (name, value) in headers {
+        if name.trim().is_empty() {
+            return Err("自定义请求头名称不能为空".to_string());
+        }
+        if !name.is_ascii() || name.contains(':') || name.chars().any(|c| c.is_ascii_whitespace()) {
+            return Err(format!("自定义请求头名称无效: {}（仅支持ASCII字符，且不能包含冒号或空白）", name));
+        }
+        if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err() {
+            return Err(format!("自定义请求头名称无效: {}", name));
+        }
+        if reqwest::header::HeaderValue::from_str(value).is_err() {
+            return Err(format!("自定义请求头 {} 的值包含非法字符", name));
+        }
+    }
+    Ok(())
+}
+
+/// 结果窗口上次关闭时的位置与尺寸（物理像素）
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ResultWindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
 }
@@ -0,0 +1,169 @@
+use crate::core::app_state::AppState;
+use crate::utils::utils_helpers::{get_logs_dir_path, AppSettingsData};
+use std::backtrace::Backtrace;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+/// 崩溃信息写入的日志文件名
+const PANIC_LOG_FILE_NAME: &str = "panic.log";
+/// 诊断包中包含的最近日志文件数量
+const DIAGNOSTICS_LOG_FILE_COUNT: usize = 2;
+
+/// 以指定名称启动后台线程，线程名用于在崩溃时标记所属子系统
+pub fn spawn_named<F>(name: &str, f: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    if let Err(e) = thread::Builder::new().name(name.to_string()).spawn(f) {
+        log::error!("启动线程 {} 失败: {}", name, e);
+    }
+}
+
+/// 安装全局 panic 钩子：记录消息与调用栈到日志目录，并在状态中标记出错的子系统
+pub fn install_panic_hook(state: Arc<Mutex<AppState>>) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let subsystem = thread::current().name().unwrap_or("unnamed").to_string();
+        let message = info.to_string();
+        let backtrace = Backtrace::force_capture();
+        log::error!("线程 {} 发生崩溃: {}", subsystem, message);
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let panic_log = get_logs_dir_path().join(PANIC_LOG_FILE_NAME);
+        let _ = fs::create_dir_all(get_logs_dir_path());
+        if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&panic_log) {
+            let _ = writeln!(
+                file,
+                "[{}] thread={}\n{}\n{}\n",
+                timestamp, subsystem, message, backtrace
+            );
+        }
+
+        // 崩溃可能发生在持有状态锁期间，此时锁已中毒，仍取出内部数据记录
+        let mut state_guard = match state.try_lock() {
+            Ok(guard) => Some(guard),
+            Err(std::sync::TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+            Err(std::sync::TryLockError::WouldBlock) => None,
+        };
+        if let Some(guard) = state_guard.as_mut() {
+            guard.subsystem_failures.insert(subsystem, message);
+        }
+
+        default_hook(info);
+    }));
+}
+
+/// 按修改时间取最近的若干个日志文件
+fn latest_log_files(dirs: &[PathBuf], count: usize) -> Vec<PathBuf> {
+    let mut files: Vec<(SystemTime, PathBuf)> = dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().and_then(|ext| ext.to_str()) == Some("log"))
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|meta| meta.modified()).ok()?;
+            Some((modified, path))
+        })
+        .collect();
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    files.into_iter().take(count).map(|(_, path)| path).collect()
+}
+
+/// 生成脱敏后的设置快照，API 密钥仅保留掩码
+fn redacted_settings_snapshot(settings: &AppSettingsData) -> Result<String, String> {
+    let mut value = serde_json::to_value(settings).map_err(|e| format!("序列化设置失败: {}", e))?;
+    if let Some(configs) = value
+        .get_mut("provider_configs")
+        .and_then(|configs| configs.as_object_mut())
+    {
+        for config in configs.values_mut() {
+            if let Some(config) = config.as_object_mut() {
+                config.insert(
+                    "encrypted_api_key".to_string(),
+                    serde_json::Value::String("<redacted>".to_string()),
+                );
+            }
+        }
+    }
+    if let Some(object) = value.as_object_mut() {
+        object.insert(
+            "masked_api_key".to_string(),
+            serde_json::Value::String(settings.get_masked_api_key()),
+        );
+    }
+    serde_json::to_string_pretty(&value).map_err(|e| format!("序列化设置失败: {}", e))
+}
+
+/// 生成诊断包（最近日志 + 脱敏设置 + 子系统状态）到指定路径
+pub fn write_diagnostics_bundle(
+    target: &Path,
+    log_dirs: &[PathBuf],
+    settings: &AppSettingsData,
+    subsystem_failures: &std::collections::HashMap<String, String>,
+) -> Result<(), String> {
+    let file = File::create(target).map_err(|e| format!("创建诊断包失败: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored);
+
+    for path in latest_log_files(log_dirs, DIAGNOSTICS_LOG_FILE_COUNT) {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "log.log".to_string());
+        let contents = fs::read(&path).map_err(|e| format!("读取日志文件失败: {}", e))?;
+        zip.start_file(format!("logs/{}", name), options)
+            .map_err(|e| format!("写入诊断包失败: {}", e))?;
+        zip.write_all(&contents)
+            .map_err(|e| format!("写入诊断包失败: {}", e))?;
+    }
+
+    zip.start_file("settings.json", options)
+        .map_err(|e| format!("写入诊断包失败: {}", e))?;
+    zip.write_all(redacted_settings_snapshot(settings)?.as_bytes())
+        .map_err(|e| format!("写入诊断包失败: {}", e))?;
+
+    let status = serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "subsystem_failures": subsystem_failures,
+    });
+    zip.start_file("status.json", options)
+        .map_err(|e| format!("写入诊断包失败: {}", e))?;
+    zip.write_all(status.to_string().as_bytes())
+        .map_err(|e| format!("写入诊断包失败: {}", e))?;
+
+    zip.finish().map_err(|e| format!("写入诊断包失败: {}", e))?;
+    log::info!("诊断包已生成: {:?}", target);
+    Ok(())
+}
+
+/// 收集当前日志目录与状态，生成诊断包
+pub fn generate_diagnostics_bundle(
+    app_handle: &AppHandle,
+    state: &Arc<Mutex<AppState>>,
+    target: &Path,
+) -> Result<(), String> {
+    let mut log_dirs = vec![get_logs_dir_path()];
+    if let Ok(app_log_dir) = app_handle.path().app_log_dir() {
+        log_dirs.push(app_log_dir);
+    }
+    let (settings, subsystem_failures) = {
+        let state_guard = state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        (
+            state_guard.settings.clone(),
+            state_guard.subsystem_failures.clone(),
+        )
+    };
+    write_diagnostics_bundle(target, &log_dirs, &settings, &subsystem_failures)
+}
@@ -1,12 +1,22 @@
 use log::LevelFilter;
-use std::time::Instant;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 use tauri_plugin_log::Target;
 #[cfg(debug_assertions)]
 use tauri_plugin_log::TargetKind;
 
+use crate::core::diagnostics::spawn_named;
 #[cfg(debug_assertions)]
 use crate::utils::utils_helpers::get_logs_dir_path;
 
+/// AI 请求记录文件名
+const AI_TRANSCRIPT_FILE_NAME: &str = "ai_transcript.log";
+/// AI 请求记录文件超过该大小后轮转
+const AI_TRANSCRIPT_MAX_SIZE: u64 = 2 * 1024 * 1024;
+
 /// 日志配置结构体
 pub struct LogConfig {
     pub level: LevelFilter,
@@ -41,8 +51,9 @@ impl Default for LogConfig {
 pub fn build_logger() -> tauri_plugin_log::Builder {
     let config = LogConfig::default();
 
+    // 插件本身放行全部级别，实际级别在启动后按设置通过 log::set_max_level 控制，便于运行时调整
     let mut builder = tauri_plugin_log::Builder::new()
-        .level(config.level)
+        .level(LevelFilter::Trace)
         .max_file_size(config.max_file_size)
         .rotation_strategy(tauri_plugin_log::RotationStrategy::KeepAll)
         .filter(|metadata| {
@@ -59,9 +70,85 @@ pub fn build_logger() -> tauri_plugin_log::Builder {
         builder = builder.target(target);
     }
 
+    log::set_max_level(config.level);
     builder
 }
 
+/// 解析日志级别字符串
+pub fn parse_level_filter(level: &str) -> Option<LevelFilter> {
+    match level {
+        "trace" => Some(LevelFilter::Trace),
+        "debug" => Some(LevelFilter::Debug),
+        "info" => Some(LevelFilter::Info),
+        "warn" => Some(LevelFilter::Warn),
+        "error" => Some(LevelFilter::Error),
+        _ => None,
+    }
+}
+
+/// 运行时应用日志级别
+pub fn apply_log_level(level: &str) -> Result<(), String> {
+    let filter = parse_level_filter(level).ok_or_else(|| format!("不支持的日志级别: {}", level))?;
+    log::set_max_level(filter);
+    log::info!("日志级别已设置为 {}", level);
+    Ok(())
+}
+
+/// 保存设置时切换日志级别，记录变更前后的级别
+pub fn apply_log_level_change(new_level: &str) -> Result<(), String> {
+    let old_level = log::max_level();
+    apply_log_level(new_level)?;
+    log::info!("日志级别由 {} 调整为 {}", old_level, new_level);
+    Ok(())
+}
+
+/// 删除目录中的 .log 文件；指定 older_than 时只删除修改时间早于该时长的文件，返回删除数量
+pub fn remove_log_files(dir: &Path, older_than: Option<Duration>) -> Result<usize, String> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let now = SystemTime::now();
+    let mut removed = 0;
+    for entry in fs::read_dir(dir).map_err(|e| format!("读取日志目录失败: {}", e))? {
+        let path = entry.map_err(|e| format!("读取日志目录失败: {}", e))?.path();
+        if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("log") {
+            continue;
+        }
+        if let Some(max_age) = older_than {
+            let expired = fs::metadata(&path)
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age > max_age);
+            if !expired {
+                continue;
+            }
+        }
+        fs::remove_file(&path).map_err(|e| format!("删除日志文件失败: {}", e))?;
+        log::info!("删除日志文件: {:?}", path);
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+/// 启动日志保留清理任务：启动时执行一次，之后每天执行一次
+pub fn start_log_retention_task<F>(log_dirs: Vec<PathBuf>, retention_days: F)
+where
+    F: Fn() -> u32 + Send + 'static,
+{
+    spawn_named("log-retention", move || loop {
+        let max_age = Duration::from_secs(u64::from(retention_days()) * 24 * 60 * 60);
+        for dir in &log_dirs {
+            match remove_log_files(dir, Some(max_age)) {
+                Ok(0) => {}
+                Ok(removed) => log::info!("已清理过期日志 {} 个: {:?}", removed, dir),
+                Err(e) => log::warn!("清理过期日志失败: {}", e),
+            }
+        }
+        thread::sleep(Duration::from_secs(24 * 60 * 60));
+    });
+}
+
 /// 性能埋点工具
 pub struct PerfTracer {
     name: String,
@@ -85,3 +172,42 @@ impl PerfTracer {
         log::info!("[Perf End] {} - cost: {:?}", self.name, duration);
     }
 }
+
+/// 追加一条 AI 请求记录（提示词与完整响应），文本中的疑似密钥会被掩码；
+/// 文件超过上限时轮转为 ai_transcript.log.1
+pub fn append_ai_transcript(kind: &str, model: &str, prompt: &str, response: &str, outcome: &str) {
+    let dir = crate::utils::utils_helpers::get_logs_dir_path();
+    if let Err(e) = fs::create_dir_all(&dir) {
+        log::warn!("创建日志目录失败: {}", e);
+        return;
+    }
+    let path = dir.join(AI_TRANSCRIPT_FILE_NAME);
+    if fs::metadata(&path).is_ok_and(|meta| meta.len() >= AI_TRANSCRIPT_MAX_SIZE) {
+        let rotated = dir.join(format!("{}.1", AI_TRANSCRIPT_FILE_NAME));
+        if let Err(e) = fs::rename(&path, &rotated) {
+            log::warn!("轮转AI请求记录失败: {}", e);
+        }
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let entry = format!(
+        "===== [{}] type={} model={} outcome={} =====\n--- prompt ---\n{}\n--- response ---\n{}\n\n",
+        timestamp,
+        kind,
+        model,
+        outcome,
+        crate::utils::utils_helpers::mask_secrets(prompt),
+        crate::utils::utils_helpers::mask_secrets(response)
+    );
+    let result = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(entry.as_bytes()));
+    if let Err(e) = result {
+        log::warn!("写入AI请求记录失败: {}", e);
+    }
+}
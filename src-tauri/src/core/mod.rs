@@ -1,4 +1,9 @@
 pub mod app_state;
+pub mod chord_shortcuts;
+pub mod cli;
 pub mod config;
+pub mod diagnostics;
 pub mod error;
 pub mod logger;
+pub mod single_instance;
+pub mod ui_worker;
@@ -0,0 +1,217 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use crate::core::cli::CliResponse;
+use crate::core::diagnostics::spawn_named;
+use crate::services::http_api::generate_token;
+use crate::utils::utils_helpers::constant_time_eq;
+
+/// 单实例锁名称，同时用作实例间通信的握手标识
+pub const SINGLE_INSTANCE_LOCK_NAME: &str = "fuyun_tools";
+/// 单实例通信端口（仅监听本机回环地址）
+const SINGLE_INSTANCE_PORT: u16 = 47913;
+const ACTIVATE_MESSAGE: &str = "activate";
+const ACK_MESSAGE: &str = "ok";
+/// 命令行请求前缀，格式为 {锁名称}:cli:{通道密钥}:{JSON}
+const CLI_MESSAGE: &str = "cli";
+/// 命令行通道密钥文件名，位于仅当前用户可访问的目录
+const CLI_SECRET_FILE_NAME: &str = "cli.secret";
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(300);
+/// 等待运行中实例处理命令行请求的超时时间（翻译可能较慢）
+const CLI_RESPONSE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// 获取单实例锁的结果
+pub enum InstanceLock {
+    /// 当前进程为首个实例，持有监听器
+    Primary(TcpListener),
+    /// 已通知正在运行的实例激活窗口，当前进程应退出
+    ActivatedExisting,
+    /// 无法获取锁（端口被其他程序占用），按无单实例保护继续运行
+    Unavailable,
+}
+
+fn instance_addr() -> SocketAddr {
+    SocketAddr::from((Ipv4Addr::LOCALHOST, SINGLE_INSTANCE_PORT))
+}
+
+/// 尝试获取单实例锁；若已有实例运行则通知其激活
+pub fn acquire_instance_lock() -> InstanceLock {
+    if notify_running_instance() {
+        return InstanceLock::ActivatedExisting;
+    }
+    match TcpListener::bind(instance_addr()) {
+        Ok(listener) => InstanceLock::Primary(listener),
+        Err(e) => {
+            log::warn!("获取单实例锁失败，跳过单实例检测: {}", e);
+            InstanceLock::Unavailable
+        }
+    }
+}
+
+/// 向已运行的实例发送激活请求，返回是否成功
+fn notify_running_instance() -> bool {
+    let Ok(mut stream) = TcpStream::connect_timeout(&instance_addr(), CONNECT_TIMEOUT) else {
+        return false;
+    };
+    let _ = stream.set_read_timeout(Some(CONNECT_TIMEOUT));
+    let message = format!("{}:{}\n", SINGLE_INSTANCE_LOCK_NAME, ACTIVATE_MESSAGE);
+    if stream.write_all(message.as_bytes()).is_err() {
+        return false;
+    }
+    let mut response = String::new();
+    if BufReader::new(&stream).read_line(&mut response).is_err() {
+        return false;
+    }
+    response.trim() == format!("{}:{}", SINGLE_INSTANCE_LOCK_NAME, ACK_MESSAGE)
+}
+
+/// 命令行通道密钥文件所在目录：Windows 为当前用户的 LOCALAPPDATA，
+/// 其他系统优先使用 XDG_RUNTIME_DIR，否则为主目录下的隐藏目录
+fn cli_secret_dir() -> Option<PathBuf> {
+    #[cfg(target_os = "windows")]
+    let base = std::env::var_os("LOCALAPPDATA").map(|dir| PathBuf::from(dir).join(SINGLE_INSTANCE_LOCK_NAME));
+    #[cfg(not(target_os = "windows"))]
+    let base = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|dir| PathBuf::from(dir).join(SINGLE_INSTANCE_LOCK_NAME))
+        .or_else(|| {
+            std::env::var_os("HOME").map(|dir| PathBuf::from(dir).join(format!(".{}", SINGLE_INSTANCE_LOCK_NAME)))
+        });
+    base
+}
+
+/// 生成本次运行的命令行通道密钥并写入仅当前用户可读写的文件
+fn write_cli_secret() -> Result<String, String> {
+    let dir = cli_secret_dir().ok_or_else(|| "无法确定命令行通道密钥目录".to_string())?;
+    create_private_dir(&dir).map_err(|e| format!("创建命令行通道密钥目录失败: {}", e))?;
+    let secret = generate_token()?;
+    write_private_file(&dir.join(CLI_SECRET_FILE_NAME), &secret)
+        .map_err(|e| format!("写入命令行通道密钥失败: {}", e))?;
+    Ok(secret)
+}
+
+/// 读取运行中实例写入的命令行通道密钥
+fn read_cli_secret() -> Result<String, String> {
+    let path = cli_secret_dir()
+        .ok_or_else(|| "无法确定命令行通道密钥目录".to_string())?
+        .join(CLI_SECRET_FILE_NAME);
+    fs::read_to_string(&path)
+        .map(|secret| secret.trim().to_string())
+        .map_err(|e| format!("读取命令行通道密钥失败: {}", e))
+}
+
+#[cfg(unix)]
+fn create_private_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    fs::DirBuilder::new().recursive(true).mode(0o700).create(dir)?;
+    fs::set_permissions(dir, fs::Permissions::from_mode(0o700))
+}
+
+#[cfg(not(unix))]
+fn create_private_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir)
+}
+
+#[cfg(unix)]
+fn write_private_file(path: &std::path::Path, content: &str) -> std::io::Result<()> {
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    // 文件已存在时 mode 不生效，显式收紧权限
+    file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    file.write_all(content.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn write_private_file(path: &std::path::Path, content: &str) -> std::io::Result<()> {
+    fs::write(path, content)
+}
+
+/// 向运行中的实例发送命令行请求并返回其单行响应；没有运行中的实例时返回 None
+pub fn send_cli_request(payload: &str) -> Option<Result<String, String>> {
+    let mut stream = TcpStream::connect_timeout(&instance_addr(), CONNECT_TIMEOUT).ok()?;
+    let _ = stream.set_read_timeout(Some(CLI_RESPONSE_TIMEOUT));
+    let secret = match read_cli_secret() {
+        Ok(secret) => secret,
+        Err(e) => return Some(Err(e)),
+    };
+    let message = format!("{}:{}:{}:{}\n", SINGLE_INSTANCE_LOCK_NAME, CLI_MESSAGE, secret, payload);
+    if let Err(e) = stream.write_all(message.as_bytes()) {
+        return Some(Err(format!("发送命令行请求失败: {}", e)));
+    }
+    let mut response = String::new();
+    Some(match BufReader::new(&stream).read_line(&mut response) {
+        Ok(0) => Err("运行中的实例未返回响应".to_string()),
+        Ok(_) => Ok(response.trim_end().to_string()),
+        Err(e) => Err(format!("读取命令行响应失败: {}", e)),
+    })
+}
+
+/// 启动单实例监听线程，收到激活请求时调用 on_activate；
+/// 命令行请求校验通道密钥后在独立线程中交给 on_cli 处理，其返回值作为单行响应写回。
+/// 密钥文件写入失败时不接受命令行请求
+pub fn start_instance_listener<F, C>(
+    listener: TcpListener,
+    app_handle: AppHandle,
+    on_activate: F,
+    on_cli: C,
+) where
+    F: Fn(&AppHandle) + Send + 'static,
+    C: Fn(&AppHandle, &str) -> String + Send + Sync + 'static,
+{
+    let on_cli = Arc::new(on_cli);
+    let cli_prefix = format!("{}:{}:", SINGLE_INSTANCE_LOCK_NAME, CLI_MESSAGE);
+    let cli_secret = write_cli_secret()
+        .inspect_err(|e| log::error!("{}，命令行请求将被拒绝", e))
+        .ok();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let _ = stream.set_read_timeout(Some(CONNECT_TIMEOUT));
+            let mut request = String::new();
+            if BufReader::new(&stream).read_line(&mut request).is_err() {
+                continue;
+            }
+            if let Some(message) = request.trim().strip_prefix(&cli_prefix) {
+                let authorized = match (message.split_once(':'), cli_secret.as_deref()) {
+                    (Some((secret, payload)), Some(expected)) if constant_time_eq(secret, expected) => {
+                        Some(payload.to_string())
+                    }
+                    _ => None,
+                };
+                let Some(payload) = authorized else {
+                    log::warn!("拒绝未通过认证的命令行请求");
+                    let response = serde_json::to_string(&CliResponse::failure("命令行通道认证失败"))
+                        .unwrap_or_else(|_| r#"{"ok":false}"#.to_string());
+                    let _ = stream.write_all(format!("{}\n", response).as_bytes());
+                    continue;
+                };
+                let on_cli = on_cli.clone();
+                let app_handle = app_handle.clone();
+                spawn_named("cli-request", move || {
+                    let response = on_cli(&app_handle, &payload);
+                    let _ = stream.write_all(format!("{}\n", response).as_bytes());
+                });
+                continue;
+            }
+            if request.trim() != format!("{}:{}", SINGLE_INSTANCE_LOCK_NAME, ACTIVATE_MESSAGE) {
+                continue;
+            }
+            let ack = format!("{}:{}\n", SINGLE_INSTANCE_LOCK_NAME, ACK_MESSAGE);
+            let _ = stream.write_all(ack.as_bytes());
+            log::info!("收到新实例的激活请求");
+            on_activate(&app_handle);
+        }
+    });
+}
@@ -0,0 +1,104 @@
+use crate::core::diagnostics::spawn_named;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::mpsc::{self, Sender};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+
+pub type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// 提交短任务的方式，回填等路径通过它排队任务，便于替换与测试
+pub trait JobSpawner: Send + Sync {
+    fn spawn(&self, job: Job);
+}
+
+/// 在共享工作线程上按提交顺序执行任务，不为单个任务创建线程
+pub struct UiWorkerSpawner;
+
+impl JobSpawner for UiWorkerSpawner {
+    fn spawn(&self, job: Job) {
+        if worker_sender().send(job).is_err() {
+            log::error!("UI工作线程已退出，任务未执行");
+        }
+    }
+}
+
+/// 回填、划词等需要阻塞等待的短任务共用一个工作线程，避免每次操作都创建新线程
+static UI_WORKER: OnceLock<Sender<Job>> = OnceLock::new();
+
+fn worker_sender() -> &'static Sender<Job> {
+    UI_WORKER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel::<Job>();
+        spawn_named("ui-worker", move || {
+            while let Ok(job) = rx.recv() {
+                // 单个任务崩溃不影响后续任务
+                if catch_unwind(AssertUnwindSafe(job)).is_err() {
+                    log::error!("UI工作线程任务发生崩溃");
+                }
+            }
+        });
+        tx
+    })
+}
+
+/// 在共享工作线程上按提交顺序执行任务；任务内可阻塞（等待窗口隐藏、模拟按键等）
+pub fn run_on_ui_worker<F>(job: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    UiWorkerSpawner.spawn(Box::new(job));
+}
+
+/// 延迟 delay 后在共享工作线程上执行任务
+pub fn run_on_ui_worker_after<F>(delay: Duration, job: F)
+where
+    F: FnOnce() + Send + 'static,
+{
+    run_on_ui_worker(move || {
+        thread::sleep(delay);
+        job();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jobs_run_in_order_on_one_shared_thread() {
+        let spawner: &dyn JobSpawner = &UiWorkerSpawner;
+        let (tx, rx) = mpsc::channel();
+        for i in 0..20 {
+            let tx = tx.clone();
+            let job = move || {
+                let current = thread::current();
+                let _ = tx.send((i, current.id(), current.name().map(str::to_string)));
+            };
+            match i % 3 {
+                0 => spawner.spawn(Box::new(job)),
+                1 => run_on_ui_worker(job),
+                _ => run_on_ui_worker_after(Duration::from_millis(1), job),
+            }
+        }
+        let results: Vec<_> = (0..20)
+            .map(|_| rx.recv_timeout(Duration::from_secs(5)).expect("任务未执行"))
+            .collect();
+
+        assert_eq!(results.iter().map(|(i, _, _)| *i).collect::<Vec<_>>(), (0..20).collect::<Vec<_>>());
+        let worker_id = results[0].1;
+        assert_ne!(worker_id, thread::current().id());
+        // 所有任务都在同一个命名工作线程上执行，没有为单个任务创建线程
+        assert!(results.iter().all(|(_, id, _)| *id == worker_id));
+        assert!(results.iter().all(|(_, _, name)| name.as_deref() == Some("ui-worker")));
+    }
+
+    #[test]
+    fn panicking_job_does_not_stop_worker() {
+        run_on_ui_worker(|| panic!("测试任务崩溃"));
+        let (tx, rx) = mpsc::channel();
+        run_on_ui_worker(move || {
+            let _ = tx.send(());
+        });
+        assert!(rx.recv_timeout(Duration::from_secs(5)).is_ok());
+    }
+}
@@ -0,0 +1,395 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::core::app_state::{clipboard_manager_of, lock_checked, AppState};
+use crate::ui::window_manager::show_result_window;
+
+/// 参数模板中替换为输入文本的占位符
+const TEXT_PLACEHOLDER: &str = "{text}";
+/// 参数模板中替换为输入文本临时文件路径的占位符
+const FILE_PLACEHOLDER: &str = "{file}";
+/// 未配置超时时间时的默认值
+const DEFAULT_CUSTOM_ACTION_TIMEOUT_SECS: u64 = 10;
+/// 超时时间上限
+const MAX_CUSTOM_ACTION_TIMEOUT_SECS: u64 = 120;
+/// stdout 与 stderr 各自最多读取的字节数，超出部分丢弃
+const CUSTOM_ACTION_MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+/// 等待进程退出时的轮询间隔
+const CUSTOM_ACTION_POLL_INTERVAL: Duration = Duration::from_millis(20);
+/// 进程超时被终止后等待输出读取线程结束的最长时间
+const CUSTOM_ACTION_READER_GRACE: Duration = Duration::from_millis(500);
+
+/// 自定义动作输出的去向
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CustomActionDestination {
+    /// 替换剪贴板内容
+    Clipboard,
+    /// 显示在结果窗口
+    ResultWindow,
+    /// 写入剪贴板后粘贴到前台窗口
+    Paste,
+}
+
+/// 将划词或历史文本交给外部命令处理的自定义动作，只能在本地设置文件中定义。
+/// args 中的 {text} 替换为输入文本，{file} 替换为写有输入文本的临时文件路径；
+/// 两者都未使用时输入文本通过 stdin 传入
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CustomAction {
+    pub id: String,
+    pub name: String,
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    pub destination: CustomActionDestination,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl CustomAction {
+    /// 检查动作配置是否有效
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() || self.name.trim().is_empty() {
+            return Err("自定义动作的标识和名称都不能为空".to_string());
+        }
+        if self.program.trim().is_empty() {
+            return Err(format!("自定义动作 {} 未指定可执行程序", self.name));
+        }
+        if matches!(self.timeout_secs, Some(timeout) if !(1..=MAX_CUSTOM_ACTION_TIMEOUT_SECS).contains(&timeout)) {
+            return Err(format!(
+                "自定义动作 {} 的 timeout_secs 必须在1-{}之间",
+                self.name, MAX_CUSTOM_ACTION_TIMEOUT_SECS
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 检查动作列表，标识不能重复
+pub fn validate_custom_actions(actions: &[CustomAction]) -> Result<(), String> {
+    let mut ids = HashSet::new();
+    for action in actions {
+        action.validate()?;
+        if !ids.insert(action.id.as_str()) {
+            return Err(format!("自定义动作标识重复: {}", action.id));
+        }
+    }
+    Ok(())
+}
+
+/// 外部命令的执行结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CustomActionOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub truncated: bool,
+}
+
+/// 执行设置中定义的自定义动作，并将 stdout 送往配置的去向
+pub async fn run_custom_action(
+    app: &AppHandle,
+    state: &Arc<Mutex<AppState>>,
+    action_id: &str,
+    text: String,
+) -> Result<CustomActionOutput, String> {
+    let action = state
+        .lock()
+        .unwrap()
+        .settings
+        .custom_actions
+        .iter()
+        .find(|action| action.id == action_id)
+        .cloned()
+        .ok_or_else(|| format!("未找到自定义动作: {}", action_id))?;
+    action.validate()?;
+
+    log::info!("执行自定义动作: {} ({})", action.name, action.program);
+    let input = text.clone();
+    let action_for_run = action.clone();
+    let output = tauri::async_runtime::spawn_blocking(move || execute(&action_for_run, &input))
+        .await
+        .map_err(|e| format!("自定义动作任务异常: {}", e))??;
+    if output.truncated {
+        log::warn!("自定义动作 {} 的输出超过 {} 字节，已截断", action.name, CUSTOM_ACTION_MAX_OUTPUT_BYTES);
+    }
+
+    match action.destination {
+        CustomActionDestination::Clipboard | CustomActionDestination::Paste => {
+            {
+                let clipboard_manager = clipboard_manager_of(state);
+                let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+                manager.set_clipboard_content(app, &output.stdout)?;
+            }
+            if action.destination == CustomActionDestination::Paste {
+                crate::ui::window_manager::simulate_paste().map_err(|e| format!("自动粘贴失败: {}", e))?;
+            }
+        }
+        CustomActionDestination::ResultWindow => {
+            show_result_window(
+                action.name.clone(),
+                output.stdout.clone(),
+                "custom_action".to_string(),
+                text,
+                String::new(),
+                app.clone(),
+            )
+            .await?;
+        }
+    }
+    Ok(output)
+}
+
+/// 启动进程、传入输入并在超时前收集输出
+fn execute(action: &CustomAction, text: &str) -> Result<CustomActionOutput, String> {
+    let uses_text = action.args.iter().any(|arg| arg.contains(TEXT_PLACEHOLDER));
+    let input_file = if action.args.iter().any(|arg| arg.contains(FILE_PLACEHOLDER)) {
+        Some(write_input_file(text)?)
+    } else {
+        None
+    };
+    let file_arg = input_file
+        .as_ref()
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let args: Vec<String> = action
+        .args
+        .iter()
+        .map(|arg| arg.replace(FILE_PLACEHOLDER, &file_arg).replace(TEXT_PLACEHOLDER, text))
+        .collect();
+    let use_stdin = !uses_text && input_file.is_none();
+
+    let result = spawn_and_wait(action, &args, use_stdin.then_some(text));
+    if let Some(path) = input_file {
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
+
+fn spawn_and_wait(
+    action: &CustomAction,
+    args: &[String],
+    stdin_text: Option<&str>,
+) -> Result<CustomActionOutput, String> {
+    let mut command = Command::new(action.program.trim());
+    command
+        .args(args)
+        .stdin(if stdin_text.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    hide_console_window(&mut command);
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("启动自定义动作 {} 失败: {}", action.name, e))?;
+
+    if let (Some(text), Some(mut stdin)) = (stdin_text, child.stdin.take()) {
+        let text = text.to_string();
+        thread::spawn(move || {
+            let _ = stdin.write_all(text.as_bytes());
+        });
+    }
+    let stdout_reader = spawn_output_reader(child.stdout.take());
+    let stderr_reader = spawn_output_reader(child.stderr.take());
+
+    let timeout = Duration::from_secs(action.timeout_secs.unwrap_or(DEFAULT_CUSTOM_ACTION_TIMEOUT_SECS));
+    let status = match wait_with_timeout(&mut child, timeout) {
+        Ok(status) => status,
+        Err(e) => {
+            reap_output_readers([stdout_reader, stderr_reader]);
+            return Err(format!("自定义动作 {} {}", action.name, e));
+        }
+    };
+    let (stdout, stdout_truncated) = stdout_reader.join().unwrap_or_default();
+    let (stderr, stderr_truncated) = stderr_reader.join().unwrap_or_default();
+
+    if !status.success() {
+        return Err(format!(
+            "自定义动作 {} 执行失败({}): {}",
+            action.name,
+            status,
+            stderr.trim()
+        ));
+    }
+    Ok(CustomActionOutput {
+        stdout,
+        stderr,
+        truncated: stdout_truncated || stderr_truncated,
+    })
+}
+
+/// 等待进程退出，超时后终止进程
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Result<std::process::ExitStatus, String> {
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Ok(status),
+            Ok(None) if started.elapsed() >= timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("执行超时（{} 秒），已终止", timeout.as_secs()));
+            }
+            Ok(None) => thread::sleep(CUSTOM_ACTION_POLL_INTERVAL),
+            Err(e) => return Err(format!("等待进程失败: {}", e)),
+        }
+    }
+}
+
+/// 进程被终止后回收输出读取线程：管道关闭后线程很快结束，在宽限时间内等到则 join；
+/// 进程派生的子进程仍持有管道时读取线程会一直阻塞，超过宽限时间后分离，管道关闭时自行退出
+fn reap_output_readers(readers: [thread::JoinHandle<(String, bool)>; 2]) {
+    let deadline = Instant::now() + CUSTOM_ACTION_READER_GRACE;
+    for reader in readers {
+        while !reader.is_finished() && Instant::now() < deadline {
+            thread::sleep(CUSTOM_ACTION_POLL_INTERVAL);
+        }
+        if reader.is_finished() {
+            let _ = reader.join();
+        } else {
+            log::warn!("自定义动作的输出管道仍被其他进程占用，读取线程已分离");
+        }
+    }
+}
+
+/// 在后台线程读取输出，超过上限的部分读出后丢弃，避免子进程因管道写满而阻塞
+fn spawn_output_reader<R: Read + Send + 'static>(source: Option<R>) -> thread::JoinHandle<(String, bool)> {
+    thread::spawn(move || {
+        let Some(mut source) = source else {
+            return (String::new(), false);
+        };
+        let mut collected = Vec::new();
+        let mut buffer = [0u8; 8192];
+        let mut truncated = false;
+        while let Ok(read) = source.read(&mut buffer) {
+            if read == 0 {
+                break;
+            }
+            let remaining = CUSTOM_ACTION_MAX_OUTPUT_BYTES.saturating_sub(collected.len());
+            if read > remaining {
+                truncated = true;
+            }
+            collected.extend_from_slice(&buffer[..read.min(remaining)]);
+        }
+        (String::from_utf8_lossy(&collected).to_string(), truncated)
+    })
+}
+
+/// 将输入文本写入临时文件，执行结束后删除
+fn write_input_file(text: &str) -> Result<PathBuf, String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let path = std::env::temp_dir().join(format!("fuyun_tools_action_{}_{}.txt", std::process::id(), timestamp));
+    std::fs::write(&path, text).map_err(|e| format!("写入自定义动作输入文件失败: {}", e))?;
+    Ok(path)
+}
+
+/// Windows 上不为控制台程序弹出命令行窗口
+#[cfg(target_os = "windows")]
+fn hide_console_window(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    command.creation_flags(CREATE_NO_WINDOW);
+}
+
+#[cfg(not(target_os = "windows"))]
+fn hide_console_window(_command: &mut Command) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(id: &str, args: &[&str], timeout_secs: Option<u64>) -> CustomAction {
+        CustomAction {
+            id: id.to_string(),
+            name: format!("动作{}", id),
+            program: "sh".to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            destination: CustomActionDestination::Clipboard,
+            timeout_secs,
+        }
+    }
+
+    #[test]
+    fn validate_rejects_duplicate_ids_and_bad_timeouts() {
+        assert!(validate_custom_actions(&[action("a", &[], None), action("b", &[], Some(120))]).is_ok());
+
+        let duplicate = validate_custom_actions(&[action("a", &[], None), action("a", &[], Some(5))]);
+        assert!(duplicate.unwrap_err().contains("标识重复"));
+
+        for timeout in [0, MAX_CUSTOM_ACTION_TIMEOUT_SECS + 1] {
+            let result = validate_custom_actions(&[action("a", &[], Some(timeout))]);
+            assert!(result.unwrap_err().contains("timeout_secs"), "timeout {}", timeout);
+        }
+
+        let mut no_program = action("a", &[], None);
+        no_program.program = "  ".to_string();
+        assert!(validate_custom_actions(&[no_program]).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn text_placeholder_is_passed_as_argument_without_stdin() {
+        // 未通过 stdin 传入时 cat 读到空输入，只输出参数中的文本
+        let action = action("a", &["-c", "cat; printf '%s' \"$0\"", "{text}"], None);
+        let output = execute(&action, "划词 'text' $HOME").unwrap();
+        assert_eq!(output.stdout, "划词 'text' $HOME");
+        assert!(!output.truncated);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn file_placeholder_writes_temporary_input_file() {
+        let action_cat = action("a", &["-c", "cat \"$0\"", "{file}"], None);
+        assert_eq!(execute(&action_cat, "文件输入\n第二行").unwrap().stdout, "文件输入\n第二行");
+
+        // 执行结束后临时文件被删除
+        let action_path = action("b", &["-c", "printf '%s' \"$0\"", "{file}"], None);
+        let path = execute(&action_path, "内容").unwrap().stdout;
+        assert!(path.contains("fuyun_tools_action_"));
+        assert!(!std::path::Path::new(&path).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn input_goes_to_stdin_without_placeholders() {
+        let action = action("a", &["-c", "tr a-z A-Z"], None);
+        assert_eq!(execute(&action, "stdin text").unwrap().stdout, "STDIN TEXT");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn output_is_truncated_at_limit() {
+        let script = format!("head -c {} /dev/zero | tr '\\0' x", CUSTOM_ACTION_MAX_OUTPUT_BYTES + 4096);
+        let action = action("a", &["-c", &script], None);
+        let output = execute(&action, "").unwrap();
+        assert!(output.truncated);
+        assert_eq!(output.stdout.len(), CUSTOM_ACTION_MAX_OUTPUT_BYTES);
+        assert!(output.stdout.bytes().all(|byte| byte == b'x'));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn timeout_kills_process_without_waiting_for_held_pipes() {
+        // sh 被终止后 sleep 仍持有输出管道，读取线程在宽限时间后被分离
+        let action = action("a", &["-c", "sleep 5; echo done"], Some(1));
+        let started = Instant::now();
+        let error = execute(&action, "").unwrap_err();
+        assert!(error.contains("超时"), "{}", error);
+        assert!(started.elapsed() < Duration::from_secs(3), "{:?}", started.elapsed());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_command_reports_stderr() {
+        let action = action("a", &["-c", "echo 出错了 >&2; exit 3"], None);
+        let error = execute(&action, "").unwrap_err();
+        assert!(error.contains("出错了"), "{}", error);
+    }
+}
@@ -0,0 +1,132 @@
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+use crate::core::app_state::{AppState as SharedAppState, UI_FLAGS};
+use crate::utils::utils_helpers::save_settings;
+
+/// 免打扰是否生效：手动开启，或开启了全屏自动免打扰且前台为全屏应用。
+/// 生效期间暂停剪贴板记录、划词检测与划词工具栏，剪贴板面板快捷键不受影响
+pub fn is_active() -> bool {
+    UI_FLAGS.is_do_not_disturb() || (UI_FLAGS.is_auto_dnd_fullscreen() && is_foreground_fullscreen())
+}
+
+/// 按设置初始化免打扰标志，启动时调用
+pub fn init_from_settings(state: &Arc<Mutex<SharedAppState>>) {
+    let state_guard = state.lock().unwrap();
+    UI_FLAGS.set_do_not_disturb(state_guard.settings.do_not_disturb);
+    UI_FLAGS.set_auto_dnd_fullscreen(state_guard.settings.auto_dnd_fullscreen);
+}
+
+/// 开启或关闭免打扰：保存设置、同步托盘勾选状态并通知前端
+pub fn set_enabled(
+    app: &AppHandle,
+    state: &Arc<Mutex<SharedAppState>>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = state.lock().unwrap().settings.clone();
+    if settings.do_not_disturb != enabled {
+        settings.do_not_disturb = enabled;
+        save_settings(&settings).map_err(|e| e.to_string())?;
+    }
+    UI_FLAGS.set_do_not_disturb(enabled);
+
+    let tray_item = {
+        let mut state_guard = state.lock().unwrap();
+        state_guard.settings = settings;
+        state_guard
+            .tray_menu_items
+            .as_ref()
+            .map(|items| items.dnd_item.clone())
+    };
+    if let Some(item) = tray_item {
+        let _ = item.set_checked(enabled);
+    }
+    if enabled {
+        if let Some(toolbar) = app.get_webview_window("selection_toolbar") {
+            let _ = toolbar.hide();
+        }
+    }
+    if let Err(e) = app.emit("do-not-disturb-changed", enabled) {
+        log::error!("发送免打扰状态事件失败: {}", e);
+    }
+    log::info!("免打扰已{}", if enabled { "开启" } else { "关闭" });
+    Ok(())
+}
+
+/// 切换免打扰状态
+pub fn toggle(app: &AppHandle, state: &Arc<Mutex<SharedAppState>>) {
+    let enabled = !UI_FLAGS.is_do_not_disturb();
+    if let Err(e) = set_enabled(app, state, enabled) {
+        log::error!("切换免打扰失败: {}", e);
+    }
+}
+
+/// 注册免打扰切换快捷键，空字符串表示不注册
+pub fn register_shortcut(
+    app_handle: &AppHandle,
+    state: Arc<Mutex<SharedAppState>>,
+    hot_key: &str,
+) -> Result<(), String> {
+    if hot_key.trim().is_empty() {
+        return Ok(());
+    }
+    let app_for_shortcut = app_handle.clone();
+    app_handle
+        .global_shortcut()
+        .on_shortcut(hot_key, move |_app, _shortcut, event| {
+            if let ShortcutState::Pressed = event.state {
+                toggle(&app_for_shortcut, &state);
+            }
+        })
+        .map_err(|e| format!("注册免打扰快捷键失败: {}", e))
+}
+
+/// 前台窗口是否覆盖整个显示器（全屏游戏、演示、视频等）
+#[cfg(target_os = "windows")]
+fn is_foreground_fullscreen() -> bool {
+    use winapi::shared::windef::RECT;
+    use winapi::um::winuser::{
+        GetClassNameW, GetForegroundWindow, GetMonitorInfoW, GetShellWindow, GetWindowRect,
+        MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST,
+    };
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_null() || hwnd == GetShellWindow() {
+            return false;
+        }
+        // 桌面本身也是全屏大小，需要排除
+        let mut class_buffer = [0u16; 64];
+        let class_len = GetClassNameW(hwnd, class_buffer.as_mut_ptr(), class_buffer.len() as i32);
+        let class = String::from_utf16_lossy(&class_buffer[..class_len.max(0) as usize]);
+        if class == "WorkerW" || class == "Progman" {
+            return false;
+        }
+
+        let mut window_rect: RECT = std::mem::zeroed();
+        if GetWindowRect(hwnd, &mut window_rect) == 0 {
+            return false;
+        }
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        if monitor.is_null() {
+            return false;
+        }
+        let mut monitor_info: MONITORINFO = std::mem::zeroed();
+        monitor_info.cbSize = std::mem::size_of::<MONITORINFO>() as u32;
+        if GetMonitorInfoW(monitor, &mut monitor_info) == 0 {
+            return false;
+        }
+        let screen = monitor_info.rcMonitor;
+        window_rect.left <= screen.left
+            && window_rect.top <= screen.top
+            && window_rect.right >= screen.right
+            && window_rect.bottom >= screen.bottom
+    }
+}
+
+/// 非 Windows 平台不检测全屏应用
+#[cfg(not(target_os = "windows"))]
+fn is_foreground_fullscreen() -> bool {
+    false
+}
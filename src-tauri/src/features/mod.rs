@@ -1,2 +1,6 @@
+pub mod custom_actions;
+pub mod do_not_disturb;
 pub mod mouse_listener;
-pub mod text_selection;
\ No newline at end of file
+pub mod text_selection;
+#[cfg(target_os = "windows")]
+pub mod windows_text_selection;
\ No newline at end of file
@@ -1,16 +1,23 @@
 use log;
 use rdev::{listen, Button, EventType, Key};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender, TrySendError};
 use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
-use tauri::AppHandle;
-
-use crate::core::app_state::AppState as SharedAppState;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+use crate::core::app_state::{AppState as SharedAppState, UI_FLAGS};
+use crate::core::config::CAPTURE_SELECTION_DELAY;
+use crate::core::diagnostics::spawn_named;
+use crate::features::do_not_disturb;
+use crate::core::ui_worker::run_on_ui_worker_after;
 use crate::ui::window_manager::{
     handle_selection_toolbar_autoclose, hide_selection_toolbar_impl, show_selection_toolbar_impl,
 };
 use crate::utils::clipboard::ClipboardManager;
+use crate::utils::utils_helpers::{classify_content, save_settings, AppSettingsData, ContentType};
+use serde::Serialize;
 #[cfg(target_os = "windows")]
 use winapi::um::winuser::{GetAsyncKeyState, VK_LCONTROL, VK_RCONTROL};
 
@@ -21,14 +28,24 @@ enum MouseActionState {
     MouseUp(u64, u64, std::time::Instant),
 }
 
+/// 划词检测请求，由鼠标事件回调发送给检测线程
+#[derive(Debug, Clone, Copy)]
+struct DetectionRequest {
+    pos: (i32, i32),
+    timestamp: Instant,
+}
+
+/// 检测请求通道容量，突发请求在检测线程中合并为一次
+const DETECTION_CHANNEL_CAPACITY: usize = 8;
+/// 两次划词检测之间的最小间隔
+const DETECTION_DEBOUNCE: Duration = Duration::from_millis(100);
+
 struct GlobalState {
     mouse_action_state: Arc<Mutex<MouseActionState>>,
     ctrl_left_pressed: AtomicBool,
     ctrl_right_pressed: AtomicBool,
-    needs_detection: AtomicBool,
-    last_processed_time: Arc<Mutex<std::time::Instant>>,
+    detection_tx: Mutex<Option<SyncSender<DetectionRequest>>>,
     last_mouse_pos: Arc<Mutex<(u64, u64)>>,
-    detection_anchor_pos: Arc<Mutex<(i32, i32)>>,
     last_toolbar_emit: Arc<Mutex<Option<(String, (i32, i32), std::time::Instant)>>>,
     last_click: Arc<Mutex<Option<(u64, u64, std::time::Instant)>>>,
 }
@@ -38,10 +55,8 @@ lazy_static::lazy_static! {
         mouse_action_state: Arc::new(Mutex::new(MouseActionState::Idle)),
         ctrl_left_pressed: AtomicBool::new(false),
         ctrl_right_pressed: AtomicBool::new(false),
-        needs_detection: AtomicBool::new(false),
-        last_processed_time: Arc::new(Mutex::new(std::time::Instant::now())),
+        detection_tx: Mutex::new(None),
         last_mouse_pos: Arc::new(Mutex::new((0, 0))),
-        detection_anchor_pos: Arc::new(Mutex::new((0, 0))),
         last_toolbar_emit: Arc::new(Mutex::new(None)),
         last_click: Arc::new(Mutex::new(None)),
     };
@@ -49,6 +64,11 @@ lazy_static::lazy_static! {
 
 static LISTENER_STARTED: AtomicBool = AtomicBool::new(false);
 static LISTENER_ENABLED: AtomicBool = AtomicBool::new(true);
+/// 会话是否处于锁屏状态，锁屏期间鼠标键盘事件与划词检测请求都直接丢弃
+static SESSION_LOCKED: AtomicBool = AtomicBool::new(false);
+/// 锁屏状态轮询间隔
+#[cfg(target_os = "windows")]
+const SESSION_LOCK_POLL_INTERVAL: Duration = Duration::from_secs(2);
 
 /// 设置划词监听器启用状态
 pub fn set_selection_listener_enabled(
@@ -60,11 +80,50 @@ pub fn set_selection_listener_enabled(
     if enabled {
         MouseListener::start_mouse_listener(app_handle, state);
     } else {
-        GLOBAL_STATE.needs_detection.store(false, Ordering::SeqCst);
         hide_selection_toolbar_impl(app_handle);
     }
 }
 
+/// 开启或关闭划词：保存 selection_enabled 设置、启停监听（关闭时隐藏工具栏）、
+/// 同步托盘勾选状态并通知前端
+pub fn set_text_selection_enabled(
+    app: &AppHandle,
+    state: &Arc<Mutex<SharedAppState>>,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut settings = state.lock().unwrap().settings.clone();
+    if settings.selection_enabled != enabled {
+        settings.selection_enabled = enabled;
+        save_settings(&settings).map_err(|e| e.to_string())?;
+    }
+
+    let tray_item = {
+        let mut state_guard = state.lock().unwrap();
+        state_guard.settings = settings;
+        state_guard
+            .tray_menu_items
+            .as_ref()
+            .map(|items| items.selection_item.clone())
+    };
+    set_selection_listener_enabled(app.clone(), state.clone(), enabled);
+    if let Some(item) = tray_item {
+        let _ = item.set_checked(enabled);
+    }
+    if let Err(e) = app.emit("text-selection-changed", enabled) {
+        log::error!("发送划词开关事件失败: {}", e);
+    }
+    log::info!("划词已{}", if enabled { "开启" } else { "关闭" });
+    Ok(())
+}
+
+/// 退出前停止划词监听：关闭检测通道使检测线程退出。
+/// rdev 的全局钩子无法在进程内卸载，仅通过 LISTENER_ENABLED 使其回调不再处理事件
+pub fn stop_selection_listener() {
+    LISTENER_ENABLED.store(false, Ordering::SeqCst);
+    GLOBAL_STATE.detection_tx.lock().unwrap().take();
+    log::info!("划词监听已停止");
+}
+
 /// 检查是否有Ctrl键被按下
 fn is_any_ctrl_pressed() -> bool {
     GLOBAL_STATE.ctrl_left_pressed.load(Ordering::SeqCst)
@@ -104,6 +163,19 @@ fn is_ctrl_effectively_pressed() -> bool {
     tracked_pressed || os_pressed
 }
 
+/// 向检测线程提交划词检测请求，通道已满时丢弃（检测线程会合并突发请求）
+fn request_detection(pos: (i32, i32), timestamp: Instant) {
+    let tx_guard = GLOBAL_STATE.detection_tx.lock().unwrap();
+    let Some(tx) = tx_guard.as_ref() else {
+        return;
+    };
+    match tx.try_send(DetectionRequest { pos, timestamp }) {
+        Ok(()) => log::info!("已提交划词检测请求"),
+        Err(TrySendError::Full(_)) => log::info!("划词检测请求队列已满，合并到待处理请求"),
+        Err(TrySendError::Disconnected(_)) => log::error!("划词检测线程已退出"),
+    }
+}
+
 /// 重置Ctrl键状态
 pub fn reset_ctrl_key_state() {
     clear_ctrl_key_state_silent();
@@ -129,33 +201,35 @@ impl MouseListener {
         let detection_thread_app_handle = app_handle.clone();
         let detection_state = state.clone();
 
-        thread::spawn(move || loop {
-            if !LISTENER_ENABLED.load(Ordering::SeqCst) {
-                thread::sleep(Duration::from_millis(200));
-                continue;
-            }
+        let (detection_tx, detection_rx) =
+            mpsc::sync_channel::<DetectionRequest>(DETECTION_CHANNEL_CAPACITY);
+        *GLOBAL_STATE.detection_tx.lock().unwrap() = Some(detection_tx);
+        start_session_lock_monitor(app_handle.clone());
 
-            if GLOBAL_STATE.needs_detection.load(Ordering::SeqCst) {
-                GLOBAL_STATE.needs_detection.store(false, Ordering::SeqCst);
+        spawn_named("selection-detection", move || {
+            let mut last_processed: Option<Instant> = None;
 
-                let (selection_enabled, should_skip_detection) = {
-                    let state_guard = detection_state.lock().unwrap();
-                    (
-                        state_guard.settings.selection_enabled,
-                        state_guard.is_visible
-                            || state_guard.is_image_visible
-                            || state_guard.is_processing_selection
-                            || state_guard.is_updating_clipboard,
-                    )
-                };
+            while let Ok(mut request) = detection_rx.recv() {
+                while let Ok(newer) = detection_rx.try_recv() {
+                    request = newer;
+                }
 
-                if !selection_enabled {
-                    thread::sleep(Duration::from_millis(50));
+                if !LISTENER_ENABLED.load(Ordering::SeqCst) || SESSION_LOCKED.load(Ordering::SeqCst) {
                     continue;
                 }
 
-                if should_skip_detection {
-                    thread::sleep(Duration::from_millis(50));
+                if let Some(last) = last_processed {
+                    if request.timestamp.saturating_duration_since(last) <= DETECTION_DEBOUNCE {
+                        log::info!("操作过于频繁，跳过此次检测");
+                        continue;
+                    }
+                }
+                last_processed = Some(request.timestamp);
+
+                let selection_enabled = detection_state.lock().unwrap().settings.selection_enabled;
+                let should_skip_detection = UI_FLAGS.is_busy();
+
+                if !selection_enabled || should_skip_detection {
                     continue;
                 }
 
@@ -169,14 +243,16 @@ impl MouseListener {
                     clipboard_manager,
                 ) {
                     if !text.trim().is_empty() {
-                        if is_valid_selection(&text) {
+                        let is_valid = {
+                            let state_guard = detection_state.lock().unwrap();
+                            check_selection(&text, &state_guard.settings).valid
+                        };
+                        if is_valid {
                             log::info!("检测到有效的选中文本: '{}'", text);
+                            detection_state.lock().unwrap().record_selection(&text);
                             let app_handle_clone = detection_thread_app_handle.clone();
                             let text_clone = text.clone();
-                            let anchor_pos = {
-                                let pos_guard = GLOBAL_STATE.detection_anchor_pos.lock().unwrap();
-                                *pos_guard
-                            };
+                            let anchor_pos = request.pos;
                             let should_debounce = {
                                 let mut last_emit_guard = GLOBAL_STATE.last_toolbar_emit.lock().unwrap();
                                 let now = std::time::Instant::now();
@@ -210,16 +286,15 @@ impl MouseListener {
                 }
             }
 
-            thread::sleep(Duration::from_millis(50));
+            log::warn!("划词检测通道已关闭，检测线程退出");
         });
 
-        let listener_state = state.clone();
         let listener_app_handle = app_handle.clone();
 
-        thread::spawn(move || {
+        spawn_named("selection-input-listener", move || {
             log::info!("开始监听鼠标键盘事件");
             if let Err(error) = listen(move |event| {
-                if !LISTENER_ENABLED.load(Ordering::SeqCst) {
+                if !LISTENER_ENABLED.load(Ordering::SeqCst) || SESSION_LOCKED.load(Ordering::SeqCst) {
                     return;
                 }
 
@@ -315,38 +390,21 @@ impl MouseListener {
                                 log::info!("检测到双击/三击操作");
                             }
 
-                            if !is_foreground_window_console() {
+                            if is_foreground_window_own(&listener_app_handle) {
+                                log::info!("前台窗口属于本应用，跳过划词检测");
+                            } else if !is_foreground_window_console() {
                                 if !is_ctrl_effectively_pressed() {
-                                    let app_busy_or_visible = {
-                                        let state_guard = listener_state.lock().unwrap();
-                                        state_guard.is_visible
-                                            || state_guard.is_image_visible
-                                            || state_guard.is_processing_selection
-                                            || state_guard.is_updating_clipboard
-                                    };
+                                    let app_busy_or_visible = UI_FLAGS.is_busy();
                                     if app_busy_or_visible {
                                         log::info!("当前应用窗口可见或正在处理回填，跳过划词检测触发");
                                         return;
                                     }
-
-                                    let last_processed = {
-                                        GLOBAL_STATE.last_processed_time.lock().unwrap().clone()
-                                    };
-
-                                    if up_time.duration_since(last_processed)
-                                        > Duration::from_millis(100)
-                                    {
-                                        {
-                                            let mut pos_guard = GLOBAL_STATE.detection_anchor_pos.lock().unwrap();
-                                            *pos_guard = (last_x as i32, last_y as i32);
-                                        }
-                                        GLOBAL_STATE.needs_detection.store(true, Ordering::SeqCst);
-                                        log::info!("设置划词检测标志");
-
-                                        *GLOBAL_STATE.last_processed_time.lock().unwrap() = up_time;
-                                    } else {
-                                        log::info!("操作过于频繁，跳过此次检测");
+                                    if do_not_disturb::is_active() {
+                                        log::debug!("免打扰中，跳过划词检测");
+                                        return;
                                     }
+
+                                    request_detection((last_x as i32, last_y as i32), up_time);
                                 } else {
                                     log::info!("Ctrl键被按下，忽略此次点击");
                                 }
@@ -385,11 +443,21 @@ fn perform_text_selection_detection(
 ) -> Option<String> {
     log::info!("开始执行划词检测");
 
+    if !LISTENER_ENABLED.load(Ordering::SeqCst) {
+        log::info!("划词已关闭，跳过划词检测");
+        return None;
+    }
+
     if is_foreground_window_console() {
         log::info!("在命令行/终端环境中，跳过划词检测");
         return None;
     }
 
+    if is_foreground_window_own(app_handle) {
+        log::info!("前台窗口属于本应用，跳过划词检测");
+        return None;
+    }
+
     match get_selected_text(app_handle, clipboard_manager) {
         Some(text) if !text.trim().is_empty() => {
             log::info!("成功获取选中文本: '{}'", text);
@@ -402,6 +470,65 @@ fn perform_text_selection_detection(
     }
 }
 
+/// 立即捕获当前选中文本（跳过拖拽判断，仍遵守终端/本应用窗口排除规则）
+pub fn capture_selected_text(
+    app_handle: &AppHandle,
+    state: &Arc<Mutex<SharedAppState>>,
+) -> Result<String, String> {
+    let busy = UI_FLAGS.is_processing_selection() || UI_FLAGS.is_updating_clipboard();
+    let clipboard_manager = state.lock().unwrap().clipboard_manager.clone();
+    if busy {
+        return Err("正在处理其他剪贴板操作，请稍后重试".to_string());
+    }
+
+    let text = perform_text_selection_detection(app_handle, clipboard_manager)
+        .ok_or_else(|| "未能获取选中文本".to_string())?;
+    state.lock().unwrap().record_selection(&text);
+    Ok(text)
+}
+
+/// 立即捕获当前选中文本并打开划词工具栏
+pub fn capture_selection_now(
+    app_handle: &AppHandle,
+    state: &Arc<Mutex<SharedAppState>>,
+) -> Result<String, String> {
+    let text = capture_selected_text(app_handle, state)?;
+
+    let anchor_pos = {
+        let pos_guard = GLOBAL_STATE.last_mouse_pos.lock().unwrap();
+        (pos_guard.0 as i32, pos_guard.1 as i32)
+    };
+    show_selection_toolbar_impl(app_handle.clone(), text.clone(), Some(anchor_pos));
+    Ok(text)
+}
+
+/// 注册手动划词快捷键，空字符串表示不注册
+pub fn register_capture_selection_shortcut(
+    app_handle: &AppHandle,
+    state: Arc<Mutex<SharedAppState>>,
+    hot_key: &str,
+) -> Result<(), String> {
+    if hot_key.trim().is_empty() {
+        return Ok(());
+    }
+    let app_for_shortcut = app_handle.clone();
+    app_handle
+        .global_shortcut()
+        .on_shortcut(hot_key, move |_app, _shortcut, event| {
+            if let ShortcutState::Pressed = event.state {
+                let app_for_capture = app_for_shortcut.clone();
+                let state_for_capture = state.clone();
+                // 等待快捷键的修饰键释放，避免干扰模拟复制
+                run_on_ui_worker_after(CAPTURE_SELECTION_DELAY, move || {
+                    if let Err(e) = capture_selection_now(&app_for_capture, &state_for_capture) {
+                        log::info!("手动划词未完成: {}", e);
+                    }
+                });
+            }
+        })
+        .map_err(|e| format!("注册手动划词快捷键失败: {}", e))
+}
+
 /// 计算两点间距离
 fn calculate_distance(x1: u64, y1: u64, x2: u64, y2: u64) -> f64 {
     let dx = x2 as f64 - x1 as f64;
@@ -429,6 +556,71 @@ fn is_valid_drag_operation(distance: f64, duration: Duration) -> bool {
     is_distance_valid && is_duration_valid
 }
 
+/// 检查当前前台窗口是否属于本应用进程（剪贴板面板、设置等）
+#[cfg(target_os = "windows")]
+fn is_foreground_window_own(_app_handle: &AppHandle) -> bool {
+    use winapi::um::processthreadsapi::GetCurrentProcessId;
+    use winapi::um::winuser::{GetForegroundWindow, GetWindowThreadProcessId};
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_null() {
+            return false;
+        }
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(hwnd, &mut process_id);
+        process_id != 0 && process_id == GetCurrentProcessId()
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn is_foreground_window_own(app_handle: &AppHandle) -> bool {
+    use tauri::Manager;
+
+    app_handle
+        .webview_windows()
+        .values()
+        .any(|window| window.is_focused().unwrap_or(false))
+}
+
+/// 轮询会话锁屏状态：锁屏时暂停划词处理并隐藏工具栏，解锁后清除锁屏前残留的按键与拖拽状态。
+/// rdev 的全局钩子无法在进程内卸载，暂停期间钩子仍在，但回调会立即返回
+#[cfg(target_os = "windows")]
+fn start_session_lock_monitor(app_handle: AppHandle) {
+    spawn_named("session-lock-monitor", move || loop {
+        let locked = is_session_locked();
+        if SESSION_LOCKED.swap(locked, Ordering::SeqCst) != locked {
+            if locked {
+                log::info!("检测到会话已锁定，暂停划词检测");
+                hide_selection_toolbar_impl(app_handle.clone());
+            } else {
+                log::info!("检测到会话已解锁，恢复划词检测");
+                clear_ctrl_key_state_silent();
+                *GLOBAL_STATE.mouse_action_state.lock().unwrap() = MouseActionState::Idle;
+            }
+        }
+        std::thread::sleep(SESSION_LOCK_POLL_INTERVAL);
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+fn start_session_lock_monitor(_app_handle: AppHandle) {}
+
+/// 当前输入桌面无法切换时说明处于锁屏或安全桌面（UAC 提示等）
+#[cfg(target_os = "windows")]
+fn is_session_locked() -> bool {
+    use winapi::um::winuser::{CloseDesktop, OpenInputDesktop, SwitchDesktop, DESKTOP_SWITCHDESKTOP};
+    unsafe {
+        let desktop = OpenInputDesktop(0, 0, DESKTOP_SWITCHDESKTOP);
+        if desktop.is_null() {
+            return true;
+        }
+        let switchable = SwitchDesktop(desktop) != 0;
+        CloseDesktop(desktop);
+        !switchable
+    }
+}
+
 /// 检查当前前台窗口是否为命令行窗口
 fn is_foreground_window_console() -> bool {
     {
@@ -553,37 +745,56 @@ fn get_selected_text(
     result
 }
 
-/// 验证选中文本是否有效
-fn is_valid_selection(text: &str) -> bool {
+/// 选中文本被过滤的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionRejectReason {
+    /// 空文本
+    Empty,
+    /// 内容类型在设置的屏蔽列表中
+    SuppressedType,
+    /// 获取选中文本失败时得到的错误占位文本
+    Error,
+}
+
+/// 选中文本的过滤结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SelectionCheck {
+    pub valid: bool,
+    pub reason: Option<SelectionRejectReason>,
+    /// 文本的内容类型，空文本时为 None
+    pub content_type: Option<ContentType>,
+}
+
+/// 验证选中文本是否有效并给出被过滤的原因：内容类型在设置的屏蔽列表中时不弹出工具栏
+pub fn check_selection(text: &str, settings: &AppSettingsData) -> SelectionCheck {
     let trimmed = text.trim();
 
     if trimmed.is_empty() {
         log::info!("检测到空文本，跳过");
-        return false;
-    }
-
-    if is_phone_number(trimmed) {
-        log::info!("检测到可能是电话号码的选择: {}", trimmed);
-        return false;
-    }
-
-    if is_email_address(trimmed) {
-        log::info!("检测到可能是邮箱地址的选择: {}", trimmed);
-        return false;
+        return SelectionCheck {
+            valid: false,
+            reason: Some(SelectionRejectReason::Empty),
+            content_type: None,
+        };
     }
 
-    if is_url(trimmed) {
-        log::info!("检测到可能是URL的选择: {}", trimmed);
-        return false;
-    }
-
-    if is_error_text(trimmed) {
+    let content_type = classify_content(trimmed);
+    let reason = if settings.suppress_toolbar_for.contains(&content_type) {
+        log::info!("选中内容类型 {:?} 已设置为不弹出工具栏: {}", content_type, trimmed);
+        Some(SelectionRejectReason::SuppressedType)
+    } else if is_error_text(trimmed) {
         log::info!("检测到错误文本: {}", trimmed);
-        return false;
+        Some(SelectionRejectReason::Error)
+    } else {
+        log::info!("文本通过所有验证，认为是有效的选中文本: {}", trimmed);
+        None
+    };
+    SelectionCheck {
+        valid: reason.is_none(),
+        reason,
+        content_type: Some(content_type),
     }
-
-    log::info!("文本通过所有验证，认为是有效的选中文本: {}", trimmed);
-    true
 }
 
 /// 检查是否为错误文本
@@ -606,45 +817,3 @@ fn is_error_text(text: &str) -> bool {
 
     false
 }
-
-/// 检查是否为电话号码
-fn is_phone_number(text: &str) -> bool {
-    let phone_patterns = [
-        r"^\+?[\d\s\-\(\)]{10,}$",
-        r"^\d{3}-\d{3}-\d{4}$",
-        r"^\d{3}\.\d{3}\.\d{4}$",
-        r"^\(\d{3}\)\s*\d{3}-\d{4}$",
-        r"^\+1\s*\d{3}\s*\d{3}\s*\d{4}$",
-    ];
-
-    for pattern in &phone_patterns {
-        if let Ok(regex) = regex::Regex::new(pattern) {
-            if regex.is_match(text) {
-                return true;
-            }
-        }
-    }
-    false
-}
-
-/// 检查是否为邮箱地址
-fn is_email_address(text: &str) -> bool {
-    let email_pattern = r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$";
-
-    if let Ok(regex) = regex::Regex::new(email_pattern) {
-        regex.is_match(text)
-    } else {
-        false
-    }
-}
-
-/// 检查是否为URL
-fn is_url(text: &str) -> bool {
-    let url_pattern = r"^https?://[^\s/$.?#].\S*$|^www\.\S+$";
-
-    if let Ok(regex) = regex::Regex::new(url_pattern) {
-        regex.is_match(text)
-    } else {
-        false
-    }
-}
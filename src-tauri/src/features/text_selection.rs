@@ -7,14 +7,7 @@ use std::thread;
 use std::time::Duration;
 use tauri::AppHandle;
 
-/// 划词捕获最大重试时长
-const CAPTURE_RETRY_MAX_DURATION: Duration = Duration::from_millis(600);
-/// 轮询间隔，使用序列号检测时可以更频繁
-const CAPTURE_RETRY_INTERVAL: Duration = Duration::from_millis(10);
-/// 模拟按键后的初始等待时间
-const INITIAL_DELAY: Duration = Duration::from_millis(10);
-
-use crate::core::app_state::AppState as SharedAppState;
+use crate::core::app_state::{AppState as SharedAppState, UI_FLAGS};
 use crate::core::config::CTRL_KEY;
 use tauri::Manager;
 #[cfg(target_os = "windows")]
@@ -35,13 +28,18 @@ fn get_selected_text_windows(
 ) -> Option<String> {
     let state_manager = app_handle.state::<Arc<Mutex<SharedAppState>>>();
 
-    {
-        let mut state = state_manager.lock().unwrap();
-        if !state.settings.selection_enabled {
+    // 最长等待时间与轮询间隔来自设置，轮询间隔同时作为模拟按键后的初始等待时间
+    let (max_duration, retry_interval) = {
+        let state_guard = state_manager.lock().unwrap();
+        if !state_guard.settings.selection_enabled {
             return None;
         }
-        state.is_processing_selection = true;
-    }
+        (
+            Duration::from_millis(state_guard.settings.selection_capture_timeout_ms),
+            Duration::from_millis(state_guard.settings.selection_capture_interval_ms),
+        )
+    };
+    UI_FLAGS.set_processing_selection(true);
 
     // 1. 获取原始剪贴板内容（用于后续恢复）
     let original_content =
@@ -66,7 +64,7 @@ fn get_selected_text_windows(
 
     log::info!("已发送Ctrl+C模拟按键");
 
-    thread::sleep(INITIAL_DELAY);
+    thread::sleep(retry_interval);
     crate::features::mouse_listener::reset_ctrl_key_state();
 
     // 4. 等待剪贴板更新并获取新内容
@@ -75,6 +73,8 @@ fn get_selected_text_windows(
         app_handle,
         &original_content,
         sequence_before_copy,
+        max_duration,
+        retry_interval,
     );
 
     // 5. 恢复原始剪贴板内容
@@ -82,10 +82,18 @@ fn get_selected_text_windows(
         safe_restore_clipboard_content(&clipboard_manager, app_handle, original, &new_content);
     }
 
-    {
-        let mut state = state_manager.lock().unwrap();
-        state.is_processing_selection = false;
-    }
+    UI_FLAGS.set_processing_selection(false);
+
+    // 6. 剪贴板未捕获到内容时，尝试通过 UI Automation 直接读取焦点控件的选区
+    #[cfg(target_os = "windows")]
+    let new_content = new_content.or_else(|| {
+        let max_chars = state_manager.lock().unwrap().settings.selection_uia_max_chars;
+        let uia_text = crate::features::windows_text_selection::get_selected_text_via_uia(max_chars);
+        if uia_text.is_some() {
+            log::info!("剪贴板未捕获到选中文本，已通过 UI Automation 读取");
+        }
+        uia_text
+    });
 
     match &new_content {
         Some(content) => {
@@ -117,22 +125,25 @@ fn get_current_clipboard_content_with_manager(
     content
 }
 
-/// 等待剪贴板更新
-///
+/// 等待剪贴板更新，捕获到新内容后立即返回。
+/// 捕获失败（未选中文本或目标应用不响应复制）时会阻塞划词检测线程直到 max_duration，
+/// 因此默认值较短，仅为较慢的应用与远程桌面在设置中放宽
 fn wait_for_clipboard_update(
     clipboard_manager: &Arc<Mutex<ClipboardManager>>,
     app_handle: &AppHandle,
     original_content: &Option<String>,
     sequence_before_copy: u32,
+    max_duration: Duration,
+    retry_interval: Duration,
 ) -> Option<String> {
     let start_time = std::time::Instant::now();
     let mut attempts = 0;
 
     log::info!("使用内容轮询检测模式");
     
-    while start_time.elapsed() < CAPTURE_RETRY_MAX_DURATION {
+    while start_time.elapsed() < max_duration {
         attempts += 1;
-        thread::sleep(CAPTURE_RETRY_INTERVAL);
+        thread::sleep(retry_interval);
 
         let current_sequence = get_clipboard_sequence_number();
         let current_content = get_current_clipboard_content_with_manager(clipboard_manager, app_handle);
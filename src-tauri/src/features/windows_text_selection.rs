@@ -0,0 +1,133 @@
+use windows::Win32::System::Com::{
+    CoCreateInstance, CoInitializeEx, CoUninitialize, CLSCTX_INPROC_SERVER,
+    COINIT_APARTMENTTHREADED,
+};
+use windows::Win32::UI::Accessibility::{
+    CUIAutomation, IUIAutomation, IUIAutomationTextPattern, UIA_TextPatternId,
+};
+
+/// 焦点元素选区的读取接口，测试中可替换为模拟实现
+pub trait SelectionTextSource {
+    /// 依次返回各个选区的文本，每个选区最多读取 max_chars 个字符
+    fn selected_ranges(&self, max_chars: i32) -> Result<Vec<String>, String>;
+}
+
+/// 通过 UI Automation 文本模式读取焦点元素的选区
+pub struct UiaSelectionSource;
+
+impl SelectionTextSource for UiaSelectionSource {
+    fn selected_ranges(&self, max_chars: i32) -> Result<Vec<String>, String> {
+        read_focused_selection(max_chars).map_err(|e| e.to_string())
+    }
+}
+
+/// 通过 UI Automation 读取当前焦点元素的选中文本，最多 max_chars 个字符
+///
+/// 作为模拟 Ctrl+C 失败时的兜底，不会改动剪贴板；焦点元素不支持文本模式或没有选区时返回 None
+pub fn get_selected_text_via_uia(max_chars: usize) -> Option<String> {
+    // 当前线程可能已以其他模式初始化 COM，只有本次初始化成功时才需要反初始化
+    let com_initialized = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) }.is_ok();
+
+    let text = read_selection(&UiaSelectionSource, max_chars);
+
+    if com_initialized {
+        unsafe { CoUninitialize() };
+    }
+    text
+}
+
+/// 按行拼接各选区并截断到 max_chars 个字符；没有选区或选区为空时视为未选中，
+/// 不读取整篇文档，以便调用方继续走剪贴板兜底
+fn read_selection(source: &impl SelectionTextSource, max_chars: usize) -> Option<String> {
+    let limit = i32::try_from(max_chars).unwrap_or(i32::MAX);
+    let ranges = match source.selected_ranges(limit) {
+        Ok(ranges) => ranges,
+        Err(e) => {
+            log::debug!("UI Automation 读取选中文本失败: {}", e);
+            return None;
+        }
+    };
+    let text: String = ranges
+        .into_iter()
+        .filter(|range| !range.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+        .chars()
+        .take(max_chars)
+        .collect();
+    if text.trim().is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// 读取焦点元素文本模式下的全部选区，每个选区最多 max_chars 个字符
+fn read_focused_selection(max_chars: i32) -> windows::core::Result<Vec<String>> {
+    unsafe {
+        let automation: IUIAutomation =
+            CoCreateInstance(&CUIAutomation, None, CLSCTX_INPROC_SERVER)?;
+        let element = automation.GetFocusedElement()?;
+        let pattern: IUIAutomationTextPattern =
+            element.GetCurrentPatternAs(UIA_TextPatternId)?;
+        let ranges = pattern.GetSelection()?;
+
+        let mut parts = Vec::new();
+        for index in 0..ranges.Length()? {
+            parts.push(ranges.GetElement(index)?.GetText(max_chars)?.to_string());
+        }
+        Ok(parts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// 模拟 UI Automation：按 GetText 的语义把每个选区截断到请求的长度
+    struct MockSelection {
+        ranges: Result<Vec<&'static str>, &'static str>,
+        requested_max: Cell<Option<i32>>,
+    }
+
+    impl MockSelection {
+        fn new(ranges: Result<Vec<&'static str>, &'static str>) -> Self {
+            Self {
+                ranges,
+                requested_max: Cell::new(None),
+            }
+        }
+    }
+
+    impl SelectionTextSource for MockSelection {
+        fn selected_ranges(&self, max_chars: i32) -> Result<Vec<String>, String> {
+            self.requested_max.set(Some(max_chars));
+            let ranges = self.ranges.clone().map_err(str::to_string)?;
+            Ok(ranges
+                .into_iter()
+                .map(|range| range.chars().take(max_chars as usize).collect())
+                .collect())
+        }
+    }
+
+    #[test]
+    fn empty_selection_is_not_a_selection() {
+        assert_eq!(read_selection(&MockSelection::new(Ok(vec![])), 100), None);
+        assert_eq!(read_selection(&MockSelection::new(Ok(vec!["", ""])), 100), None);
+        assert_eq!(read_selection(&MockSelection::new(Ok(vec!["  \n"])), 100), None);
+    }
+
+    #[test]
+    fn passes_cap_to_get_text_and_truncates_joined_ranges() {
+        let source = MockSelection::new(Ok(vec!["选中的第一段", "second"]));
+        assert_eq!(read_selection(&source, 9), Some("选中的第一段\nse".to_string()));
+        assert_eq!(source.requested_max.get(), Some(9));
+    }
+
+    #[test]
+    fn uia_errors_fall_back_to_none() {
+        let source = MockSelection::new(Err("元素不支持文本模式"));
+        assert_eq!(read_selection(&source, 100), None);
+    }
+}
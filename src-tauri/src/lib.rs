@@ -4,20 +4,28 @@ pub mod ui;
 pub mod utils;
 pub mod features;
 
-use crate::core::app_state::AppState;
-use crate::core::config::DEFAULT_HIDE_SHORTCUT;
-use crate::services::ai_services::{stream_explain_text, stream_translate_text};
+use crate::core::app_state::{AppState, UI_FLAGS};
+use crate::core::config::{AUTOSTART_ARG, DEFAULT_HIDE_SHORTCUT, MINIMIZED_ARG};
+use crate::core::single_instance::{acquire_instance_lock, start_instance_listener, InstanceLock};
+use crate::services::ai_services::{
+    get_last_explain_depth, open_selection_chat, retry_last_request, send_chat_message,
+    test_current_provider,
+    stream_explain_text, stream_translate_multi_target, stream_translate_text, translate_clipboard,
+    translate_clipboard_history_item_inplace,
+    undo_clipboard_item_translation,
+};
 use crate::services::clipboard_manager::start_clipboard_listener;
 use crate::services::image_clipboard_manager::start_image_clipboard_listener;
 use crate::ui::commands::*;
-use crate::ui::tray_menu::rebuild_tray_menu;
+use crate::ui::tray_menu::{graceful_shutdown, open_settings, rebuild_tray_menu};
 use crate::ui::window_manager::{
-    hide_clipboard_window, hide_image_clipboard_window, show_clipboard_window,
-    show_image_clipboard_window,
+    hide_clipboard_window, hide_image_clipboard_window, register_global_search_shortcut,
+    register_window_shortcuts_on_startup,
 };
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Manager};
+use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use tauri_plugin_notification::NotificationExt;
 
 /// 启动划词选择监听器
 pub fn start_text_selection_listener(app_handle: AppHandle, state: Arc<Mutex<AppState>>) {
@@ -33,10 +41,31 @@ pub fn start_text_selection_listener(app_handle: AppHandle, state: Arc<Mutex<App
     );
 }
 
+/// 是否由开机自启（或带 --minimized 参数）启动
+fn launched_by_autostart() -> bool {
+    std::env::args().any(|arg| arg == AUTOSTART_ARG || arg == MINIMIZED_ARG)
+}
+
+/// 启动剪贴板、图片剪贴板与划词监听
+fn start_listeners(app_handle: AppHandle, state: Arc<Mutex<AppState>>) {
+    start_clipboard_listener(app_handle.clone(), state.clone());
+    start_image_clipboard_listener(app_handle.clone(), state.clone());
+
+    #[cfg(windows)]
+    start_text_selection_listener(app_handle, state);
+}
+
 /// 运行Tauri应用程序
 pub fn run() {
+    let instance_listener = match acquire_instance_lock() {
+        InstanceLock::Primary(listener) => Some(listener),
+        InstanceLock::ActivatedExisting => return,
+        InstanceLock::Unavailable => None,
+    };
+
     let initial_state = AppState::default();
     let state_arc = Arc::new(Mutex::new(initial_state));
+    core::diagnostics::install_panic_hook(state_arc.clone());
 
     let builder = tauri::Builder::default()
         .manage(state_arc.clone())
@@ -52,77 +81,158 @@ pub fn run() {
             }
 
             let app_handle = app.handle();
-            rebuild_tray_menu(&app_handle, state_arc.clone());
-            let state_clone = state_arc.clone();
-            let app_handle_clone = app_handle.clone();
-            let hot_key = state_arc
-                .lock().unwrap().settings.hot_key.clone();
-            let image_hot_key = state_arc
-                .lock().unwrap().settings.image_hot_key.clone();
-            app.global_shortcut()
-                .on_shortcut(hot_key.as_str(), move |_app, _shortcut, event| {
-                    if let ShortcutState::Pressed = event.state {
-                        let state_guard = state_clone.lock().unwrap();
-                        if !state_guard.is_visible && !state_guard.is_image_visible && !state_guard.is_processing_selection {
-                            drop(state_guard);
-                            show_clipboard_window(app_handle_clone.clone(), state_clone.clone());
+            let log_level = state_arc.lock().unwrap().settings.log_level.clone();
+            if let Err(e) = core::logger::apply_log_level(&log_level) {
+                log::warn!("{}", e);
+            }
+            utils::utils_helpers::apply_sentence_punctuation(
+                &state_arc.lock().unwrap().settings.sentence_punctuation,
+            );
+            let mut log_dirs = vec![utils::utils_helpers::get_logs_dir_path()];
+            if let Ok(app_log_dir) = app_handle.path().app_log_dir() {
+                log_dirs.push(app_log_dir);
+            }
+            let state_for_logs = state_arc.clone();
+            core::logger::start_log_retention_task(log_dirs, move || {
+                state_for_logs.lock().unwrap().settings.log_retention_days
+            });
 
-                            features::mouse_listener::reset_ctrl_key_state();
-                        }
-                    }
-                })
-                .map_err(|e| e.to_string())?;
+            if let Some(notice) = utils::utils_helpers::take_settings_recovery_notice() {
+                log::warn!("{}", notice);
+                if let Err(e) = app_handle
+                    .notification()
+                    .builder()
+                    .title("fy_tools")
+                    .body(notice)
+                    .show()
+                {
+                    log::error!("发送设置修复通知失败: {}", e);
+                }
+            }
 
-            let state_clone_image = state_arc.clone();
-            let app_handle_clone_image = app_handle.clone();
-            app.global_shortcut()
-                .on_shortcut(image_hot_key.as_str(), move |_app, _shortcut, event| {
-                    if let ShortcutState::Pressed = event.state {
-                        let state_guard = state_clone_image.lock().unwrap();
-                        if !state_guard.is_visible && !state_guard.is_image_visible && !state_guard.is_processing_selection {
-                            drop(state_guard);
-                            show_image_clipboard_window(app_handle_clone_image.clone(), state_clone_image.clone());
-                        }
-                    }
-                })
-                .map_err(|e| e.to_string())?;
+            apply_similarity_debug(app_handle, &state_arc);
+            utils::drag_export::cleanup_expired_exports();
+            features::do_not_disturb::init_from_settings(&state_arc);
+            rebuild_tray_menu(&app_handle, state_arc.clone());
+            register_window_shortcuts_on_startup(app_handle, &state_arc);
 
-            let state_clone_hide = state_arc.clone();
             let app_handle_clone_hide = app_handle.clone();
             app.global_shortcut()
                 .on_shortcut(DEFAULT_HIDE_SHORTCUT, move |_app, _shortcut, event| {
                     if let ShortcutState::Pressed = event.state {
-                        hide_clipboard_window(
-                            app_handle_clone_hide.clone(),
-                            state_clone_hide.clone(),
-                        );
-                        hide_image_clipboard_window(
-                            app_handle_clone_hide.clone(),
-                            state_clone_hide.clone(),
-                        );
+                        if UI_FLAGS.is_visible() {
+                            let _ = app_handle_clone_hide.emit_to("clipboard", "clipboard-nav-escape", ());
+                        }
+                        hide_clipboard_window(app_handle_clone_hide.clone());
+                        hide_image_clipboard_window(app_handle_clone_hide.clone());
 
                         features::mouse_listener::reset_ctrl_key_state();
                     }
                 })
                 .map_err(|e| e.to_string())?;
 
-            start_clipboard_listener(app_handle.clone(), state_arc.clone());
-            start_image_clipboard_listener(app_handle.clone(), state_arc.clone());
+            if let Some(listener) = instance_listener {
+                start_instance_listener(
+                    listener,
+                    app_handle.clone(),
+                    |app| {
+                        open_settings(app, None);
+                        if let Some(settings_window) = app.get_webview_window("settings") {
+                            let _ = settings_window.set_focus();
+                        }
+                    },
+                    core::cli::handle_cli_request,
+                );
+            }
+
+            let capture_hot_key = state_arc
+                .lock().unwrap().settings.capture_selection_hot_key.clone();
+            if let Err(e) = features::mouse_listener::register_capture_selection_shortcut(
+                app_handle,
+                state_arc.clone(),
+                &capture_hot_key,
+            ) {
+                log::error!("{}", e);
+            }
+
+            let dnd_hot_key = state_arc.lock().unwrap().settings.dnd_hot_key.clone();
+            if let Err(e) = features::do_not_disturb::register_shortcut(
+                app_handle,
+                state_arc.clone(),
+                &dnd_hot_key,
+            ) {
+                log::error!("{}", e);
+            }
+
+            let global_search_hot_key =
+                state_arc.lock().unwrap().settings.global_search_hot_key.clone();
+            if let Err(e) = register_global_search_shortcut(
+                app_handle,
+                &global_search_hot_key,
+            ) {
+                log::error!("{}", e);
+            }
+
+            core::chord_shortcuts::register_chord_shortcuts(app_handle, state_arc.clone());
+            services::http_api::apply_settings(app_handle, &state_arc);
 
-            #[cfg(windows)]
-            start_text_selection_listener(app_handle.clone(), state_arc.clone());
+            // 开机自启时不显示任何窗口，并等桌面加载完成后再开始轮询剪贴板和监听鼠标
+            if launched_by_autostart() {
+                let delay_secs = state_arc.lock().unwrap().settings.autostart_listener_delay_secs;
+                log::info!("以开机自启模式启动，{} 秒后启动剪贴板与划词监听", delay_secs);
+                let app_handle_for_listeners = app_handle.clone();
+                let state_for_listeners = state_arc.clone();
+                tauri::async_runtime::spawn(async move {
+                    tokio::time::sleep(std::time::Duration::from_secs(delay_secs)).await;
+                    start_listeners(app_handle_for_listeners, state_for_listeners);
+                });
+            } else {
+                start_listeners(app_handle.clone(), state_arc.clone());
+            }
 
             #[cfg(desktop)]
             app_handle
                 .plugin(tauri_plugin_updater::Builder::new().build())
                 .map_err(|e| e.to_string())?;
 
+            #[cfg(desktop)]
+            services::update_checker::start_background_update_checker(
+                app_handle.clone(),
+                state_arc.clone(),
+            );
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             remove_clipboard_item,
+            remove_clipboard_items,
             remove_image_clipboard_item,
             get_clipboard_history,
+            get_clipboard_history_full,
+            get_clipboard_entry,
+            create_clipboard_snapshot,
+            list_clipboard_snapshots,
+            restore_clipboard_snapshot,
+            delete_clipboard_snapshot,
+            transform_clipboard_item,
+            materialize_history_item,
+            materialize_image_history_item,
+            advance_selection_index,
+            retreat_selection_index,
+            set_selected_index,
+            move_selection,
+            confirm_selection,
+            filter_history,
+            set_selection_wrap_around,
+            set_translation_presets,
+            set_ai_dry_run,
+            navigate_result,
+            estimate_input_tokens,
+            set_provider_token_price,
+            set_large_input_confirm_tokens,
+            set_custom_prompts,
+            export_custom_prompts,
+            import_custom_prompts,
             get_image_clipboard_history,
             open_image_preview_window,
             close_image_preview_window,
@@ -138,11 +248,67 @@ pub fn run() {
             get_clipboard_bottom_offset,
             preview_clipboard_bottom_offset,
             save_clipboard_bottom_offset,
+            get_result_window_default_size,
+            save_result_window_default_size,
+            reset_result_window_geometry,
+            toggle_result_window_always_on_top,
+            get_selection_history,
+            clear_selection_history,
+            set_record_selection_history,
+            reopen_selection_history_item,
+            capture_selection_now,
+            global_search,
+            fuzzy_search_clipboard,
+            convert_settings_format,
+            save_update_settings,
+            set_dedup_mode,
+            get_last_dedup_decision,
+            set_debug_show_similarity,
+            subscribe_clipboard_history_updates,
+            unsubscribe_clipboard_history_updates,
+            set_log_level,
+            set_chord_shortcuts,
+            set_sentence_punctuation,
+            set_window_position_strategy,
+            set_paste_method,
+            set_post_paste_action,
+            set_paste_method_rules,
+            set_type_text_key_delay,
+            set_paste_transforms,
+            set_do_not_disturb,
+            set_text_selection_enabled,
+            check_selection_valid,
+            set_auto_dnd_fullscreen,
+            set_dnd_hot_key,
+            set_global_search_hot_key,
+            add_suppressed_content_type,
+            remove_suppressed_content_type,
+            set_fallback_translate_url,
+            set_log_retention_days,
+            set_autostart_listener_delay,
+            set_selection_capture_timing,
+            set_translation_chunk_chars,
+            set_selection_uia_max_chars,
+            set_http_api_enabled,
+            regenerate_http_api_token,
+            get_http_api_status,
+            set_log_ai_transcripts,
+            set_max_concurrent_ai_requests,
+            get_active_ai_request_count,
+            translate_clipboard_history_item_inplace,
+            undo_clipboard_item_translation,
+            notify_update_milestone,
+            scan_clipboard_for_secrets,
+            remove_secrets_from_history,
+            get_diagnostics_bundle,
             window_blur,
             image_window_blur,
             selection_toolbar_blur,
             copy_text,
             copy_and_paste_text,
+            run_custom_action,
+            copy_result_pair,
+            copy_result_only,
             get_ai_settings,
             get_poll_metrics_history,
             get_poll_metrics_minute_aggregates,
@@ -151,14 +317,25 @@ pub fn run() {
             get_text_dedup_metrics,
             save_app_settings,
             test_ai_connection,
+            test_current_provider,
             stream_translate_text,
+            stream_translate_multi_target,
             stream_explain_text,
+            translate_clipboard,
+            open_selection_chat,
+            send_chat_message,
+            retry_last_request,
+            get_last_explain_depth,
             get_provider_config,
             remove_ai_provider,
             get_all_configured_providers,
         ])
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        .plugin(tauri_plugin_autostart::Builder::new().build());
+        .plugin(
+            tauri_plugin_autostart::Builder::new()
+                .arg(AUTOSTART_ARG)
+                .build(),
+        );
 
     // 使用统一的日志配置
     let builder = builder.plugin(core::logger::build_logger().build());
@@ -171,5 +348,12 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .build(tauri::generate_context!())
         .expect("构建Tauri应用时出错")
-        .run(|_app_handle, _event| {});
+        .run(|app_handle, event| match event {
+            // 退出（含注销/关机）时统一在这里保存历史；单个窗口关闭不触发保存
+            tauri::RunEvent::ExitRequested { .. } | tauri::RunEvent::Exit => {
+                let state = app_handle.state::<Arc<Mutex<AppState>>>();
+                graceful_shutdown(app_handle, state.inner());
+            }
+            _ => {}
+        });
 }
@@ -1,7 +1,12 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use fuyun_tools_lib::core::cli::run_from_args;
 use fuyun_tools_lib::run;
 
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(exit_code) = run_from_args(&args) {
+        std::process::exit(exit_code);
+    }
     run();
 }
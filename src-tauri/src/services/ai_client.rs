@@ -8,7 +8,9 @@ use async_openai::{
     },
     Client,
 };
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Message {
@@ -59,11 +61,29 @@ pub struct ChatCompletionRequest {
     pub stream: Option<bool>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct AIConfig {
     pub api_key: String,
     pub base_url: String,
     pub model: String,
+    /// 附加到每个请求的自定义请求头
+    pub custom_headers: HashMap<String, String>,
+}
+
+/// 构建默认请求头：API 密钥认证头加上全部自定义请求头
+fn build_default_headers(config: &AIConfig) -> Result<HeaderMap, String> {
+    let mut headers = HeaderMap::new();
+    let auth = HeaderValue::from_str(&format!("Bearer {}", config.api_key))
+        .map_err(|_| "API密钥包含非法字符".to_string())?;
+    headers.insert(AUTHORIZATION, auth);
+    for (name, value) in &config.custom_headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| format!("自定义请求头名称无效: {}", name))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| format!("自定义请求头 {} 的值包含非法字符", name))?;
+        headers.insert(name, value);
+    }
+    Ok(headers)
 }
 
 #[derive(Debug, Clone)]
@@ -79,13 +99,20 @@ impl AIClient {
             .with_api_key(&config.api_key)
             .with_api_base(&config.base_url);
 
-        let client = Client::with_config(openai_config);
+        let mut client = Client::with_config(openai_config);
+        if !config.custom_headers.is_empty() {
+            let http_client = reqwest::Client::builder()
+                .default_headers(build_default_headers(&config)?)
+                .build()
+                .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
+            client = client.with_http_client(http_client);
+        }
 
         Ok(AIClient { client, config })
     }
 
     /// 将内部消息格式转换为OpenAI消息格式
-    fn convert_messages(&self, messages: &[Message]) -> Vec<ChatCompletionRequestMessage> {
+    fn convert_messages(messages: &[Message]) -> Vec<ChatCompletionRequestMessage> {
         messages
             .iter()
             .map(|msg| {
@@ -115,11 +142,10 @@ impl AIClient {
 
     /// 构建OpenAI聊天完成请求
     fn build_chat_request(
-        &self,
         request: &ChatCompletionRequest,
         stream: bool,
     ) -> Result<async_openai::types::CreateChatCompletionRequest, String> {
-        let messages = self.convert_messages(&request.messages);
+        let messages = Self::convert_messages(&request.messages);
 
         let mut binding = CreateChatCompletionRequestArgs::default();
         let mut builder = binding
@@ -143,7 +169,7 @@ impl AIClient {
         &self,
         request: &ChatCompletionRequest,
     ) -> Result<ChatCompletionResponse, String> {
-        let openai_request = self.build_chat_request(request, false)?;
+        let openai_request = Self::build_chat_request(request, false)?;
 
         let response = self
             .client
@@ -187,7 +213,7 @@ impl AIClient {
     where
         F: FnMut(String) -> bool,
     {
-        let openai_request = self.build_chat_request(request, true)?;
+        let openai_request = Self::build_chat_request(request, true)?;
 
         let mut stream = self
             .client
@@ -256,23 +282,15 @@ impl AIClient {
         }
     }
 
-    /// 流式文本生成
-    pub async fn generate_text_stream<F>(
-        &self,
-        prompt: &str,
-        max_tokens: Option<u32>,
-        callback: F,
-    ) -> Result<(), String>
-    where
-        F: FnMut(String) -> bool,
-    {
+    /// 构建流式文本生成使用的请求
+    fn text_stream_request(config: &AIConfig, prompt: &str, max_tokens: Option<u32>) -> ChatCompletionRequest {
         let messages = vec![Message {
             role: "user".to_string(),
             content: prompt.to_string(),
         }];
 
-        let request = ChatCompletionRequest {
-            model: self.config.model.clone(),
+        ChatCompletionRequest {
+            model: config.model.clone(),
             messages,
             temperature: Some(0.7),
             max_tokens,
@@ -281,10 +299,33 @@ impl AIClient {
             frequency_penalty: Some(0.0),
             presence_penalty: Some(0.0),
             stream: Some(true),
-        };
+        }
+    }
+
+    /// 流式文本生成
+    pub async fn generate_text_stream<F>(
+        &self,
+        prompt: &str,
+        max_tokens: Option<u32>,
+        callback: F,
+    ) -> Result<(), String>
+    where
+        F: FnMut(String) -> bool,
+    {
+        let request = Self::text_stream_request(&self.config, prompt, max_tokens);
         self.chat_completion_stream(&request, callback).await
     }
 
+    /// 按配置返回流式文本生成将发送的请求体（JSON），无需创建客户端
+    pub fn preview_text_stream_request(
+        config: &AIConfig,
+        prompt: &str,
+        max_tokens: Option<u32>,
+    ) -> Result<String, String> {
+        let request = Self::build_chat_request(&Self::text_stream_request(config, prompt, max_tokens), true)?;
+        serde_json::to_string_pretty(&request).map_err(|e| format!("序列化请求失败: {}", e))
+    }
+
     /// 测试连接
     pub async fn test_connection(&self) -> Result<bool, String> {
         let messages = vec![Message {
@@ -1,19 +1,28 @@
-use crate::core::app_state::AppState as SharedAppState;
+use crate::core::app_state::{AiRequestContext, AppState as SharedAppState};
+use crate::core::config::{
+    ExplainDepth, AI_REQUESTS_PER_WINDOW, AI_REQUEST_QUEUE_TIMEOUT, CHAT_SESSION_MAX_MESSAGES,
+    DEFAULT_AI_REQUEST_TIMEOUT_SECS, STREAM_FLUSH_CHARS, STREAM_FLUSH_INTERVAL, STREAM_MAX_TOKENS,
+    STREAM_PROGRESS_CHARS_PER_TOKEN,
+};
 use crate::core::error::{AppError, AppResult, ErrorCode};
-use crate::services::ai_client::{AIClient, AIConfig};
-use crate::ui::window_manager::{hide_selection_toolbar_impl, show_result_window, update_result_window};
+use crate::services::ai_client::{AIClient, AIConfig, ChatCompletionRequest, Message};
+use crate::services::fallback_translator::{is_network_error, FallbackTranslator};
+use crate::ui::window_manager::{
+    emit_result_update, hide_selection_toolbar_impl, is_result_window_closed, show_result_window, update_result_window,
+};
 use crate::utils::utils_helpers::{
     default_explanation_prompt_template, default_translation_prompt_template,
+    split_translation_chunks, AppSettingsData, TranslationChunk,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinSet;
 use tauri::{AppHandle, Emitter, Manager, State};
 
-/// 验证AI提供商配置
-fn validate_provider_config(state: &Arc<Mutex<SharedAppState>>) -> AppResult<()> {
-    let state_guard = state.lock().unwrap();
-    let settings = &state_guard.settings;
-
+/// 验证AI提供商配置，并根据当前提供商生成客户端配置（含从密钥库读取的密钥）
+fn validate_provider_config(settings: &AppSettingsData) -> AppResult<AIConfig> {
     if settings.ai_provider.is_empty() {
         return Err(AppError::new(ErrorCode::ConfigError, "未配置AI提供商，请在设置中选择提供商"));
     }
@@ -50,34 +59,44 @@ fn validate_provider_config(state: &Arc<Mutex<SharedAppState>>) -> AppResult<()>
         return Err(AppError::new(ErrorCode::ConfigError, "API地址格式不正确，请确保以 http:// 或 https:// 开头"));
     }
 
-    Ok(())
+    Ok(AIConfig {
+        api_key,
+        base_url: provider_config.api_url.clone(),
+        model: provider_config.model_name.clone(),
+        custom_headers: provider_config.custom_headers.clone(),
+    })
 }
 
-/// 获取或创建AI客户端
+/// 获取或创建AI客户端。只在读取设置与缓存、写回新客户端时各加锁一次，
+/// 读取密钥库与创建客户端都在锁外进行
 pub async fn get_or_create_ai_client(state: Arc<Mutex<SharedAppState>>) -> AppResult<AIClient> {
-    validate_provider_config(&state)?;
-    
-    let current_config = {
+    let (settings, cached_client) = {
         let state_guard = state.lock().unwrap();
-        let api_key = state_guard
-            .settings
-            .get_provider_api_key(&state_guard.settings.ai_provider)
-            .map_err(|e| AppError::new(ErrorCode::SystemError, format!("获取API密钥失败: {}", e)))?;
-        if api_key.is_empty() {
-            return Err(AppError::new(ErrorCode::ConfigError, "API密钥为空，无法创建客户端"));
-        }
-        let provider_config = state_guard.settings.get_current_provider_config()
-            .ok_or(AppError::new(ErrorCode::ConfigError, "获取当前提供商配置失败"))?;
-        AIConfig {
-            api_key,
-            base_url: provider_config.api_url.clone(),
-            model: provider_config.model_name.clone(),
-        }
+        (state_guard.settings.clone(), state_guard.try_get_cached_ai_client())
     };
+
+    let current_config = validate_provider_config(&settings)?;
+    if let Some(client) = cached_client {
+        if client.config == current_config {
+            return Ok(client);
+        }
+    }
+    log::debug!("AI配置已变化或尚未创建客户端，重新创建");
     let client = AIClient::new(current_config).map_err(|e| AppError::new(ErrorCode::SystemError, format!("客户端初始化失败: {}", e)))?;
+    state.lock().unwrap().update_ai_client_cache(client.clone());
     Ok(client)
 }
 
+/// 从当前提供商配置实时读取请求超时时间，修改设置后无需重启即可生效
+fn current_request_timeout_secs(state: &Arc<Mutex<SharedAppState>>) -> u64 {
+    let state_guard = state.lock().unwrap();
+    state_guard
+        .settings
+        .get_current_provider_config()
+        .map(|config| config.request_timeout_secs())
+        .unwrap_or(DEFAULT_AI_REQUEST_TIMEOUT_SECS)
+}
+
 fn fill_prompt_template(
     template: &str,
     text: &str,
@@ -103,17 +122,23 @@ enum AiStreamKind {
 }
 
 impl AiStreamKind {
-    fn kind_name(self) -> &'static str {
-        match self {
-            Self::Translation => "translation",
-            Self::Explanation => "explanation",
+    fn from_kind_name(name: &str) -> Option<Self> {
+        match name {
+            "translation" => Some(Self::Translation),
+            "explanation" => Some(Self::Explanation),
+            _ => None,
         }
     }
 
-    fn window_label(self) -> &'static str {
+    /// 由窗口类型解析请求类型，多语言翻译窗口形如 translation_{语言}
+    fn from_window_type(window_type: &str) -> Option<Self> {
+        Self::from_kind_name(window_type.split('_').next().unwrap_or_default())
+    }
+
+    fn kind_name(self) -> &'static str {
         match self {
-            Self::Translation => "result_translation",
-            Self::Explanation => "result_explanation",
+            Self::Translation => "translation",
+            Self::Explanation => "explanation",
         }
     }
 
@@ -132,20 +157,47 @@ impl AiStreamKind {
     }
 }
 
-fn set_active_operation(state: &Arc<Mutex<SharedAppState>>, kind: AiStreamKind, operation_id: u64) {
+/// 将操作设为窗口的当前操作并返回其中止信号；被取代的旧操作会立即收到中止通知，
+/// 不必等到下一个分块才停止并归还请求名额
+fn set_active_operation(
+    state: &Arc<Mutex<SharedAppState>>,
+    window_type: &str,
+    operation_id: u64,
+) -> Arc<Notify> {
+    let abort = Arc::new(Notify::new());
     let mut state_guard = state.lock().unwrap();
-    match kind {
-        AiStreamKind::Translation => state_guard.active_translation_op_id = operation_id,
-        AiStreamKind::Explanation => state_guard.active_explanation_op_id = operation_id,
+    state_guard
+        .active_ai_op_ids
+        .insert(window_type.to_string(), operation_id);
+    if let Some(previous) = state_guard
+        .ai_operation_aborts
+        .insert(window_type.to_string(), abort.clone())
+    {
+        // notify_one 会保留通知，旧操作尚未进入等待时也能在下次等待时立即返回
+        previous.notify_one();
     }
+    abort
 }
 
-fn is_operation_active(state: &Arc<Mutex<SharedAppState>>, kind: AiStreamKind, operation_id: u64) -> bool {
+fn is_operation_active(state: &Arc<Mutex<SharedAppState>>, window_type: &str, operation_id: u64) -> bool {
     let state_guard = state.lock().unwrap();
-    match kind {
-        AiStreamKind::Translation => state_guard.active_translation_op_id == operation_id,
-        AiStreamKind::Explanation => state_guard.active_explanation_op_id == operation_id,
-    }
+    state_guard.active_ai_op_ids.get(window_type) == Some(&operation_id)
+}
+
+/// 多语言翻译时某一目标语言使用的窗口类型；窗口标签只允许 ASCII，其余字符转为十六进制
+fn multi_target_window_type(target_language: &str) -> String {
+    let slug: String = target_language
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase().to_string()
+            } else {
+                format!("{:x}", c as u32)
+            }
+        })
+        .collect();
+    format!("{}_{}", AiStreamKind::Translation.kind_name(), slug)
 }
 
 #[derive(Deserialize)]
@@ -167,6 +219,9 @@ pub struct StreamExplainRequest {
     pub target_language: String,
     #[serde(default)]
     pub scene_hint: Option<String>,
+    /// 解释深度，未指定时沿用本次运行中最近使用的深度
+    #[serde(default)]
+    pub depth: Option<ExplainDepth>,
     #[serde(default)]
     pub op_id: Option<u64>,
 }
@@ -176,15 +231,316 @@ struct StreamExecutionRequest {
     source_language: Option<String>,
     target_language: String,
     scene_hint: Option<String>,
+    explain_depth: ExplainDepth,
     op_id: Option<u64>,
+    /// 结果窗口类型，普通请求与 kind_name 相同
+    window_type: String,
+    /// 调用方已获取的全局名额，未提供时在请求中尝试获取
+    global_permit: Option<OwnedSemaphorePermit>,
+}
+
+/// 获取指定结果窗口类型的AI请求名额；已满时排队等待，超时后拒绝。
+/// 名额随返回值释放，请求完成、失败或被取消时都会归还
+async fn acquire_ai_request_permit(
+    state: &Arc<Mutex<SharedAppState>>,
+    kind: AiStreamKind,
+    window_type: &str,
+) -> AppResult<OwnedSemaphorePermit> {
+    let semaphore = {
+        let mut state_guard = state.lock().unwrap();
+        state_guard
+            .ai_request_semaphores
+            .entry(window_type.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(AI_REQUESTS_PER_WINDOW)))
+            .clone()
+    };
+
+    match tokio::time::timeout(AI_REQUEST_QUEUE_TIMEOUT, semaphore.acquire_owned()).await {
+        Ok(Ok(permit)) => Ok(permit),
+        Ok(Err(_)) => Err(AppError::new(ErrorCode::SystemError, "AI请求队列已关闭")),
+        Err(_) => {
+            log::warn!("{}请求排队超时", kind.display_name());
+            Err(AppError::new(
+                ErrorCode::ValidationError,
+                format!("已有{}请求在进行中，请稍后再试", kind.display_name()),
+            ))
+        }
+    }
+}
+
+/// 获取全局AI请求名额，不等待：已达 max_concurrent_ai_requests 时直接拒绝。
+/// 名额随返回值释放
+fn try_acquire_global_ai_permit(state: &Arc<Mutex<SharedAppState>>) -> AppResult<OwnedSemaphorePermit> {
+    let semaphore = state.lock().unwrap().ai_request_semaphore.clone();
+    semaphore
+        .try_acquire_owned()
+        .map_err(|_| AppError::new(ErrorCode::ValidationError, "AI请求并发数已达上限，请稍候"))
+}
+
+/// 流式结果缓冲：累积小块内容，达到字符数或时间间隔后再推送到结果窗口
+struct StreamBuffer {
+    buf: String,
+    last_flush: Instant,
+    flush_size: usize,
+    flush_interval: Duration,
+    window_type: String,
+    app: AppHandle,
+    window_closed: bool,
+    /// 本次流已经推送或跳过的内容长度（字节），与结果记录中的长度一致
+    streamed_len: usize,
+}
+
+impl StreamBuffer {
+    fn new(window_type: &str, app: AppHandle) -> Self {
+        Self {
+            buf: String::new(),
+            last_flush: Instant::now(),
+            flush_size: STREAM_FLUSH_CHARS,
+            flush_interval: STREAM_FLUSH_INTERVAL,
+            window_type: window_type.to_string(),
+            app,
+            window_closed: false,
+            streamed_len: 0,
+        }
+    }
+
+    /// 追加内容，满足推送条件时立即推送；结果窗口已关闭时返回 false，调用方应停止流
+    fn push(&mut self, chunk: &str) -> bool {
+        self.buf.push_str(chunk);
+        if self.buf.chars().count() >= self.flush_size
+            || self.last_flush.elapsed() >= self.flush_interval
+        {
+            self.flush();
+        }
+        !self.window_closed
+    }
+
+    /// 推送缓冲区中的内容并清空
+    fn flush(&mut self) {
+        self.last_flush = Instant::now();
+        if self.buf.is_empty() || self.window_closed {
+            return;
+        }
+        let content = std::mem::take(&mut self.buf);
+        let start = self.streamed_len;
+        self.streamed_len += content.len();
+        // 从旧结果切回时已整体推送过最新结果，只推送之后新增的部分
+        let synced_len = self
+            .app
+            .try_state::<Arc<Mutex<SharedAppState>>>()
+            .and_then(|state| state.lock().unwrap().result_synced_len.get(&self.window_type).copied())
+            .unwrap_or(0);
+        let content = match synced_len.checked_sub(start) {
+            Some(skip) => match content.get(skip..) {
+                Some(rest) if !rest.is_empty() => rest.to_string(),
+                _ => return,
+            },
+            None => content,
+        };
+        match emit_result_update(&content, &self.window_type, &self.app) {
+            Ok(()) => {}
+            Err(e) if is_result_window_closed(&e) => self.window_closed = true,
+            Err(e) => log::error!("更新结果窗口失败: {}", e),
+        }
+    }
+
+    /// 结果窗口是否已被用户关闭
+    fn is_window_closed(&self) -> bool {
+        self.window_closed
+    }
+}
+
+/// ai-stream-progress 事件内容
+#[derive(Debug, Clone, Serialize)]
+struct StreamProgressPayload {
+    window_type: String,
+    chars_generated: usize,
+    estimated_max: usize,
+    percent: f64,
+}
+
+/// 按已生成字符数与 max_tokens 粗略估算生成进度，流结束前最多报告 99%
+struct StreamProgress {
+    window_type: String,
+    app: AppHandle,
+    chars_generated: usize,
+    estimated_max: usize,
+    last_reported: Option<u32>,
+}
+
+impl StreamProgress {
+    fn new(window_type: &str, max_tokens: u32, app: AppHandle) -> Self {
+        Self {
+            window_type: window_type.to_string(),
+            app,
+            chars_generated: 0,
+            estimated_max: (max_tokens as usize * STREAM_PROGRESS_CHARS_PER_TOKEN).max(1),
+            last_reported: None,
+        }
+    }
+
+    /// 累计新收到的内容，整数百分比变化时才发送事件
+    fn advance(&mut self, chunk: &str) {
+        self.chars_generated += chunk.chars().count();
+        let percent = (self.chars_generated as f64 / self.estimated_max as f64 * 100.0).min(99.0);
+        if self.last_reported != Some(percent as u32) {
+            self.last_reported = Some(percent as u32);
+            self.emit(percent);
+        }
+    }
+
+    /// 流结束时报告 100%
+    fn finish(&mut self) {
+        self.emit(100.0);
+    }
+
+    fn emit(&self, percent: f64) {
+        let payload = StreamProgressPayload {
+            window_type: self.window_type.clone(),
+            chars_generated: self.chars_generated,
+            estimated_max: self.estimated_max,
+            percent,
+        };
+        if let Err(e) = self.app.emit("ai-stream-progress", payload) {
+            log::warn!("发送生成进度失败: {}", e);
+        }
+    }
+}
+
+/// 推送最终状态（错误、超时提示）到结果窗口，窗口已关闭视为用户取消而非错误
+async fn update_result_window_unless_closed(
+    content: String,
+    window_type: String,
+    app: AppHandle,
+) -> AppResult<()> {
+    match update_result_window(content, window_type, app).await {
+        Err(e) if !is_result_window_closed(&e) => Err(AppError::new(ErrorCode::SystemError, e)),
+        _ => Ok(()),
+    }
+}
+
+/// 开启 log_ai_transcripts 时，记录本次请求的提示词与响应；
+/// response_start 为本次请求开始时结果记录的长度，只记录其后新增的内容
+fn record_ai_transcript(
+    state: &Arc<Mutex<SharedAppState>>,
+    window_type: &str,
+    client: &AIClient,
+    prompt: &str,
+    response_start: usize,
+    outcome: String,
+) {
+    let response = {
+        let state_guard = state.lock().unwrap();
+        if !state_guard.settings.log_ai_transcripts {
+            return;
+        }
+        state_guard
+            .ai_results
+            .get(window_type)
+            .and_then(|record| record.content.get(response_start..))
+            .unwrap_or_default()
+            .to_string()
+    };
+    crate::core::logger::append_ai_transcript(
+        window_type,
+        &client.config.model,
+        prompt,
+        &response,
+        &outcome,
+    );
+}
+
+/// 主AI服务网络不可达时改用离线翻译接口，结果已写入窗口时返回 true。
+/// 未配置接口或窗口中已有部分流式内容时不使用
+async fn try_fallback_translation(
+    state: &Arc<Mutex<SharedAppState>>,
+    window_type: &str,
+    text: &str,
+    source_language: &str,
+    target_language: &str,
+    app: &AppHandle,
+) -> bool {
+    let (url, has_partial_result) = {
+        let state_guard = state.lock().unwrap();
+        (
+            state_guard.settings.fallback_translate_url.clone(),
+            state_guard
+                .ai_results
+                .get(window_type)
+                .is_some_and(|record| !record.content.is_empty()),
+        )
+    };
+    if url.trim().is_empty() || has_partial_result {
+        return false;
+    }
+
+    let translator = match FallbackTranslator::new(&url) {
+        Ok(translator) => translator,
+        Err(e) => {
+            log::error!("{}", e);
+            return false;
+        }
+    };
+    match translator.translate(text, source_language, target_language).await {
+        Ok(translated) => {
+            log::info!("AI服务不可达，已改用离线翻译: {}", url);
+            if let Some(record) = state.lock().unwrap().ai_results.get_mut(window_type) {
+                record.content = translated.clone();
+            }
+            if let Err(e) = emit_result_update(&translated, window_type, app) {
+                log::error!("更新结果窗口失败: {}", e);
+            }
+            true
+        }
+        Err(e) => {
+            log::warn!("离线翻译失败: {}", e);
+            false
+        }
+    }
+}
+
+/// 试运行：记录并在结果窗口展示提示词与将发送的请求体，不创建客户端也不调用AI接口
+fn show_dry_run_preview(
+    state: &Arc<Mutex<SharedAppState>>,
+    window_type: &str,
+    prompt: &str,
+    app: &AppHandle,
+) {
+    let config = validate_provider_config(&state.lock().unwrap().settings);
+    let body = match config {
+        Ok(config) => AIClient::preview_text_stream_request(&config, prompt, Some(STREAM_MAX_TOKENS))
+            .unwrap_or_else(|e| format!("<{}>", e)),
+        Err(e) => format!("<{}>", e),
+    };
+    log::info!("AI试运行，未调用接口: window={}\n提示词:\n{}\n请求体:\n{}", window_type, prompt, body);
+
+    let content = format!("[试运行，未调用AI接口]\n\n提示词：\n{}\n\n请求体：\n{}", prompt, body);
+    if let Some(record) = state.lock().unwrap().ai_results.get_mut(window_type) {
+        record.content = content.clone();
+    }
+    if let Err(e) = emit_result_update(&content, window_type, app) {
+        if !is_result_window_closed(&e) {
+            log::error!("更新结果窗口失败: {}", e);
+        }
+    }
+}
+
+/// 结果窗口标题，多语言翻译窗口附带目标语言
+fn result_window_title(kind: AiStreamKind, window_type: &str, target_language: &str) -> String {
+    if window_type == kind.kind_name() {
+        kind.window_title().to_string()
+    } else {
+        format!("{}（{}）", kind.window_title(), target_language)
+    }
 }
 
 async fn execute_stream_request(
     kind: AiStreamKind,
-    request: StreamExecutionRequest,
+    mut request: StreamExecutionRequest,
     app: AppHandle,
     state_arc: Arc<Mutex<SharedAppState>>,
 ) -> Result<(), AppError> {
+    let window_type = request.window_type.clone();
     let text = request.text.trim().to_string();
     if text.is_empty() {
         let msg = match kind {
@@ -202,14 +558,49 @@ async fn execute_stream_request(
         }
     };
 
+    {
+        let mut state_guard = state_arc.lock().unwrap();
+        state_guard.last_ai_requests.insert(
+            window_type.clone(),
+            AiRequestContext {
+                text: text.clone(),
+                source_language: request.source_language.clone(),
+                target_language: request.target_language.clone(),
+                scene_hint: request.scene_hint.clone(),
+                explain_depth: request.explain_depth,
+            },
+        );
+    }
+
     let operation_id = request.op_id.unwrap_or_else(|| next_ai_operation_id(&state_arc));
-    set_active_operation(&state_arc, kind, operation_id);
-    let client: AIClient = get_or_create_ai_client(state_arc.clone()).await?;
+    // 新请求接管窗口后旧操作立即中止并归还名额，这里无需等到排队超时
+    let abort = set_active_operation(&state_arc, &window_type, operation_id);
+    let _permit = tokio::select! {
+        permit = acquire_ai_request_permit(&state_arc, kind, &window_type) => permit?,
+        _ = abort.notified() => {
+            log::info!("{}请求在排队期间被新请求取代: op_id={}", kind.display_name(), operation_id);
+            return Ok(());
+        }
+    };
+    if !is_operation_active(&state_arc, &window_type, operation_id) {
+        log::info!("{}请求在排队期间被新请求取代: op_id={}", kind.display_name(), operation_id);
+        return Ok(());
+    }
+    let _global_permit = match request.global_permit.take() {
+        Some(permit) => permit,
+        None => try_acquire_global_ai_permit(&state_arc)?,
+    };
+    // 试运行只展示请求内容，不创建客户端
+    let client = if state_arc.lock().unwrap().settings.ai_dry_run {
+        None
+    } else {
+        Some(get_or_create_ai_client(state_arc.clone()).await?)
+    };
 
     show_result_window(
-        kind.window_title().to_string(),
+        result_window_title(kind, &window_type, &request.target_language),
         "".to_string(),
-        kind.kind_name().to_string(),
+        window_type.clone(),
         text.clone(),
         request.target_language.clone(),
         app.clone(),
@@ -219,6 +610,8 @@ async fn execute_stream_request(
 
     hide_selection_toolbar_impl(app.clone());
 
+    state_arc.lock().unwrap().begin_result_slot(&window_type, text.clone());
+
     let source_language_name = request
         .source_language
         .unwrap_or_default()
@@ -233,15 +626,21 @@ async fn execute_stream_request(
         configured_prompt
     };
 
-    let text_for_prompt = if let Some(scene_hint) = request.scene_hint {
-        let hint = scene_hint.trim();
-        if hint.is_empty() {
-            text.clone()
-        } else {
-            format!("{}\n\n附加要求：\n{}", text, hint)
+    let mut hints: Vec<String> = request
+        .scene_hint
+        .map(|hint| hint.trim().to_string())
+        .filter(|hint| !hint.is_empty())
+        .into_iter()
+        .collect();
+    if let AiStreamKind::Explanation = kind {
+        if let Some(suffix) = request.explain_depth.prompt_suffix() {
+            hints.push(suffix.to_string());
         }
-    } else {
+    }
+    let text_for_prompt = if hints.is_empty() {
         text.clone()
+    } else {
+        format!("{}\n\n附加要求：\n{}", text, hints.join("\n"))
     };
 
     let messages = fill_prompt_template(
@@ -255,20 +654,48 @@ async fn execute_stream_request(
         &request.target_language,
     );
 
-    if let Some(window) = app.clone().get_webview_window(kind.window_label()) {
+    if let Some(window) = app.clone().get_webview_window(&format!("result_{}", window_type)) {
         let _ = window.emit(
             "result-clean",
             serde_json::json!({
-                "type": kind.kind_name(),
-                "opId": operation_id
+                "type": window_type,
+                "opId": operation_id,
+                "targetLanguage": request.target_language
             }),
         );
     }
 
+    let Some(client) = client else {
+        show_dry_run_preview(&state_arc, &window_type, &messages, &app);
+        return Ok(());
+    };
+
+    let timeout_secs = current_request_timeout_secs(&state_arc);
+
+    let chunk_chars = state_arc.lock().unwrap().settings.translation_chunk_chars;
+    if matches!(kind, AiStreamKind::Translation) && chunk_chars > 0 && text.chars().count() > chunk_chars {
+        let job = ChunkedTranslation {
+            window_type,
+            operation_id,
+            prompt_template,
+            hints,
+            source_language: Some(source_language_name).filter(|name| !name.is_empty()),
+            target_language: request.target_language,
+            chunks: split_translation_chunks(&text, chunk_chars),
+            timeout_secs,
+            abort,
+        };
+        stream_translation_in_chunks(job, &client, &app, &state_arc).await;
+        return Ok(());
+    }
+
     let state_for_stream = state_arc.clone();
-    let result = client
-        .generate_text_stream(messages.as_str(), Some(1000), |content_chunk| {
-            if !is_operation_active(&state_for_stream, kind, operation_id) {
+    let window_type_for_stream = window_type.clone();
+    let mut buffer = StreamBuffer::new(&window_type, app.clone());
+    let mut progress = StreamProgress::new(&window_type, STREAM_MAX_TOKENS, app.clone());
+    let stream = client
+        .generate_text_stream(messages.as_str(), Some(STREAM_MAX_TOKENS), |content_chunk| {
+            if !is_operation_active(&state_for_stream, &window_type_for_stream, operation_id) {
                 log::info!(
                     "{}流已被新请求接管，停止旧流: op_id={}",
                     kind.display_name(),
@@ -276,21 +703,95 @@ async fn execute_stream_request(
                 );
                 return false;
             }
-            let app_clone = app.clone();
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) =
-                    update_result_window(content_chunk, kind.kind_name().to_string(), app_clone).await
-                {
-                    log::error!("更新{}结果窗口失败: {}", kind.display_name(), e);
-                }
-            });
+            if let Some(record) = state_for_stream
+                .lock()
+                .unwrap()
+                .ai_results
+                .get_mut(&window_type_for_stream)
+            {
+                record.content.push_str(&content_chunk);
+            }
+            progress.advance(&content_chunk);
+            if !buffer.push(&content_chunk) {
+                log::info!(
+                    "结果窗口已关闭，停止{}流: op_id={}",
+                    kind.display_name(),
+                    operation_id
+                );
+                return false;
+            }
             true
-        })
-        .await;
+        });
+
+    let stream_result = tokio::select! {
+        result = tokio::time::timeout(Duration::from_secs(timeout_secs), stream) => result,
+        _ = abort.notified() => {
+            log::info!("{}流已被新请求接管，立即停止旧流: op_id={}", kind.display_name(), operation_id);
+            return Ok(());
+        }
+    };
+    if is_operation_active(&state_arc, &window_type, operation_id) {
+        buffer.flush();
+        progress.finish();
+    }
+    if buffer.is_window_closed() {
+        log::info!("{}已被用户取消: op_id={}", kind.display_name(), operation_id);
+        return Ok(());
+    }
+    let result = match stream_result {
+        Ok(result) => result,
+        Err(_) => {
+            if !is_operation_active(&state_arc, &window_type, operation_id) {
+                return Ok(());
+            }
+            log::warn!(
+                "{}请求超时({}秒): op_id={}",
+                kind.display_name(),
+                timeout_secs,
+                operation_id
+            );
+            if matches!(kind, AiStreamKind::Translation)
+                && try_fallback_translation(
+                    &state_arc,
+                    &window_type,
+                    &text,
+                    &source_language_name,
+                    &request.target_language,
+                    &app,
+                )
+                .await
+            {
+                return Ok(());
+            }
+            let _ = app.emit(
+                "ai-stream-timeout",
+                serde_json::json!({
+                    "type": window_type,
+                    "opId": operation_id,
+                    "timeoutSecs": timeout_secs
+                }),
+            );
+            let error_msg = "请求超时，请检查网络连接或增加超时时间";
+            update_result_window_unless_closed(error_msg.to_string(), window_type.clone(), app).await?;
+            return Err(AppError::new(ErrorCode::NetworkError, error_msg));
+        }
+    };
+
+    record_ai_transcript(
+        &state_arc,
+        &window_type,
+        &client,
+        &messages,
+        0,
+        match &result {
+            Ok(()) => "ok".to_string(),
+            Err(e) => format!("error: {}", e),
+        },
+    );
 
     match result {
         Ok(()) => {
-            if is_operation_active(&state_arc, kind, operation_id) {
+            if is_operation_active(&state_arc, &window_type, operation_id) {
                 log::info!("{}完成: op_id={}", kind.display_name(), operation_id);
             } else {
                 log::info!(
@@ -301,7 +802,7 @@ async fn execute_stream_request(
             }
         }
         Err(e) => {
-            if !is_operation_active(&state_arc, kind, operation_id) {
+            if !is_operation_active(&state_arc, &window_type, operation_id) {
                 log::info!(
                     "忽略过期{}错误: op_id={}, error={}",
                     kind.display_name(),
@@ -310,10 +811,22 @@ async fn execute_stream_request(
                 );
                 return Ok(());
             }
-            let error_msg = format!("{}失败: {}", kind.display_name(), e);
-            update_result_window(error_msg.clone(), kind.kind_name().to_string(), app)
+            if matches!(kind, AiStreamKind::Translation)
+                && is_network_error(&e)
+                && try_fallback_translation(
+                    &state_arc,
+                    &window_type,
+                    &text,
+                    &source_language_name,
+                    &request.target_language,
+                    &app,
+                )
                 .await
-                .map_err(|e| AppError::new(ErrorCode::SystemError, e))?;
+            {
+                return Ok(());
+            }
+            let error_msg = format!("{}失败: {}", kind.display_name(), e);
+            update_result_window_unless_closed(error_msg.clone(), window_type.clone(), app).await?;
             log::error!("{}", error_msg);
         }
     }
@@ -321,6 +834,144 @@ async fn execute_stream_request(
     Ok(())
 }
 
+/// 长文本分段翻译的参数
+struct ChunkedTranslation {
+    window_type: String,
+    operation_id: u64,
+    prompt_template: String,
+    hints: Vec<String>,
+    source_language: Option<String>,
+    target_language: String,
+    chunks: Vec<TranslationChunk>,
+    timeout_secs: u64,
+    /// 被新请求取代时的中止信号
+    abort: Arc<Notify>,
+}
+
+/// 将内容追加到结果记录并推送到结果窗口；结果窗口已关闭时返回 false
+fn append_result_content(
+    state: &Arc<Mutex<SharedAppState>>,
+    window_type: &str,
+    buffer: &mut StreamBuffer,
+    content: &str,
+) -> bool {
+    if content.is_empty() {
+        return !buffer.is_window_closed();
+    }
+    if let Some(record) = state.lock().unwrap().ai_results.get_mut(window_type) {
+        record.content.push_str(content);
+    }
+    buffer.push(content)
+}
+
+/// 当前结果记录的内容长度，分段翻译据此只记录单段的响应
+fn ai_result_len(state: &Arc<Mutex<SharedAppState>>, window_type: &str) -> usize {
+    state
+        .lock()
+        .unwrap()
+        .ai_results
+        .get(window_type)
+        .map(|record| record.content.len())
+        .unwrap_or(0)
+}
+
+/// 译文长度与原文相近，按段落长度放宽输出上限
+fn chunk_max_tokens(text: &str) -> u32 {
+    (text.chars().count() as u32 * 2).max(STREAM_MAX_TOKENS)
+}
+
+/// 依次翻译各段并按顺序流式写入结果窗口，段落之间补回原文的分隔；
+/// 单段失败或超时时在该位置保留原文并继续翻译后续段落
+async fn stream_translation_in_chunks(
+    job: ChunkedTranslation,
+    client: &AIClient,
+    app: &AppHandle,
+    state_arc: &Arc<Mutex<SharedAppState>>,
+) {
+    let total = job.chunks.len();
+    log::info!("文本较长，分 {} 段翻译: op_id={}", total, job.operation_id);
+    let mut buffer = StreamBuffer::new(&job.window_type, app.clone());
+    let total_max_tokens = job.chunks.iter().map(|chunk| chunk_max_tokens(&chunk.text)).sum();
+    let mut progress = StreamProgress::new(&job.window_type, total_max_tokens, app.clone());
+    let mut failed = 0;
+
+    for (i, chunk) in job.chunks.iter().enumerate() {
+        if !is_operation_active(state_arc, &job.window_type, job.operation_id) {
+            log::info!("分段翻译已被新请求接管，停止剩余段落: op_id={}", job.operation_id);
+            return;
+        }
+        if !append_result_content(state_arc, &job.window_type, &mut buffer, chunk.separator) {
+            log::info!("结果窗口已关闭，停止剩余段落: op_id={}", job.operation_id);
+            return;
+        }
+
+        let text_for_prompt = if job.hints.is_empty() {
+            chunk.text.clone()
+        } else {
+            format!("{}\n\n附加要求：\n{}", chunk.text, job.hints.join("\n"))
+        };
+        let prompt = fill_prompt_template(
+            &job.prompt_template,
+            &text_for_prompt,
+            job.source_language.as_deref(),
+            &job.target_language,
+        );
+        let max_tokens = chunk_max_tokens(&chunk.text);
+        let response_start = ai_result_len(state_arc, &job.window_type);
+
+        let stream = client.generate_text_stream(&prompt, Some(max_tokens), |content_chunk| {
+            if !is_operation_active(state_arc, &job.window_type, job.operation_id) {
+                return false;
+            }
+            progress.advance(&content_chunk);
+            append_result_content(state_arc, &job.window_type, &mut buffer, &content_chunk)
+        });
+        let result = tokio::select! {
+            result = tokio::time::timeout(Duration::from_secs(job.timeout_secs), stream) => match result {
+                Ok(result) => result,
+                Err(_) => Err(format!("请求超时({}秒)", job.timeout_secs)),
+            },
+            _ = job.abort.notified() => {
+                log::info!("分段翻译已被新请求接管，立即停止: op_id={}", job.operation_id);
+                return;
+            }
+        };
+        record_ai_transcript(
+            state_arc,
+            &job.window_type,
+            client,
+            &prompt,
+            response_start,
+            match &result {
+                Ok(()) => format!("ok (chunk {}/{})", i + 1, total),
+                Err(e) => format!("error (chunk {}/{}): {}", i + 1, total, e),
+            },
+        );
+
+        if buffer.is_window_closed() {
+            log::info!("结果窗口已关闭，停止剩余段落: op_id={}", job.operation_id);
+            return;
+        }
+        if let Err(e) = result {
+            if !is_operation_active(state_arc, &job.window_type, job.operation_id) {
+                return;
+            }
+            failed += 1;
+            log::warn!("第 {}/{} 段翻译失败: op_id={}, {}", i + 1, total, job.operation_id, e);
+            let note = format!("\n[第 {} 段翻译失败: {}，以下为原文]\n{}", i + 1, e, chunk.text);
+            append_result_content(state_arc, &job.window_type, &mut buffer, &note);
+        }
+    }
+
+    buffer.flush();
+    progress.finish();
+    if failed > 0 {
+        log::warn!("分段翻译完成，{}/{} 段失败: op_id={}", failed, total, job.operation_id);
+    } else {
+        log::info!("分段翻译完成，共 {} 段: op_id={}", total, job.operation_id);
+    }
+}
+
 /// 流式翻译文本
 #[tauri::command]
 pub async fn stream_translate_text(
@@ -335,7 +986,10 @@ pub async fn stream_translate_text(
             source_language: Some(request.source_language),
             target_language: request.target_language,
             scene_hint: request.scene_hint,
+            explain_depth: ExplainDepth::Standard,
             op_id: request.op_id,
+            window_type: AiStreamKind::Translation.kind_name().to_string(),
+            global_permit: None,
         },
         app,
         state.inner().clone(),
@@ -350,6 +1004,13 @@ pub async fn stream_explain_text(
     app: AppHandle,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
 ) -> Result<(), AppError> {
+    let explain_depth = {
+        let mut state_guard = state.lock().unwrap();
+        if let Some(depth) = request.depth {
+            state_guard.last_explain_depth = depth;
+        }
+        state_guard.last_explain_depth
+    };
     execute_stream_request(
         AiStreamKind::Explanation,
         StreamExecutionRequest {
@@ -357,10 +1018,500 @@ pub async fn stream_explain_text(
             source_language: None,
             target_language: request.target_language,
             scene_hint: request.scene_hint,
+            explain_depth,
             op_id: request.op_id,
+            window_type: AiStreamKind::Explanation.kind_name().to_string(),
+            global_permit: None,
         },
         app,
         state.inner().clone(),
     )
     .await
 }
+
+/// 对选中文本执行翻译或解释（供快捷键等后端入口使用，语言与划词工具栏一致）
+pub async fn stream_selection_action(
+    action: &str,
+    text: String,
+    app: AppHandle,
+    state: Arc<Mutex<SharedAppState>>,
+) -> Result<(), AppError> {
+    let explain_depth = state.lock().unwrap().last_explain_depth;
+    let (kind, source_language, target_language) = match action {
+        "translate" => (
+            AiStreamKind::Translation,
+            Some("自动识别".to_string()),
+            "简体中文".to_string(),
+        ),
+        "explain" => (AiStreamKind::Explanation, None, "中文".to_string()),
+        _ => {
+            return Err(AppError::new(
+                ErrorCode::ValidationError,
+                format!("不支持的动作: {}", action),
+            ))
+        }
+    };
+    execute_stream_request(
+        kind,
+        StreamExecutionRequest {
+            text,
+            source_language,
+            target_language,
+            scene_hint: None,
+            explain_depth,
+            op_id: None,
+            window_type: kind.kind_name().to_string(),
+            global_permit: None,
+        },
+        app,
+        state,
+    )
+    .await
+}
+
+/// 同时将文本翻译为多种目标语言，每种语言使用独立的结果窗口。
+/// 各语言排队等待全局并发名额，单个语言失败只在其窗口中显示错误；任务启动后立即返回
+#[tauri::command]
+pub async fn stream_translate_multi_target(
+    text: String,
+    source_language: String,
+    target_languages: Vec<String>,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), AppError> {
+    if text.trim().is_empty() {
+        return Err(AppError::new(ErrorCode::ValidationError, "文本为空，无法翻译"));
+    }
+    let mut targets: Vec<String> = Vec::new();
+    for target in target_languages {
+        let target = target.trim().to_string();
+        if !target.is_empty() && !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+    if targets.is_empty() {
+        return Err(AppError::new(ErrorCode::ValidationError, "请至少选择一种目标语言"));
+    }
+
+    let semaphore = state.lock().unwrap().ai_request_semaphore.clone();
+    let mut tasks = JoinSet::new();
+    for target in targets {
+        let text = text.clone();
+        let source_language = source_language.clone();
+        let app = app.clone();
+        let state_arc = state.inner().clone();
+        let semaphore = semaphore.clone();
+        tasks.spawn(async move {
+            let window_type = multi_target_window_type(&target);
+            let result = match semaphore.acquire_owned().await {
+                Ok(permit) => {
+                    execute_stream_request(
+                        AiStreamKind::Translation,
+                        StreamExecutionRequest {
+                            text: text.clone(),
+                            source_language: Some(source_language),
+                            target_language: target.clone(),
+                            scene_hint: None,
+                            explain_depth: ExplainDepth::Standard,
+                            op_id: None,
+                            window_type: window_type.clone(),
+                            global_permit: Some(permit),
+                        },
+                        app.clone(),
+                        state_arc,
+                    )
+                    .await
+                }
+                Err(_) => Err(AppError::new(ErrorCode::SystemError, "AI请求队列已关闭")),
+            };
+            if let Err(e) = result {
+                log::error!("{}翻译失败: {}", target, e);
+                let title = result_window_title(AiStreamKind::Translation, &window_type, &target);
+                let message = format!("翻译失败: {}", e.message);
+                if let Err(e) = show_result_window(title, message, window_type, text, target, app).await {
+                    log::error!("显示翻译错误失败: {}", e);
+                }
+            }
+        });
+    }
+    tauri::async_runtime::spawn(async move {
+        while let Some(joined) = tasks.join_next().await {
+            if let Err(e) = joined {
+                log::error!("多语言翻译任务异常退出: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+/// 读取当前剪贴板文本并翻译，结果显示在翻译结果窗口中
+pub async fn translate_clipboard_content(
+    source_language: Option<String>,
+    target_language: Option<String>,
+    app: AppHandle,
+    state: Arc<Mutex<SharedAppState>>,
+) -> Result<(), AppError> {
+    let clipboard_manager = state.lock().unwrap().clipboard_manager.clone();
+    let text = clipboard_manager
+        .lock()
+        .unwrap()
+        .get_content(&app)
+        .unwrap_or_default();
+    if text.trim().is_empty() {
+        return Err(AppError::new(
+            ErrorCode::ClipboardError,
+            "剪贴板中没有可翻译的文本",
+        ));
+    }
+    execute_stream_request(
+        AiStreamKind::Translation,
+        StreamExecutionRequest {
+            text,
+            source_language: Some(source_language.unwrap_or_else(|| "自动识别".to_string())),
+            target_language: target_language.unwrap_or_else(|| "简体中文".to_string()),
+            scene_hint: None,
+            explain_depth: ExplainDepth::Standard,
+            op_id: None,
+            window_type: AiStreamKind::Translation.kind_name().to_string(),
+            global_permit: None,
+        },
+        app,
+        state,
+    )
+    .await
+}
+
+/// 翻译当前剪贴板中的文本
+#[tauri::command]
+pub async fn translate_clipboard(
+    source: Option<String>,
+    target: Option<String>,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), AppError> {
+    translate_clipboard_content(source, target, app, state.inner().clone()).await
+}
+
+/// 当前提供商连接测试结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProviderTestResult {
+    pub provider: String,
+    pub model: String,
+    pub latency_ms: u64,
+}
+
+/// 使用已保存的当前提供商配置测试连接，无需重新输入密钥
+#[tauri::command]
+pub async fn test_current_provider(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<ProviderTestResult, AppError> {
+    let client = get_or_create_ai_client(state.inner().clone()).await?;
+    let provider = state.lock().unwrap().settings.ai_provider.clone();
+
+    let started = Instant::now();
+    client.test_connection().await.map_err(|e| {
+        log::error!("提供商 {} 连接测试失败: {}", provider, e);
+        AppError::new(ErrorCode::NetworkError, format!("连接测试失败: {}", e))
+    })?;
+    let latency_ms = started.elapsed().as_millis() as u64;
+    log::info!("提供商 {} 连接测试成功，耗时 {} ms", provider, latency_ms);
+
+    Ok(ProviderTestResult {
+        provider,
+        model: client.config.model.clone(),
+        latency_ms,
+    })
+}
+
+/// 获取本次运行中最近使用的解释深度
+#[tauri::command]
+pub async fn get_last_explain_depth(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<ExplainDepth, AppError> {
+    Ok(state.lock().unwrap().last_explain_depth)
+}
+
+/// 使用最近一次请求的参数重试翻译或解释
+#[tauri::command]
+pub async fn retry_last_request(
+    window_type: String,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), AppError> {
+    let kind = AiStreamKind::from_window_type(&window_type).ok_or_else(|| {
+        AppError::new(ErrorCode::ValidationError, format!("不支持的窗口类型: {}", window_type))
+    })?;
+    let context = {
+        let state_guard = state.lock().unwrap();
+        state_guard.last_ai_requests.get(&window_type).cloned()
+    }
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "没有可重试的请求"))?;
+
+    log::info!("重试{}请求", kind.display_name());
+    execute_stream_request(
+        kind,
+        StreamExecutionRequest {
+            text: context.text,
+            source_language: context.source_language,
+            target_language: context.target_language,
+            scene_hint: context.scene_hint,
+            explain_depth: context.explain_depth,
+            op_id: None,
+            window_type,
+            global_permit: None,
+        },
+        app,
+        state.inner().clone(),
+    )
+    .await
+}
+
+const CHAT_WINDOW_LABEL: &str = "chat_selection";
+
+/// 裁剪会话消息，保留开头的系统消息与最近的对话
+fn trim_chat_session(messages: &mut Vec<Message>) {
+    if messages.len() <= CHAT_SESSION_MAX_MESSAGES {
+        return;
+    }
+    let keep_from = usize::from(messages.first().is_some_and(|m| m.role == "system"));
+    let overflow = messages.len() - CHAT_SESSION_MAX_MESSAGES;
+    messages.drain(keep_from..keep_from + overflow);
+}
+
+fn emit_chat_stream(app: &AppHandle, payload: serde_json::Value) {
+    if let Some(window) = app.get_webview_window(CHAT_WINDOW_LABEL) {
+        if let Err(e) = window.emit("chat-stream", payload) {
+            log::error!("发送对话数据失败: {}", e);
+        }
+    }
+}
+
+/// 打开划词对话窗口，并以选中文本创建新会话
+#[tauri::command]
+pub async fn open_selection_chat(
+    initial_text: String,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), AppError> {
+    let text = initial_text.trim().to_string();
+    if text.is_empty() {
+        return Err(AppError::new(ErrorCode::ValidationError, "文本为空，无法开始对话"));
+    }
+
+    let session_id = format!("chat-{}", next_ai_operation_id(state.inner()));
+    {
+        let mut state_guard = state.lock().unwrap();
+        // 对话窗口只有一个，新会话开始时丢弃旧会话
+        state_guard.chat_sessions.clear();
+        state_guard.chat_sessions.insert(
+            session_id.clone(),
+            vec![Message {
+                role: "system".to_string(),
+                content: format!(
+                    "你是耐心的助手，请围绕下面这段用户选中的文本回答后续问题。\n\n选中文本：\n{}",
+                    text
+                ),
+            }],
+        );
+    }
+
+    hide_selection_toolbar_impl(app.clone());
+
+    let payload = serde_json::json!({
+        "sessionId": session_id,
+        "initialText": text
+    });
+
+    if let Some(existing_window) = app.get_webview_window(CHAT_WINDOW_LABEL) {
+        let script = format!(
+            "window.__INITIAL_DATA__ = {}; window.dispatchEvent(new Event('init-data'));",
+            payload
+        );
+        let _ = existing_window.eval(&script);
+        let _ = existing_window.show();
+        let _ = existing_window.set_focus();
+        return Ok(());
+    }
+
+    let window = tauri::WebviewWindowBuilder::new(
+        &app,
+        CHAT_WINDOW_LABEL,
+        tauri::WebviewUrl::App("chat.html".into()),
+    )
+        .title("划词对话")
+        .visible(false)
+        .inner_size(480.0, 600.0)
+        .resizable(true)
+        .decorations(true)
+        .on_page_load(move |window, _| {
+            let script = format!("window.__INITIAL_DATA__ = {};", payload);
+            let _ = window.eval(&script);
+        })
+        .build()
+        .map_err(|e| AppError::new(ErrorCode::SystemError, format!("创建对话窗口失败: {}", e)))?;
+
+    let _ = window.show();
+    let _ = window.set_focus();
+    Ok(())
+}
+
+/// 不打开结果窗口，直接返回整段译文（原地翻译、命令行翻译使用）
+pub async fn translate_text_once(
+    state_arc: Arc<Mutex<SharedAppState>>,
+    text: &str,
+    target_language: &str,
+) -> AppResult<String> {
+    if text.trim().is_empty() {
+        return Err(AppError::new(ErrorCode::ValidationError, "文本为空，无法翻译"));
+    }
+    let configured_prompt = state_arc.lock().unwrap().settings.translation_prompt_template.clone();
+    let prompt_template = if configured_prompt.trim().is_empty() {
+        default_translation_prompt_template()
+    } else {
+        configured_prompt
+    };
+    let prompt = fill_prompt_template(&prompt_template, text, None, target_language);
+
+    let _global_permit = try_acquire_global_ai_permit(&state_arc)?;
+    let client = get_or_create_ai_client(state_arc.clone()).await?;
+    let translated = client
+        .generate_text(&prompt, Some(500))
+        .await
+        .map_err(|e| AppError::new(ErrorCode::NetworkError, format!("翻译失败: {}", e)))?;
+    Ok(translated.trim().to_string())
+}
+
+/// 翻译剪贴板历史中的指定条目，并用译文原地替换（可撤销）
+#[tauri::command]
+pub async fn translate_clipboard_history_item_inplace(
+    index: usize,
+    target_language: String,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), AppError> {
+    let state_arc = state.inner().clone();
+    let clipboard_manager = state_arc.lock().unwrap().clipboard_manager.clone();
+    let original = clipboard_manager
+        .lock()
+        .unwrap()
+        .get_history()
+        .get(index)
+        .cloned()
+        .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "索引超出范围"))?;
+    let translated = translate_text_once(state_arc, &original, &target_language).await?;
+
+    let manager = clipboard_manager.lock().unwrap();
+    // 请求期间历史可能变动，确认目标条目未变再替换
+    if manager.get_history().get(index) != Some(&original) {
+        return Err(AppError::new(ErrorCode::ValidationError, "历史记录已变化，请重试"));
+    }
+    manager
+        .replace_at_with_undo(index, translated)
+        .map_err(|e| AppError::new(ErrorCode::ClipboardError, e))?;
+    log::info!("已原地翻译历史记录: index={}", index);
+    Ok(())
+}
+
+/// 撤销剪贴板历史条目的原地翻译
+#[tauri::command]
+pub async fn undo_clipboard_item_translation(
+    index: usize,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), AppError> {
+    let clipboard_manager = state.lock().unwrap().clipboard_manager.clone();
+    let manager = clipboard_manager.lock().unwrap();
+    manager
+        .restore_previous_content(index)
+        .map_err(|e| AppError::new(ErrorCode::ValidationError, e))
+}
+
+/// 在划词对话会话中发送消息，并通过 chat-stream 事件流式返回回复
+#[tauri::command]
+pub async fn send_chat_message(
+    session_id: String,
+    user_message: String,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), AppError> {
+    let user_message = user_message.trim().to_string();
+    if user_message.is_empty() {
+        return Err(AppError::new(ErrorCode::ValidationError, "消息不能为空"));
+    }
+
+    let messages = {
+        let mut state_guard = state.lock().unwrap();
+        let session = state_guard
+            .chat_sessions
+            .get_mut(&session_id)
+            .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "对话会话不存在或已过期"))?;
+        session.push(Message {
+            role: "user".to_string(),
+            content: user_message,
+        });
+        trim_chat_session(session);
+        session.clone()
+    };
+
+    let _global_permit = try_acquire_global_ai_permit(state.inner())?;
+    let client = get_or_create_ai_client(state.inner().clone()).await?;
+    let request = ChatCompletionRequest {
+        model: client.config.model.clone(),
+        messages,
+        temperature: Some(0.7),
+        max_tokens: Some(1000),
+        max_completion_tokens: None,
+        top_p: None,
+        frequency_penalty: None,
+        presence_penalty: None,
+        stream: Some(true),
+    };
+
+    let mut reply = String::new();
+    let result = client
+        .chat_completion_stream(&request, |content_chunk| {
+            reply.push_str(&content_chunk);
+            emit_chat_stream(
+                &app,
+                serde_json::json!({
+                    "sessionId": session_id,
+                    "content": content_chunk,
+                    "done": false
+                }),
+            );
+            true
+        })
+        .await;
+
+    if let Err(e) = result {
+        let error_msg = format!("对话失败: {}", e);
+        log::error!("{}", error_msg);
+        emit_chat_stream(
+            &app,
+            serde_json::json!({
+                "sessionId": session_id,
+                "error": error_msg,
+                "done": true
+            }),
+        );
+        return Err(AppError::new(ErrorCode::NetworkError, error_msg));
+    }
+
+    {
+        let mut state_guard = state.lock().unwrap();
+        if let Some(session) = state_guard.chat_sessions.get_mut(&session_id) {
+            session.push(Message {
+                role: "assistant".to_string(),
+                content: reply,
+            });
+            trim_chat_session(session);
+        }
+    }
+
+    emit_chat_stream(
+        &app,
+        serde_json::json!({
+            "sessionId": session_id,
+            "done": true
+        }),
+    );
+    Ok(())
+}
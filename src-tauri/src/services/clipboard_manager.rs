@@ -1,16 +1,58 @@
-use crate::core::app_state::AppState;
+use crate::core::app_state::{
+    clipboard_manager_of, is_shutting_down, lock_checked, AppState, UI_FLAGS,
+};
 use crate::core::config::{
-    CLIPBOARD_POLL_IDLE_INTERVAL, CLIPBOARD_POLL_MAX_INTERVAL, CLIPBOARD_POLL_MIN_INTERVAL,
-    CLIPBOARD_POLL_REPORT_INTERVAL, CLIPBOARD_POLL_WARM_INTERVAL,
+    CLIPBOARD_IDLE_POLLS_BEFORE_BACKOFF, CLIPBOARD_POLL_BACKOFF_INTERVAL,
+    CLIPBOARD_POLL_IDLE_INTERVAL, CLIPBOARD_POLL_INTERVAL, CLIPBOARD_POLL_MAX_INTERVAL,
+    CLIPBOARD_POLL_MIN_INTERVAL, CLIPBOARD_POLL_REPORT_INTERVAL, CLIPBOARD_POLL_WARM_INTERVAL,
 };
 use crate::services::adaptive_poll::{AdaptivePollConfig, AdaptivePoller};
 use crate::services::clipboard_wakeup::ClipboardWakeBackend;
 use crate::services::poll_metrics;
 use std::sync::{Arc, Mutex};
-use std::thread;
+use crate::core::diagnostics::spawn_named;
+use crate::features::do_not_disturb;
 use std::time::Duration;
 use tauri::AppHandle;
 
+/// 剪贴板长时间无变化时的轮询退避状态，由监听线程独占
+struct BackoffState {
+    no_change_count: u32,
+    current_interval: Duration,
+}
+
+impl BackoffState {
+    fn new() -> Self {
+        Self {
+            no_change_count: 0,
+            current_interval: CLIPBOARD_POLL_INTERVAL,
+        }
+    }
+
+    /// 检测到变化时立即恢复正常轮询
+    fn record_change(&mut self) {
+        self.no_change_count = 0;
+        self.current_interval = CLIPBOARD_POLL_INTERVAL;
+    }
+
+    /// 连续未变化达到阈值后退避到空闲间隔
+    fn record_no_change(&mut self) {
+        self.no_change_count = self.no_change_count.saturating_add(1);
+        if self.no_change_count >= CLIPBOARD_IDLE_POLLS_BEFORE_BACKOFF {
+            self.current_interval = CLIPBOARD_POLL_BACKOFF_INTERVAL;
+        }
+    }
+
+    /// 退避期间使用退避间隔，否则沿用自适应轮询给出的等待时间
+    fn next_wait(&self, adaptive_wait: Duration) -> Duration {
+        if self.current_interval > CLIPBOARD_POLL_INTERVAL {
+            self.current_interval
+        } else {
+            adaptive_wait
+        }
+    }
+}
+
 fn resolve_poll_config_from_state(state: &Arc<Mutex<AppState>>) -> AdaptivePollConfig {
     let guard = state.lock().unwrap();
     let settings = &guard.settings;
@@ -61,7 +103,7 @@ fn log_metrics_if_due(
 
 /// 启动剪贴板监听器
 pub fn start_clipboard_listener(app_handle: AppHandle, state: Arc<Mutex<AppState>>) {
-    thread::spawn(move || {
+    spawn_named("clipboard-listener", move || {
         let mut last_content = String::new();
         let mut wake_backend = ClipboardWakeBackend::new();
         let mut poller = AdaptivePoller::new(AdaptivePollConfig {
@@ -71,6 +113,7 @@ pub fn start_clipboard_listener(app_handle: AppHandle, state: Arc<Mutex<AppState
             max_interval: CLIPBOARD_POLL_MAX_INTERVAL,
             report_interval: CLIPBOARD_POLL_REPORT_INTERVAL,
         });
+        let mut backoff = BackoffState::new();
 
         loop {
             let (metrics_enabled, metrics_level) = {
@@ -84,15 +127,13 @@ pub fn start_clipboard_listener(app_handle: AppHandle, state: Arc<Mutex<AppState
             if poller.config() != runtime_cfg {
                 poller.reconfigure(runtime_cfg);
             }
-            wake_backend.wait(poller.next_wait());
-
-            let is_updating = {
-                let state_guard = state.lock().unwrap();
-                state_guard.is_updating_clipboard
-                    || state_guard.is_processing_selection
-                    || state_guard.is_visible
-                    || state_guard.is_image_visible
-            };
+            wake_backend.wait(backoff.next_wait(poller.next_wait()));
+            if is_shutting_down() {
+                log::info!("应用退出中，停止剪贴板监听");
+                break;
+            }
+
+            let is_updating = UI_FLAGS.is_busy();
 
             if is_updating {
                 poller.mark_busy_skip();
@@ -101,22 +142,35 @@ pub fn start_clipboard_listener(app_handle: AppHandle, state: Arc<Mutex<AppState
             }
 
             let current_content = {
-                let state_guard = state.lock().unwrap();
-                let manager = state_guard.clipboard_manager.lock().unwrap();
+                let clipboard_manager = clipboard_manager_of(&state);
+                let manager = lock_checked(&clipboard_manager, "ClipboardManager");
                 manager.get_content(&app_handle)
             };
 
+            if do_not_disturb::is_active() {
+                // 免打扰期间只记住当前内容，关闭后不会补记这段时间的复制
+                if let Some(current_content) = current_content {
+                    last_content = current_content;
+                }
+                poller.mark_busy_skip();
+                log_metrics_if_due(&mut poller, "text", metrics_enabled, &metrics_level);
+                continue;
+            }
+
             if let Some(current_content) = current_content {
                 if !current_content.is_empty() && current_content != last_content {
                     add_to_clipboard_history(current_content.clone(), state.clone());
                     last_content = current_content.clone();
                     poller.mark_change();
+                    backoff.record_change();
                     log::info!("检测到剪贴板内容变化，已添加到历史记录");
                 } else {
                     poller.mark_idle();
+                    backoff.record_no_change();
                 }
             } else {
                 poller.mark_idle();
+                backoff.record_no_change();
             }
 
             log_metrics_if_due(&mut poller, "text", metrics_enabled, &metrics_level);
@@ -130,23 +184,42 @@ pub fn add_to_clipboard_history(content: String, state: Arc<Mutex<AppState>>) {
         return;
     }
 
-    let should_skip = {
-        let state_guard = state.lock().unwrap();
-        state_guard.is_processing_selection
-    };
-
-    if should_skip {
+    if UI_FLAGS.is_processing_selection() {
         log::debug!("正在进行划词操作，跳过添加到历史记录");
         return;
     }
 
-    let manager_result = {
-        let state_guard = state.lock().unwrap();
-        state_guard.clipboard_manager.clone()
-    };
+    let clipboard_manager = clipboard_manager_of(&state);
+    lock_checked(&clipboard_manager, "ClipboardManager").add_to_history(content);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backs_off_after_consecutive_idle_polls() {
+        let adaptive_wait = Duration::from_millis(50);
+        let mut backoff = BackoffState::new();
+        for _ in 1..CLIPBOARD_IDLE_POLLS_BEFORE_BACKOFF {
+            backoff.record_no_change();
+        }
+        assert_eq!(backoff.next_wait(adaptive_wait), adaptive_wait);
+
+        backoff.record_no_change();
+        assert_eq!(backoff.next_wait(adaptive_wait), CLIPBOARD_POLL_BACKOFF_INTERVAL);
+    }
 
-    {
-        let manager = manager_result.lock().unwrap();
-        manager.add_to_history(content);
+    #[test]
+    fn first_change_ends_backoff() {
+        let adaptive_wait = Duration::from_millis(50);
+        let mut backoff = BackoffState::new();
+        for _ in 0..CLIPBOARD_IDLE_POLLS_BEFORE_BACKOFF {
+            backoff.record_no_change();
+        }
+        backoff.record_change();
+        assert_eq!(backoff.no_change_count, 0);
+        assert_eq!(backoff.current_interval, CLIPBOARD_POLL_INTERVAL);
+        assert_eq!(backoff.next_wait(adaptive_wait), adaptive_wait);
     }
 }
@@ -0,0 +1,103 @@
+use serde::Deserialize;
+use std::time::Duration;
+
+/// 离线翻译接口的请求超时时间
+const FALLBACK_TRANSLATE_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TranslateResponse {
+    translated_text: Option<String>,
+    error: Option<String>,
+}
+
+/// LibreTranslate 兼容接口的翻译客户端，主 AI 服务网络不可达时使用。
+/// 直接 POST 到翻译接口，不经过 OpenAI 格式的 AIClient
+pub struct FallbackTranslator {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl FallbackTranslator {
+    /// 创建客户端，url 为翻译接口完整地址（例如 http://localhost:5000/translate）
+    pub fn new(url: &str) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .timeout(FALLBACK_TRANSLATE_TIMEOUT)
+            .build()
+            .map_err(|e| format!("创建离线翻译客户端失败: {}", e))?;
+        Ok(Self {
+            client,
+            url: url.trim().to_string(),
+        })
+    }
+
+    /// 翻译文本，语言使用界面上的中文名称
+    pub async fn translate(
+        &self,
+        text: &str,
+        source_language: &str,
+        target_language: &str,
+    ) -> Result<String, String> {
+        let body = serde_json::json!({
+            "q": text,
+            "source": language_code(source_language),
+            "target": language_code(target_language),
+            "format": "text"
+        });
+        let response = self
+            .client
+            .post(&self.url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("离线翻译请求失败: {}", e))?;
+        let status = response.status();
+        let parsed: TranslateResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("解析离线翻译结果失败: {}", e))?;
+        if !status.is_success() {
+            return Err(format!(
+                "离线翻译接口返回错误({}): {}",
+                status,
+                parsed.error.unwrap_or_default()
+            ));
+        }
+        parsed
+            .translated_text
+            .filter(|text| !text.trim().is_empty())
+            .ok_or_else(|| "离线翻译接口返回空结果".to_string())
+    }
+}
+
+/// 将界面语言名称转换为 LibreTranslate 语言代码，未知名称原样传递
+fn language_code(language: &str) -> &str {
+    match language.trim() {
+        "" | "自动识别" => "auto",
+        "简体中文" | "中文" => "zh",
+        "繁体中文" => "zt",
+        "英语" | "英文" => "en",
+        "日语" | "日文" => "ja",
+        "韩语" | "韩文" => "ko",
+        "法语" => "fr",
+        "德语" => "de",
+        "西班牙语" => "es",
+        other => other,
+    }
+}
+
+/// 判断错误信息是否由网络不可达导致（连接、DNS、超时等），而非接口返回的业务错误
+pub fn is_network_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "http error",
+        "error sending request",
+        "connect",
+        "dns",
+        "timed out",
+        "timeout",
+        "transport",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
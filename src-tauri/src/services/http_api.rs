@@ -0,0 +1,500 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::core::app_state::AppState;
+use crate::core::cli::{execute_request, CliRequest, CliResponse};
+use crate::utils::utils_helpers::constant_time_eq;
+
+/// 单个请求的读写超时
+const HTTP_API_IO_TIMEOUT: Duration = Duration::from_secs(10);
+/// 请求体大小上限
+const HTTP_API_MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// 执行已解析的请求，正式运行时转交 cli::execute_request
+type RequestExecutor = Arc<dyn Fn(CliRequest) -> CliResponse + Send + Sync>;
+
+/// 运行中的本地 HTTP 接口
+struct HttpApiServer {
+    addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+impl HttpApiServer {
+    /// 停止接口并等待监听线程退出
+    fn shutdown(self) {
+        self.stop.store(true, Ordering::Relaxed);
+        // 连接一次以唤醒阻塞在 accept 上的监听线程
+        let _ = TcpStream::connect_timeout(&self.addr, Duration::from_millis(300));
+        if self.thread.join().is_err() {
+            log::error!("本地HTTP接口线程异常退出");
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref HTTP_API_SERVER: Mutex<Option<HttpApiServer>> = Mutex::new(None);
+}
+
+/// 本地 HTTP 接口状态
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpApiStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: Option<u16>,
+    pub token: String,
+}
+
+#[derive(Deserialize)]
+struct AddHistoryBody {
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct TranslateBody {
+    text: String,
+    to: String,
+}
+
+/// 使用操作系统的安全随机数生成访问令牌（64 位十六进制字符）
+pub fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("生成访问令牌失败: {}", e))?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// 按设置启动或停止本地 HTTP 接口，启动时调用；设置变更后也调用以重启
+pub fn apply_settings(app: &AppHandle, state: &Arc<Mutex<AppState>>) {
+    stop();
+    let (enabled, port, token) = {
+        let state_guard = state.lock().unwrap();
+        (
+            state_guard.settings.http_api_enabled,
+            state_guard.settings.http_api_port,
+            state_guard.settings.http_api_token.clone(),
+        )
+    };
+    if !enabled {
+        return;
+    }
+    if token.is_empty() {
+        log::error!("本地HTTP接口未配置访问令牌，未启动");
+        return;
+    }
+    let app = app.clone();
+    let executor: RequestExecutor = Arc::new(move |request| {
+        let state = app.state::<Arc<Mutex<AppState>>>().inner().clone();
+        execute_request(&app, state, request)
+    });
+    match start(executor, port, token) {
+        Ok(server) => *HTTP_API_SERVER.lock().unwrap() = Some(server),
+        Err(e) => log::error!("{}", e),
+    }
+}
+
+/// 当前状态
+pub fn status(state: &Arc<Mutex<AppState>>) -> HttpApiStatus {
+    let (enabled, token) = {
+        let state_guard = state.lock().unwrap();
+        (
+            state_guard.settings.http_api_enabled,
+            state_guard.settings.http_api_token.clone(),
+        )
+    };
+    let port = HTTP_API_SERVER
+        .lock()
+        .unwrap()
+        .as_ref()
+        .map(|server| server.addr.port());
+    HttpApiStatus {
+        enabled,
+        running: port.is_some(),
+        port,
+        token,
+    }
+}
+
+/// 在 127.0.0.1 上启动接口，port 为 0 时由系统分配端口
+fn start(executor: RequestExecutor, port: u16, token: String) -> Result<HttpApiServer, String> {
+    let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
+        .map_err(|e| format!("本地HTTP接口绑定端口 {} 失败: {}", port, e))?;
+    let addr = listener
+        .local_addr()
+        .map_err(|e| format!("获取本地HTTP接口地址失败: {}", e))?;
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_for_thread = stop.clone();
+    let token = Arc::new(token);
+
+    let thread = thread::Builder::new()
+        .name("http-api".to_string())
+        .spawn(move || {
+            for stream in listener.incoming() {
+                if stop_for_thread.load(Ordering::Relaxed) {
+                    break;
+                }
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let executor = executor.clone();
+                let token = token.clone();
+                thread::spawn(move || handle_connection(stream, executor.as_ref(), &token));
+            }
+            log::info!("本地HTTP接口已停止");
+        })
+        .map_err(|e| format!("启动本地HTTP接口线程失败: {}", e))?;
+
+    log::info!("本地HTTP接口已启动: http://{}", addr);
+    Ok(HttpApiServer { addr, stop, thread })
+}
+
+/// 停止接口并等待监听线程退出
+pub fn stop() {
+    let server = HTTP_API_SERVER.lock().unwrap().take();
+    if let Some(server) = server {
+        server.shutdown();
+    }
+}
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    body: Vec<u8>,
+}
+
+fn read_request(stream: &TcpStream) -> Result<HttpRequest, String> {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .map_err(|e| format!("读取请求失败: {}", e))?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0usize;
+    let mut authorization = None;
+    loop {
+        let mut line = String::new();
+        reader
+            .read_line(&mut line)
+            .map_err(|e| format!("读取请求头失败: {}", e))?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => {
+                content_length = value.trim().parse().map_err(|_| "无效的 Content-Length".to_string())?;
+            }
+            "authorization" => authorization = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+    if content_length > HTTP_API_MAX_BODY_BYTES {
+        return Err("请求体过大".to_string());
+    }
+    let mut body = vec![0u8; content_length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|e| format!("读取请求体失败: {}", e))?;
+
+    Ok(HttpRequest {
+        method,
+        path,
+        authorization,
+        body,
+    })
+}
+
+/// 比较令牌，耗时与不匹配的位置无关
+fn token_matches(authorization: Option<&str>, token: &str) -> bool {
+    let Some(provided) = authorization.and_then(|value| value.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    constant_time_eq(provided.trim(), token)
+}
+
+/// 读取请求并校验令牌，失败时返回状态码与错误响应
+fn read_authorized_request(stream: &TcpStream, token: &str) -> Result<HttpRequest, (u16, CliResponse)> {
+    let request = read_request(stream).map_err(|e| (400, CliResponse::failure(e)))?;
+    if !token_matches(request.authorization.as_deref(), token) {
+        return Err((401, CliResponse::failure("缺少或错误的访问令牌")));
+    }
+    Ok(request)
+}
+
+fn handle_connection(mut stream: TcpStream, executor: &dyn Fn(CliRequest) -> CliResponse, token: &str) {
+    let _ = stream.set_read_timeout(Some(HTTP_API_IO_TIMEOUT));
+    let _ = stream.set_write_timeout(Some(HTTP_API_IO_TIMEOUT));
+    let (status, response) = match read_authorized_request(&stream, token) {
+        Ok(request) => route(executor, request),
+        Err(rejected) => rejected,
+    };
+    write_response(&mut stream, status, &response);
+}
+
+/// 将 HTTP 请求映射为命令行请求，复用同一套执行逻辑
+fn route(executor: &dyn Fn(CliRequest) -> CliResponse, request: HttpRequest) -> (u16, CliResponse) {
+    let path = request.path.split('?').next().unwrap_or_default();
+    let cli_request = match (request.method.as_str(), path) {
+        ("GET", "/history") => Ok(CliRequest::HistoryList),
+        ("POST", "/history") => serde_json::from_slice::<AddHistoryBody>(&request.body)
+            .map(|body| CliRequest::HistoryAdd { text: body.text })
+            .map_err(|e| (400, format!("请求体格式错误: {}", e))),
+        ("POST", "/translate") => serde_json::from_slice::<TranslateBody>(&request.body)
+            .map(|body| CliRequest::Translate {
+                text: body.text,
+                to: body.to,
+            })
+            .map_err(|e| (400, format!("请求体格式错误: {}", e))),
+        ("POST", path) if path.starts_with("/paste/") => path["/paste/".len()..]
+            .parse()
+            .map(|index| CliRequest::HistoryPaste { index })
+            .map_err(|_| (400, "无效的索引".to_string())),
+        _ => Err((404, "未找到接口".to_string())),
+    };
+    match cli_request {
+        Ok(cli_request) => {
+            log::info!("本地HTTP接口请求: {} {}", request.method, path);
+            let response = executor(cli_request);
+            (if response.ok { 200 } else { 500 }, response)
+        }
+        Err((status, message)) => (status, CliResponse::failure(message)),
+    }
+}
+
+fn write_response(stream: &mut TcpStream, status: u16, response: &CliResponse) {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        _ => "Internal Server Error",
+    };
+    let body = serde_json::to_string(response).unwrap_or_else(|_| r#"{"ok":false}"#.to_string());
+    let message = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    if let Err(e) = stream.write_all(message.as_bytes()) {
+        log::warn!("写入本地HTTP接口响应失败: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::cli::CliHistoryItem;
+
+    const TOKEN: &str = "0123456789abcdef";
+
+    /// 在临时端口上发送原始请求，返回服务端读取并校验后的结果
+    fn send_raw(raw: Vec<u8>) -> Result<HttpRequest, (u16, CliResponse)> {
+        let listener = TcpListener::bind(SocketAddr::from((Ipv4Addr::LOCALHOST, 0))).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let client = thread::spawn(move || {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            let _ = stream.write_all(&raw);
+            stream
+        });
+        let (stream, _) = listener.accept().unwrap();
+        stream.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        let result = read_authorized_request(&stream, TOKEN);
+        drop(client.join().unwrap());
+        result
+    }
+
+    fn post_history(authorization: Option<&str>, body: &str) -> Vec<u8> {
+        let mut raw = "POST /history HTTP/1.1\r\nHost: 127.0.0.1\r\n".to_string();
+        if let Some(value) = authorization {
+            raw.push_str(&format!("Authorization: {}\r\n", value));
+        }
+        raw.push_str(&format!("Content-Length: {}\r\n\r\n{}", body.len(), body));
+        raw.into_bytes()
+    }
+
+    fn status_of(result: Result<HttpRequest, (u16, CliResponse)>) -> u16 {
+        match result {
+            Ok(_) => 200,
+            Err((status, _)) => status,
+        }
+    }
+
+    #[test]
+    fn parses_authorized_request() {
+        let body = r#"{"text":"hello"}"#;
+        let request = send_raw(post_history(Some(&format!("Bearer {}", TOKEN)), body))
+            .unwrap_or_else(|(status, _)| panic!("请求被拒绝: {}", status));
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/history");
+        assert_eq!(request.body, body.as_bytes());
+    }
+
+    #[test]
+    fn rejects_missing_or_wrong_token() {
+        let body = r#"{"text":"hello"}"#;
+        assert_eq!(status_of(send_raw(post_history(None, body))), 401);
+        assert_eq!(status_of(send_raw(post_history(Some("Bearer wrong-token-value"), body))), 401);
+        assert_eq!(status_of(send_raw(post_history(Some(&format!("Basic {}", TOKEN)), body))), 401);
+        assert_eq!(status_of(send_raw(post_history(Some("Bearer 0123456789abcdeF"), body))), 401);
+    }
+
+    #[test]
+    fn rejects_oversized_body() {
+        let raw = format!(
+            "POST /history HTTP/1.1\r\nAuthorization: Bearer {}\r\nContent-Length: {}\r\n\r\n",
+            TOKEN,
+            HTTP_API_MAX_BODY_BYTES + 1
+        );
+        let result = send_raw(raw.into_bytes());
+        assert_eq!(status_of(result), 400);
+    }
+
+    #[test]
+    fn generated_tokens_are_random_hex() {
+        let first = generate_token().unwrap();
+        let second = generate_token().unwrap();
+        assert_eq!(first.len(), 64);
+        assert!(first.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(first, second);
+    }
+
+    /// 在临时端口上启动接口，请求由内存中的历史记录处理，并记录收到的命令
+    fn start_mock_server(history: Vec<&'static str>) -> (HttpApiServer, Arc<Mutex<Vec<CliRequest>>>) {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let received_for_executor = received.clone();
+        let executor: RequestExecutor = Arc::new(move |request: CliRequest| {
+            received_for_executor.lock().unwrap().push(request.clone());
+            match request {
+                CliRequest::HistoryList => CliResponse {
+                    ok: true,
+                    items: Some(
+                        history
+                            .iter()
+                            .enumerate()
+                            .map(|(index, text)| CliHistoryItem {
+                                index,
+                                text: text.to_string(),
+                            })
+                            .collect(),
+                    ),
+                    ..Default::default()
+                },
+                CliRequest::HistoryPaste { index } if index < history.len() => CliResponse {
+                    ok: true,
+                    ..Default::default()
+                },
+                CliRequest::HistoryPaste { .. } => CliResponse::failure("索引超出范围"),
+                _ => CliResponse::failure("测试中不支持"),
+            }
+        });
+        let server = start(executor, 0, TOKEN.to_string()).unwrap();
+        (server, received)
+    }
+
+    /// 不走系统代理，直接访问本机接口
+    fn local_client() -> reqwest::Client {
+        reqwest::Client::builder().no_proxy().build().unwrap()
+    }
+
+    #[tokio::test]
+    async fn serves_history_and_paste_over_http() {
+        let (server, received) = start_mock_server(vec!["first", "second"]);
+        let base = format!("http://{}", server.addr);
+        let client = local_client();
+        let auth = format!("Bearer {}", TOKEN);
+
+        let response = client
+            .get(format!("{}/history", base))
+            .header("Authorization", &auth)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        let body: CliResponse = response.json().await.unwrap();
+        assert!(body.ok);
+        let texts: Vec<String> = body.items.unwrap().into_iter().map(|item| item.text).collect();
+        assert_eq!(texts, vec!["first", "second"]);
+
+        let response = client
+            .post(format!("{}/paste/1", base))
+            .header("Authorization", &auth)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        let response = client
+            .post(format!("{}/paste/5", base))
+            .header("Authorization", &auth)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 500);
+        let body: CliResponse = response.json().await.unwrap();
+        assert_eq!(body.error.as_deref(), Some("索引超出范围"));
+
+        let response = client
+            .post(format!("{}/paste/abc", base))
+            .header("Authorization", &auth)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 400);
+
+        server.shutdown();
+        let received = received.lock().unwrap();
+        assert_eq!(received.len(), 3);
+        assert!(matches!(received[0], CliRequest::HistoryList));
+        assert!(matches!(received[1], CliRequest::HistoryPaste { index: 1 }));
+        assert!(matches!(received[2], CliRequest::HistoryPaste { index: 5 }));
+    }
+
+    #[tokio::test]
+    async fn rejects_unauthorized_and_unknown_paths_over_http() {
+        let (server, received) = start_mock_server(vec!["secret"]);
+        let base = format!("http://{}", server.addr);
+        let client = local_client();
+
+        let response = client.get(format!("{}/history", base)).send().await.unwrap();
+        assert_eq!(response.status(), 401);
+        let response = client
+            .post(format!("{}/paste/0", base))
+            .header("Authorization", "Bearer wrong-token-value")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 401);
+
+        let auth = format!("Bearer {}", TOKEN);
+        for (method, path) in [
+            (reqwest::Method::GET, "/unknown"),
+            (reqwest::Method::DELETE, "/history"),
+            (reqwest::Method::GET, "/paste/0"),
+        ] {
+            let response = client
+                .request(method, format!("{}{}", base, path))
+                .header("Authorization", &auth)
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 404, "{}", path);
+        }
+
+        server.shutdown();
+        assert!(received.lock().unwrap().is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::core::app_state::AppState;
+use crate::core::app_state::{is_shutting_down, AppState, UI_FLAGS};
 use crate::core::config::{
     CLIPBOARD_POLL_IDLE_INTERVAL, CLIPBOARD_POLL_MAX_INTERVAL, CLIPBOARD_POLL_MIN_INTERVAL,
     CLIPBOARD_POLL_REPORT_INTERVAL, CLIPBOARD_POLL_WARM_INTERVAL,
@@ -8,7 +8,8 @@ use crate::services::clipboard_wakeup::ClipboardWakeBackend;
 use crate::services::poll_metrics;
 use crate::utils::image_clipboard::ImageClipboardManager;
 use std::sync::{Arc, Mutex};
-use std::thread;
+use crate::core::diagnostics::spawn_named;
+use crate::features::do_not_disturb;
 use std::time::Duration;
 use tauri::{AppHandle, Emitter};
 
@@ -61,7 +62,7 @@ fn log_metrics_if_due(
 }
 
 pub fn start_image_clipboard_listener(app_handle: AppHandle, state: Arc<Mutex<AppState>>) {
-    thread::spawn(move || {
+    spawn_named("image-clipboard-listener", move || {
         let mut last_signature = String::new();
         let mut last_error = String::new();
         let mut wake_backend = ClipboardWakeBackend::new();
@@ -86,14 +87,12 @@ pub fn start_image_clipboard_listener(app_handle: AppHandle, state: Arc<Mutex<Ap
                 poller.reconfigure(runtime_cfg);
             }
             wake_backend.wait(poller.next_wait());
+            if is_shutting_down() {
+                log::info!("应用退出中，停止图片剪贴板监听");
+                break;
+            }
 
-            let should_skip = {
-                let state_guard = state.lock().unwrap();
-                state_guard.is_updating_clipboard
-                    || state_guard.is_processing_selection
-                    || state_guard.is_visible
-                    || state_guard.is_image_visible
-            };
+            let should_skip = UI_FLAGS.is_busy();
 
             if should_skip {
                 poller.mark_busy_skip();
@@ -102,6 +101,15 @@ pub fn start_image_clipboard_listener(app_handle: AppHandle, state: Arc<Mutex<Ap
             }
 
             let image = ImageClipboardManager::read_clipboard_images_rgba(&app_handle);
+            if do_not_disturb::is_active() {
+                // 免打扰期间只记住当前图片，关闭后不会补记这段时间的复制
+                if let Ok(images) = image {
+                    last_signature = build_fast_signature(&images);
+                }
+                poller.mark_busy_skip();
+                log_metrics_if_due(&mut poller, "image", metrics_enabled, &metrics_level);
+                continue;
+            }
             if let Ok(images) = image {
                 last_error.clear();
                 let signature = build_fast_signature(&images);
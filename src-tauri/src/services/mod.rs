@@ -1,7 +1,10 @@
 pub mod ai_client;
 pub mod ai_services;
+pub mod fallback_translator;
+pub mod http_api;
 pub mod adaptive_poll;
 pub mod clipboard_wakeup;
 pub mod clipboard_manager;
 pub mod image_clipboard_manager;
 pub mod poll_metrics;
+pub mod update_checker;
@@ -0,0 +1,74 @@
+use crate::core::app_state::AppState as SharedAppState;
+use crate::core::config::{
+    UPDATE_CHECK_INITIAL_DELAY_SECS, UPDATE_CHECK_INTERVAL_SECS, UPDATE_ENDPOINT_BETA,
+    UPDATE_ENDPOINT_STABLE,
+};
+use crate::utils::utils_helpers::save_settings;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+use tauri_plugin_updater::UpdaterExt;
+
+/// 根据更新通道返回更新检查地址
+pub fn update_endpoint_for_channel(channel: &str) -> &'static str {
+    match channel {
+        "beta" => UPDATE_ENDPOINT_BETA,
+        _ => UPDATE_ENDPOINT_STABLE,
+    }
+}
+
+/// 启动后台静默更新检查：启动后延迟一次，之后每 24 小时一次
+pub fn start_background_update_checker(app_handle: AppHandle, state: Arc<Mutex<SharedAppState>>) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(UPDATE_CHECK_INITIAL_DELAY_SECS)).await;
+        loop {
+            let auto_check = state.lock().unwrap().settings.auto_check_updates;
+            if auto_check {
+                if let Err(e) = check_for_updates_silently(&app_handle, &state).await {
+                    log::warn!("后台检查更新失败: {}", e);
+                }
+            }
+            tokio::time::sleep(Duration::from_secs(UPDATE_CHECK_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+/// 静默检查更新，发现新版本时发送系统通知（同一版本只提醒一次）
+async fn check_for_updates_silently(
+    app_handle: &AppHandle,
+    state: &Arc<Mutex<SharedAppState>>,
+) -> Result<(), String> {
+    let channel = state.lock().unwrap().settings.update_channel.clone();
+    let endpoint = tauri::Url::parse(update_endpoint_for_channel(&channel))
+        .map_err(|e| format!("更新地址无效: {}", e))?;
+    let updater = app_handle
+        .updater_builder()
+        .endpoints(vec![endpoint])
+        .map_err(|e| format!("配置更新地址失败: {}", e))?
+        .build()
+        .map_err(|e| format!("创建更新检查器失败: {}", e))?;
+    let Some(update) = updater.check().await.map_err(|e| e.to_string())? else {
+        log::info!("后台检查更新: 已是最新版本 (通道: {})", channel);
+        return Ok(());
+    };
+
+    let mut settings = state.lock().unwrap().settings.clone();
+    if settings.last_notified_update_version == update.version {
+        return Ok(());
+    }
+
+    log::info!("后台检查发现新版本: {}", update.version);
+    app_handle
+        .notification()
+        .builder()
+        .title("fy_tools")
+        .body(format!("发现新版本 {}，点击托盘菜单更新", update.version))
+        .show()
+        .map_err(|e| format!("发送更新通知失败: {}", e))?;
+
+    settings.last_notified_update_version = update.version.clone();
+    save_settings(&settings)?;
+    state.lock().unwrap().settings.last_notified_update_version = update.version;
+    Ok(())
+}
@@ -1,32 +1,78 @@
-use crate::core::app_state::AppState as SharedAppState;
-use crate::core::config::{AIProvider, ProviderConfig};
+use crate::core::app_state::{
+    clipboard_manager_of, image_clipboard_manager_of, lock_checked, AiResultRecord,
+    AppState as SharedAppState, SelectionHistoryEntry, UI_FLAGS,
+};
+use crate::core::config::{
+    AIProvider, ProviderConfig, DEFAULT_GLOBAL_SEARCH_RESULTS_PER_SOURCE, FILL_AFTER_HIDE_DELAY,
+    HISTORY_FILTER_MAX_DISTANCE,
+    FILL_WINDOW_HIDE_TIMEOUT, PASTE_INITIAL_DELAY, PASTE_RETRY_DELAY, MAX_SELECTION_CAPTURE_TIMEOUT_MS,
+    validate_custom_headers,
+};
+use crate::core::ui_worker::{JobSpawner, UiWorkerSpawner};
+use crate::core::chord_shortcuts::{register_chord_shortcuts, ChordShortcut};
+use crate::core::diagnostics::{generate_diagnostics_bundle, spawn_named};
 use crate::features;
+use crate::features::custom_actions::CustomActionOutput;
 use crate::services::ai_client::{AIClient, AIConfig};
+use crate::services::http_api::{self, HttpApiStatus};
 use crate::services::poll_metrics;
+use crate::ui::tray_menu::refresh_tray_menu;
 use crate::ui::window_manager::{
-    hide_clipboard_window, hide_image_clipboard_window, hide_image_preview_window, set_window_position,
-    show_clipboard_window, show_image_clipboard_window, show_image_preview_loading_window,
+    hide_clipboard_window, hide_image_clipboard_window, hide_image_preview_window, register_clipboard_shortcut,
+    register_global_search_shortcut, register_image_clipboard_shortcut, set_window_position, show_image_preview_loading_window,
     show_image_preview_window,
 };
+use crate::utils::drag_export;
+use crate::utils::paste_transforms::{apply_paste_transforms, apply_transform_op, PasteTransform, TransformOp};
+use crate::utils::history_snapshot::{self, SnapshotId, SnapshotInfo};
 use crate::utils::image_clipboard::ImageHistoryPreviewItem;
 use crate::utils::utils_helpers::{
-    default_explanation_prompt_template, default_translation_prompt_template, load_settings,
-    save_settings, get_dedup_scan_metrics,
+    apply_sentence_punctuation, convert_settings_to_format, default_explanation_prompt_template,
+    default_translation_prompt_template, detect_secret_pattern, estimate_tokens,
+    get_dedup_scan_metrics, is_valid_fallback_translate_url, is_valid_selection_capture_timing,
+    is_valid_selection_uia_max_chars, is_valid_token_price, is_valid_translation_chunk_chars,
+    normalize_hot_key, paste_method_for_window, rank_text_matches, save_settings, to_fuzzy_matches,
+    validate_custom_prompts, with_match_offsets, ClipboardHistoryEvent, ContentType, CustomPrompt,
+    DedupDecision, DedupMode, FuzzyMatch, HistoryDelta, HistorySortMode, PasteMethod,
+    PasteMethodRule, PostPasteAction, SentencePunctuation, SettingsFormat, SimilarityDebugEvent,
+    TextSearchMatch, TranslationPreset, WindowPositionStrategy,
 };
 use std::collections::HashMap;
 use std::fs;
-use std::sync::{Arc, Mutex};
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
 use std::time::Duration;
-use tauri::{AppHandle, Manager, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 use tauri_plugin_clipboard_manager::ClipboardExt;
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use tauri_plugin_global_shortcut::GlobalShortcutExt;
+use tauri_plugin_notification::NotificationExt;
 
 #[derive(serde::Serialize)]
 pub struct HistoryResponse {
     history: Vec<String>,
-    categories: HashMap<String, String>,
+    content_types: Vec<ContentType>,
+    /// 与 history 一一对应的分类，history 只含预览，无法按内容查找分类
+    categories: Vec<Option<String>>,
     category_list: Vec<String>,
+    /// 与 history 一一对应的粘贴使用次数
+    use_counts: Vec<u32>,
+    /// 按请求的排序方式展示时各记录在 history 中的索引
+    display_order: Vec<usize>,
+}
+
+#[derive(serde::Serialize)]
+pub struct GlobalSearchResult {
+    clipboard_matches: Vec<TextSearchMatch>,
+    selection_matches: Vec<TextSearchMatch>,
+}
+
+#[derive(serde::Serialize)]
+pub struct SecretWarning {
+    index: usize,
+    content_preview: String,
+    pattern_matched: String,
 }
 
 #[derive(serde::Serialize)]
@@ -42,6 +88,9 @@ pub struct SelectAndFillRequest {
     index: usize,
     #[serde(default)]
     op_id: Option<u64>,
+    /// 本次回填使用的方式，为空时按窗口规则与设置决定
+    #[serde(default)]
+    paste_method: Option<PasteMethod>,
 }
 
 #[derive(serde::Deserialize)]
@@ -82,9 +131,9 @@ impl FillKind {
 }
 
 fn begin_fill_sequence(state: &Arc<Mutex<SharedAppState>>, kind: FillKind) -> u64 {
+    UI_FLAGS.set_updating_clipboard(true);
+    UI_FLAGS.set_processing_selection(true);
     let mut state_guard = state.lock().unwrap();
-    state_guard.is_updating_clipboard = true;
-    state_guard.is_processing_selection = true;
     match kind {
         FillKind::Text => {
             state_guard.text_fill_seq = state_guard.text_fill_seq.wrapping_add(1);
@@ -103,10 +152,10 @@ fn is_fill_latest(state: &Arc<Mutex<SharedAppState>>, kind: FillKind, fill_seq:
 }
 
 fn finish_fill_if_latest(state: &Arc<Mutex<SharedAppState>>, kind: FillKind, fill_seq: u64) {
-    if let Ok(mut guard) = state.lock() {
+    if let Ok(guard) = state.lock() {
         if kind.current_seq(&guard) == fill_seq {
-            guard.is_processing_selection = false;
-            guard.is_updating_clipboard = false;
+            UI_FLAGS.set_processing_selection(false);
+            UI_FLAGS.set_updating_clipboard(false);
         }
     }
 }
@@ -115,25 +164,31 @@ fn wait_for_fill_window_hidden(app: &AppHandle, window_label: &str, label: &str)
     if let Err(e) = crate::ui::window_manager::wait_for_window_hidden(
         app,
         window_label,
-        Duration::from_millis(900),
+        FILL_WINDOW_HIDE_TIMEOUT,
     ) {
         log::warn!("等待{}窗口隐藏失败: {}", label, e);
     } else {
-        thread::sleep(Duration::from_millis(40));
+        thread::sleep(FILL_AFTER_HIDE_DELAY);
     }
 }
 
-fn spawn_fill_task<F>(
+/// 通过 spawner 排队执行回填：等待窗口隐藏、写入并粘贴，粘贴成功后执行 after_paste
+#[allow(clippy::too_many_arguments)]
+fn spawn_fill_task<F, G>(
+    spawner: &dyn JobSpawner,
     kind: FillKind,
     app_handle: AppHandle,
     state: Arc<Mutex<SharedAppState>>,
     fill_seq: u64,
     operation_id: u64,
+    paste_method: PasteMethod,
     write_stage: F,
+    after_paste: G,
 ) where
     F: FnOnce(&AppHandle, &Arc<Mutex<SharedAppState>>) -> Result<(), String> + Send + 'static,
+    G: FnOnce(&AppHandle, &Arc<Mutex<SharedAppState>>) + Send + 'static,
 {
-    thread::spawn(move || {
+    spawner.spawn(Box::new(move || {
         let started_at = std::time::Instant::now();
         wait_for_fill_window_hidden(&app_handle, kind.window_label(), kind.label());
 
@@ -152,21 +207,59 @@ fn spawn_fill_task<F>(
                 );
                 return;
             }
-            simulate_paste_with_retry(kind.label(), Some(operation_id), started_at);
+            let pasted = match paste_method {
+                PasteMethod::ClipboardPaste => {
+                    simulate_paste_with_retry(kind.label(), Some(operation_id), started_at)
+                }
+                // 写入阶段已直接键入文本，无需再粘贴
+                PasteMethod::TypeText => {
+                    log::info!(
+                        "{}键入完成: op_id={}, 耗时: {}ms",
+                        kind.label(),
+                        operation_id,
+                        started_at.elapsed().as_millis()
+                    );
+                    true
+                }
+                // 内容留在剪贴板由用户手动粘贴，不执行粘贴后操作以免覆盖剪贴板
+                PasteMethod::CopyOnly => {
+                    notify_copied_for_manual_paste(&app_handle);
+                    false
+                }
+            };
+            if pasted {
+                after_paste(&app_handle, &state);
+            }
         } else if let Err(e) = fill_result {
             log::error!("{}回填失败（写入阶段）: op_id={}, {}", kind.label(), operation_id, e);
         }
 
         finish_fill_if_latest(&state, kind, fill_seq);
-    });
+    }));
+}
+
+fn notify_copied_for_manual_paste(app: &AppHandle) {
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title("fy_tools")
+        .body("已复制到剪贴板，请手动粘贴")
+        .show()
+    {
+        log::error!("发送复制完成通知失败: {}", e);
+    }
 }
 
+/// 模拟粘贴，失败时重试一次；返回是否粘贴成功
 fn simulate_paste_with_retry(
     label: &str,
     operation_id: Option<u64>,
     started_at: std::time::Instant,
-) {
-    thread::sleep(Duration::from_millis(135));
+) -> bool {
+    // 目标窗口已确认位于前台时无需再等待焦点切换
+    if !crate::ui::window_manager::activate_paste_target() {
+        thread::sleep(PASTE_INITIAL_DELAY);
+    }
     match crate::ui::window_manager::simulate_paste() {
         Ok(_) => {
             if let Some(op_id) = operation_id {
@@ -179,9 +272,10 @@ fn simulate_paste_with_retry(
             } else {
                 log::info!("{}回填完成，耗时: {}ms", label, started_at.elapsed().as_millis());
             }
+            true
         }
         Err(first_error) => {
-            thread::sleep(Duration::from_millis(140));
+            thread::sleep(PASTE_RETRY_DELAY);
             match crate::ui::window_manager::simulate_paste() {
                 Ok(_) => {
                     if let Some(op_id) = operation_id {
@@ -200,6 +294,7 @@ fn simulate_paste_with_retry(
                             started_at.elapsed().as_millis()
                         );
                     }
+                    true
                 }
                 Err(second_error) => {
                     if let Some(op_id) = operation_id {
@@ -218,28 +313,20 @@ fn simulate_paste_with_retry(
                             second_error
                         );
                     }
+                    false
                 }
             }
         }
     }
 }
 
-fn set_updating_clipboard(state: &Arc<Mutex<SharedAppState>>, updating: bool) {
-    if let Ok(mut state_guard) = state.lock() {
-        state_guard.is_updating_clipboard = updating;
-    }
-}
-
-fn with_updating_clipboard<T, F>(
-    state: &Arc<Mutex<SharedAppState>>,
-    operation: F,
-) -> Result<T, String>
+fn with_updating_clipboard<T, F>(operation: F) -> Result<T, String>
 where
     F: FnOnce() -> Result<T, String>,
 {
-    set_updating_clipboard(state, true);
+    UI_FLAGS.set_updating_clipboard(true);
     let result = operation();
-    set_updating_clipboard(state, false);
+    UI_FLAGS.set_updating_clipboard(false);
     result
 }
 
@@ -249,8 +336,8 @@ fn try_replace_text_clipboard_after_remove(
     removed_item: &str,
 ) {
     let current_clipboard = {
-        let state_guard = state.lock().unwrap();
-        let manager = state_guard.clipboard_manager.lock().unwrap();
+        let clipboard_manager = clipboard_manager_of(state);
+        let manager = lock_checked(&clipboard_manager, "ClipboardManager");
         manager.get_content(app)
     };
 
@@ -259,13 +346,13 @@ fn try_replace_text_clipboard_after_remove(
     }
 
     let next_item = {
-        let state_guard = state.lock().unwrap();
-        let manager = state_guard.clipboard_manager.lock().unwrap();
+        let clipboard_manager = clipboard_manager_of(state);
+        let manager = lock_checked(&clipboard_manager, "ClipboardManager");
         manager.get_history().first().cloned()
     };
     if let Some(next) = next_item {
-        let state_guard = state.lock().unwrap();
-        let manager = state_guard.clipboard_manager.lock().unwrap();
+        let clipboard_manager = clipboard_manager_of(state);
+        let manager = lock_checked(&clipboard_manager, "ClipboardManager");
         if let Err(e) = manager.set_clipboard_content(app, &next) {
             log::warn!("删除文本后写入下一条到剪贴板失败: {}", e);
         }
@@ -292,8 +379,8 @@ fn try_replace_image_clipboard_after_remove(
     }
 
     let next_image = {
-        let state_guard = state.lock().unwrap();
-        let manager = state_guard.image_clipboard_manager.lock().unwrap();
+        let image_clipboard_manager = image_clipboard_manager_of(state);
+        let manager = lock_checked(&image_clipboard_manager, "ImageClipboardManager");
         manager.get_image_by_index(0).ok()
     };
     if let Some(image) = next_image {
@@ -305,6 +392,23 @@ fn try_replace_image_clipboard_after_remove(
     }
 }
 
+/// 将指定历史记录回填到当前焦点应用（命令行粘贴使用）
+pub(crate) fn paste_history_item(
+    index: usize,
+    state: Arc<Mutex<SharedAppState>>,
+    app: AppHandle,
+) -> Result<String, String> {
+    execute_select_and_fill_text(
+        SelectAndFillRequest {
+            index,
+            op_id: None,
+            paste_method: None,
+        },
+        state,
+        app,
+    )
+}
+
 fn execute_select_and_fill_text(
     request: SelectAndFillRequest,
     state: Arc<Mutex<SharedAppState>>,
@@ -314,43 +418,115 @@ fn execute_select_and_fill_text(
     let fill_seq = begin_fill_sequence(&state, FillKind::Text);
     let operation_id = request.op_id.unwrap_or(fill_seq);
 
-    let item_content = {
+    // 回填方式优先级：调用方指定 > 匹配目标窗口的规则 > 设置
+    let target_title = crate::ui::window_manager::paste_target_title();
+    let (paste_method, post_paste_action, paste_transforms, key_delay) = {
         let state_guard = state.lock().unwrap();
-        let manager = state_guard.clipboard_manager.lock().unwrap();
-        manager
-            .promote_to_top(index)
-            .map_err(|e| format!("索引 {} 超出范围: {}", index, e))?
+        let settings = &state_guard.settings;
+        let paste_method = request
+            .paste_method
+            .or_else(|| {
+                target_title
+                    .as_deref()
+                    .and_then(|title| paste_method_for_window(&settings.paste_method_rules, title))
+            })
+            .unwrap_or(settings.paste_method);
+        (
+            paste_method,
+            settings.post_paste_action,
+            settings.paste_transforms.clone(),
+            Duration::from_millis(settings.type_text_key_delay_ms),
+        )
+    };
+
+    // 只累加使用次数时记录保持原位置，其余情况照常移到顶部。
+    // 移到顶部不改变原位置之后的记录索引，CopyNextItem 仍按原索引取下一条
+    let (item_content, pasted_index) = {
+        let clipboard_manager = clipboard_manager_of(&state);
+        let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+        if post_paste_action == PostPasteAction::IncrementAccessCount {
+            let item = manager
+                .get_entry(index)
+                .ok_or_else(|| format!("索引 {} 超出范围", index))?;
+            (item, index)
+        } else {
+            let item = manager
+                .record_use(index)
+                .map_err(|e| format!("索引 {} 超出范围: {}", index, e))?;
+            if post_paste_action == PostPasteAction::CopyNextItem {
+                (item, index)
+            } else {
+                (item, 0)
+            }
+        }
     };
 
-    hide_clipboard_window(app.clone(), state.clone());
+    hide_clipboard_window(app.clone());
 
-    let item_content_clone = item_content.clone();
+    // 只转换粘贴出去的内容，历史记录保持原样
+    let pasted_content = apply_paste_transforms(&item_content, &paste_transforms);
     spawn_fill_task(
+        &UiWorkerSpawner,
         FillKind::Text,
         app,
         state,
         fill_seq,
         operation_id,
+        paste_method,
+        move |app_handle, state_ref| match paste_method {
+            PasteMethod::ClipboardPaste | PasteMethod::CopyOnly => {
+                let clipboard_manager = clipboard_manager_of(state_ref);
+                let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+                manager.set_clipboard_content(app_handle, &pasted_content)
+            }
+            PasteMethod::TypeText => {
+                crate::ui::window_manager::activate_paste_target();
+                crate::ui::window_manager::simulate_typing(&pasted_content, key_delay)
+            }
+        },
         move |app_handle, state_ref| {
-            let state_guard = state_ref.lock().unwrap();
-            let manager = state_guard.clipboard_manager.lock().unwrap();
-            manager.set_clipboard_content(app_handle, &item_content_clone)
+            apply_post_paste_action(app_handle, state_ref, pasted_index, post_paste_action)
         },
     );
 
     Ok(item_content)
 }
 
+/// 文本回填粘贴成功后执行设置中的后续操作
+fn apply_post_paste_action(
+    app: &AppHandle,
+    state: &Arc<Mutex<SharedAppState>>,
+    index: usize,
+    action: PostPasteAction,
+) {
+    let clipboard_manager = clipboard_manager_of(state);
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    match manager.apply_post_paste_action(index, action) {
+        Ok(Some((next_index, next_item))) => {
+            // 回填尚未结束，剪贴板监听仍处于忽略状态，写入的下一条不会被重复记录
+            if let Err(e) = manager.set_clipboard_content(app, &next_item) {
+                log::warn!("粘贴后写入下一条记录到剪贴板失败: {}", e);
+                return;
+            }
+            if let Err(e) = app.emit_to("clipboard", "clipboard-selection-advanced", next_index) {
+                log::error!("发送选中下一条记录事件失败: {}", e);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("执行粘贴后操作 {:?} 失败: {}", action, e),
+    }
+}
+
 fn execute_remove_clipboard_item(
     index: usize,
     state: Arc<Mutex<SharedAppState>>,
     app: AppHandle,
 ) -> Result<(), String> {
     log::info!("删除剪贴板项目，索引: {}", index);
-    with_updating_clipboard(&state, || {
+    with_updating_clipboard(|| {
         let removed_item = {
-            let state_guard = state.lock().unwrap();
-            let manager = state_guard.clipboard_manager.lock().unwrap();
+            let clipboard_manager = clipboard_manager_of(&state);
+            let manager = lock_checked(&clipboard_manager, "ClipboardManager");
             manager.remove_from_history(index)?
         };
         try_replace_text_clipboard_after_remove(&state, &app, &removed_item);
@@ -358,15 +534,34 @@ fn execute_remove_clipboard_item(
     })
 }
 
+fn execute_remove_clipboard_items(
+    indices: Vec<usize>,
+    state: Arc<Mutex<SharedAppState>>,
+    app: AppHandle,
+) -> Result<usize, String> {
+    log::info!("批量删除剪贴板项目，数量: {}", indices.len());
+    with_updating_clipboard(|| {
+        let removed_items = {
+            let clipboard_manager = clipboard_manager_of(&state);
+            let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+            manager.remove_many_from_history(&indices)?
+        };
+        for removed_item in &removed_items {
+            try_replace_text_clipboard_after_remove(&state, &app, removed_item);
+        }
+        Ok(removed_items.len())
+    })
+}
+
 fn execute_remove_image_clipboard_item(
     index: usize,
     state: Arc<Mutex<SharedAppState>>,
     app: AppHandle,
 ) -> Result<(), String> {
-    with_updating_clipboard(&state, || {
+    with_updating_clipboard(|| {
         let removed_signature = {
-            let state_guard = state.lock().unwrap();
-            let manager = state_guard.image_clipboard_manager.lock().unwrap();
+            let image_clipboard_manager = image_clipboard_manager_of(&state);
+            let manager = lock_checked(&image_clipboard_manager, "ImageClipboardManager");
             let (_, _, signature) = manager.remove_from_history(index)?;
             signature
         };
@@ -384,18 +579,20 @@ fn execute_select_and_fill_image(
     let fill_seq = begin_fill_sequence(&state, FillKind::Image);
     let operation_id = request.op_id.unwrap_or(fill_seq);
 
-    hide_image_clipboard_window(app.clone(), state.clone());
+    hide_image_clipboard_window(app.clone());
 
     spawn_fill_task(
+        &UiWorkerSpawner,
         FillKind::Image,
         app,
         state,
         fill_seq,
         operation_id,
+        PasteMethod::ClipboardPaste,
         move |app_handle, state_ref| {
             let image = {
-                let state_guard = state_ref.lock().unwrap();
-                let manager = state_guard.image_clipboard_manager.lock().unwrap();
+                let image_clipboard_manager = image_clipboard_manager_of(state_ref);
+                let manager = lock_checked(&image_clipboard_manager, "ImageClipboardManager");
                 manager.promote_to_top(index)?;
                 manager.get_image_by_index(0)?
             };
@@ -404,6 +601,7 @@ fn execute_select_and_fill_image(
             )?;
             Ok(())
         },
+        |_, _| {},
     );
 
     Ok(())
@@ -411,743 +609,2589 @@ fn execute_select_and_fill_image(
 
 #[tauri::command]
 pub async fn get_clipboard_history(
+    sort_by: Option<HistorySortMode>,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
 ) -> Result<HistoryResponse, String> {
-    let state_guard = state.lock().unwrap();
-    let manager = state_guard.clipboard_manager.lock().unwrap();
+    let clipboard_manager = clipboard_manager_of(&state);
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    let use_counts = manager.get_use_counts();
+    let mut display_order: Vec<usize> = (0..use_counts.len()).collect();
+    if sort_by.unwrap_or_default() == HistorySortMode::Frequency {
+        display_order.sort_by_key(|&index| std::cmp::Reverse(use_counts[index]));
+    }
     Ok(HistoryResponse {
-        history: manager.get_history(),
-        categories: manager.get_categories(),
+        history: manager.get_previews(),
+        content_types: manager.get_content_types(),
+        categories: manager.get_item_categories(),
         category_list: manager.get_category_list(),
+        use_counts,
+        display_order,
     })
 }
 
+/// 获取完整的剪贴板历史内容（get_clipboard_history 只返回预览）
 #[tauri::command]
-pub async fn set_item_category(
-    item: String,
-    category: String,
+pub async fn get_clipboard_history_full(
     state: State<'_, Arc<Mutex<SharedAppState>>>,
-) -> Result<(), String> {
-    let state_guard = state.lock().unwrap();
-    let manager = state_guard.clipboard_manager.lock().unwrap();
-    manager.set_category(item, category)
+) -> Result<Vec<String>, String> {
+    let clipboard_manager = clipboard_manager_of(&state);
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    Ok(manager.get_history())
 }
 
+/// 获取单条剪贴板记录，索引越界时返回 None
 #[tauri::command]
-pub async fn remove_category(
-    category: String,
+pub async fn get_clipboard_entry(
+    index: usize,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
-) -> Result<(), String> {
-    let state_guard = state.lock().unwrap();
-    let manager = state_guard.clipboard_manager.lock().unwrap();
-    manager.remove_category(category)
+) -> Result<Option<String>, String> {
+    let clipboard_manager = clipboard_manager_of(&state);
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    Ok(manager.get_entry(index))
+}
+
+/// 在长度为 len 的列表中从 position 移动 offset 位，列表为空时返回 None
+fn step_position(position: usize, offset: i32, len: usize, wrap_around: bool) -> Option<usize> {
+    if len == 0 {
+        return None;
+    }
+    let target = position as i64 + offset as i64;
+    let len = len as i64;
+    Some(if wrap_around {
+        target.rem_euclid(len)
+    } else {
+        target.clamp(0, len - 1)
+    } as usize)
+}
+
+/// 将当前选中位置移动 offset 位，按 selection_wrap_around 设置循环或停在两端，
+/// 返回新的索引（不移动记录本身）。剪贴板窗口有输入过滤时只在匹配的记录间移动
+pub(crate) fn shift_selection_index(state: &Arc<Mutex<SharedAppState>>, offset: i32) -> Result<usize, String> {
+    let (wrap_around, filter) = {
+        let state_guard = state.lock().unwrap();
+        (state_guard.settings.selection_wrap_around, state_guard.history_filter.clone())
+    };
+    let clipboard_manager = clipboard_manager_of(state);
+    // 在管理器锁内读写选中位置，并发的移动请求因此依次执行
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    let base = UI_FLAGS.selected_index.load(Ordering::SeqCst);
+    if let Some(contents) = filter {
+        let indices = manager.current_indices_of(&contents);
+        let position = indices.iter().position(|index| *index == base).unwrap_or(0);
+        let position = step_position(position, offset, indices.len(), wrap_around)
+            .ok_or_else(|| "没有匹配的记录".to_string())?;
+        UI_FLAGS.selected_index.store(indices[position], Ordering::SeqCst);
+        return Ok(indices[position]);
+    }
+    let index = if wrap_around {
+        manager.wrapped_index(base, offset)
+    } else {
+        manager.clamped_index(base, offset)
+    }
+    .ok_or_else(|| "剪贴板历史为空".to_string())?;
+    UI_FLAGS.selected_index.store(index, Ordering::SeqCst);
+    Ok(index)
+}
+
+fn emit_selection_changed(app: &AppHandle, index: usize) {
+    if let Err(e) = app.emit_to("clipboard", "selection-changed", index) {
+        log::error!("发送选中位置变更事件失败: {}", e);
+    }
 }
 
+/// 设置剪贴板窗口的选中位置，超出历史范围时取最后一条，返回实际位置
 #[tauri::command]
-pub async fn add_category(
-    category: String,
+pub async fn set_selected_index(
+    index: usize,
+    app: AppHandle,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
-) -> Result<(), String> {
-    let state_guard = state.lock().unwrap();
-    let manager = state_guard.clipboard_manager.lock().unwrap();
-    manager.add_category(category)
+) -> Result<usize, String> {
+    let index = {
+        let clipboard_manager = clipboard_manager_of(&state);
+        let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+        let index = manager
+            .clamped_index(index, 0)
+            .ok_or_else(|| "剪贴板历史为空".to_string())?;
+        UI_FLAGS.selected_index.store(index, Ordering::SeqCst);
+        index
+    };
+    emit_selection_changed(&app, index);
+    Ok(index)
 }
 
+/// 将选中位置移动 delta 位，返回新的位置
 #[tauri::command]
-pub async fn get_image_clipboard_history(
+pub async fn move_selection(
+    delta: i32,
+    app: AppHandle,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
-) -> Result<ImageHistoryResponse, String> {
-    let state_guard = state.lock().unwrap();
-    let manager = state_guard.image_clipboard_manager.lock().unwrap();
-    Ok(ImageHistoryResponse {
-        history: manager.get_history_preview(),
-        categories: manager.get_categories(),
-        category_list: manager.get_category_list(),
-    })
+) -> Result<usize, String> {
+    let index = shift_selection_index(state.inner(), delta)?;
+    emit_selection_changed(&app, index);
+    Ok(index)
 }
 
+/// 回填当前选中的记录，供剪贴板窗口中按回车时调用
 #[tauri::command]
-pub async fn open_image_preview_window(
-    index: usize,
+pub async fn confirm_selection(
     state: State<'_, Arc<Mutex<SharedAppState>>>,
     app: AppHandle,
-) -> Result<(), String> {
-    show_image_preview_loading_window(app.clone())?;
-    let state_clone = state.inner().clone();
-    let app_clone = app.clone();
-    thread::spawn(move || {
-        let result: Result<(), String> = (|| {
-            let (rgba_base64, width, height) = {
-                let state_guard = state_clone.lock().unwrap();
-                let manager = state_guard.image_clipboard_manager.lock().unwrap();
-                manager.get_preview_window_payload_by_index(index)?
-            };
-            show_image_preview_window(app_clone, rgba_base64, width, height)
-        })();
-        if let Err(e) = result {
-            log::error!("加载预览图片失败: {}", e);
-        }
+) -> Result<String, String> {
+    let index = UI_FLAGS.selected_index.load(Ordering::SeqCst);
+    let filter = state.lock().unwrap().history_filter.clone();
+    let filtered_out = filter.is_some_and(|contents| {
+        let clipboard_manager = clipboard_manager_of(&state);
+        let indices = lock_checked(&clipboard_manager, "ClipboardManager").current_indices_of(&contents);
+        !indices.contains(&index)
     });
-    Ok(())
+    if filtered_out {
+        return Err("没有匹配的记录".to_string());
+    }
+    execute_select_and_fill_text(
+        SelectAndFillRequest {
+            index,
+            op_id: None,
+            paste_method: None,
+        },
+        state.inner().clone(),
+        app,
+    )
 }
 
+/// 剪贴板窗口输入过滤：模糊匹配历史记录，按匹配度返回预览并选中最匹配的一条。
+/// 返回条目的 index 为历史中的实际位置，之后的移动与回填只在匹配的记录间进行，
+/// 期间历史有变动时按内容重新对应到记录的当前位置；
+/// query 为空时取消过滤，返回全部记录并选中第一条
 #[tauri::command]
-pub async fn close_image_preview_window(app: AppHandle) -> Result<(), String> {
-    hide_image_preview_window(app);
-    Ok(())
+pub async fn filter_history(
+    query: String,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<Vec<FuzzyMatch>, String> {
+    let query = query.trim();
+    let matches = {
+        let clipboard_manager = clipboard_manager_of(&state);
+        let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+        let matches = if query.is_empty() {
+            manager
+                .get_history()
+                .into_iter()
+                .enumerate()
+                .map(|(index, text)| (index, 1.0, text))
+                .collect()
+        } else {
+            manager.fuzzy_search(query, HISTORY_FILTER_MAX_DISTANCE)
+        };
+        if let Some((index, _, _)) = matches.first() {
+            UI_FLAGS.selected_index.store(*index, Ordering::SeqCst);
+        }
+        matches
+    };
+    state.lock().unwrap().history_filter =
+        (!query.is_empty()).then(|| matches.iter().map(|(_, _, text)| text.clone()).collect());
+    if let Some((index, _, _)) = matches.first() {
+        emit_selection_changed(&app, *index);
+    }
+    Ok(to_fuzzy_matches(matches))
 }
 
+/// 保存划词工具栏的快捷翻译语言组合，名称与语言去掉首尾空白
 #[tauri::command]
-pub async fn warmup_image_clipboard_item(
-    index: usize,
+pub async fn set_translation_presets(
+    presets: Vec<TranslationPreset>,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
 ) -> Result<(), String> {
-    let state_guard = state.lock().unwrap();
-    let manager = state_guard.image_clipboard_manager.lock().unwrap();
-    manager.warmup_image_by_index(index)
+    let presets: Vec<TranslationPreset> = presets
+        .into_iter()
+        .map(|preset| TranslationPreset {
+            name: preset.name.trim().to_string(),
+            source_language: preset.source_language.trim().to_string(),
+            target_language: preset.target_language.trim().to_string(),
+        })
+        .collect();
+    for preset in &presets {
+        preset.validate()?;
+    }
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.translation_presets = presets;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
+
+/// 发送前的输入 token 估算
+#[derive(serde::Serialize)]
+pub struct TokenEstimate {
+    pub tokens: usize,
+    /// 按当前提供商的价格估算的输入费用，未配置价格时为空
+    pub estimated_cost: Option<f64>,
+    /// 是否超过 large_input_confirm_tokens，前端应先请用户确认
+    pub needs_confirmation: bool,
 }
 
+/// 估算文本作为输入时的 token 数与费用，不发送请求
 #[tauri::command]
-pub async fn set_image_item_category(
-    item_id: String,
-    category: String,
+pub async fn estimate_input_tokens(
+    text: String,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
-) -> Result<(), String> {
+) -> Result<TokenEstimate, String> {
+    let tokens = estimate_tokens(&text);
     let state_guard = state.lock().unwrap();
-    let manager = state_guard.image_clipboard_manager.lock().unwrap();
-    manager.set_category(item_id, category)
+    let settings = &state_guard.settings;
+    let estimated_cost = settings
+        .get_current_provider_config()
+        .and_then(|config| config.price_per_million_tokens)
+        .map(|price| tokens as f64 * price / 1_000_000.0);
+    let threshold = settings.large_input_confirm_tokens;
+    Ok(TokenEstimate {
+        tokens,
+        estimated_cost,
+        needs_confirmation: threshold > 0 && tokens > threshold,
+    })
 }
 
+/// 设置当前提供商每百万输入 token 的价格，为空时不估算费用
 #[tauri::command]
-pub async fn remove_image_category(
-    category: String,
+pub async fn set_provider_token_price(
+    price: Option<f64>,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
 ) -> Result<(), String> {
-    let state_guard = state.lock().unwrap();
-    let manager = state_guard.image_clipboard_manager.lock().unwrap();
-    manager.remove_category(category)
+    if matches!(price, Some(price) if !is_valid_token_price(price)) {
+        return Err("价格必须是非负数".to_string());
+    }
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    let provider = settings.ai_provider.clone();
+    settings
+        .provider_configs
+        .get_mut(&provider)
+        .ok_or_else(|| format!("未找到提供商配置: {}", provider))?
+        .price_per_million_tokens = price;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
 }
 
+/// 设置需要确认的输入 token 数，0 表示不确认
 #[tauri::command]
-pub async fn add_image_category(
-    category: String,
+pub async fn set_large_input_confirm_tokens(
+    tokens: usize,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
 ) -> Result<(), String> {
-    let state_guard = state.lock().unwrap();
-    let manager = state_guard.image_clipboard_manager.lock().unwrap();
-    manager.add_category(category)
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.large_input_confirm_tokens = tokens;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
 }
 
+/// 保存自定义提示词库
 #[tauri::command]
-pub async fn get_clipboard_bottom_offset(
+pub async fn set_custom_prompts(
+    prompts: Vec<CustomPrompt>,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
-) -> Result<i32, String> {
-    let state_guard = state.lock().unwrap();
-    Ok(state_guard.settings.clipboard_bottom_offset)
+) -> Result<(), String> {
+    validate_custom_prompts(&prompts)?;
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.custom_prompts = prompts;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
 }
 
+/// 将自定义提示词库导出为 JSON 文件（只含名称与内容），返回导出条数
 #[tauri::command]
-pub async fn preview_clipboard_bottom_offset(
-    offset: i32,
-    app: AppHandle,
-) -> Result<(), String> {
-    let final_offset = offset.max(0);
-    if let Some(window) = app.get_webview_window("clipboard") {
-        set_window_position(&window, final_offset);
-    }
-    if let Some(window) = app.get_webview_window("image_clipboard") {
-        set_window_position(&window, final_offset);
+pub async fn export_custom_prompts(
+    path: String,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<usize, String> {
+    if path.trim().is_empty() {
+        return Err("导出路径不能为空".to_string());
     }
-    Ok(())
+    let prompts = state.lock().unwrap().settings.custom_prompts.clone();
+    let content = serde_json::to_string_pretty(&prompts).map_err(|e| format!("序列化提示词失败: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("写入导出文件失败: {}", e))?;
+    log::info!("已导出 {} 条自定义提示词到 {}", prompts.len(), path);
+    Ok(prompts.len())
 }
 
+/// 从 JSON 文件导入自定义提示词，返回导入条数。merge 为 false 时替换整个提示词库；
+/// 为 true 时按名称合并，同名但内容不同的提示词保留现有版本，
+/// 并逐条发送 prompt-import-conflict 事件交由前端处理
 #[tauri::command]
-pub async fn save_clipboard_bottom_offset(
-    offset: i32,
+pub async fn import_custom_prompts(
+    path: String,
+    merge: bool,
     app: AppHandle,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
-) -> Result<(), String> {
-    let final_offset = offset.clamp(0, 400);
+) -> Result<usize, String> {
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取导入文件失败: {}", e))?;
+    let imported: Vec<CustomPrompt> =
+        serde_json::from_str(&content).map_err(|e| format!("提示词文件格式错误: {}", e))?;
+    validate_custom_prompts(&imported)?;
+
     let mut settings = {
         let state_guard = state.lock().unwrap();
         state_guard.settings.clone()
     };
-    settings.clipboard_bottom_offset = final_offset;
+    let mut conflicts = Vec::new();
+    let count = if merge {
+        let mut added = 0;
+        for prompt in imported {
+            match settings
+                .custom_prompts
+                .iter()
+                .find(|existing| existing.name.trim() == prompt.name.trim())
+            {
+                Some(existing) if existing.template != prompt.template => conflicts.push(prompt),
+                Some(_) => {}
+                None => {
+                    settings.custom_prompts.push(prompt);
+                    added += 1;
+                }
+            }
+        }
+        added
+    } else {
+        let count = imported.len();
+        settings.custom_prompts = imported;
+        count
+    };
     save_settings(&settings).map_err(|e| e.to_string())?;
-
     {
         let mut state_guard = state.lock().unwrap();
         state_guard.settings = settings;
     }
 
-    if let Some(window) = app.get_webview_window("clipboard") {
-        set_window_position(&window, final_offset);
-    }
-    if let Some(window) = app.get_webview_window("image_clipboard") {
-        set_window_position(&window, final_offset);
+    for prompt in &conflicts {
+        if let Err(e) = app.emit("prompt-import-conflict", prompt) {
+            log::error!("发送提示词导入冲突事件失败: {}", e);
+        }
     }
-    Ok(())
+    log::info!("已导入 {} 条自定义提示词，{} 条名称冲突", count, conflicts.len());
+    Ok(count)
 }
 
+/// 开关AI试运行：开启后翻译与解释只展示提示词和请求体，不调用接口
 #[tauri::command]
-pub async fn select_and_fill(
-    request: SelectAndFillRequest,
+pub async fn set_ai_dry_run(
+    enabled: bool,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
-    app: AppHandle,
-) -> Result<String, String> {
-    execute_select_and_fill_text(request, state.inner().clone(), app)
+) -> Result<(), String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.ai_dry_run = enabled;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    log::info!("AI试运行已{}", if enabled { "开启" } else { "关闭" });
+    Ok(())
 }
 
+/// 设置选中位置移动到两端时是否循环
 #[tauri::command]
-pub async fn remove_clipboard_item(
-    index: usize,
+pub async fn set_selection_wrap_around(
+    enabled: bool,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
-    app: AppHandle,
 ) -> Result<(), String> {
-    execute_remove_clipboard_item(index, state.inner().clone(), app)
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.selection_wrap_around = enabled;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
 }
 
+/// 选中下一条记录（开启循环时到末尾后回到第一条），记录仅在实际粘贴时才移到最前
 #[tauri::command]
-pub async fn remove_image_clipboard_item(
-    index: usize,
+pub async fn advance_selection_index(
     state: State<'_, Arc<Mutex<SharedAppState>>>,
-    app: AppHandle,
-) -> Result<(), String> {
-    execute_remove_image_clipboard_item(index, state.inner().clone(), app)
+) -> Result<usize, String> {
+    shift_selection_index(state.inner(), 1)
 }
 
+/// 选中上一条记录（开启循环时到第一条后回到末尾）
 #[tauri::command]
-pub async fn select_and_fill_image(
-    request: SelectAndFillImageRequest,
+pub async fn retreat_selection_index(
     state: State<'_, Arc<Mutex<SharedAppState>>>,
-    app: AppHandle,
-) -> Result<(), String> {
-    execute_select_and_fill_image(request, state.inner().clone(), app)
+) -> Result<usize, String> {
+    shift_selection_index(state.inner(), -1)
 }
 
+/// 并行搜索剪贴板历史与划词记录，按完全匹配、前缀匹配、包含排序
 #[tauri::command]
-pub async fn window_blur(
+pub async fn global_search(
+    query: String,
+    max_results_per_source: Option<usize>,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
-    app: AppHandle,
-) -> Result<(), String> {
-    let is_visible = {
-        let state_guard = state.lock().unwrap();
-        state_guard.is_visible
-    };
-    if is_visible {
-        let state_clone = state.inner().clone();
-        hide_clipboard_window(app, state_clone);
-    }
-    Ok(())
+) -> Result<GlobalSearchResult, String> {
+    let limit = max_results_per_source.unwrap_or(DEFAULT_GLOBAL_SEARCH_RESULTS_PER_SOURCE);
+
+    let clipboard_manager = clipboard_manager_of(&state);
+    let clipboard_query = query.clone();
+    let clipboard_search = tauri::async_runtime::spawn_blocking(move || {
+        let matches = lock_checked(&clipboard_manager, "ClipboardManager").search(&clipboard_query, limit);
+        with_match_offsets(matches, &clipboard_query)
+    });
+
+    let state_for_selection = state.inner().clone();
+    let selection_search = tauri::async_runtime::spawn_blocking(move || {
+        let selection_texts: Vec<String> = state_for_selection
+            .lock()
+            .unwrap()
+            .selection_history
+            .iter()
+            .map(|entry| entry.text.clone())
+            .collect();
+        with_match_offsets(rank_text_matches(selection_texts.iter(), &query, limit), &query)
+    });
+
+    let (clipboard_matches, selection_matches) = tokio::join!(clipboard_search, selection_search);
+    Ok(GlobalSearchResult {
+        clipboard_matches: clipboard_matches.map_err(|e| format!("搜索剪贴板历史失败: {}", e))?,
+        selection_matches: selection_matches.map_err(|e| format!("搜索划词记录失败: {}", e))?,
+    })
 }
 
+/// 按三元组相似度模糊搜索剪贴板历史，threshold 为 0~1 的相似度下限
 #[tauri::command]
-pub async fn image_window_blur(
+pub async fn fuzzy_search_clipboard(
+    query: String,
+    threshold: f64,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<Vec<FuzzyMatch>, String> {
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err("相似度阈值必须在 0 到 1 之间".to_string());
+    }
+    let max_distance = ((1.0 - threshold) * 100.0).round() as u32;
+    let clipboard_manager = clipboard_manager_of(&state);
+    let matches = lock_checked(&clipboard_manager, "ClipboardManager").fuzzy_search(&query, max_distance);
+    Ok(to_fuzzy_matches(matches))
+}
+
+/// 生成疑似密钥条目的脱敏预览
+fn mask_secret_preview(text: &str) -> String {
+    let prefix: String = text.chars().take(6).collect();
+    format!("{}***（{}字符）", prefix, text.chars().count())
+}
+
+/// 订阅剪贴板历史变更，变更时向前端发送 clipboard-count-changed 事件；
+/// 剪贴板窗口可见时还会发送 history-added / history-removed / history-cleared / history-reset 增量事件，
+/// 窗口隐藏期间的增量事件直接丢弃，再次显示时由完整历史重新同步
+#[tauri::command]
+pub async fn subscribe_clipboard_history_updates(
     app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
 ) -> Result<(), String> {
-    let is_visible = {
-        let state_guard = state.lock().unwrap();
-        state_guard.is_image_visible
-    };
-    if is_visible {
-        let state_clone = state.inner().clone();
-        hide_image_clipboard_window(app, state_clone);
+    let (tx, rx) = mpsc::channel::<ClipboardHistoryEvent>();
+    let (delta_tx, delta_rx) = mpsc::channel::<HistoryDelta>();
+    {
+        let clipboard_manager = clipboard_manager_of(&state);
+        let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+        manager.set_change_listener(Some(tx));
+        manager.set_delta_listener(Some(delta_tx));
     }
+    let delta_app = app.clone();
+    spawn_named("clipboard-history-events", move || {
+        while let Ok(event) = rx.recv() {
+            if let Err(e) = app.emit("clipboard-count-changed", event) {
+                log::error!("发送剪贴板历史变更事件失败: {}", e);
+            }
+        }
+        log::debug!("剪贴板历史变更订阅已结束");
+    });
+    spawn_named("clipboard-history-deltas", move || {
+        while let Ok(delta) = delta_rx.recv() {
+            if !UI_FLAGS.is_visible() {
+                continue;
+            }
+            if let Err(e) = delta_app.emit_to("clipboard", delta.event_name(), &delta) {
+                log::error!("发送剪贴板历史增量事件失败: {}", e);
+            }
+        }
+        log::debug!("剪贴板历史增量订阅已结束");
+    });
     Ok(())
 }
 
+/// 取消订阅剪贴板历史变更
 #[tauri::command]
-pub async fn selection_toolbar_blur(app: AppHandle) -> Result<(), String> {
-    if let Some(toolbar_window) = app.get_webview_window("selection_toolbar") {
-        let _ = toolbar_window.hide();
-    }
+pub async fn unsubscribe_clipboard_history_updates(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let clipboard_manager = clipboard_manager_of(&state);
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    manager.set_change_listener(None);
+    manager.set_delta_listener(None);
     Ok(())
 }
 
+/// 按设置开启或关闭相似度调试：开启时把每次版本比较结果以 similarity-debug 事件发送给前端
+pub fn apply_similarity_debug(app: &AppHandle, state: &Arc<Mutex<SharedAppState>>) {
+    let enabled = state.lock().unwrap().settings.debug_show_similarity;
+    let clipboard_manager = clipboard_manager_of(state);
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    if !enabled {
+        manager.set_similarity_debug_listener(None);
+        return;
+    }
+    let (tx, rx) = mpsc::channel::<SimilarityDebugEvent>();
+    manager.set_similarity_debug_listener(Some(tx));
+    let app = app.clone();
+    spawn_named("similarity-debug-events", move || {
+        while let Ok(event) = rx.recv() {
+            if let Err(e) = app.emit("similarity-debug", event) {
+                log::error!("发送相似度调试事件失败: {}", e);
+            }
+        }
+        log::debug!("相似度调试已关闭");
+    });
+}
 
+/// 开启或关闭相似度调试事件
 #[tauri::command]
-pub async fn get_ai_settings() -> Result<HashMap<String, serde_json::Value>, String> {
-    let settings = load_settings()?;
+pub async fn set_debug_show_similarity(
+    enabled: bool,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.debug_show_similarity = enabled;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    // 转换为HashMap格式，便于前端处理
-    let mut result = HashMap::new();
+    state.lock().unwrap().settings = settings;
+    apply_similarity_debug(&app, state.inner());
+    Ok(())
+}
 
-    // 添加基本设置
-    result.insert(
-        "version".to_string(),
-        serde_json::Value::String(settings.version.clone()),
-    );
-    result.insert(
-        "max_items".to_string(),
-        serde_json::Value::Number(serde_json::Number::from(settings.max_items)),
-    );
-    result.insert(
-        "ai_provider".to_string(),
-        serde_json::Value::String(settings.ai_provider.clone()),
-    );
-    result.insert(
-        "hot_key".to_string(),
-        serde_json::Value::String(settings.hot_key.clone()),
-    );
-    result.insert(
-        "image_hot_key".to_string(),
-        serde_json::Value::String(settings.image_hot_key.clone()),
-    );
-    result.insert(
-        "selection_enabled".to_string(),
-        serde_json::Value::Bool(settings.selection_enabled),
-    );
-    result.insert(
-        "grouped_items_protected_from_limit".to_string(),
-        serde_json::Value::Bool(settings.grouped_items_protected_from_limit),
-    );
-    result.insert(
-        "translation_prompt_template".to_string(),
-        serde_json::Value::String(settings.translation_prompt_template.clone()),
-    );
-    result.insert(
-        "explanation_prompt_template".to_string(),
-        serde_json::Value::String(settings.explanation_prompt_template.clone()),
-    );
-    result.insert(
-        "clipboard_poll_min_interval_ms".to_string(),
-        serde_json::Value::Number(serde_json::Number::from(
-            settings.clipboard_poll_min_interval_ms,
-        )),
-    );
-    result.insert(
-        "clipboard_poll_warm_interval_ms".to_string(),
-        serde_json::Value::Number(serde_json::Number::from(
-            settings.clipboard_poll_warm_interval_ms,
-        )),
-    );
-    result.insert(
-        "clipboard_poll_idle_interval_ms".to_string(),
-        serde_json::Value::Number(serde_json::Number::from(
-            settings.clipboard_poll_idle_interval_ms,
-        )),
-    );
-    result.insert(
-        "clipboard_poll_max_interval_ms".to_string(),
-        serde_json::Value::Number(serde_json::Number::from(
-            settings.clipboard_poll_max_interval_ms,
-        )),
-    );
-    result.insert(
-        "clipboard_poll_report_interval_secs".to_string(),
-        serde_json::Value::Number(serde_json::Number::from(
-            settings.clipboard_poll_report_interval_secs,
-        )),
-    );
-    result.insert(
-        "clipboard_poll_metrics_enabled".to_string(),
-        serde_json::Value::Bool(settings.clipboard_poll_metrics_enabled),
-    );
-    result.insert(
-        "clipboard_poll_metrics_log_level".to_string(),
-        serde_json::Value::String(settings.clipboard_poll_metrics_log_level.clone()),
-    );
+/// 将当前剪贴板历史保存为快照，返回快照标识
+#[tauri::command]
+pub async fn create_clipboard_snapshot(
+    label: String,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<SnapshotId, String> {
+    let clipboard_manager = clipboard_manager_of(&state);
+    let data = lock_checked(&clipboard_manager, "ClipboardManager").snapshot_data();
+    history_snapshot::write_snapshot(&label, &data)
+}
 
-    // 处理provider_configs，将encrypted_api_key替换为解密后的api_key
-    let mut provider_configs_map: HashMap<String, serde_json::Value> = HashMap::new();
+/// 列出所有剪贴板历史快照
+#[tauri::command]
+pub async fn list_clipboard_snapshots() -> Result<Vec<SnapshotInfo>, String> {
+    history_snapshot::list_snapshots()
+}
 
-    let provider_keys: Vec<String> = settings.provider_configs.keys().cloned().collect();
+/// 用快照替换当前剪贴板历史
+#[tauri::command]
+pub async fn restore_clipboard_snapshot(
+    id: SnapshotId,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let data = history_snapshot::read_snapshot(&id)?;
+    let clipboard_manager = clipboard_manager_of(&state);
+    lock_checked(&clipboard_manager, "ClipboardManager").restore_data(data);
+    Ok(())
+}
 
-    for provider_key in provider_keys.iter() {
-        if let Ok(api_key) = settings.get_provider_api_key(provider_key) {
-            if let Some(decrypted_config) = settings.provider_configs.get(provider_key) {
-                let mut config_map = HashMap::new();
-                config_map.insert(
-                    "api_url".to_string(),
-                    serde_json::Value::String(decrypted_config.api_url.clone()),
-                );
-                config_map.insert(
-                    "model_name".to_string(),
-                    serde_json::Value::String(decrypted_config.model_name.clone()),
-                );
-                config_map.insert("api_key".to_string(), serde_json::Value::String(api_key));
+/// 删除剪贴板历史快照
+#[tauri::command]
+pub async fn delete_clipboard_snapshot(id: SnapshotId) -> Result<(), String> {
+    history_snapshot::delete_snapshot(&id)
+}
 
-                provider_configs_map.insert(
-                    provider_key.clone(),
-                    serde_json::Value::Object(config_map.into_iter().collect()),
-                );
-            }
-        }
+/// 对历史条目执行文本转换并原地替换，可用 undo_clipboard_item_translation 撤销
+#[tauri::command]
+pub async fn transform_clipboard_item(
+    index: usize,
+    op: TransformOp,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<String, String> {
+    let clipboard_manager = clipboard_manager_of(&state);
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    let original = manager.get_entry(index).ok_or_else(|| "索引超出范围".to_string())?;
+    let transformed = apply_transform_op(&original, op)?;
+    if transformed != original {
+        manager.replace_at_with_undo(index, transformed.clone())?;
+        log::info!("已转换历史记录: index={}, op={:?}", index, op);
     }
-
-    result.insert(
-        "provider_configs".to_string(),
-        serde_json::Value::Object(provider_configs_map.into_iter().collect()),
-    );
-
-    Ok(result)
+    Ok(transformed)
 }
 
+/// 将文本历史条目写入临时文件供拖拽到其他程序，返回文件路径；文件 24 小时后清理
 #[tauri::command]
-pub async fn get_poll_metrics_history(limit: Option<usize>) -> Result<Vec<serde_json::Value>, String> {
-    if !cfg!(debug_assertions) {
-        return Err("仅开发环境可用".to_string());
+pub async fn materialize_history_item(
+    index: usize,
+    extension: Option<String>,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<String, String> {
+    let content = {
+        let clipboard_manager = clipboard_manager_of(&state);
+        let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+        manager.get_entry(index)
     }
-    let points = poll_metrics::list(limit.unwrap_or(120));
-    let mapped = points
-        .into_iter()
-        .filter_map(|item| serde_json::to_value(item).ok())
-        .collect();
-    Ok(mapped)
+    .ok_or_else(|| format!("索引 {} 超出范围", index))?;
+    let path = drag_export::materialize_text(&content, extension.as_deref().unwrap_or("txt"))?;
+    Ok(path.to_string_lossy().to_string())
 }
 
+/// 将图片历史条目写入临时 PNG 文件供拖拽到其他程序，返回文件路径；文件 24 小时后清理
 #[tauri::command]
-pub async fn get_poll_metrics_minute_aggregates(
-    limit_minutes: Option<usize>,
-) -> Result<Vec<serde_json::Value>, String> {
-    if !cfg!(debug_assertions) {
-        return Err("仅开发环境可用".to_string());
-    }
-    let rows = poll_metrics::aggregate_by_minute(limit_minutes.unwrap_or(60));
-    let mapped = rows
-        .into_iter()
-        .filter_map(|item| serde_json::to_value(item).ok())
-        .collect();
-    Ok(mapped)
+pub async fn materialize_image_history_item(
+    index: usize,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<String, String> {
+    let image = {
+        let image_clipboard_manager = image_clipboard_manager_of(&state);
+        let manager = lock_checked(&image_clipboard_manager, "ImageClipboardManager");
+        manager.get_image_by_index(index)?
+    };
+    let path = drag_export::materialize_png(image.rgba(), image.width(), image.height())?;
+    Ok(path.to_string_lossy().to_string())
 }
 
+/// 获取最近一次去重处理的决定，用于解释条目为何被替换或前移
 #[tauri::command]
-pub async fn export_poll_metrics(format: String, limit: Option<usize>) -> Result<String, String> {
-    if !cfg!(debug_assertions) {
-        return Err("仅开发环境可用".to_string());
-    }
-    let limit = limit.unwrap_or(720);
-    match format.as_str() {
-        "json" => poll_metrics::export_json(limit),
-        "csv" => Ok(poll_metrics::export_csv(limit)),
-        _ => Err("不支持的导出格式，仅支持 json/csv".to_string()),
-    }
+pub async fn get_last_dedup_decision(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<Option<DedupDecision>, String> {
+    let clipboard_manager = clipboard_manager_of(&state);
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    Ok(manager.get_last_dedup_decision())
 }
 
+/// 扫描剪贴板历史中疑似误复制的密钥
 #[tauri::command]
-pub async fn export_poll_metrics_to_file(
-    format: String,
-    limit: Option<usize>,
-    file_path: String,
-) -> Result<String, String> {
-    if !cfg!(debug_assertions) {
-        return Err("仅开发环境可用".to_string());
-    }
-    if file_path.trim().is_empty() {
-        return Err("导出路径不能为空".to_string());
-    }
-    let content = export_poll_metrics(format, limit).await?;
-    fs::write(&file_path, content).map_err(|e| format!("写入导出文件失败: {}", e))?;
-    Ok(file_path)
+pub async fn scan_clipboard_for_secrets(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<Vec<SecretWarning>, String> {
+    let history = {
+        let clipboard_manager = clipboard_manager_of(&state);
+        let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+        manager.get_history()
+    };
+    Ok(history
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            detect_secret_pattern(item).map(|pattern| SecretWarning {
+                index,
+                content_preview: mask_secret_preview(item),
+                pattern_matched: pattern.to_string(),
+            })
+        })
+        .collect())
 }
 
+/// 从剪贴板历史中移除所有疑似密钥条目
 #[tauri::command]
-pub async fn get_text_dedup_metrics() -> Result<serde_json::Value, String> {
-    if !cfg!(debug_assertions) {
-        return Err("仅开发环境可用".to_string());
-    }
-    serde_json::to_value(get_dedup_scan_metrics()).map_err(|e| e.to_string())
+pub async fn remove_secrets_from_history(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<usize, String> {
+    let clipboard_manager = clipboard_manager_of(&state);
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    let removed = manager.remove_secret_items();
+    log::info!("已移除疑似密钥条目: {}", removed);
+    Ok(removed)
 }
 
 #[tauri::command]
-pub async fn save_app_settings(
-    max_items: usize,
+pub async fn set_item_category(
+    item: String,
+    category: String,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let clipboard_manager = clipboard_manager_of(&state);
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    manager.set_category(item, category)
+}
+
+#[tauri::command]
+pub async fn remove_category(
+    category: String,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let clipboard_manager = clipboard_manager_of(&state);
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    manager.remove_category(category)
+}
+
+#[tauri::command]
+pub async fn add_category(
+    category: String,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let clipboard_manager = clipboard_manager_of(&state);
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    manager.add_category(category)
+}
+
+#[tauri::command]
+pub async fn get_image_clipboard_history(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<ImageHistoryResponse, String> {
+    let image_clipboard_manager = image_clipboard_manager_of(&state);
+    let manager = lock_checked(&image_clipboard_manager, "ImageClipboardManager");
+    Ok(ImageHistoryResponse {
+        history: manager.get_history_preview(),
+        categories: manager.get_categories(),
+        category_list: manager.get_category_list(),
+    })
+}
+
+#[tauri::command]
+pub async fn open_image_preview_window(
+    index: usize,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+    app: AppHandle,
+) -> Result<(), String> {
+    show_image_preview_loading_window(app.clone())?;
+    let state_clone = state.inner().clone();
+    let app_clone = app.clone();
+    thread::spawn(move || {
+        let result: Result<(), String> = (|| {
+            let (rgba_base64, width, height) = {
+                let image_clipboard_manager = image_clipboard_manager_of(&state_clone);
+                let manager = lock_checked(&image_clipboard_manager, "ImageClipboardManager");
+                manager.get_preview_window_payload_by_index(index)?
+            };
+            show_image_preview_window(app_clone, rgba_base64, width, height)
+        })();
+        if let Err(e) = result {
+            log::error!("加载预览图片失败: {}", e);
+        }
+    });
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn close_image_preview_window(app: AppHandle) -> Result<(), String> {
+    hide_image_preview_window(app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn warmup_image_clipboard_item(
+    index: usize,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let image_clipboard_manager = image_clipboard_manager_of(&state);
+    let manager = lock_checked(&image_clipboard_manager, "ImageClipboardManager");
+    manager.warmup_image_by_index(index)
+}
+
+#[tauri::command]
+pub async fn set_image_item_category(
+    item_id: String,
+    category: String,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let image_clipboard_manager = image_clipboard_manager_of(&state);
+    let manager = lock_checked(&image_clipboard_manager, "ImageClipboardManager");
+    manager.set_category(item_id, category)
+}
+
+#[tauri::command]
+pub async fn remove_image_category(
+    category: String,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let image_clipboard_manager = image_clipboard_manager_of(&state);
+    let manager = lock_checked(&image_clipboard_manager, "ImageClipboardManager");
+    manager.remove_category(category)
+}
+
+#[tauri::command]
+pub async fn add_image_category(
+    category: String,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let image_clipboard_manager = image_clipboard_manager_of(&state);
+    let manager = lock_checked(&image_clipboard_manager, "ImageClipboardManager");
+    manager.add_category(category)
+}
+
+#[tauri::command]
+pub async fn get_clipboard_bottom_offset(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<i32, String> {
+    let state_guard = state.lock().unwrap();
+    Ok(state_guard.settings.clipboard_bottom_offset)
+}
+
+#[tauri::command]
+pub async fn preview_clipboard_bottom_offset(
+    offset: i32,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let final_offset = offset.max(0);
+    let strategy = state.lock().unwrap().settings.window_position_strategy;
+    if let Some(window) = app.get_webview_window("clipboard") {
+        set_window_position(&window, final_offset, strategy);
+    }
+    if let Some(window) = app.get_webview_window("image_clipboard") {
+        set_window_position(&window, final_offset, strategy);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn save_clipboard_bottom_offset(
+    offset: i32,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let final_offset = offset.clamp(0, 400);
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.clipboard_bottom_offset = final_offset;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let strategy = settings.window_position_strategy;
+    {
+        let mut state_guard = state.lock().unwrap();
+        state_guard.settings = settings;
+    }
+
+    if let Some(window) = app.get_webview_window("clipboard") {
+        set_window_position(&window, final_offset, strategy);
+    }
+    if let Some(window) = app.get_webview_window("image_clipboard") {
+        set_window_position(&window, final_offset, strategy);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_result_window_default_size(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(u32, u32), String> {
+    let state_guard = state.lock().unwrap();
+    Ok((
+        state_guard.settings.result_window_width,
+        state_guard.settings.result_window_height,
+    ))
+}
+
+#[tauri::command]
+pub async fn save_result_window_default_size(
+    width: u32,
+    height: u32,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.result_window_width = width.clamp(240, 3840);
+    settings.result_window_height = height.clamp(160, 2160);
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
+
+/// 清除已记住的结果窗口位置，未指定类型时清除全部
+#[tauri::command]
+pub async fn reset_result_window_geometry(
+    window_type: Option<String>,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    match window_type {
+        Some(window_type) => {
+            settings.result_window_geometries.remove(&window_type);
+        }
+        None => settings.result_window_geometries.clear(),
+    }
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
+
+/// 切换结果窗口置顶状态，返回切换后的状态
+#[tauri::command]
+pub async fn toggle_result_window_always_on_top(
+    window_type: String,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<bool, String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    let pinned = !settings
+        .result_window_always_on_top
+        .get(&window_type)
+        .copied()
+        .unwrap_or(false);
+    settings
+        .result_window_always_on_top
+        .insert(window_type.clone(), pinned);
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    {
+        let mut state_guard = state.lock().unwrap();
+        state_guard.settings = settings;
+    }
+
+    if let Some(window) = app.get_webview_window(&format!("result_{}", window_type)) {
+        window
+            .set_always_on_top(pinned)
+            .map_err(|e| format!("设置窗口置顶失败: {}", e))?;
+    }
+    Ok(pinned)
+}
+
+#[tauri::command]
+pub async fn get_selection_history(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<Vec<SelectionHistoryEntry>, String> {
+    let state_guard = state.lock().unwrap();
+    Ok(state_guard.selection_history.iter().cloned().collect())
+}
+
+#[tauri::command]
+pub async fn clear_selection_history(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let mut state_guard = state.lock().unwrap();
+    state_guard.selection_history.clear();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_record_selection_history(
+    enabled: bool,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.record_selection_history = enabled;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    {
+        let mut state_guard = state.lock().unwrap();
+        state_guard.settings = settings;
+        if !enabled {
+            state_guard.selection_history.clear();
+        }
+    }
+    refresh_tray_menu(&app, state.inner().clone());
+    Ok(())
+}
+
+/// 用划词记录中的文本重新打开划词工具栏，以便再次翻译或解释；该条记录移到最前
+#[tauri::command]
+pub async fn reopen_selection_history_item(
+    index: usize,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<String, String> {
+    let text = {
+        let mut state_guard = state.lock().unwrap();
+        let text = state_guard
+            .selection_history
+            .get(index)
+            .map(|entry| entry.text.clone())
+            .ok_or_else(|| "划词记录索引无效".to_string())?;
+        state_guard.record_selection(&text);
+        text
+    };
+    crate::ui::window_manager::show_selection_toolbar_impl(app, text.clone(), None);
+    Ok(text)
+}
+
+/// 立即捕获前台应用中的选中文本，delay_ms 用于留出切换窗口的时间
+#[tauri::command]
+pub async fn capture_selection_now(
+    delay_ms: Option<u64>,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<String, String> {
+    let state_arc = state.inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(delay) = delay_ms {
+            thread::sleep(Duration::from_millis(delay.min(10_000)));
+        }
+        features::mouse_listener::capture_selection_now(&app, &state_arc)
+    })
+    .await
+    .map_err(|e| format!("手动划词任务失败: {}", e))?
+}
+
+#[tauri::command]
+pub async fn select_and_fill(
+    request: SelectAndFillRequest,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+    app: AppHandle,
+) -> Result<String, String> {
+    execute_select_and_fill_text(request, state.inner().clone(), app)
+}
+
+#[tauri::command]
+pub async fn remove_clipboard_item(
+    index: usize,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+    app: AppHandle,
+) -> Result<(), String> {
+    execute_remove_clipboard_item(index, state.inner().clone(), app)
+}
+
+/// 批量删除剪贴板记录，返回删除数量
+#[tauri::command]
+pub async fn remove_clipboard_items(
+    indices: Vec<usize>,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+    app: AppHandle,
+) -> Result<usize, String> {
+    execute_remove_clipboard_items(indices, state.inner().clone(), app)
+}
+
+#[tauri::command]
+pub async fn remove_image_clipboard_item(
+    index: usize,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+    app: AppHandle,
+) -> Result<(), String> {
+    execute_remove_image_clipboard_item(index, state.inner().clone(), app)
+}
+
+#[tauri::command]
+pub async fn select_and_fill_image(
+    request: SelectAndFillImageRequest,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+    app: AppHandle,
+) -> Result<(), String> {
+    execute_select_and_fill_image(request, state.inner().clone(), app)
+}
+
+#[tauri::command]
+pub async fn window_blur(app: AppHandle) -> Result<(), String> {
+    if UI_FLAGS.is_visible() {
+        hide_clipboard_window(app);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn image_window_blur(app: AppHandle) -> Result<(), String> {
+    if UI_FLAGS.is_image_visible() {
+        hide_image_clipboard_window(app);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn selection_toolbar_blur(app: AppHandle) -> Result<(), String> {
+    if let Some(toolbar_window) = app.get_webview_window("selection_toolbar") {
+        let _ = toolbar_window.hide();
+    }
+    Ok(())
+}
+
+
+/// 读取内存中的当前设置；设置文件写入失败或无法读取时，内存中的设置才是实际生效的值
+#[tauri::command]
+pub async fn get_ai_settings(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<HashMap<String, serde_json::Value>, String> {
+    let settings = state.lock().unwrap().settings.clone();
+
+    // 转换为HashMap格式，便于前端处理
+    let mut result = HashMap::new();
+
+    // 添加基本设置
+    result.insert(
+        "version".to_string(),
+        serde_json::Value::String(settings.version.clone()),
+    );
+    result.insert(
+        "max_items".to_string(),
+        serde_json::Value::Number(serde_json::Number::from(settings.max_items)),
+    );
+    result.insert(
+        "ai_provider".to_string(),
+        serde_json::Value::String(settings.ai_provider.clone()),
+    );
+    result.insert(
+        "hot_key".to_string(),
+        serde_json::Value::String(settings.hot_key.clone()),
+    );
+    result.insert(
+        "image_hot_key".to_string(),
+        serde_json::Value::String(settings.image_hot_key.clone()),
+    );
+    result.insert(
+        "selection_enabled".to_string(),
+        serde_json::Value::Bool(settings.selection_enabled),
+    );
+    result.insert(
+        "grouped_items_protected_from_limit".to_string(),
+        serde_json::Value::Bool(settings.grouped_items_protected_from_limit),
+    );
+    result.insert(
+        "translation_prompt_template".to_string(),
+        serde_json::Value::String(settings.translation_prompt_template.clone()),
+    );
+    result.insert(
+        "explanation_prompt_template".to_string(),
+        serde_json::Value::String(settings.explanation_prompt_template.clone()),
+    );
+    result.insert(
+        "clipboard_poll_min_interval_ms".to_string(),
+        serde_json::Value::Number(serde_json::Number::from(
+            settings.clipboard_poll_min_interval_ms,
+        )),
+    );
+    result.insert(
+        "clipboard_poll_warm_interval_ms".to_string(),
+        serde_json::Value::Number(serde_json::Number::from(
+            settings.clipboard_poll_warm_interval_ms,
+        )),
+    );
+    result.insert(
+        "clipboard_poll_idle_interval_ms".to_string(),
+        serde_json::Value::Number(serde_json::Number::from(
+            settings.clipboard_poll_idle_interval_ms,
+        )),
+    );
+    result.insert(
+        "clipboard_poll_max_interval_ms".to_string(),
+        serde_json::Value::Number(serde_json::Number::from(
+            settings.clipboard_poll_max_interval_ms,
+        )),
+    );
+    result.insert(
+        "clipboard_poll_report_interval_secs".to_string(),
+        serde_json::Value::Number(serde_json::Number::from(
+            settings.clipboard_poll_report_interval_secs,
+        )),
+    );
+    result.insert(
+        "clipboard_poll_metrics_enabled".to_string(),
+        serde_json::Value::Bool(settings.clipboard_poll_metrics_enabled),
+    );
+    result.insert(
+        "clipboard_poll_metrics_log_level".to_string(),
+        serde_json::Value::String(settings.clipboard_poll_metrics_log_level.clone()),
+    );
+    result.insert(
+        "update_channel".to_string(),
+        serde_json::Value::String(settings.update_channel.clone()),
+    );
+    result.insert(
+        "auto_check_updates".to_string(),
+        serde_json::Value::Bool(settings.auto_check_updates),
+    );
+    result.insert(
+        "window_position_strategy".to_string(),
+        serde_json::to_value(settings.window_position_strategy).unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "paste_method".to_string(),
+        serde_json::to_value(settings.paste_method).unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "post_paste_action".to_string(),
+        serde_json::to_value(settings.post_paste_action).unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "large_input_confirm_tokens".to_string(),
+        serde_json::Value::from(settings.large_input_confirm_tokens),
+    );
+    result.insert(
+        "price_per_million_tokens".to_string(),
+        settings
+            .get_current_provider_config()
+            .and_then(|config| config.price_per_million_tokens)
+            .map(serde_json::Value::from)
+            .unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "custom_prompts".to_string(),
+        serde_json::to_value(&settings.custom_prompts).unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "ai_dry_run".to_string(),
+        serde_json::Value::Bool(settings.ai_dry_run),
+    );
+    result.insert(
+        "translation_presets".to_string(),
+        serde_json::to_value(&settings.translation_presets).unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "selection_wrap_around".to_string(),
+        serde_json::Value::Bool(settings.selection_wrap_around),
+    );
+    result.insert(
+        "paste_method_rules".to_string(),
+        serde_json::to_value(&settings.paste_method_rules).unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "type_text_key_delay_ms".to_string(),
+        serde_json::Value::from(settings.type_text_key_delay_ms),
+    );
+    result.insert(
+        "paste_transforms".to_string(),
+        serde_json::to_value(&settings.paste_transforms).unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "fallback_translate_url".to_string(),
+        serde_json::Value::String(settings.fallback_translate_url.clone()),
+    );
+    result.insert(
+        "do_not_disturb".to_string(),
+        serde_json::Value::Bool(settings.do_not_disturb),
+    );
+    result.insert(
+        "auto_dnd_fullscreen".to_string(),
+        serde_json::Value::Bool(settings.auto_dnd_fullscreen),
+    );
+    result.insert(
+        "dnd_hot_key".to_string(),
+        serde_json::Value::String(settings.dnd_hot_key.clone()),
+    );
+    result.insert(
+        "global_search_hot_key".to_string(),
+        serde_json::Value::String(settings.global_search_hot_key.clone()),
+    );
+    result.insert(
+        "suppress_toolbar_for".to_string(),
+        serde_json::to_value(&settings.suppress_toolbar_for).unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "sentence_punctuation".to_string(),
+        serde_json::to_value(&settings.sentence_punctuation).unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "chord_shortcuts".to_string(),
+        serde_json::to_value(&settings.chord_shortcuts).unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "custom_actions".to_string(),
+        serde_json::to_value(&settings.custom_actions).unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "log_level".to_string(),
+        serde_json::Value::String(settings.log_level.clone()),
+    );
+    result.insert(
+        "log_retention_days".to_string(),
+        serde_json::Value::from(settings.log_retention_days),
+    );
+    result.insert(
+        "autostart_listener_delay_secs".to_string(),
+        serde_json::Value::from(settings.autostart_listener_delay_secs),
+    );
+    result.insert(
+        "selection_capture_timeout_ms".to_string(),
+        serde_json::Value::from(settings.selection_capture_timeout_ms),
+    );
+    result.insert(
+        "selection_capture_interval_ms".to_string(),
+        serde_json::Value::from(settings.selection_capture_interval_ms),
+    );
+    result.insert(
+        "translation_chunk_chars".to_string(),
+        serde_json::Value::from(settings.translation_chunk_chars),
+    );
+    result.insert(
+        "selection_uia_max_chars".to_string(),
+        serde_json::Value::from(settings.selection_uia_max_chars),
+    );
+    result.insert(
+        "max_concurrent_ai_requests".to_string(),
+        serde_json::Value::from(settings.max_concurrent_ai_requests),
+    );
+    result.insert(
+        "debug_show_similarity".to_string(),
+        serde_json::Value::Bool(settings.debug_show_similarity),
+    );
+    result.insert(
+        "log_ai_transcripts".to_string(),
+        serde_json::Value::Bool(settings.log_ai_transcripts),
+    );
+    result.insert(
+        "dedup_mode".to_string(),
+        serde_json::to_value(settings.dedup_mode).unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "settings_format".to_string(),
+        serde_json::to_value(settings.settings_format).unwrap_or(serde_json::Value::Null),
+    );
+    result.insert(
+        "capture_selection_hot_key".to_string(),
+        serde_json::Value::String(settings.capture_selection_hot_key.clone()),
+    );
+    result.insert(
+        "record_selection_history".to_string(),
+        serde_json::Value::Bool(settings.record_selection_history),
+    );
+    result.insert(
+        "result_window_width".to_string(),
+        serde_json::Value::Number(serde_json::Number::from(settings.result_window_width)),
+    );
+    result.insert(
+        "result_window_height".to_string(),
+        serde_json::Value::Number(serde_json::Number::from(settings.result_window_height)),
+    );
+
+    // 处理provider_configs，将encrypted_api_key替换为解密后的api_key
+    let mut provider_configs_map: HashMap<String, serde_json::Value> = HashMap::new();
+
+    let provider_keys: Vec<String> = settings.provider_configs.keys().cloned().collect();
+
+    for provider_key in provider_keys.iter() {
+        if let Ok(api_key) = settings.get_provider_api_key(provider_key) {
+            if let Some(decrypted_config) = settings.provider_configs.get(provider_key) {
+                let mut config_map = HashMap::new();
+                config_map.insert(
+                    "api_url".to_string(),
+                    serde_json::Value::String(decrypted_config.api_url.clone()),
+                );
+                config_map.insert(
+                    "model_name".to_string(),
+                    serde_json::Value::String(decrypted_config.model_name.clone()),
+                );
+                config_map.insert("api_key".to_string(), serde_json::Value::String(api_key));
+                config_map.insert(
+                    "timeout_secs".to_string(),
+                    serde_json::Value::from(decrypted_config.request_timeout_secs()),
+                );
+
+                provider_configs_map.insert(
+                    provider_key.clone(),
+                    serde_json::Value::Object(config_map.into_iter().collect()),
+                );
+            }
+        }
+    }
+
+    result.insert(
+        "provider_configs".to_string(),
+        serde_json::Value::Object(provider_configs_map.into_iter().collect()),
+    );
+
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn get_poll_metrics_history(limit: Option<usize>) -> Result<Vec<serde_json::Value>, String> {
+    if !cfg!(debug_assertions) {
+        return Err("仅开发环境可用".to_string());
+    }
+    let points = poll_metrics::list(limit.unwrap_or(120));
+    let mapped = points
+        .into_iter()
+        .filter_map(|item| serde_json::to_value(item).ok())
+        .collect();
+    Ok(mapped)
+}
+
+#[tauri::command]
+pub async fn get_poll_metrics_minute_aggregates(
+    limit_minutes: Option<usize>,
+) -> Result<Vec<serde_json::Value>, String> {
+    if !cfg!(debug_assertions) {
+        return Err("仅开发环境可用".to_string());
+    }
+    let rows = poll_metrics::aggregate_by_minute(limit_minutes.unwrap_or(60));
+    let mapped = rows
+        .into_iter()
+        .filter_map(|item| serde_json::to_value(item).ok())
+        .collect();
+    Ok(mapped)
+}
+
+#[tauri::command]
+pub async fn export_poll_metrics(format: String, limit: Option<usize>) -> Result<String, String> {
+    if !cfg!(debug_assertions) {
+        return Err("仅开发环境可用".to_string());
+    }
+    let limit = limit.unwrap_or(720);
+    match format.as_str() {
+        "json" => poll_metrics::export_json(limit),
+        "csv" => Ok(poll_metrics::export_csv(limit)),
+        _ => Err("不支持的导出格式，仅支持 json/csv".to_string()),
+    }
+}
+
+#[tauri::command]
+pub async fn export_poll_metrics_to_file(
+    format: String,
+    limit: Option<usize>,
+    file_path: String,
+) -> Result<String, String> {
+    if !cfg!(debug_assertions) {
+        return Err("仅开发环境可用".to_string());
+    }
+    if file_path.trim().is_empty() {
+        return Err("导出路径不能为空".to_string());
+    }
+    let content = export_poll_metrics(format, limit).await?;
+    fs::write(&file_path, content).map_err(|e| format!("写入导出文件失败: {}", e))?;
+    Ok(file_path)
+}
+
+/// 生成诊断包（最近日志与脱敏设置）到指定路径
+#[tauri::command]
+pub async fn get_diagnostics_bundle(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+    path: String,
+) -> Result<String, String> {
+    if path.trim().is_empty() {
+        return Err("导出路径不能为空".to_string());
+    }
+    generate_diagnostics_bundle(&app, state.inner(), Path::new(&path))?;
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn get_text_dedup_metrics() -> Result<serde_json::Value, String> {
+    if !cfg!(debug_assertions) {
+        return Err("仅开发环境可用".to_string());
+    }
+    serde_json::to_value(get_dedup_scan_metrics()).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn save_app_settings(
+    max_items: usize,
     ai_provider: String,
     ai_api_url: String,
     ai_model_name: String,
     ai_api_key: String,
-    hot_key: String,
-    image_hot_key: String,
-    selection_enabled: bool,
-    grouped_items_protected_from_limit: bool,
-    translation_prompt_template: String,
-    explanation_prompt_template: String,
-    clipboard_poll_min_interval_ms: u64,
-    clipboard_poll_warm_interval_ms: u64,
-    clipboard_poll_idle_interval_ms: u64,
-    clipboard_poll_max_interval_ms: u64,
-    clipboard_poll_report_interval_secs: u64,
-    clipboard_poll_metrics_enabled: bool,
-    clipboard_poll_metrics_log_level: String,
+    hot_key: String,
+    image_hot_key: String,
+    selection_enabled: bool,
+    grouped_items_protected_from_limit: bool,
+    translation_prompt_template: String,
+    explanation_prompt_template: String,
+    clipboard_poll_min_interval_ms: u64,
+    clipboard_poll_warm_interval_ms: u64,
+    clipboard_poll_idle_interval_ms: u64,
+    clipboard_poll_max_interval_ms: u64,
+    clipboard_poll_report_interval_secs: u64,
+    clipboard_poll_metrics_enabled: bool,
+    clipboard_poll_metrics_log_level: String,
+    capture_selection_hot_key: Option<String>,
+    ai_request_timeout_secs: Option<u64>,
+    log_level: Option<String>,
+    custom_headers: Option<HashMap<String, String>>,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let version = app.package_info().version.to_string();
+
+    if let Some(headers) = &custom_headers {
+        validate_custom_headers(headers)?;
+    }
+
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+
+    settings.version = version;
+    settings.max_items = max_items;
+    settings.selection_enabled = selection_enabled;
+    settings.grouped_items_protected_from_limit = grouped_items_protected_from_limit;
+    settings.clipboard_poll_min_interval_ms = clipboard_poll_min_interval_ms;
+    settings.clipboard_poll_warm_interval_ms = clipboard_poll_warm_interval_ms;
+    settings.clipboard_poll_idle_interval_ms = clipboard_poll_idle_interval_ms;
+    settings.clipboard_poll_max_interval_ms = clipboard_poll_max_interval_ms;
+    settings.clipboard_poll_report_interval_secs = clipboard_poll_report_interval_secs;
+    settings.clipboard_poll_metrics_enabled = clipboard_poll_metrics_enabled;
+    settings.clipboard_poll_metrics_log_level = clipboard_poll_metrics_log_level;
+    settings.translation_prompt_template = if translation_prompt_template.trim().is_empty() {
+        default_translation_prompt_template()
+    } else {
+        translation_prompt_template
+    };
+    settings.explanation_prompt_template = if explanation_prompt_template.trim().is_empty() {
+        default_explanation_prompt_template()
+    } else {
+        explanation_prompt_template
+    };
+
+    let hot_key = normalize_hot_key(&hot_key)?;
+    let image_hot_key =
+        normalize_hot_key(&image_hot_key).map_err(|e| format!("图片窗口{}", e))?;
+
+    if hot_key == image_hot_key {
+        return Err("文字与图片窗口快捷键不能相同".to_string());
+    }
+
+    if ai_provider.is_empty() {
+        return Err("提供商名称不能为空".to_string());
+    }
+
+    if ai_api_key.trim().is_empty() {
+        return Err("API密钥不能为空，请填写有效的API密钥".to_string());
+    }
+
+    if hot_key != settings.hot_key {
+        if app.global_shortcut().is_registered(hot_key.as_str()) {
+            return Err("快捷键冲突".to_string());
+        }
+
+        app.global_shortcut()
+            .unregister(settings.hot_key.as_str())
+            .map_err(|e| format!("保存配置失败: {}", e.to_string()))?;
+        register_clipboard_shortcut(&app, state.inner().clone(), &hot_key)?;
+    }
+
+    if image_hot_key != settings.image_hot_key {
+        if app.global_shortcut().is_registered(image_hot_key.as_str()) {
+            return Err("图片窗口快捷键冲突".to_string());
+        }
+
+        app.global_shortcut()
+            .unregister(settings.image_hot_key.as_str())
+            .map_err(|e| format!("保存配置失败: {}", e))?;
+        register_image_clipboard_shortcut(&app, state.inner().clone(), &image_hot_key)?;
+    }
+
+    if let Some(capture_hot_key) = capture_selection_hot_key {
+        let capture_hot_key = capture_hot_key.trim().to_string();
+        if capture_hot_key != settings.capture_selection_hot_key {
+            if !capture_hot_key.is_empty() {
+                if capture_hot_key == hot_key || capture_hot_key == image_hot_key {
+                    return Err("手动划词快捷键不能与窗口快捷键相同".to_string());
+                }
+                if capture_hot_key == settings.dnd_hot_key
+                    || capture_hot_key == settings.global_search_hot_key
+                {
+                    return Err("手动划词快捷键不能与其他快捷键相同".to_string());
+                }
+                if app.global_shortcut().is_registered(capture_hot_key.as_str()) {
+                    return Err("手动划词快捷键冲突".to_string());
+                }
+            }
+            // 先注册新快捷键，失败时旧快捷键仍然有效
+            features::mouse_listener::register_capture_selection_shortcut(
+                &app,
+                state.inner().clone(),
+                &capture_hot_key,
+            )?;
+            if !settings.capture_selection_hot_key.is_empty() {
+                if let Err(e) = app
+                    .global_shortcut()
+                    .unregister(settings.capture_selection_hot_key.as_str())
+                {
+                    // 撤销新注册的快捷键，保持与未修改的设置一致
+                    if !capture_hot_key.is_empty() {
+                        let _ = app.global_shortcut().unregister(capture_hot_key.as_str());
+                    }
+                    return Err(format!("保存配置失败: {}", e));
+                }
+            }
+            settings.capture_selection_hot_key = capture_hot_key;
+        }
+    }
+
+    if let Some(log_level) = log_level {
+        if log_level != settings.log_level {
+            crate::core::logger::apply_log_level_change(&log_level)?;
+            settings.log_level = log_level;
+        }
+    }
+
+    settings.hot_key = hot_key;
+    settings.image_hot_key = image_hot_key;
+    settings.ai_provider = ai_provider.clone();
+
+    settings.migrate_from_old();
+
+    let config = settings
+        .provider_configs
+        .entry(ai_provider.clone())
+        .or_insert_with(|| ProviderConfig::default());
+
+    config.api_url = ai_api_url;
+    config.model_name = ai_model_name;
+    if let Some(timeout) = ai_request_timeout_secs {
+        config.timeout_secs = Some(timeout);
+    }
+    if let Some(headers) = custom_headers {
+        config.custom_headers = headers;
+    }
+
+    settings
+        .save_current_provider_config(&ai_api_key)
+        .map_err(|e| format!("保存提供商配置失败: {}", e))?;
+
+    match settings.get_provider_api_key(&ai_provider) {
+        Ok(key) if key == ai_api_key => {
+            log::info!("密钥保存验证通过");
+        },
+        Ok(_) => {
+            log::warn!("密钥保存验证失败: 读取到的密钥与保存的不一致");
+            return Err("系统凭据管理器异常: 密钥保存验证失败，请重试".to_string());
+        },
+        Err(e) => {
+            log::error!("密钥保存验证错误: {}", e);
+            return Err(format!("系统凭据管理器错误: 无法读取刚保存的密钥 ({})", e));
+        }
+    }
+
+    settings
+        .validate()
+        .map_err(|e| format!("设置验证失败: {}", e))?;
+
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let selection_enabled = settings.selection_enabled;
+    {
+        let clipboard_manager = clipboard_manager_of(&state);
+        let mut manager = lock_checked(&clipboard_manager, "ClipboardManager");
+        manager.set_max_items(max_items);
+        manager.set_grouped_items_protected_from_limit(grouped_items_protected_from_limit);
+    }
+    {
+        let image_clipboard_manager = image_clipboard_manager_of(&state);
+        let mut manager = lock_checked(&image_clipboard_manager, "ImageClipboardManager");
+        manager.set_max_items(max_items);
+        manager.set_grouped_items_protected_from_limit(grouped_items_protected_from_limit);
+    }
+    state.lock().unwrap().settings = settings.clone();
+
+    features::mouse_listener::set_selection_listener_enabled(
+        app.clone(),
+        state.inner().clone(),
+        selection_enabled,
+    );
+    refresh_tray_menu(&app, state.inner().clone());
+
+    log::info!(
+        "设置保存成功: max_items={}, provider={}",
+        max_items,
+        ai_provider
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn test_ai_connection(
+    ai_api_url: String,
+    ai_model_name: String,
+    ai_api_key: String,
+    custom_headers: Option<HashMap<String, String>>,
+) -> Result<String, String> {
+    let custom_headers = custom_headers.unwrap_or_default();
+    validate_custom_headers(&custom_headers)?;
+    let config = AIConfig {
+        api_key: ai_api_key,
+        base_url: ai_api_url,
+        model: ai_model_name,
+        custom_headers,
+    };
+
+    let client = AIClient::new(config).map_err(|e| format!("客户端初始化失败: {}", e))?;
+
+    match client.test_connection().await {
+        Ok(success) => {
+            if success {
+                Ok("连接成功".to_string())
+            } else {
+                Err("连接测试未返回预期结果".to_string())
+            }
+        }
+        Err(e) => {
+            log::error!("AI连接测试失败: {}", e);
+            Err(format!("连接测试失败: {}", e))
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn copy_text(text: String, app: AppHandle) -> Result<(), String> {
+    match app.clipboard().write_text(text) {
+        Ok(()) => {
+            log::info!("文本已复制到剪贴板");
+            Ok(())
+        }
+        Err(e) => {
+            let error_msg = format!("复制文本失败: {}", e);
+            log::error!("{}", error_msg);
+            Err(error_msg)
+        }
+    }
+}
+
+/// 结果窗口翻页后的位置
+#[derive(serde::Serialize)]
+pub struct ResultNavigation {
+    pub index: usize,
+    pub total: usize,
+    pub original: String,
+}
+
+/// 在结果窗口中前后翻看最近的结果，direction 为负向前、为正向后。
+/// 翻看旧结果期间最新请求仍继续接收内容，翻回最新结果时整体推送
+#[tauri::command]
+pub async fn navigate_result(
+    window_type: String,
+    direction: i32,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<ResultNavigation, String> {
+    // 持锁推送整段内容，流式推送需要同一把锁，因此不会穿插到整段内容之前
+    let mut state_guard = state.lock().unwrap();
+    let (current, total) = state_guard.result_position(&window_type);
+    if total == 0 {
+        return Err("结果尚未生成".to_string());
+    }
+    let index = (current as i64 + direction as i64).clamp(0, total as i64 - 1) as usize;
+    let record = state_guard
+        .result_at(&window_type, index)
+        .ok_or_else(|| "结果位置无效".to_string())?;
+    if index + 1 == total {
+        state_guard.result_view_index.remove(&window_type);
+        state_guard
+            .result_synced_len
+            .insert(window_type.clone(), record.content.len());
+    } else {
+        state_guard.result_view_index.insert(window_type.clone(), index);
+    }
+
+    if let Some(window) = app.get_webview_window(&format!("result_{}", window_type)) {
+        let _ = window.emit("result-clean", serde_json::json!({ "type": window_type }));
+    }
+    crate::ui::window_manager::emit_result_slot(&record.content, &window_type, index, total, &app)?;
+    drop(state_guard);
+    Ok(ResultNavigation {
+        index,
+        total,
+        original: record.original,
+    })
+}
+
+fn latest_ai_result(
+    state: &Arc<Mutex<SharedAppState>>,
+    window_type: &str,
+) -> Result<AiResultRecord, String> {
+    let state_guard = state.lock().unwrap();
+    match state_guard.ai_results.get(window_type) {
+        Some(record) if !record.content.trim().is_empty() => Ok(record.clone()),
+        _ => Err("结果尚未生成".to_string()),
+    }
+}
+
+/// 复制结果窗口的原文与结果
+#[tauri::command]
+pub async fn copy_result_pair(
+    window_type: String,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let record = latest_ai_result(state.inner(), &window_type)?;
+    let result_label = match window_type.as_str() {
+        "explanation" => "解释",
+        _ => "译文",
+    };
+    let text = format!(
+        "原文:\n{}\n\n{}:\n{}",
+        record.original.trim(),
+        result_label,
+        record.content.trim()
+    );
+    copy_text(text, app).await
+}
+
+/// 仅复制结果窗口的结果
+#[tauri::command]
+pub async fn copy_result_only(
+    window_type: String,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let record = latest_ai_result(state.inner(), &window_type)?;
+    copy_text(record.content.trim().to_string(), app).await
+}
+
+/// 执行设置文件中定义的自定义动作。动作只能在本地设置文件中定义，
+/// 没有创建或修改动作的命令，本地 HTTP 接口与命令行也不会调用此命令
+#[tauri::command]
+pub async fn run_custom_action(
+    action_id: String,
+    text: String,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<CustomActionOutput, String> {
+    features::custom_actions::run_custom_action(&app, state.inner(), &action_id, text).await
+}
+
+#[tauri::command]
+pub async fn copy_and_paste_text(text: String, app: AppHandle) -> Result<(), String> {
+    app.clipboard()
+        .write_text(text)
+        .map_err(|e| format!("复制文本失败: {}", e))?;
+
+    if let Some(window) = app.get_webview_window("result_translation") {
+        let _ = window.hide();
+    }
+    if let Some(window) = app.get_webview_window("result_explanation") {
+        let _ = window.hide();
+    }
+
+    thread::sleep(Duration::from_millis(80));
+    crate::ui::window_manager::simulate_paste().map_err(|e| format!("自动粘贴失败: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_provider_config(provider: AIProvider) -> Result<(String, String), String> {
+    let (url, model) = provider.get_default_config();
+    Ok((url, model))
+}
+
+#[tauri::command]
+pub async fn remove_ai_provider(
+    provider: String,
     app: AppHandle,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
 ) -> Result<(), String> {
-    let version = app.package_info().version.to_string();
-
+    if provider.is_empty() {
+        return Err("提供商名称不能为空".to_string());
+    }
+
+    let is_builtin = matches!(provider.as_str(), "deepseek" | "qwen" | "xiaomimimo");
+    if is_builtin {
+        return Err("内置提供商不支持删除".to_string());
+    }
+
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+
+    if settings.provider_configs.remove(&provider).is_none() {
+        return Err("未找到该提供商配置".to_string());
+    }
+
+    if settings.ai_provider == provider {
+        let fallback = "deepseek".to_string();
+        if settings.provider_configs.contains_key(&fallback) {
+            settings.ai_provider = fallback;
+        } else if let Some(first_provider) = settings.provider_configs.keys().next() {
+            settings.ai_provider = first_provider.clone();
+        } else {
+            settings.ai_provider = "deepseek".to_string();
+        }
+    }
+
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    {
+        let mut state_guard = state.lock().unwrap();
+        state_guard.settings = settings;
+    }
+    refresh_tray_menu(&app, state.inner().clone());
+
+    Ok(())
+}
+
+/// 发送更新下载阶段通知，由前端在开始、50% 与完成时调用
+#[tauri::command]
+pub async fn notify_update_milestone(message: String, app: AppHandle) -> Result<(), String> {
+    app.notification()
+        .builder()
+        .title("fy_tools")
+        .body(message)
+        .show()
+        .map_err(|e| format!("发送更新通知失败: {}", e))
+}
+
+/// 保存更新通道与自动检查更新设置
+#[tauri::command]
+pub async fn save_update_settings(
+    update_channel: String,
+    auto_check_updates: bool,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    if update_channel != "stable" && update_channel != "beta" {
+        return Err("更新通道仅支持 stable 或 beta".to_string());
+    }
     let mut settings = {
         let state_guard = state.lock().unwrap();
         state_guard.settings.clone()
     };
+    settings.update_channel = update_channel;
+    settings.auto_check_updates = auto_check_updates;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    settings.version = version;
-    settings.max_items = max_items;
-    settings.selection_enabled = selection_enabled;
-    settings.grouped_items_protected_from_limit = grouped_items_protected_from_limit;
-    settings.clipboard_poll_min_interval_ms = clipboard_poll_min_interval_ms;
-    settings.clipboard_poll_warm_interval_ms = clipboard_poll_warm_interval_ms;
-    settings.clipboard_poll_idle_interval_ms = clipboard_poll_idle_interval_ms;
-    settings.clipboard_poll_max_interval_ms = clipboard_poll_max_interval_ms;
-    settings.clipboard_poll_report_interval_secs = clipboard_poll_report_interval_secs;
-    settings.clipboard_poll_metrics_enabled = clipboard_poll_metrics_enabled;
-    settings.clipboard_poll_metrics_log_level = clipboard_poll_metrics_log_level;
-    settings.translation_prompt_template = if translation_prompt_template.trim().is_empty() {
-        default_translation_prompt_template()
-    } else {
-        translation_prompt_template
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
+
+/// 设置剪贴板窗口所在显示器的选择策略
+#[tauri::command]
+pub async fn set_window_position_strategy(
+    strategy: WindowPositionStrategy,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
     };
-    settings.explanation_prompt_template = if explanation_prompt_template.trim().is_empty() {
-        default_explanation_prompt_template()
-    } else {
-        explanation_prompt_template
+    settings.window_position_strategy = strategy;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
+
+/// 设置文本记录回填到目标应用的方式
+#[tauri::command]
+pub async fn set_paste_method(
+    method: PasteMethod,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
     };
+    settings.paste_method = method;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    if hot_key.is_empty() {
-        return Err("快捷键不能为空".to_string());
-    }
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
 
-    if image_hot_key.is_empty() {
-        return Err("图片窗口快捷键不能为空".to_string());
+/// 保存按目标窗口标题强制使用的回填方式规则，按顺序匹配第一条
+#[tauri::command]
+pub async fn set_paste_method_rules(
+    rules: Vec<PasteMethodRule>,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    for rule in &rules {
+        rule.validate()?;
     }
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.paste_method_rules = rules;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    if hot_key == image_hot_key {
-        return Err("文字与图片窗口快捷键不能相同".to_string());
-    }
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
 
-    if ai_provider.is_empty() {
-        return Err("提供商名称不能为空".to_string());
+/// 设置键入回填时每个字符之间的间隔（毫秒）
+#[tauri::command]
+pub async fn set_type_text_key_delay(
+    delay_ms: u64,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    if delay_ms > 200 {
+        return Err("键入间隔必须在0-200毫秒之间".to_string());
     }
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.type_text_key_delay_ms = delay_ms;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    if ai_api_key.trim().is_empty() {
-        return Err("API密钥不能为空，请填写有效的API密钥".to_string());
-    }
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
 
-    if hot_key != settings.hot_key {
+/// 设置文本记录回填完成后执行的操作
+#[tauri::command]
+pub async fn set_post_paste_action(
+    action: PostPasteAction,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.post_paste_action = action;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
+
+/// 设置回填粘贴前启用的文本转换，重复项只保留一个
+#[tauri::command]
+pub async fn set_paste_transforms(
+    mut transforms: Vec<PasteTransform>,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let mut seen = std::collections::HashSet::new();
+    transforms.retain(|transform| seen.insert(*transform));
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.paste_transforms = transforms;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
+
+/// 按当前设置检查文本是否会被划词过滤，并返回过滤原因
+#[tauri::command]
+pub async fn check_selection_valid(
+    text: String,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<features::mouse_listener::SelectionCheck, String> {
+    let state_guard = state.lock().unwrap();
+    Ok(features::mouse_listener::check_selection(&text, &state_guard.settings))
+}
+
+/// 开启或关闭划词（关闭时隐藏划词工具栏），设置会保存
+#[tauri::command]
+pub async fn set_text_selection_enabled(
+    enabled: bool,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    features::mouse_listener::set_text_selection_enabled(&app, state.inner(), enabled)
+}
+
+/// 开启或关闭免打扰
+#[tauri::command]
+pub async fn set_do_not_disturb(
+    enabled: bool,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    features::do_not_disturb::set_enabled(&app, state.inner(), enabled)
+}
+
+/// 设置前台为全屏应用时是否自动进入免打扰
+#[tauri::command]
+pub async fn set_auto_dnd_fullscreen(
+    enabled: bool,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.auto_dnd_fullscreen = enabled;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+    UI_FLAGS.set_auto_dnd_fullscreen(enabled);
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
+
+/// 设置切换免打扰的快捷键，空字符串表示不启用
+#[tauri::command]
+pub async fn set_dnd_hot_key(
+    hot_key: String,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let hot_key = hot_key.trim().to_string();
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    if hot_key == settings.dnd_hot_key {
+        return Ok(());
+    }
+    if !hot_key.is_empty() {
+        if hot_key == settings.hot_key
+            || hot_key == settings.image_hot_key
+            || hot_key == settings.capture_selection_hot_key
+            || hot_key == settings.global_search_hot_key
+        {
+            return Err("免打扰快捷键不能与其他快捷键相同".to_string());
+        }
         if app.global_shortcut().is_registered(hot_key.as_str()) {
-            return Err("快捷键冲突".to_string());
+            return Err("免打扰快捷键冲突".to_string());
         }
-
-        app.global_shortcut()
-            .unregister(settings.hot_key.as_str())
-            .map_err(|e| format!("保存配置失败: {}", e.to_string()))?;
-        let app_clone = app.clone();
-        let state_clone = state.inner().clone();
+    }
+    if !settings.dnd_hot_key.is_empty() {
         app.global_shortcut()
-            .on_shortcut(hot_key.as_str(), move |_app, _shortcut, event| {
-                if let ShortcutState::Pressed = event.state {
-                    let sg = state_clone.lock().unwrap();
-                    if !sg.is_visible && !sg.is_processing_selection {
-                        let state_for_window = state_clone.clone();
-                        drop(sg);
-                        show_clipboard_window(app_clone.clone(), state_for_window);
-                        features::mouse_listener::reset_ctrl_key_state();
-                    }
-                }
-            })
-            .map_err(|e| e.to_string())?;
+            .unregister(settings.dnd_hot_key.as_str())
+            .map_err(|e| format!("注销免打扰快捷键失败: {}", e))?;
     }
+    features::do_not_disturb::register_shortcut(&app, state.inner().clone(), &hot_key)?;
+    settings.dnd_hot_key = hot_key;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    if image_hot_key != settings.image_hot_key {
-        if app.global_shortcut().is_registered(image_hot_key.as_str()) {
-            return Err("图片窗口快捷键冲突".to_string());
-        }
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
 
+/// 设置打开全局搜索窗口的快捷键，空字符串表示不启用
+#[tauri::command]
+pub async fn set_global_search_hot_key(
+    hot_key: String,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let hot_key = hot_key.trim().to_string();
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    if hot_key == settings.global_search_hot_key {
+        return Ok(());
+    }
+    if !hot_key.is_empty() {
+        if hot_key == settings.hot_key
+            || hot_key == settings.image_hot_key
+            || hot_key == settings.capture_selection_hot_key
+            || hot_key == settings.dnd_hot_key
+        {
+            return Err("全局搜索快捷键不能与其他快捷键相同".to_string());
+        }
+        if app.global_shortcut().is_registered(hot_key.as_str()) {
+            return Err("全局搜索快捷键冲突".to_string());
+        }
+    }
+    if !settings.global_search_hot_key.is_empty() {
         app.global_shortcut()
-            .unregister(settings.image_hot_key.as_str())
-            .map_err(|e| format!("保存配置失败: {}", e))?;
-        let app_clone = app.clone();
-        let state_clone = state.inner().clone();
-        app.global_shortcut()
-            .on_shortcut(image_hot_key.as_str(), move |_app, _shortcut, event| {
-                if let ShortcutState::Pressed = event.state {
-                    let sg = state_clone.lock().unwrap();
-                    if !sg.is_visible && !sg.is_image_visible && !sg.is_processing_selection {
-                        let state_for_window = state_clone.clone();
-                        drop(sg);
-                        show_image_clipboard_window(app_clone.clone(), state_for_window);
-                    }
-                }
-            })
-            .map_err(|e| e.to_string())?;
+            .unregister(settings.global_search_hot_key.as_str())
+            .map_err(|e| format!("注销全局搜索快捷键失败: {}", e))?;
     }
+    register_global_search_shortcut(&app, &hot_key)?;
+    settings.global_search_hot_key = hot_key;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    settings.hot_key = hot_key;
-    settings.image_hot_key = image_hot_key;
-    settings.ai_provider = ai_provider.clone();
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
+
+/// 将内容类型加入划词工具栏屏蔽列表，返回更新后的列表
+#[tauri::command]
+pub async fn add_suppressed_content_type(
+    content_type: ContentType,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<Vec<ContentType>, String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    if !settings.suppress_toolbar_for.contains(&content_type) {
+        settings.suppress_toolbar_for.push(content_type);
+        save_settings(&settings).map_err(|e| e.to_string())?;
+    }
+    let suppressed = settings.suppress_toolbar_for.clone();
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(suppressed)
+}
+
+/// 将内容类型移出划词工具栏屏蔽列表，返回更新后的列表
+#[tauri::command]
+pub async fn remove_suppressed_content_type(
+    content_type: ContentType,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<Vec<ContentType>, String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    if settings.suppress_toolbar_for.contains(&content_type) {
+        settings.suppress_toolbar_for.retain(|item| *item != content_type);
+        save_settings(&settings).map_err(|e| e.to_string())?;
+    }
+    let suppressed = settings.suppress_toolbar_for.clone();
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(suppressed)
+}
+
+/// 设置离线翻译接口地址，传入空字符串表示关闭
+#[tauri::command]
+pub async fn set_fallback_translate_url(
+    url: String,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let url = url.trim().to_string();
+    if !is_valid_fallback_translate_url(&url) {
+        return Err("离线翻译地址必须以http://或https://开头".to_string());
+    }
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.fallback_translate_url = url;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
+
+/// 保存两段式组合快捷键并重新注册
+#[tauri::command]
+pub async fn set_chord_shortcuts(
+    chords: Vec<ChordShortcut>,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    for chord in &chords {
+        chord.validate()?;
+    }
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.chord_shortcuts = chords;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    settings.migrate_from_old();
+    state.lock().unwrap().settings = settings;
+    register_chord_shortcuts(&app, state.inner().clone());
+    Ok(())
+}
 
-    let config = settings
-        .provider_configs
-        .entry(ai_provider.clone())
-        .or_insert_with(|| ProviderConfig::default());
+/// 设置去重时判断文本完整性使用的标点集合并立即生效
+#[tauri::command]
+pub async fn set_sentence_punctuation(
+    punctuation: SentencePunctuation,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    punctuation.validate()?;
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.sentence_punctuation = punctuation;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+    apply_sentence_punctuation(&settings.sentence_punctuation);
 
-    config.api_url = ai_api_url;
-    config.model_name = ai_model_name;
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
 
-    settings
-        .save_current_provider_config(&ai_api_key)
-        .map_err(|e| format!("保存提供商配置失败: {}", e))?;
+/// 设置日志级别并立即生效
+#[tauri::command]
+pub async fn set_log_level(
+    level: String,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    crate::core::logger::apply_log_level(&level)?;
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.log_level = level;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    match settings.get_provider_api_key(&ai_provider) {
-        Ok(key) if key == ai_api_key => {
-            log::info!("密钥保存验证通过");
-        },
-        Ok(_) => {
-            log::warn!("密钥保存验证失败: 读取到的密钥与保存的不一致");
-            return Err("系统凭据管理器异常: 密钥保存验证失败，请重试".to_string());
-        },
-        Err(e) => {
-            log::error!("密钥保存验证错误: {}", e);
-            return Err(format!("系统凭据管理器错误: 无法读取刚保存的密钥 ({})", e));
-        }
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
+
+/// 设置日志文件保留天数，下次清理时生效
+#[tauri::command]
+pub async fn set_log_retention_days(
+    days: u32,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    if !(1..=365).contains(&days) {
+        return Err("日志保留天数必须在1-365之间".to_string());
     }
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.log_retention_days = days;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    settings
-        .validate()
-        .map_err(|e| format!("设置验证失败: {}", e))?;
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
 
+/// 设置开机自启后延迟启动监听的秒数，下次开机自启时生效
+#[tauri::command]
+pub async fn set_autostart_listener_delay(
+    seconds: u64,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    if seconds > 300 {
+        return Err("自启动延迟必须在0-300秒之间".to_string());
+    }
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.autostart_listener_delay_secs = seconds;
     save_settings(&settings).map_err(|e| e.to_string())?;
 
-    let selection_enabled = settings.selection_enabled;
-    {
-        let mut state_guard = state.lock().unwrap();
-        {
-            let mut manager = state_guard.clipboard_manager.lock().unwrap();
-            manager.set_max_items(max_items);
-            manager.set_grouped_items_protected_from_limit(grouped_items_protected_from_limit);
-        }
-        {
-            let mut manager = state_guard.image_clipboard_manager.lock().unwrap();
-            manager.set_max_items(max_items);
-            manager.set_grouped_items_protected_from_limit(grouped_items_protected_from_limit);
-        }
-        state_guard.settings = settings.clone();
-    }
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
 
-    features::mouse_listener::set_selection_listener_enabled(
-        app.clone(),
-        state.inner().clone(),
-        selection_enabled,
-    );
+/// 设置划词捕获等待剪贴板更新的最长时间与轮询间隔（毫秒），下次划词时生效
+#[tauri::command]
+pub async fn set_selection_capture_timing(
+    timeout_ms: u64,
+    interval_ms: u64,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    if !is_valid_selection_capture_timing(timeout_ms, interval_ms) {
+        return Err(format!(
+            "划词捕获最长时间必须在100-{}毫秒之间，轮询间隔必须在5-200毫秒之间且不超过最长时间",
+            MAX_SELECTION_CAPTURE_TIMEOUT_MS
+        ));
+    }
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.selection_capture_timeout_ms = timeout_ms;
+    settings.selection_capture_interval_ms = interval_ms;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    log::info!(
-        "设置保存成功: max_items={}, provider={}",
-        max_items,
-        ai_provider
-    );
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
     Ok(())
 }
 
+/// 开启或关闭本地 HTTP 接口；port 为空时沿用已保存的端口，首次开启时生成访问令牌
 #[tauri::command]
-pub async fn test_ai_connection(
-    ai_api_url: String,
-    ai_model_name: String,
-    ai_api_key: String,
-) -> Result<String, String> {
-    let config = AIConfig {
-        api_key: ai_api_key,
-        base_url: ai_api_url,
-        model: ai_model_name,
+pub async fn set_http_api_enabled(
+    enabled: bool,
+    port: Option<u16>,
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<HttpApiStatus, String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
     };
+    settings.http_api_enabled = enabled;
+    if let Some(port) = port {
+        settings.http_api_port = port;
+    }
+    if enabled && settings.http_api_token.is_empty() {
+        settings.http_api_token = http_api::generate_token()?;
+    }
+    save_settings(&settings).map_err(|e| e.to_string())?;
+    {
+        let mut state_guard = state.lock().unwrap();
+        state_guard.settings = settings;
+    }
 
-    let client = AIClient::new(config).map_err(|e| format!("客户端初始化失败: {}", e))?;
-
-    match client.test_connection().await {
-        Ok(success) => {
-            if success {
-                Ok("连接成功".to_string())
-            } else {
-                Err("连接测试未返回预期结果".to_string())
-            }
-        }
-        Err(e) => {
-            log::error!("AI连接测试失败: {}", e);
-            Err(format!("连接测试失败: {}", e))
-        }
+    http_api::apply_settings(&app, state.inner());
+    let status = http_api::status(state.inner());
+    if enabled && !status.running {
+        return Err("本地HTTP接口启动失败，请检查端口是否被占用".to_string());
     }
+    Ok(status)
 }
 
+/// 重新生成本地 HTTP 接口的访问令牌，旧令牌立即失效
 #[tauri::command]
-pub async fn copy_text(text: String, app: AppHandle) -> Result<(), String> {
-    match app.clipboard().write_text(text) {
-        Ok(()) => {
-            log::info!("文本已复制到剪贴板");
-            Ok(())
-        }
-        Err(e) => {
-            let error_msg = format!("复制文本失败: {}", e);
-            log::error!("{}", error_msg);
-            Err(error_msg)
-        }
+pub async fn regenerate_http_api_token(
+    app: AppHandle,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<HttpApiStatus, String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.http_api_token = http_api::generate_token()?;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+    {
+        let mut state_guard = state.lock().unwrap();
+        state_guard.settings = settings;
     }
+
+    http_api::apply_settings(&app, state.inner());
+    Ok(http_api::status(state.inner()))
 }
 
+/// 获取本地 HTTP 接口状态（是否运行、实际端口与访问令牌）
 #[tauri::command]
-pub async fn copy_and_paste_text(text: String, app: AppHandle) -> Result<(), String> {
-    app.clipboard()
-        .write_text(text)
-        .map_err(|e| format!("复制文本失败: {}", e))?;
+pub async fn get_http_api_status(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<HttpApiStatus, String> {
+    Ok(http_api::status(state.inner()))
+}
 
-    if let Some(window) = app.get_webview_window("result_translation") {
-        let _ = window.hide();
-    }
-    if let Some(window) = app.get_webview_window("result_explanation") {
-        let _ = window.hide();
+/// 设置长文本分段翻译的每段最大字符数，0 表示不分段
+#[tauri::command]
+pub async fn set_translation_chunk_chars(
+    chars: usize,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    if !is_valid_translation_chunk_chars(chars) {
+        return Err("分段字符数必须为0或在200-20000之间".to_string());
     }
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.translation_chunk_chars = chars;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    thread::sleep(Duration::from_millis(80));
-    crate::ui::window_manager::simulate_paste().map_err(|e| format!("自动粘贴失败: {}", e))?;
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
     Ok(())
 }
 
+/// 设置 UI Automation 兜底读取选中文本的最大字符数
 #[tauri::command]
-pub async fn get_provider_config(provider: AIProvider) -> Result<(String, String), String> {
-    let (url, model) = provider.get_default_config();
-    Ok((url, model))
+pub async fn set_selection_uia_max_chars(
+    chars: usize,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    if !is_valid_selection_uia_max_chars(chars) {
+        return Err("选中文本最大字符数必须在100-100000之间".to_string());
+    }
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.selection_uia_max_chars = chars;
+    save_settings(&settings).map_err(|e| e.to_string())?;
+
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
 }
 
+/// 设置全局允许同时进行的AI请求数
 #[tauri::command]
-pub async fn remove_ai_provider(
-    provider: String,
+pub async fn set_max_concurrent_ai_requests(
+    limit: u8,
     state: State<'_, Arc<Mutex<SharedAppState>>>,
 ) -> Result<(), String> {
-    if provider.is_empty() {
-        return Err("提供商名称不能为空".to_string());
+    if !(1..=10).contains(&limit) {
+        return Err("AI请求并发数必须在1-10之间".to_string());
     }
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.max_concurrent_ai_requests = limit;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    let is_builtin = matches!(provider.as_str(), "deepseek" | "qwen" | "xiaomimimo");
-    if is_builtin {
-        return Err("内置提供商不支持删除".to_string());
-    }
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    state_guard.set_ai_request_limit(limit);
+    Ok(())
+}
+
+/// 获取当前正在进行的AI请求数，用于诊断并发限制
+#[tauri::command]
+pub async fn get_active_ai_request_count(
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<u8, String> {
+    Ok(state.lock().unwrap().active_ai_request_count())
+}
 
+/// 开启或关闭 AI 请求记录（ai_transcript.log）
+#[tauri::command]
+pub async fn set_log_ai_transcripts(
+    enabled: bool,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
     let mut settings = {
         let state_guard = state.lock().unwrap();
         state_guard.settings.clone()
     };
+    settings.log_ai_transcripts = enabled;
+    save_settings(&settings).map_err(|e| e.to_string())?;
 
-    if settings.provider_configs.remove(&provider).is_none() {
-        return Err("未找到该提供商配置".to_string());
-    }
-
-    if settings.ai_provider == provider {
-        let fallback = "deepseek".to_string();
-        if settings.provider_configs.contains_key(&fallback) {
-            settings.ai_provider = fallback;
-        } else if let Some(first_provider) = settings.provider_configs.keys().next() {
-            settings.ai_provider = first_provider.clone();
-        } else {
-            settings.ai_provider = "deepseek".to_string();
-        }
-    }
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
+    Ok(())
+}
 
+/// 设置剪贴板历史去重方式（none / exact / fuzzy）
+#[tauri::command]
+pub async fn set_dedup_mode(
+    mode: DedupMode,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    settings.dedup_mode = mode;
     save_settings(&settings).map_err(|e| e.to_string())?;
 
-    {
-        let mut state_guard = state.lock().unwrap();
-        state_guard.settings = settings;
-    }
+    let clipboard_manager = clipboard_manager_of(&state);
+    lock_checked(&clipboard_manager, "ClipboardManager").set_dedup_mode(mode);
+    state.lock().unwrap().settings = settings;
+    Ok(())
+}
 
+/// 切换设置文件格式（json / toml）
+#[tauri::command]
+pub async fn convert_settings_format(
+    target: SettingsFormat,
+    state: State<'_, Arc<Mutex<SharedAppState>>>,
+) -> Result<(), String> {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    convert_settings_to_format(&mut settings, target)?;
+    let mut state_guard = state.lock().unwrap();
+    state_guard.settings = settings;
     Ok(())
 }
 
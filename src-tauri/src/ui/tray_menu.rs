@@ -1,22 +1,74 @@
-use crate::core::app_state::{AppState, TrayMenuItems};
+use crate::core::config::TRAY_MENU_REBUILD_DELAY;
+use crate::core::app_state::{
+    clipboard_manager_of, lock_checked, request_shutdown, AppState, TrayMenuItems, UI_FLAGS,
+};
+use crate::core::diagnostics::generate_diagnostics_bundle;
+use crate::features::do_not_disturb;
+use crate::features::mouse_listener::{self, stop_selection_listener};
+use crate::services::ai_services::translate_clipboard_content;
 use crate::ui::window_manager::cleanup_enigo_instance;
+use crate::utils::history_snapshot::write_snapshot;
+use crate::utils::utils_helpers::{save_settings, AppSettingsData};
+#[cfg(debug_assertions)]
+use crate::core::logger::remove_log_files;
 #[cfg(debug_assertions)]
 use crate::utils::utils_helpers::get_logs_dir_path;
 use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
-use tauri::menu::{Menu, MenuItem, Submenu};
+use tauri::menu::{CheckMenuItem, Menu, MenuItem, Submenu};
 use tauri::tray::TrayIconBuilder;
-use tauri::{menu::CheckMenuItemBuilder, AppHandle, Manager};
+use tauri::{menu::CheckMenuItemBuilder, AppHandle, Emitter, Manager};
 use tauri_plugin_autostart::ManagerExt;
+use tauri_plugin_dialog::DialogExt;
+use tauri_plugin_global_shortcut::GlobalShortcutExt;
+use tauri_plugin_notification::NotificationExt;
 #[cfg(debug_assertions)]
 use tauri_plugin_opener::OpenerExt;
 
+/// 托盘菜单中可选显示的菜单项
+#[derive(Debug, Clone, Copy)]
+pub struct TrayMenuOptions {
+    pub show_check_update: bool,
+    pub show_clear_selection_history: bool,
+}
+
+impl TrayMenuOptions {
+    /// 根据当前设置决定显示哪些菜单项
+    pub fn from_settings(settings: &AppSettingsData) -> Self {
+        Self {
+            show_check_update: true,
+            show_clear_selection_history: settings.record_selection_history,
+        }
+    }
+}
+
+/// 托盘中切换 AI 提供商菜单项的 id 前缀
+const PROVIDER_MENU_ID_PREFIX: &str = "provider:";
+
+/// 生成托盘提示文字，包含版本与当前 AI 提供商
+fn tray_tooltip(app_handle: &AppHandle, ai_provider: &str) -> String {
+    let version = app_handle.package_info().version.clone();
+    format!("fy_tools v{}\nAI: {}", version, ai_provider)
+}
+
+/// 丢弃现有菜单并按当前设置重新创建托盘
+pub fn refresh_tray_menu(app_handle: &AppHandle, state: Arc<Mutex<AppState>>) {
+    state.lock().unwrap().tray_menu_items = None;
+    rebuild_tray_menu(app_handle, state);
+}
+
 /// 重建托盘菜单
 pub fn rebuild_tray_menu(app_handle: &AppHandle, state: Arc<Mutex<AppState>>) {
     let mut state_guard = state.lock().unwrap();
+    let options = TrayMenuOptions::from_settings(&state_guard.settings);
+    let tooltip = tray_tooltip(app_handle, &state_guard.settings.ai_provider);
+    let current_provider = state_guard.settings.ai_provider.clone();
+    let selection_enabled = state_guard.settings.selection_enabled;
+    let mut providers: Vec<String> = state_guard.settings.provider_configs.keys().cloned().collect();
+    providers.sort();
     let tray_menu_items = &mut state_guard.tray_menu_items;
     if let Some(ref mut items) = *tray_menu_items {
+        let _ = items.dnd_item.set_checked(UI_FLAGS.is_do_not_disturb());
+        let _ = items.selection_item.set_checked(selection_enabled);
         match app_handle.autolaunch().is_enabled() {
             Ok(autostart_enabled) => {
                 let _ = items.autostart_item.set_checked(autostart_enabled);
@@ -34,27 +86,45 @@ pub fn rebuild_tray_menu(app_handle: &AppHandle, state: Arc<Mutex<AppState>>) {
 
         let quit_item = create_menu_item("quit", "退出");
         let clear_history_item = create_menu_item("clear_history", "清除记录");
+        let clear_selection_history_item =
+            create_menu_item("clear_selection_history", "清除划词记录");
+        let clear_secrets_item = create_menu_item("clear_secrets", "清除疑似密钥");
         #[cfg(debug_assertions)]
         let clear_logs_item = create_menu_item("clear_logs", "清除日志");
         #[cfg(debug_assertions)]
         let open_logs_item = create_menu_item("open_logs", "打开日志目录");
         let settings_item = create_menu_item("settings", "设置");
+        let ai_settings_item = create_menu_item("ai_settings", "AI 设置");
+        let check_update_item = create_menu_item("check_update", "检查更新");
+        let diagnostics_item = create_menu_item("diagnostics", "生成诊断包");
+        let translate_clipboard_item = create_menu_item("translate_clipboard", "翻译剪贴板");
         let autostart_enabled = app_handle.autolaunch().is_enabled().unwrap_or(false);
         let autostart_item = CheckMenuItemBuilder::with_id("autostart", "开机自启")
             .checked(autostart_enabled)
             .build(app_handle)
             .expect("创建开机自启菜单项失败");
 
+        let dnd_item = CheckMenuItemBuilder::with_id("do_not_disturb", "免打扰")
+            .checked(UI_FLAGS.is_do_not_disturb())
+            .build(app_handle)
+            .expect("创建免打扰菜单项失败");
+
+        let selection_item = CheckMenuItemBuilder::with_id("text_selection", "启用划词")
+            .checked(selection_enabled)
+            .build(app_handle)
+            .expect("创建划词菜单项失败");
+
         *tray_menu_items = Some(TrayMenuItems {
             autostart_item: autostart_item.clone(),
+            dnd_item: dnd_item.clone(),
+            selection_item: selection_item.clone(),
         });
 
-        #[cfg(debug_assertions)]
         let mut clear_submenu_items: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> =
-            vec![&clear_history_item];
-        #[cfg(not(debug_assertions))]
-        let clear_submenu_items: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> =
-            vec![&clear_history_item];
+            vec![&clear_history_item, &clear_secrets_item];
+        if options.show_clear_selection_history {
+            clear_submenu_items.push(&clear_selection_history_item);
+        }
 
         #[cfg(debug_assertions)]
         clear_submenu_items.push(&clear_logs_item);
@@ -63,12 +133,35 @@ pub fn rebuild_tray_menu(app_handle: &AppHandle, state: Arc<Mutex<AppState>>) {
             Submenu::with_items(app_handle, "清除", true, &clear_submenu_items)
                 .expect("未能创建清除子菜单");
 
+        let provider_items: Vec<CheckMenuItem<tauri::Wry>> = providers
+            .iter()
+            .map(|provider| {
+                CheckMenuItemBuilder::with_id(format!("{}{}", PROVIDER_MENU_ID_PREFIX, provider), provider)
+                    .checked(*provider == current_provider)
+                    .build(app_handle)
+                    .unwrap_or_else(|_| panic!("创建提供商菜单项 '{}' 失败", provider))
+            })
+            .collect();
+        let provider_submenu_items: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> = provider_items
+            .iter()
+            .map(|item| item as &dyn tauri::menu::IsMenuItem<tauri::Wry>)
+            .collect();
+        let provider_submenu =
+            Submenu::with_items(app_handle, "AI 提供商", !providers.is_empty(), &provider_submenu_items)
+                .expect("未能创建提供商子菜单");
+
         let mut menu_items: Vec<&dyn tauri::menu::IsMenuItem<tauri::Wry>> =
-            vec![&autostart_item, &clear_submenu];
+            vec![&autostart_item, &dnd_item, &selection_item, &provider_submenu, &clear_submenu];
 
         #[cfg(debug_assertions)]
         menu_items.push(&open_logs_item);
 
+        if options.show_check_update {
+            menu_items.push(&check_update_item);
+        }
+        menu_items.push(&translate_clipboard_item);
+        menu_items.push(&diagnostics_item);
+        menu_items.push(&ai_settings_item);
         menu_items.push(&settings_item);
         menu_items.push(&quit_item);
 
@@ -77,10 +170,9 @@ pub fn rebuild_tray_menu(app_handle: &AppHandle, state: Arc<Mutex<AppState>>) {
         if let Some(_old_tray) = app_handle.tray_by_id("main") {
             let _ = app_handle.remove_tray_by_id("main");
         }
-        let version = app_handle.package_info().version.clone();
         let tray_builder = TrayIconBuilder::with_id("main")
             .icon(app_handle.default_window_icon().unwrap().clone())
-            .tooltip(&format!("fy_tools v{}", version))
+            .tooltip(&tooltip)
             .menu(&menu);
 
         tray_builder
@@ -90,11 +182,24 @@ pub fn rebuild_tray_menu(app_handle: &AppHandle, state: Arc<Mutex<AppState>>) {
                     let event_id = event.id().as_ref();
                     match event_id {
                         "quit" => {
-                            handle_quit_event(&app);
+                            handle_quit_event(app);
                         }
                         "autostart" => {
                             handle_autostart_event(&app, &state_for_events);
                         }
+                        "do_not_disturb" => {
+                            do_not_disturb::toggle(app, &state_for_events);
+                        }
+                        "text_selection" => {
+                            let enabled = !state_for_events.lock().unwrap().settings.selection_enabled;
+                            if let Err(e) = mouse_listener::set_text_selection_enabled(
+                                app,
+                                &state_for_events,
+                                enabled,
+                            ) {
+                                log::error!("切换划词失败: {}", e);
+                            }
+                        }
                         #[cfg(debug_assertions)]
                         "open_logs" => {
                             if let Err(e) = open_log_directory(&app) {
@@ -104,14 +209,36 @@ pub fn rebuild_tray_menu(app_handle: &AppHandle, state: Arc<Mutex<AppState>>) {
                         "clear_history" => {
                             handle_clear_history_event(&state_for_events);
                         }
+                        "clear_secrets" => {
+                            handle_clear_secrets_event(app, &state_for_events);
+                        }
+                        "clear_selection_history" => {
+                            handle_clear_selection_history_event(&state_for_events);
+                        }
                         #[cfg(debug_assertions)]
                         "clear_logs" => {
                             if let Err(e) = clear_log_files() {
                                 log::error!("清除日志文件失败: {}", e);
                             }
                         }
+                        "check_update" => {
+                            handle_check_update_event(app);
+                        }
+                        "diagnostics" => {
+                            handle_diagnostics_event(app, &state_for_events);
+                        }
+                        "translate_clipboard" => {
+                            handle_translate_clipboard_event(app, &state_for_events);
+                        }
+                        "ai_settings" => {
+                            open_settings(app, Some("ai"));
+                        }
                         "settings" => {
-                            open_settings(app);
+                            open_settings(app, None);
+                        }
+                        _ if event_id.starts_with(PROVIDER_MENU_ID_PREFIX) => {
+                            let provider = &event_id[PROVIDER_MENU_ID_PREFIX.len()..];
+                            handle_switch_provider_event(app, &state_for_events, provider);
                         }
                         _ => {
                             log::info!("未知的菜单事件: {}", event_id);
@@ -124,21 +251,80 @@ pub fn rebuild_tray_menu(app_handle: &AppHandle, state: Arc<Mutex<AppState>>) {
     }
 }
 
-/// 打开设置窗口
-pub fn open_settings(app: &AppHandle) {
+/// 打开设置窗口，可指定要切换到的标签页（clipboard / ai / about）
+pub fn open_settings(app: &AppHandle, tab: Option<&str>) {
+    if let Some(settings_window) = app.get_webview_window("settings") {
+        let _ = settings_window.show();
+        if let Some(tab) = tab {
+            let _ = settings_window.set_focus();
+            if let Err(e) = settings_window.emit("open-settings-tab", tab) {
+                log::error!("发送设置标签页事件失败: {}", e);
+            }
+        }
+    }
+}
+
+/// 打开设置窗口并通知前端检查更新
+pub fn handle_check_update_event(app: &AppHandle) {
     if let Some(settings_window) = app.get_webview_window("settings") {
         let _ = settings_window.show();
+        let _ = settings_window.set_focus();
+        if let Err(e) = settings_window.emit("tray-check-update", ()) {
+            log::error!("发送检查更新事件失败: {}", e);
+        }
     }
 }
 
-/// 处理退出事件
+/// 处理退出事件：按顺序停止监听、保存数据后再退出
 pub fn handle_quit_event(app: &AppHandle) {
     log::info!("退出应用");
-    cleanup_enigo_instance();
+    let state = app.state::<Arc<Mutex<AppState>>>();
+    graceful_shutdown(app, state.inner());
     app.exit(0);
 }
 
-/// 处理自启动设置事件
+/// 有序关闭：通知监听线程停止、注销快捷键、清理输入钩子，并显式保存历史与设置。
+/// 可重复调用，仅首次生效
+pub fn graceful_shutdown(app: &AppHandle, state: &Arc<Mutex<AppState>>) {
+    if !request_shutdown() {
+        return;
+    }
+    log::info!("开始有序关闭");
+    stop_selection_listener();
+    crate::services::http_api::stop();
+    if let Err(e) = app.global_shortcut().unregister_all() {
+        log::error!("注销全局快捷键失败: {}", e);
+    }
+    cleanup_enigo_instance();
+    flush_histories_on_exit(state);
+    let settings = state.lock().unwrap().settings.clone();
+    if let Err(e) = save_settings(&settings) {
+        log::error!("退出时保存设置失败: {}", e);
+    }
+    log::info!("有序关闭完成");
+}
+
+/// 退出时显式保存文本与图片历史记录。
+/// app.exit 最终调用 std::process::exit，托管状态中的管理器不会被析构，Drop 中的保存不会执行
+pub fn flush_histories_on_exit(state: &Arc<Mutex<AppState>>) {
+    let (clipboard_manager, image_clipboard_manager) = {
+        let state_guard = state.lock().unwrap();
+        (
+            state_guard.clipboard_manager.clone(),
+            state_guard.image_clipboard_manager.clone(),
+        )
+    };
+    if let Err(e) = clipboard_manager.lock().unwrap().save_history_on_exit() {
+        log::error!("退出时保存历史记录失败: {}", e);
+    }
+    if let Err(e) = image_clipboard_manager.lock().unwrap().save_history_on_exit() {
+        log::error!("退出时保存图片历史记录失败: {}", e);
+    }
+    log::info!("退出前历史记录已保存");
+}
+
+/// 处理自启动设置事件。启用时注册的启动命令带有 --autostart 参数（见插件 Builder），
+/// 之后开机自启不显示窗口并延迟启动监听
 pub fn handle_autostart_event(app: &AppHandle, state: &Arc<Mutex<AppState>>) {
     log::info!("切换开机自启状态");
 
@@ -173,22 +359,136 @@ pub fn handle_autostart_event(app: &AppHandle, state: &Arc<Mutex<AppState>>) {
     if result {
         let app_handle = app.clone();
         let state_clone = state.clone();
-        thread::spawn(move || {
-            thread::sleep(Duration::from_millis(100));
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(TRAY_MENU_REBUILD_DELAY).await;
             rebuild_tray_menu(&app_handle, state_clone);
         });
     }
 }
 
+/// 处理托盘切换 AI 提供商事件
+pub fn handle_switch_provider_event(app: &AppHandle, state: &Arc<Mutex<AppState>>, provider: &str) {
+    let mut settings = {
+        let state_guard = state.lock().unwrap();
+        state_guard.settings.clone()
+    };
+    if !settings.provider_configs.contains_key(provider) {
+        log::warn!("未找到提供商配置: {}", provider);
+        return;
+    }
+    if settings.ai_provider != provider {
+        settings.ai_provider = provider.to_string();
+        if let Err(e) = save_settings(&settings) {
+            log::error!("切换AI提供商失败: {}", e);
+            return;
+        }
+        state.lock().unwrap().settings = settings;
+        log::info!("已切换AI提供商: {}", provider);
+        if let Err(e) = app.emit("ai-provider-changed", provider) {
+            log::error!("发送提供商切换事件失败: {}", e);
+        }
+    }
+
+    // 菜单事件回调中延迟重建，确保勾选状态与当前提供商一致
+    let app_handle = app.clone();
+    let state_clone = state.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(TRAY_MENU_REBUILD_DELAY).await;
+        refresh_tray_menu(&app_handle, state_clone);
+    });
+}
+
 /// 处理清除历史记录事件
 pub fn handle_clear_history_event(state: &Arc<Mutex<AppState>>) {
-    let state_guard = state.lock().unwrap();
-    let manager = state_guard.clipboard_manager.lock().unwrap();
+    let clipboard_manager = clipboard_manager_of(state);
+    let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+    let data = manager.snapshot_data();
+    if !data.items.is_empty() {
+        if let Err(e) = write_snapshot("before_clear", &data) {
+            log::error!("清除前创建历史快照失败: {}", e);
+        }
+    }
     if let Err(e) = manager.clear_history() {
         log::error!("清除历史记录失败: {}", e);
     }
 }
 
+/// 处理清除疑似密钥事件，并通过系统通知告知移除数量
+pub fn handle_clear_secrets_event(app: &AppHandle, state: &Arc<Mutex<AppState>>) {
+    let removed = {
+        let clipboard_manager = clipboard_manager_of(state);
+        let manager = lock_checked(&clipboard_manager, "ClipboardManager");
+        manager.remove_secret_items()
+    };
+    log::info!("已移除疑似密钥条目: {}", removed);
+    let body = if removed == 0 {
+        "未发现疑似密钥".to_string()
+    } else {
+        format!("已从剪贴板历史中移除 {} 条疑似密钥", removed)
+    };
+    if let Err(e) = app.notification().builder().title("fy_tools").body(body).show() {
+        log::error!("发送通知失败: {}", e);
+    }
+}
+
+/// 处理生成诊断包事件：选择保存位置后生成，并通过系统通知告知结果
+pub fn handle_diagnostics_event(app: &AppHandle, state: &Arc<Mutex<AppState>>) {
+    let app_handle = app.clone();
+    let state = state.clone();
+    app.dialog()
+        .file()
+        .set_file_name("fy_tools_diagnostics.zip")
+        .add_filter("zip", &["zip"])
+        .save_file(move |file_path| {
+            let Some(path) = file_path.and_then(|p| p.into_path().ok()) else {
+                return;
+            };
+            let body = match generate_diagnostics_bundle(&app_handle, &state, &path) {
+                Ok(()) => format!("诊断包已保存到 {}", path.display()),
+                Err(e) => {
+                    log::error!("{}", e);
+                    format!("生成诊断包失败: {}", e)
+                }
+            };
+            if let Err(e) = app_handle
+                .notification()
+                .builder()
+                .title("fy_tools")
+                .body(body)
+                .show()
+            {
+                log::error!("发送通知失败: {}", e);
+            }
+        });
+}
+
+/// 处理翻译剪贴板事件
+pub fn handle_translate_clipboard_event(app: &AppHandle, state: &Arc<Mutex<AppState>>) {
+    let app_handle = app.clone();
+    let state = state.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = translate_clipboard_content(None, None, app_handle.clone(), state).await {
+            log::warn!("翻译剪贴板失败: {}", e);
+            if let Err(e) = app_handle
+                .notification()
+                .builder()
+                .title("fy_tools")
+                .body(e.to_string())
+                .show()
+            {
+                log::error!("发送通知失败: {}", e);
+            }
+        }
+    });
+}
+
+/// 处理清除划词记录事件
+pub fn handle_clear_selection_history_event(state: &Arc<Mutex<AppState>>) {
+    let mut state_guard = state.lock().unwrap();
+    state_guard.selection_history.clear();
+    log::info!("已清除划词记录");
+}
+
 /// 打开日志目录
 #[cfg(debug_assertions)]
 fn open_log_directory(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::Error>> {
@@ -206,20 +506,6 @@ fn open_log_directory(app_handle: &AppHandle) -> Result<(), Box<dyn std::error::
 /// 清除日志文件
 #[cfg(debug_assertions)]
 fn clear_log_files() -> Result<(), Box<dyn std::error::Error>> {
-    let log_dir = get_logs_dir_path();
-
-    if !log_dir.exists() {
-        return Ok(());
-    }
-
-    for entry in std::fs::read_dir(&log_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_file() && path.extension().map_or(false, |ext| ext == "log") {
-            std::fs::remove_file(&path)?;
-            log::info!("删除日志文件: {:?}", path);
-        }
-    }
-
+    remove_log_files(&get_logs_dir_path(), None)?;
     Ok(())
 }
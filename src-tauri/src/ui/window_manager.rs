@@ -1,17 +1,29 @@
-use crate::core::app_state::AppState;
-use crate::core::config::CLIPBOARD_WINDOW_BOTTOM_EXTRA_MARGIN;
+use crate::core::app_state::{
+    clipboard_manager_of, image_clipboard_manager_of, lock_checked, AppState, UI_FLAGS,
+};
+use crate::core::config::{
+    ResultWindowGeometry, CLIPBOARD_WINDOW_BOTTOM_EXTRA_MARGIN, DEFAULT_IMAGE_TOGGLE_SHORTCUT,
+    DEFAULT_RESULT_WINDOW_HEIGHT, DEFAULT_RESULT_WINDOW_WIDTH, DEFAULT_TOGGLE_SHORTCUT,
+    GLOBAL_SEARCH_WINDOW_LABEL, RESULT_WINDOW_GEOMETRY_SAVE_DELAY,
+};
+use crate::core::ui_worker::{run_on_ui_worker, run_on_ui_worker_after};
+use crate::features::do_not_disturb;
+use crate::ui::commands::shift_selection_index;
+use crate::utils::utils_helpers::{normalize_hot_key, save_settings, WindowPositionStrategy};
 use lazy_static::lazy_static;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
 use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
 use tauri_plugin_positioner::{Position, WindowExt};
 #[cfg(target_os = "windows")]
 use winapi::shared::windef::RECT;
 #[cfg(target_os = "windows")]
 use winapi::um::winuser::{
-    GetForegroundWindow, GetSystemMetrics, GetWindowTextW, SystemParametersInfoW, SM_CYSCREEN,
-    SPI_GETWORKAREA,
+    GetForegroundWindow, GetSystemMetrics, GetWindowRect, GetWindowTextW, SystemParametersInfoW,
+    SM_CYSCREEN, SPI_GETWORKAREA,
 };
 
 lazy_static! {
@@ -27,36 +39,32 @@ pub fn cleanup_enigo_instance() {
 
 /// 显示剪贴板窗口
 pub fn show_clipboard_window(app_handle: AppHandle, state: Arc<Mutex<AppState>>) {
-    {
-        let state_guard = state.lock().unwrap();
-        if state_guard.is_visible {
-            return;
-        }
-    }
-
-    {
-        let mut state_guard = state.lock().unwrap();
-        state_guard.is_visible = true;
+    if UI_FLAGS.is_visible.swap(true, Ordering::SeqCst) {
+        return;
     }
+    remember_paste_target();
+    // 上次打开时的输入过滤不延续到本次
+    state.lock().unwrap().history_filter = None;
 
-    let selected_index = {
-        let state_guard = state.lock().unwrap();
-        state_guard.selected_index
-    };
+    let selected_index = UI_FLAGS.selected_index.load(Ordering::SeqCst);
 
-    let (history, categories, category_list) = {
-        let state_guard = state.lock().unwrap();
-        let manager = state_guard.clipboard_manager.lock().unwrap();
+    let (history, content_types, categories, category_list) = {
+        let clipboard_manager = clipboard_manager_of(&state);
+        let manager = lock_checked(&clipboard_manager, "ClipboardManager");
         (
             manager.get_history(),
+            manager.get_content_types(),
             manager.get_categories(),
             manager.get_category_list(),
         )
     };
 
-    let bottom_offset = {
+    let (bottom_offset, position_strategy) = {
         let state_guard = state.lock().unwrap();
-        state_guard.settings.clipboard_bottom_offset
+        (
+            state_guard.settings.clipboard_bottom_offset,
+            state_guard.settings.window_position_strategy,
+        )
     };
 
     if let Some(_window) = app_handle.get_webview_window("clipboard") {
@@ -64,13 +72,15 @@ pub fn show_clipboard_window(app_handle: AppHandle, state: Arc<Mutex<AppState>>)
         let history_clone = history.clone();
         let categories_clone = categories.clone();
         let category_list_clone = category_list.clone();
-        thread::spawn(move || {
+        tauri::async_runtime::spawn(async move {
             if let Some(window) = app_handle_clone.get_webview_window("clipboard") {
-                set_window_position(&window, bottom_offset);
+                set_window_position(&window, bottom_offset, position_strategy);
                 if window.show().is_ok() {
                     let _ = window.set_focus();
+                    KeyboardNavigationHandler::register(&app_handle_clone, state.clone());
                     let payload = serde_json::json!({
                         "history": history_clone,
+                        "content_types": content_types,
                         "categories": categories_clone,
                         "category_list": category_list_clone,
                         "bottomOffset": bottom_offset,
@@ -83,15 +93,129 @@ pub fn show_clipboard_window(app_handle: AppHandle, state: Arc<Mutex<AppState>>)
     }
 }
 
-pub fn show_image_clipboard_window(app_handle: AppHandle, state: Arc<Mutex<AppState>>) {
-    let already_visible = {
+/// 注册打开文本剪贴板窗口的快捷键，启动与保存设置共用
+pub fn register_clipboard_shortcut(
+    app_handle: &AppHandle,
+    state: Arc<Mutex<AppState>>,
+    hot_key: &str,
+) -> Result<(), String> {
+    let app_for_shortcut = app_handle.clone();
+    app_handle
+        .global_shortcut()
+        .on_shortcut(hot_key, move |_app, _shortcut, event| {
+            if let ShortcutState::Pressed = event.state {
+                if !UI_FLAGS.any_window_visible() && !UI_FLAGS.is_processing_selection() {
+                    show_clipboard_window(app_for_shortcut.clone(), state.clone());
+                    crate::features::mouse_listener::reset_ctrl_key_state();
+                }
+            }
+        })
+        .map_err(|e| format!("注册快捷键 {} 失败: {}", hot_key, e))
+}
+
+/// 注册打开图片剪贴板窗口的快捷键，启动与保存设置共用
+pub fn register_image_clipboard_shortcut(
+    app_handle: &AppHandle,
+    state: Arc<Mutex<AppState>>,
+    hot_key: &str,
+) -> Result<(), String> {
+    let app_for_shortcut = app_handle.clone();
+    app_handle
+        .global_shortcut()
+        .on_shortcut(hot_key, move |_app, _shortcut, event| {
+            if let ShortcutState::Pressed = event.state {
+                if !UI_FLAGS.any_window_visible() && !UI_FLAGS.is_processing_selection() {
+                    show_image_clipboard_window(app_for_shortcut.clone(), state.clone());
+                }
+            }
+        })
+        .map_err(|e| format!("注册图片窗口快捷键 {} 失败: {}", hot_key, e))
+}
+
+type RegisterShortcut = fn(&AppHandle, Arc<Mutex<AppState>>, &str) -> Result<(), String>;
+
+/// 启动时实际使用的快捷键，以及是否需要写回设置
+#[derive(Debug, PartialEq)]
+struct ShortcutChoice {
+    hot_key: String,
+    persist: bool,
+}
+
+/// 保存的快捷键有效且注册成功时使用它，否则注册默认值；实际使用的值与保存值不同时需要写回
+fn choose_shortcut(
+    saved: &str,
+    default: &str,
+    mut register: impl FnMut(&str) -> Result<(), String>,
+) -> ShortcutChoice {
+    let hot_key = match normalize_hot_key(saved).and_then(|hot_key| register(&hot_key).map(|_| hot_key)) {
+        Ok(hot_key) => hot_key,
+        Err(e) => {
+            log::warn!("{}，改用默认快捷键 {}", e, default);
+            if let Err(e) = register(default) {
+                log::error!("{}", e);
+            }
+            default.to_string()
+        }
+    };
+    ShortcutChoice {
+        persist: hot_key != saved,
+        hot_key,
+    }
+}
+
+/// 注册保存的快捷键，无效或注册失败时改用默认值
+fn register_saved_or_default(
+    app_handle: &AppHandle,
+    state: &Arc<Mutex<AppState>>,
+    saved: &str,
+    default: &str,
+    register: RegisterShortcut,
+) -> ShortcutChoice {
+    choose_shortcut(saved, default, |hot_key| register(app_handle, state.clone(), hot_key))
+}
+
+/// 启动时按设置注册文本与图片窗口快捷键；回退到默认值时写回设置，避免下次启动再次失败
+pub fn register_window_shortcuts_on_startup(app_handle: &AppHandle, state: &Arc<Mutex<AppState>>) {
+    let (hot_key, image_hot_key) = {
         let state_guard = state.lock().unwrap();
-        state_guard.is_image_visible
+        (
+            state_guard.settings.hot_key.clone(),
+            state_guard.settings.image_hot_key.clone(),
+        )
     };
+    let hot_key_choice = register_saved_or_default(
+        app_handle,
+        state,
+        &hot_key,
+        DEFAULT_TOGGLE_SHORTCUT,
+        register_clipboard_shortcut,
+    );
+    let image_hot_key_choice = register_saved_or_default(
+        app_handle,
+        state,
+        &image_hot_key,
+        DEFAULT_IMAGE_TOGGLE_SHORTCUT,
+        register_image_clipboard_shortcut,
+    );
+    if !hot_key_choice.persist && !image_hot_key_choice.persist {
+        return;
+    }
 
-    {
+    let settings = {
         let mut state_guard = state.lock().unwrap();
-        state_guard.is_image_visible = true;
+        state_guard.settings.hot_key = hot_key_choice.hot_key;
+        state_guard.settings.image_hot_key = image_hot_key_choice.hot_key;
+        state_guard.settings.clone()
+    };
+    if let Err(e) = save_settings(&settings) {
+        log::error!("保存回退后的快捷键失败: {}", e);
+    }
+}
+
+pub fn show_image_clipboard_window(app_handle: AppHandle, state: Arc<Mutex<AppState>>) {
+    let already_visible = UI_FLAGS.is_image_visible.swap(true, Ordering::SeqCst);
+    if !already_visible {
+        remember_paste_target();
     }
 
     {
@@ -112,30 +236,30 @@ pub fn show_image_clipboard_window(app_handle: AppHandle, state: Arc<Mutex<AppSt
         }
     }
 
-    let selected_index = {
-        let state_guard = state.lock().unwrap();
-        state_guard.image_selected_index
-    };
+    let selected_index = UI_FLAGS.image_selected_index.load(Ordering::SeqCst);
 
     let (history, categories, category_list) = {
-        let state_guard = state.lock().unwrap();
-        let manager = state_guard.image_clipboard_manager.lock().unwrap();
+        let image_clipboard_manager = image_clipboard_manager_of(&state);
+        let manager = lock_checked(&image_clipboard_manager, "ImageClipboardManager");
         (
             manager.get_history_preview(),
             manager.get_categories(),
             manager.get_category_list(),
         )
     };
-    let bottom_offset = {
+    let (bottom_offset, position_strategy) = {
         let state_guard = state.lock().unwrap();
-        state_guard.settings.clipboard_bottom_offset
+        (
+            state_guard.settings.clipboard_bottom_offset,
+            state_guard.settings.window_position_strategy,
+        )
     };
 
     if let Some(_window) = app_handle.get_webview_window("image_clipboard") {
         let app_handle_clone = app_handle.clone();
-        thread::spawn(move || {
+        tauri::async_runtime::spawn(async move {
             if let Some(window) = app_handle_clone.get_webview_window("image_clipboard") {
-                set_window_position(&window, bottom_offset);
+                set_window_position(&window, bottom_offset, position_strategy);
                 if (!already_visible && window.show().is_ok()) || already_visible {
                     if !already_visible {
                         let _ = window.set_focus();
@@ -155,44 +279,112 @@ pub fn show_image_clipboard_window(app_handle: AppHandle, state: Arc<Mutex<AppSt
 }
 
 /// 隐藏剪贴板窗口
-pub fn hide_clipboard_window(app_handle: AppHandle, state: Arc<Mutex<AppState>>) {
-    let is_visible = {
-        let state_guard = state.lock().unwrap();
-        state_guard.is_visible
-    };
-
-    if !is_visible {
+pub fn hide_clipboard_window(app_handle: AppHandle) {
+    if !UI_FLAGS.is_visible() {
         return;
     }
 
     if let Some(window) = app_handle.get_webview_window("clipboard") {
         let _ = window.hide();
     }
-    {
-        let mut state_guard = state.lock().unwrap();
-        state_guard.is_visible = false;
-        state_guard.selected_index = 0;
-    }
+    UI_FLAGS.is_visible.store(false, Ordering::SeqCst);
+    UI_FLAGS.selected_index.store(0, Ordering::SeqCst);
+    KeyboardNavigationHandler::unregister(&app_handle);
 }
 
-pub fn hide_image_clipboard_window(app_handle: AppHandle, state: Arc<Mutex<AppState>>) {
-    let is_visible = {
-        let state_guard = state.lock().unwrap();
-        state_guard.is_image_visible
-    };
+/// 剪贴板窗口可见期间临时注册的导航按键及其触发的事件。
+/// Escape 已作为常驻的隐藏窗口快捷键注册，由其回调发送 clipboard-nav-escape
+const CLIPBOARD_NAV_SHORTCUTS: [(&str, &str); 4] = [
+    ("ArrowUp", "clipboard-nav-up"),
+    ("ArrowDown", "clipboard-nav-down"),
+    ("Enter", "clipboard-nav-select"),
+    ("Delete", "clipboard-nav-delete"),
+];
+
+lazy_static! {
+    static ref CLIPBOARD_NAV_REGISTERED: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+}
+
+/// 剪贴板窗口的键盘导航：窗口显示时注册方向键、回车和删除键，隐藏时注销。
+/// 上下键在后端移动 UI_FLAGS.selected_index，事件负载均为移动后的选中位置
+pub struct KeyboardNavigationHandler;
 
-    if !is_visible {
+impl KeyboardNavigationHandler {
+    /// 注册导航按键；注册与注销都在共享工作线程上按顺序执行，避免快速开关窗口时交错
+    pub fn register(app_handle: &AppHandle, state: Arc<Mutex<AppState>>) {
+        let app_handle = app_handle.clone();
+        run_on_ui_worker(move || {
+            let mut registered = CLIPBOARD_NAV_REGISTERED.lock().unwrap();
+            if !registered.is_empty() || !UI_FLAGS.is_visible() {
+                return;
+            }
+            for (key, event_name) in CLIPBOARD_NAV_SHORTCUTS {
+                let app_for_key = app_handle.clone();
+                let state_for_key = state.clone();
+                let result = app_handle
+                    .global_shortcut()
+                    .on_shortcut(key, move |_app, _shortcut, event| {
+                        if let ShortcutState::Pressed = event.state {
+                            Self::dispatch(&app_for_key, &state_for_key, event_name);
+                        }
+                    });
+                match result {
+                    Ok(()) => registered.push(key),
+                    Err(e) => log::warn!("注册剪贴板导航按键 {} 失败: {}", key, e),
+                }
+            }
+        });
+    }
+
+    /// 注销导航按键，可在快捷键回调中调用
+    pub fn unregister(app_handle: &AppHandle) {
+        let app_handle = app_handle.clone();
+        run_on_ui_worker(move || {
+            let registered = std::mem::take(&mut *CLIPBOARD_NAV_REGISTERED.lock().unwrap());
+            for key in registered {
+                if let Err(e) = app_handle.global_shortcut().unregister(key) {
+                    log::debug!("注销剪贴板导航按键 {} 失败: {}", key, e);
+                }
+            }
+        });
+    }
+
+    fn dispatch(app_handle: &AppHandle, state: &Arc<Mutex<AppState>>, event_name: &str) {
+        if !UI_FLAGS.is_visible() {
+            return;
+        }
+        let offset = match event_name {
+            "clipboard-nav-up" => -1,
+            "clipboard-nav-down" => 1,
+            _ => 0,
+        };
+        let index = if offset == 0 {
+            UI_FLAGS.selected_index.load(Ordering::SeqCst)
+        } else {
+            match shift_selection_index(state, offset) {
+                Ok(index) => index,
+                Err(e) => {
+                    log::debug!("移动选中位置失败: {}", e);
+                    return;
+                }
+            }
+        };
+        if let Err(e) = app_handle.emit_to("clipboard", event_name, index) {
+            log::error!("发送剪贴板导航事件 {} 失败: {}", event_name, e);
+        }
+    }
+}
+
+pub fn hide_image_clipboard_window(app_handle: AppHandle) {
+    if !UI_FLAGS.is_image_visible() {
         return;
     }
 
     if let Some(window) = app_handle.get_webview_window("image_clipboard") {
         let _ = window.hide();
     }
-    {
-        let mut state_guard = state.lock().unwrap();
-        state_guard.is_image_visible = false;
-        state_guard.image_selected_index = 0;
-    }
+    UI_FLAGS.is_image_visible.store(false, Ordering::SeqCst);
+    UI_FLAGS.image_selected_index.store(0, Ordering::SeqCst);
 }
 
 pub fn wait_for_window_hidden(
@@ -276,9 +468,108 @@ pub fn hide_image_preview_window(app_handle: AppHandle) {
     }
 }
 
+/// 显示全局搜索浮动窗口，并让页面聚焦到搜索框
+pub fn show_global_search_window(app_handle: &AppHandle) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(GLOBAL_SEARCH_WINDOW_LABEL) {
+        let _ = window.center();
+        let _ = window.show();
+        let _ = window.set_focus();
+        let _ = app_handle.emit_to(GLOBAL_SEARCH_WINDOW_LABEL, "global-search-focus", ());
+        return Ok(());
+    }
+
+    // 新建的窗口由页面加载完成后自行聚焦搜索框
+    let window = tauri::WebviewWindowBuilder::new(
+        app_handle,
+        GLOBAL_SEARCH_WINDOW_LABEL,
+        tauri::WebviewUrl::App("global_search.html".into()),
+    )
+        .title("全局搜索")
+        .visible(false)
+        .inner_size(560.0, 420.0)
+        .center()
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .decorations(false)
+        .build()
+        .map_err(|e| format!("创建全局搜索窗口失败: {}", e))?;
+
+    let _ = window.show();
+    let _ = window.set_focus();
+    Ok(())
+}
+
+/// 注册打开全局搜索窗口的快捷键，空字符串表示不注册
+pub fn register_global_search_shortcut(app_handle: &AppHandle, hot_key: &str) -> Result<(), String> {
+    if hot_key.trim().is_empty() {
+        return Ok(());
+    }
+    app_handle
+        .global_shortcut()
+        .on_shortcut(hot_key, move |app, _shortcut, event| {
+            if let ShortcutState::Pressed = event.state {
+                if let Err(e) = show_global_search_window(app) {
+                    log::error!("{}", e);
+                }
+            }
+        })
+        .map_err(|e| format!("注册全局搜索快捷键失败: {}", e))
+}
+
 /// 设置窗口位置和大小
-pub fn set_window_position(window: &tauri::WebviewWindow, bottom_offset: i32) {
-    if let Some(monitor) = window.current_monitor().unwrap() {
+/// 按策略选择剪贴板窗口要显示的显示器，无法确定时退回窗口当前所在显示器
+fn resolve_target_monitor(
+    window: &tauri::WebviewWindow,
+    strategy: WindowPositionStrategy,
+) -> Option<tauri::Monitor> {
+    let target = match strategy {
+        WindowPositionStrategy::PrimaryMonitor => window.primary_monitor().ok().flatten(),
+        WindowPositionStrategy::ActiveMonitor => foreground_window_center()
+            .and_then(|(x, y)| window.monitor_from_point(x, y).ok().flatten())
+            .or_else(|| cursor_monitor(window)),
+        WindowPositionStrategy::MouseMonitor => cursor_monitor(window),
+    };
+    target.or_else(|| window.current_monitor().ok().flatten())
+}
+
+/// 鼠标所在的显示器
+fn cursor_monitor(window: &tauri::WebviewWindow) -> Option<tauri::Monitor> {
+    let cursor = window.cursor_position().ok()?;
+    window.monitor_from_point(cursor.x, cursor.y).ok().flatten()
+}
+
+/// 前台窗口中心点（物理像素）
+#[cfg(target_os = "windows")]
+fn foreground_window_center() -> Option<(f64, f64)> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.is_null() {
+            return None;
+        }
+        let mut rect: RECT = std::mem::zeroed();
+        if GetWindowRect(hwnd, &mut rect) == 0 {
+            return None;
+        }
+        Some((
+            f64::from(rect.left + rect.right) / 2.0,
+            f64::from(rect.top + rect.bottom) / 2.0,
+        ))
+    }
+}
+
+/// 非 Windows 平台无法获取前台窗口位置，由调用方退回鼠标所在显示器
+#[cfg(not(target_os = "windows"))]
+fn foreground_window_center() -> Option<(f64, f64)> {
+    None
+}
+
+pub fn set_window_position(
+    window: &tauri::WebviewWindow,
+    bottom_offset: i32,
+    strategy: WindowPositionStrategy,
+) {
+    if let Some(monitor) = resolve_target_monitor(window, strategy) {
         let monitor_position = monitor.position();
         let screen_size = monitor.size();
         let taskbar_safe_offset = get_taskbar_safe_offset() + bottom_offset.max(0);
@@ -328,6 +619,10 @@ pub fn show_selection_toolbar_impl(
     selected_text: String,
     anchor_pos: Option<(i32, i32)>,
 ) {
+    if do_not_disturb::is_active() {
+        log::debug!("免打扰中，不显示划词工具栏");
+        return;
+    }
     if let Some(state) = app_handle.try_state::<Arc<Mutex<AppState>>>() {
         if let Ok(state_guard) = state.lock() {
             if !state_guard.settings.selection_enabled {
@@ -351,7 +646,7 @@ pub fn show_selection_toolbar_impl(
 
 /// 设置工具栏窗口位置
 fn set_toolbar_window(window: &tauri::WebviewWindow, anchor_pos: Option<(i32, i32)>) {
-    let toolbar_width = 176u32;
+    let toolbar_width = 234u32;
     let toolbar_height = 50u32;
     let offset = 12i32;
     let _ = window.set_size(tauri::LogicalSize::new(toolbar_width, toolbar_height));
@@ -456,6 +751,110 @@ pub fn simulate_paste() -> Result<(), String> {
     Ok(())
 }
 
+/// 打开剪贴板窗口前的前台窗口句柄，回填时粘贴到该窗口；0 表示未记录
+#[cfg(target_os = "windows")]
+static PASTE_TARGET_WINDOW: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+/// 记录当前前台窗口作为回填目标，显示剪贴板窗口前调用
+#[cfg(target_os = "windows")]
+fn remember_paste_target() {
+    let (is_fuyun, _) = foreground_window_info();
+    let hwnd = unsafe { GetForegroundWindow() };
+    let target = if is_fuyun { 0 } else { hwnd as usize };
+    PASTE_TARGET_WINDOW.store(target, Ordering::SeqCst);
+}
+
+#[cfg(not(target_os = "windows"))]
+fn remember_paste_target() {}
+
+/// 记录的回填目标窗口标题（小写），用于匹配按窗口设置的回填方式
+#[cfg(target_os = "windows")]
+pub fn paste_target_title() -> Option<String> {
+    let target = PASTE_TARGET_WINDOW.load(Ordering::SeqCst);
+    if target == 0 {
+        return None;
+    }
+    let mut title_buffer = [0u16; 512];
+    let title_len = unsafe {
+        GetWindowTextW(
+            target as winapi::shared::windef::HWND,
+            title_buffer.as_mut_ptr(),
+            title_buffer.len() as i32,
+        )
+    };
+    (title_len > 0).then(|| String::from_utf16_lossy(&title_buffer[..title_len as usize]).to_lowercase())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn paste_target_title() -> Option<String> {
+    None
+}
+
+/// 重新激活记录的回填目标窗口，直到它确实成为前台窗口；
+/// 返回 false 表示没有记录目标或激活失败，调用方应退回固定等待
+#[cfg(target_os = "windows")]
+pub fn activate_paste_target() -> bool {
+    use crate::core::config::{PASTE_TARGET_ACTIVATE_INTERVAL, PASTE_TARGET_ACTIVATE_TIMEOUT};
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{IsWindow, SetForegroundWindow};
+
+    let target = PASTE_TARGET_WINDOW.swap(0, Ordering::SeqCst);
+    if target == 0 {
+        return false;
+    }
+    let hwnd = target as HWND;
+    if unsafe { IsWindow(hwnd) } == 0 {
+        log::debug!("回填目标窗口已关闭");
+        return false;
+    }
+    let started = std::time::Instant::now();
+    while started.elapsed() < PASTE_TARGET_ACTIVATE_TIMEOUT {
+        unsafe {
+            if GetForegroundWindow() == hwnd {
+                return true;
+            }
+            SetForegroundWindow(hwnd);
+        }
+        thread::sleep(PASTE_TARGET_ACTIVATE_INTERVAL);
+    }
+    let activated = unsafe { GetForegroundWindow() } == hwnd;
+    if !activated {
+        log::warn!("重新激活回填目标窗口超时");
+    }
+    activated
+}
+
+/// 非 Windows 平台依赖系统在窗口隐藏后自动归还焦点
+#[cfg(not(target_os = "windows"))]
+pub fn activate_paste_target() -> bool {
+    false
+}
+
+/// 在前台应用中直接模拟键入文本；key_delay 非零时逐字符键入，适用于会丢失快速输入的远程桌面
+pub fn simulate_typing(text: &str, key_delay: Duration) -> Result<(), String> {
+    use enigo::{Enigo, Keyboard, Settings};
+    wait_for_foreground_ready_for_paste()?;
+
+    let mut enigo_guard = ENIGO_INSTANCE.lock().unwrap();
+    if enigo_guard.is_none() {
+        *enigo_guard = Some(Enigo::new(&Settings::default()).map_err(|e| format!("初始化粘贴输入器失败: {}", e))?);
+    }
+    if let Some(ref mut enigo) = *enigo_guard {
+        if key_delay.is_zero() {
+            enigo.text(text).map_err(|e| format!("模拟键入文本失败: {}", e))?;
+        } else {
+            let mut buffer = [0u8; 4];
+            for c in text.chars() {
+                enigo
+                    .text(c.encode_utf8(&mut buffer))
+                    .map_err(|e| format!("模拟键入文本失败: {}", e))?;
+                thread::sleep(key_delay);
+            }
+        }
+    }
+    Ok(())
+}
+
 fn wait_for_foreground_ready_for_paste() -> Result<(), String> {
     let mut stable_not_fuyun_count = 0usize;
     let mut last_title = String::new();
@@ -512,9 +911,14 @@ pub async fn show_result_window(
     app: AppHandle,
 ) -> Result<(), String> {
     let window_label = format!("result_{}", window_type);
+    let layout = result_window_layout(&app, &window_type);
 
     if let Some(existing_window) = app.get_webview_window(&window_label) {
-        position_result_window_near_toolbar(&existing_window, &app);
+        if layout.saved_geometry.is_none() {
+            position_result_window_near_toolbar(&existing_window, &app);
+        }
+        let _ = existing_window.set_always_on_top(layout.always_on_top);
+        let _ = existing_window.set_title(&title);
         if let Ok(is_visible) = existing_window.is_visible() {
             if !is_visible {
                 let _ = existing_window.show();
@@ -537,6 +941,7 @@ pub async fn show_result_window(
         return Ok(());
     }
 
+    let page_window_type = window_type.clone();
     let window = tauri::WebviewWindowBuilder::new(
         &app,
         &window_label,
@@ -544,12 +949,13 @@ pub async fn show_result_window(
     )
         .title(&title)
         .visible(false)
-        .inner_size(560.0, 360.0)
+        .inner_size(layout.default_size.0 as f64, layout.default_size.1 as f64)
+        .always_on_top(layout.always_on_top)
         .resizable(true)
         .decorations(true)
         .on_page_load(move |window, _| {
             let payload = serde_json::json!({
-            "type": window_type.clone(),
+            "type": page_window_type.clone(),
             "original": original.clone(),
             "content": content.clone(),
             "targetLanguage": target_language.clone()
@@ -560,12 +966,174 @@ pub async fn show_result_window(
         .build()
         .map_err(|e| format!("创建窗口失败: {}", e))?;
 
-    position_result_window_near_toolbar(&window, &app);
+    let restored = layout
+        .saved_geometry
+        .map(|geometry| restore_result_window_geometry(&window, geometry))
+        .unwrap_or(false);
+    if !restored {
+        position_result_window_near_toolbar(&window, &app);
+    }
+    track_result_window_geometry(&window, app.clone(), window_type);
     let _ = window.show();
     let _ = window.set_focus();
     Ok(())
 }
 
+/// 结果窗口创建时使用的布局设置
+struct ResultWindowLayout {
+    default_size: (u32, u32),
+    saved_geometry: Option<ResultWindowGeometry>,
+    always_on_top: bool,
+}
+
+impl Default for ResultWindowLayout {
+    fn default() -> Self {
+        Self {
+            default_size: (DEFAULT_RESULT_WINDOW_WIDTH, DEFAULT_RESULT_WINDOW_HEIGHT),
+            saved_geometry: None,
+            always_on_top: false,
+        }
+    }
+}
+
+/// 读取结果窗口默认尺寸、上次保存的位置与置顶状态
+fn result_window_layout(app: &AppHandle, window_type: &str) -> ResultWindowLayout {
+    let Some(state) = app.try_state::<Arc<Mutex<AppState>>>() else {
+        return ResultWindowLayout::default();
+    };
+    let Ok(state_guard) = state.lock() else {
+        return ResultWindowLayout::default();
+    };
+    let settings = &state_guard.settings;
+    ResultWindowLayout {
+        default_size: (settings.result_window_width, settings.result_window_height),
+        saved_geometry: settings.result_window_geometries.get(window_type).copied(),
+        always_on_top: settings
+            .result_window_always_on_top
+            .get(window_type)
+            .copied()
+            .unwrap_or(false),
+    }
+}
+
+/// 按保存的位置与尺寸恢复结果窗口，并限制在当前可用显示器内
+fn restore_result_window_geometry(
+    window: &tauri::WebviewWindow,
+    geometry: ResultWindowGeometry,
+) -> bool {
+    let monitors = window.available_monitors().unwrap_or_default();
+    let center_x = geometry.x + geometry.width as i32 / 2;
+    let center_y = geometry.y + geometry.height as i32 / 2;
+    let monitor = monitors
+        .iter()
+        .find(|m| {
+            let pos = m.position();
+            let size = m.size();
+            center_x >= pos.x
+                && center_x < pos.x + size.width as i32
+                && center_y >= pos.y
+                && center_y < pos.y + size.height as i32
+        })
+        .cloned()
+        .or_else(|| window.primary_monitor().ok().flatten())
+        .or_else(|| monitors.first().cloned());
+    let Some(monitor) = monitor else {
+        return false;
+    };
+
+    let monitor_pos = monitor.position();
+    let monitor_size = monitor.size();
+    let width = geometry.width.clamp(1, monitor_size.width.max(1));
+    let height = geometry.height.clamp(1, monitor_size.height.max(1));
+    let max_x = monitor_pos.x + monitor_size.width as i32 - width as i32;
+    let max_y = monitor_pos.y + monitor_size.height as i32 - height as i32;
+    let x = geometry.x.clamp(monitor_pos.x, max_x.max(monitor_pos.x));
+    let y = geometry.y.clamp(monitor_pos.y, max_y.max(monitor_pos.y));
+
+    let _ = window.set_size(tauri::PhysicalSize::new(width, height));
+    window
+        .set_position(tauri::PhysicalPosition::new(x, y))
+        .is_ok()
+}
+
+/// 是否已有等待执行的结果窗口位置保存任务
+static GEOMETRY_SAVE_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// 监听结果窗口的移动与缩放：位置随时记入内存，停止变动后延迟写盘，关闭时立即写盘
+fn track_result_window_geometry(window: &tauri::WebviewWindow, app: AppHandle, window_type: String) {
+    let tracked_window = window.clone();
+    window.on_window_event(move |event| match event {
+        tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_)
+            if remember_result_window_geometry(&tracked_window, &app, &window_type) =>
+        {
+            schedule_result_window_geometry_save(&app);
+        }
+        tauri::WindowEvent::CloseRequested { .. }
+            if remember_result_window_geometry(&tracked_window, &app, &window_type) =>
+        {
+            save_result_window_geometries(&app);
+        }
+        _ => {}
+    });
+}
+
+/// 延迟保存结果窗口位置；拖动产生的大量事件只合并为一次写盘，写入的是执行时的最新位置
+fn schedule_result_window_geometry_save(app: &AppHandle) {
+    if GEOMETRY_SAVE_PENDING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let app = app.clone();
+    run_on_ui_worker_after(RESULT_WINDOW_GEOMETRY_SAVE_DELAY, move || {
+        GEOMETRY_SAVE_PENDING.store(false, Ordering::SeqCst);
+        save_result_window_geometries(&app);
+    });
+}
+
+/// 将内存中的结果窗口位置写入设置文件，其余设置保持内存中的当前值
+fn save_result_window_geometries(app: &AppHandle) {
+    let Some(state) = app.try_state::<Arc<Mutex<AppState>>>() else {
+        return;
+    };
+    let settings = state.lock().unwrap().settings.clone();
+    if let Err(e) = save_settings(&settings) {
+        log::error!("保存结果窗口位置失败: {}", e);
+    }
+}
+
+/// 将窗口当前的位置与大小记入内存中的设置，位置有变化时返回 true
+fn remember_result_window_geometry(
+    window: &tauri::WebviewWindow,
+    app: &AppHandle,
+    window_type: &str,
+) -> bool {
+    if window.is_minimized().unwrap_or(false) {
+        return false;
+    }
+    let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+        return false;
+    };
+    if size.width == 0 || size.height == 0 {
+        return false;
+    }
+    let Some(state) = app.try_state::<Arc<Mutex<AppState>>>() else {
+        return false;
+    };
+    let Ok(mut state_guard) = state.lock() else {
+        return false;
+    };
+    let geometry = ResultWindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    };
+    let previous = state_guard
+        .settings
+        .result_window_geometries
+        .insert(window_type.to_string(), geometry);
+    previous.as_ref() != Some(&geometry)
+}
+
 fn position_result_window_near_toolbar(window: &tauri::WebviewWindow, app: &AppHandle) {
     let Some(toolbar_window) = app.get_webview_window("selection_toolbar") else {
         let _ = window.move_window(Position::RightCenter);
@@ -630,19 +1198,127 @@ pub async fn update_result_window(
     content: String,
     window_type: String,
     app: AppHandle,
+) -> Result<(), String> {
+    emit_result_update(&content, &window_type, &app)
+}
+
+/// 结果窗口已被用户关闭时推送内容返回的错误
+pub const RESULT_WINDOW_CLOSED_ERROR: &str = "窗口不存在";
+
+/// 推送错误是否表示结果窗口已关闭，流式请求据此视为用户取消
+pub fn is_result_window_closed(error: &str) -> bool {
+    error.contains(RESULT_WINDOW_CLOSED_ERROR)
+}
+
+/// 向结果窗口推送内容（同步版本，供流式回调中按顺序推送）；
+/// 窗口正在翻看旧结果时不推送，最新结果仍在记录中累积，切回时整体推送
+pub fn emit_result_update(content: &str, window_type: &str, app: &AppHandle) -> Result<(), String> {
+    let (index, total) = match app.try_state::<Arc<Mutex<AppState>>>() {
+        Some(state) => {
+            let state_guard = state.lock().unwrap();
+            if state_guard.result_view_index.contains_key(window_type) {
+                return Ok(());
+            }
+            state_guard.result_position(window_type)
+        }
+        None => (0, 0),
+    };
+    emit_result_slot(content, window_type, index, total, app)
+}
+
+/// 向结果窗口推送指定位置的结果内容，index/total 供窗口显示前后翻页
+pub fn emit_result_slot(
+    content: &str,
+    window_type: &str,
+    index: usize,
+    total: usize,
+    app: &AppHandle,
 ) -> Result<(), String> {
     let window_label = format!("result_{}", window_type);
     if let Some(window) = app.get_webview_window(&window_label) {
         let payload = serde_json::json!({
             "type": window_type,
-            "content": content
+            "content": content,
+            "index": index,
+            "total": total
         });
         match window.emit("result-update", payload) {
             Ok(_) => Ok(()),
             Err(e) => Err(format!("发送数据失败: {}", e)),
         }
     } else {
-        log::error!("{}窗口不存在", &window_type);
-        Err("窗口不存在".to_string())
+        log::debug!("{}{}", window_type, RESULT_WINDOW_CLOSED_ERROR);
+        Err(RESULT_WINDOW_CLOSED_ERROR.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEFAULT: &str = "Ctrl+Shift+V";
+
+    /// 记录尝试注册的快捷键，failing 中的快捷键注册失败
+    fn choose_with(saved: &str, failing: &[&str]) -> (ShortcutChoice, Vec<String>) {
+        let mut attempts = Vec::new();
+        let choice = choose_shortcut(saved, DEFAULT, |hot_key| {
+            attempts.push(hot_key.to_string());
+            if failing.contains(&hot_key) {
+                Err(format!("注册快捷键失败: {}", hot_key))
+            } else {
+                Ok(())
+            }
+        });
+        (choice, attempts)
+    }
+
+    #[test]
+    fn keeps_registered_saved_shortcut_without_persisting() {
+        let (choice, attempts) = choose_with("Alt+Q", &[]);
+        assert_eq!(
+            choice,
+            ShortcutChoice {
+                hot_key: "Alt+Q".to_string(),
+                persist: false,
+            }
+        );
+        assert_eq!(attempts, vec!["Alt+Q"]);
+    }
+
+    #[test]
+    fn persists_trimmed_saved_shortcut() {
+        let (choice, _) = choose_with("  Alt+Q ", &[]);
+        assert_eq!(choice.hot_key, "Alt+Q");
+        assert!(choice.persist);
+    }
+
+    #[test]
+    fn falls_back_to_default_when_registration_fails() {
+        let (choice, attempts) = choose_with("Alt+Q", &["Alt+Q"]);
+        assert_eq!(
+            choice,
+            ShortcutChoice {
+                hot_key: DEFAULT.to_string(),
+                persist: true,
+            }
+        );
+        assert_eq!(attempts, vec!["Alt+Q", DEFAULT]);
+    }
+
+    #[test]
+    fn falls_back_to_default_for_invalid_shortcut_without_registering_it() {
+        for saved in ["", "not a shortcut"] {
+            let (choice, attempts) = choose_with(saved, &[]);
+            assert_eq!(choice.hot_key, DEFAULT);
+            assert!(choice.persist);
+            assert_eq!(attempts, vec![DEFAULT]);
+        }
+    }
+
+    #[test]
+    fn saved_default_that_fails_is_not_persisted_again() {
+        let (choice, _) = choose_with(DEFAULT, &[DEFAULT]);
+        assert_eq!(choice.hot_key, DEFAULT);
+        assert!(!choice.persist);
     }
 }
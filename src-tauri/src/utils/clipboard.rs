@@ -1,29 +1,104 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
-use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use crate::core::diagnostics::spawn_named;
 use crate::utils::utils_helpers::{
-    find_best_replacement_candidate, load_history_data, save_history_data_with_retry,
-    ClipboardHistoryData,
+    classify_content, detect_secret_pattern, find_best_replacement_candidate_observed, load_history_data,
+    rank_fuzzy_matches, rank_text_matches, save_history_data_with_retry, ClipboardHistoryData, ClipboardHistoryEvent, ContentType, HistoryDelta,
+    DedupDecision, DedupMode, PostPasteAction, SimilarityDebugEvent,
 };
 
 pub struct ClipboardManager {
     history: Arc<Mutex<Vec<String>>>,
     history_fingerprints: Arc<Mutex<Vec<(usize, u64)>>>,
     history_cache_dirty: Arc<AtomicBool>,
-    persist_tx: Sender<ClipboardHistoryData>,
+    /// 内容类型缓存，键为文本哈希，不持久化
+    content_type_cache: Arc<Mutex<HashMap<u64, ContentType>>>,
+    /// 记录预览（前 HISTORY_PREVIEW_CHARS 个字符）缓存，键为文本哈希，不持久化
+    preview_cache: Arc<Mutex<HashMap<u64, String>>>,
+    persist_tx: Sender<PersistMessage>,
     categories: Arc<Mutex<HashMap<String, String>>>,
+    /// 原地替换前的原文，键为记录当前的索引，值为 (替换后的内容, 原文)；
+    /// 随历史变更移动，记录被移除或再次被替换时清除，不持久化
+    previous_contents: Arc<Mutex<HashMap<usize, (String, String)>>>,
+    /// 最近一次去重处理的决定，不持久化
+    last_dedup_decision: Arc<Mutex<Option<DedupDecision>>>,
+    /// 历史变更监听器，为空表示无订阅
+    change_listener: Arc<Mutex<Option<Sender<ClipboardHistoryEvent>>>>,
+    /// 历史增量变更监听器，为空表示无订阅
+    delta_listener: Arc<Mutex<Option<Sender<HistoryDelta>>>>,
+    /// 相似度调试监听器，为空表示未开启调试
+    similarity_debug_listener: Arc<Mutex<Option<Sender<SimilarityDebugEvent>>>>,
     category_list: Arc<Mutex<Vec<String>>>,
+    /// 记录被粘贴使用的次数，键为记录内容
+    use_counts: Arc<Mutex<HashMap<String, u32>>>,
     max_items: usize,
     grouped_items_protected_from_limit: bool,
+    dedup_mode: DedupMode,
+}
+
+/// 历史保存线程的防抖时长
+const PERSIST_DEBOUNCE: Duration = Duration::from_millis(250);
+/// flush 等待保存线程写盘的最长时间
+const PERSIST_FLUSH_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 发送给历史保存线程的消息
+enum PersistMessage {
+    /// 最新的历史快照，防抖后写盘
+    Save(ClipboardHistoryData),
+    /// 立即写入待保存的快照，完成后通过回执通知
+    Flush(Sender<()>),
+}
+
+/// 写入待保存的快照
+fn write_pending(pending: &mut Option<ClipboardHistoryData>) {
+    if let Some(data) = pending.take() {
+        if let Err(e) = save_history_data_with_retry(&data, 3) {
+            log::error!("异步保存历史记录失败: {}", e);
+        }
+    }
+}
+
+/// 历史保存线程：合并防抖期内的快照，只写入最新的一份
+fn run_persist_writer(persist_rx: Receiver<PersistMessage>) {
+    let mut pending: Option<ClipboardHistoryData> = None;
+    loop {
+        let message = if pending.is_some() {
+            match persist_rx.recv_timeout(PERSIST_DEBOUNCE) {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => {
+                    write_pending(&mut pending);
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        } else {
+            match persist_rx.recv() {
+                Ok(message) => message,
+                Err(_) => break,
+            }
+        };
+        match message {
+            PersistMessage::Save(data) => pending = Some(data),
+            PersistMessage::Flush(ack) => {
+                write_pending(&mut pending);
+                let _ = ack.send(());
+            }
+        }
+    }
+    write_pending(&mut pending);
 }
 
 const LONG_TEXT_DEDUP_THRESHOLD: usize = 4000;
 const LONG_TEXT_DEDUP_SCAN_LIMIT: usize = 24;
+const DEDUP_DECISION_PREVIEW_CHARS: usize = 80;
+/// get_clipboard_history 返回的记录预览长度（字符数）
+const HISTORY_PREVIEW_CHARS: usize = 150;
 
 fn stable_text_hash(text: &str) -> u64 {
     let mut hasher = DefaultHasher::new();
@@ -40,54 +115,120 @@ fn build_history_fingerprints(history: &[String]) -> Vec<(usize, u64)> {
 
 impl ClipboardManager {
     /// 创建剪贴板管理器实例
-    pub fn new(max_items: usize, grouped_items_protected_from_limit: bool) -> Self {
+    pub fn new(
+        max_items: usize,
+        grouped_items_protected_from_limit: bool,
+        dedup_mode: DedupMode,
+    ) -> Self {
         let history_data = load_history_data().unwrap_or_else(|e| {
             log::error!("加载历史记录失败: {}，使用空历史记录", e);
             ClipboardHistoryData::default()
         });
-        let history_fingerprints = build_history_fingerprints(&history_data.items);
-        let (persist_tx, persist_rx) = mpsc::channel::<ClipboardHistoryData>();
-        std::thread::spawn(move || {
-            const DEBOUNCE_MS: u64 = 180;
-            loop {
-                let mut latest = match persist_rx.recv() {
-                    Ok(data) => data,
-                    Err(_) => break,
-                };
-                loop {
-                    match persist_rx.recv_timeout(Duration::from_millis(DEBOUNCE_MS)) {
-                        Ok(newer) => latest = newer,
-                        Err(RecvTimeoutError::Timeout) => break,
-                        Err(RecvTimeoutError::Disconnected) => {
-                            let _ = save_history_data_with_retry(&latest, 3);
-                            return;
-                        }
-                    }
-                }
-                if let Err(e) = save_history_data_with_retry(&latest, 3) {
-                    log::error!("异步保存历史记录失败: {}", e);
-                }
-            }
-        });
+        let (persist_tx, persist_rx) = mpsc::channel::<PersistMessage>();
+        spawn_named("clipboard-history-writer", move || run_persist_writer(persist_rx));
+        Self::from_parts(
+            history_data,
+            persist_tx,
+            max_items,
+            grouped_items_protected_from_limit,
+            dedup_mode,
+        )
+    }
 
+    /// 由已加载的历史数据和保存任务通道创建实例
+    fn from_parts(
+        history_data: ClipboardHistoryData,
+        persist_tx: Sender<PersistMessage>,
+        max_items: usize,
+        grouped_items_protected_from_limit: bool,
+        dedup_mode: DedupMode,
+    ) -> Self {
+        let history_fingerprints = build_history_fingerprints(&history_data.items);
         Self {
             history: Arc::new(Mutex::new(history_data.items)),
             history_fingerprints: Arc::new(Mutex::new(history_fingerprints)),
             history_cache_dirty: Arc::new(AtomicBool::new(false)),
+            content_type_cache: Arc::new(Mutex::new(HashMap::new())),
+            preview_cache: Arc::new(Mutex::new(HashMap::new())),
             persist_tx,
             categories: Arc::new(Mutex::new(history_data.categories)),
+            previous_contents: Arc::new(Mutex::new(HashMap::new())),
+            last_dedup_decision: Arc::new(Mutex::new(None)),
+            change_listener: Arc::new(Mutex::new(None)),
+            delta_listener: Arc::new(Mutex::new(None)),
+            similarity_debug_listener: Arc::new(Mutex::new(None)),
             category_list: Arc::new(Mutex::new(history_data.category_list)),
+            use_counts: Arc::new(Mutex::new(history_data.use_counts)),
             max_items,
             grouped_items_protected_from_limit,
+            dedup_mode,
+        }
+    }
+
+    /// 设置历史变更监听器，传入 None 取消订阅
+    pub fn set_change_listener(&self, listener: Option<Sender<ClipboardHistoryEvent>>) {
+        *self.change_listener.lock().unwrap() = listener;
+    }
+
+    /// 设置历史增量变更监听器，传入 None 取消订阅
+    pub fn set_delta_listener(&self, listener: Option<Sender<HistoryDelta>>) {
+        *self.delta_listener.lock().unwrap() = listener;
+    }
+
+    /// 设置相似度调试监听器，传入 None 关闭调试
+    pub fn set_similarity_debug_listener(&self, listener: Option<Sender<SimilarityDebugEvent>>) {
+        *self.similarity_debug_listener.lock().unwrap() = listener;
+    }
+
+    /// 通知监听器历史记录已变更，接收端已关闭时自动取消订阅
+    fn notify_change(&self, new_count: usize, operation: &str) {
+        let mut listener = self.change_listener.lock().unwrap();
+        let Some(sender) = listener.as_ref() else {
+            return;
+        };
+        let event = ClipboardHistoryEvent {
+            new_count,
+            operation: operation.to_string(),
+        };
+        if sender.send(event).is_err() {
+            *listener = None;
+        }
+    }
+
+    /// 按顺序发送增量变更，接收端已关闭时自动取消订阅；
+    /// 所有历史变更都经过这里，可撤销的原文也据此移动到记录的新位置
+    fn notify_deltas(&self, deltas: impl IntoIterator<Item = HistoryDelta>) {
+        let deltas: Vec<HistoryDelta> = deltas.into_iter().collect();
+        apply_deltas_to_undo_entries(&mut self.previous_contents.lock().unwrap(), &deltas);
+        let mut listener = self.delta_listener.lock().unwrap();
+        let Some(sender) = listener.as_ref() else {
+            return;
+        };
+        for delta in deltas {
+            if sender.send(delta).is_err() {
+                *listener = None;
+                return;
+            }
         }
     }
 
     fn enqueue_persist(&self, data: ClipboardHistoryData) {
-        if let Err(e) = self.persist_tx.send(data) {
+        if let Err(e) = self.persist_tx.send(PersistMessage::Save(data)) {
             log::error!("提交历史记录保存任务失败: {}", e);
         }
     }
 
+    /// 等待保存线程写入所有待保存的快照
+    pub fn flush(&self) -> Result<(), String> {
+        let (ack_tx, ack_rx) = mpsc::channel();
+        self.persist_tx
+            .send(PersistMessage::Flush(ack_tx))
+            .map_err(|_| "历史记录保存线程已退出".to_string())?;
+        ack_rx
+            .recv_timeout(PERSIST_FLUSH_TIMEOUT)
+            .map_err(|_| "等待历史记录写盘超时".to_string())
+    }
+
     /// 获取当前剪贴板内容
     pub fn get_content(&self, app_handle: &tauri::AppHandle) -> Option<String> {
         use tauri_plugin_clipboard_manager::ClipboardExt;
@@ -131,12 +272,76 @@ impl ClipboardManager {
         history.clone()
     }
 
+    /// 获取指定位置的记录，索引越界时返回 None
+    pub fn get_entry(&self, index: usize) -> Option<String> {
+        self.history.lock().unwrap().get(index).cloned()
+    }
+
+    /// 按内容查找记录当前在历史中的位置，按 contents 的顺序返回；
+    /// 已不在历史中的内容被跳过，内容相同的多条记录按先后依次对应
+    pub fn current_indices_of(&self, contents: &[String]) -> Vec<usize> {
+        let history = self.history.lock().unwrap();
+        let mut positions: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, text) in history.iter().enumerate().rev() {
+            positions.entry(text.as_str()).or_default().push(index);
+        }
+        contents
+            .iter()
+            .filter_map(|content| positions.get_mut(content.as_str())?.pop())
+            .collect()
+    }
+
+    /// 计算从 base 偏移 offset 后循环回绕的索引，历史为空时返回 None
+    pub fn wrapped_index(&self, base: usize, offset: i32) -> Option<usize> {
+        let len = self.history.lock().unwrap().len() as i64;
+        if len == 0 {
+            return None;
+        }
+        Some((base as i64 + offset as i64).rem_euclid(len) as usize)
+    }
+
+    /// 计算从 base 偏移 offset 后限制在历史范围内的索引，历史为空时返回 None
+    pub fn clamped_index(&self, base: usize, offset: i32) -> Option<usize> {
+        let len = self.history.lock().unwrap().len() as i64;
+        if len == 0 {
+            return None;
+        }
+        Some((base as i64 + offset as i64).clamp(0, len - 1) as usize)
+    }
+
+    /// 读取从 base 偏移 offset 处的记录（循环回绕），不改变记录顺序
+    pub fn peek_at_offset(&self, base: usize, offset: i32) -> Option<String> {
+        let index = self.wrapped_index(base, offset)?;
+        self.get_entry(index)
+    }
+
+    /// 按匹配度搜索历史记录，返回 (历史索引, 内容)
+    pub fn search(&self, query: &str, limit: usize) -> Vec<(usize, String)> {
+        let history = self.history.lock().unwrap();
+        rank_text_matches(history.iter(), query, limit)
+    }
+
+    /// 按三元组相似度模糊搜索历史记录，返回 (历史索引, 相似度, 内容)。
+    /// max_distance 为 0~100，相似度下限为 1 - max_distance / 100
+    pub fn fuzzy_search(&self, query: &str, max_distance: u32) -> Vec<(usize, f64, String)> {
+        let min_score = 1.0 - (max_distance.min(100) as f64 / 100.0);
+        let history = self.history.lock().unwrap();
+        rank_fuzzy_matches(history.iter(), query, min_score)
+    }
+
     /// 获取分类映射
     pub fn get_categories(&self) -> HashMap<String, String> {
         let categories = self.categories.lock().unwrap();
         categories.clone()
     }
 
+    /// 获取与历史记录一一对应的分类，未分类的记录为 None
+    pub fn get_item_categories(&self) -> Vec<Option<String>> {
+        let history = self.history.lock().unwrap();
+        let categories = self.categories.lock().unwrap();
+        history.iter().map(|item| categories.get(item).cloned()).collect()
+    }
+
     /// 获取分类列表
     pub fn get_category_list(&self) -> Vec<String> {
         let list = self.category_list.lock().unwrap();
@@ -162,11 +367,13 @@ impl ClipboardManager {
         };
 
         let history = self.history.lock().unwrap().clone();
+        let use_counts = self.live_use_counts(&history);
 
         self.enqueue_persist(ClipboardHistoryData {
             items: history,
             categories: categories_clone,
             category_list: category_list_clone,
+            use_counts,
         });
 
         Ok(())
@@ -192,11 +399,13 @@ impl ClipboardManager {
         };
 
         let history = self.history.lock().unwrap().clone();
+        let use_counts = self.live_use_counts(&history);
 
         self.enqueue_persist(ClipboardHistoryData {
             items: history,
             categories: categories_clone,
             category_list: category_list_clone,
+            use_counts,
         });
 
         Ok(())
@@ -214,11 +423,13 @@ impl ClipboardManager {
         };
 
         let history = self.history.lock().unwrap().clone();
+        let use_counts = self.live_use_counts(&history);
 
         self.enqueue_persist(ClipboardHistoryData {
             items: history,
             categories: categories_clone,
             category_list: category_list_clone,
+            use_counts,
         });
 
         Ok(())
@@ -232,27 +443,53 @@ impl ClipboardManager {
         log::debug!("添加到历史记录，长度: {}, 当前数量: {}", content_len, history.len());
 
         let content_hash = stable_text_hash(&content);
+        self.content_type_cache
+            .lock()
+            .unwrap()
+            .entry(content_hash)
+            .or_insert_with(|| classify_content(&content));
+        self.preview_cache
+            .lock()
+            .unwrap()
+            .entry(content_hash)
+            .or_insert_with(|| content.chars().take(HISTORY_PREVIEW_CHARS).collect());
         let mut fingerprints = self.history_fingerprints.lock().unwrap();
+        if self.dedup_mode == DedupMode::None && history.first() == Some(&content) {
+            return;
+        }
         let cache_dirty = self.history_cache_dirty.load(Ordering::Relaxed);
         if cache_dirty || fingerprints.len() != history.len() {
             *fingerprints = build_history_fingerprints(&history);
             self.history_cache_dirty.store(false, Ordering::Relaxed);
         }
-        if let Some(exact_index) = fingerprints
-            .iter()
-            .enumerate()
-            .position(|(idx, (item_len, item_hash))| {
-                *item_len == content_len
-                    && *item_hash == content_hash
-                    && history.get(idx).is_some_and(|item| item == &content)
-            })
-        {
+        let exact_match = if self.dedup_mode == DedupMode::None {
+            None
+        } else {
+            fingerprints
+                .iter()
+                .enumerate()
+                .position(|(idx, (item_len, item_hash))| {
+                    *item_len == content_len
+                        && *item_hash == content_hash
+                        && history.get(idx).is_some_and(|item| item == &content)
+                })
+        };
+        let mut deltas = Vec::new();
+        if let Some(exact_index) = exact_match {
             if exact_index != 0 {
+                self.record_dedup_decision(
+                    "exact_promoted",
+                    "与已有条目完全相同，已移动到最前面".to_string(),
+                    1.0,
+                    &history[exact_index],
+                );
                 let exact_item = history.remove(exact_index);
+                deltas.push(HistoryDelta::Removed { index: exact_index });
+                deltas.push(HistoryDelta::Added { entry: exact_item.clone(), index: 0 });
                 history.insert(0, exact_item);
             }
             let mut categories = self.categories.lock().unwrap();
-            shrink_text_history_with_group_protection(
+            let shrunk = shrink_text_history_with_group_protection(
                 &mut history,
                 self.max_items,
                 &mut categories,
@@ -263,10 +500,14 @@ impl ClipboardManager {
                 items: history.clone(),
                 categories: categories.clone(),
                 category_list: category_list.clone(),
+                use_counts: self.live_use_counts(&history),
             };
             self.enqueue_persist(data);
             *fingerprints = build_history_fingerprints(&history);
             self.history_cache_dirty.store(false, Ordering::Relaxed);
+            deltas.extend(shrunk.into_iter().map(|index| HistoryDelta::Removed { index }));
+            self.notify_deltas(deltas);
+            self.notify_change(history.len(), "add");
             return;
         }
 
@@ -279,33 +520,81 @@ impl ClipboardManager {
         };
         let candidate_history = &history[..scan_len];
 
-        if let Some((replace_index, comparison)) =
-            find_best_replacement_candidate(&content, candidate_history, similarity_threshold)
-        {
+        let replacement = if self.dedup_mode == DedupMode::Fuzzy {
+            let debug_listener = self.similarity_debug_listener.lock().unwrap().clone();
+            find_best_replacement_candidate_observed(
+                &content,
+                candidate_history,
+                similarity_threshold,
+                &mut |old_text, comparison| {
+                    if let Some(sender) = debug_listener.as_ref() {
+                        let _ = sender.send(SimilarityDebugEvent {
+                            old_text_preview: old_text.chars().take(DEDUP_DECISION_PREVIEW_CHARS).collect(),
+                            new_text_preview: content.chars().take(DEDUP_DECISION_PREVIEW_CHARS).collect(),
+                            similarity: comparison.similarity_score,
+                            completeness: format!("{:?}", comparison.new_completeness),
+                            should_replace: comparison.should_replace,
+                            reason: comparison.reason.clone(),
+                        });
+                    }
+                },
+            )
+        } else {
+            None
+        };
+        if let Some((replace_index, comparison)) = replacement {
             log::info!("检测到相似版本，正在处理: {}", comparison.reason);
             log::info!("相似度: {:.4}, 完整性: {:?}", 
                       comparison.similarity_score, 
                       comparison.new_completeness);
 
             if comparison.reason.contains("子集") || comparison.reason.contains("找回完整版本") {
+                self.record_dedup_decision(
+                    "complete_promoted",
+                    comparison.reason.clone(),
+                    comparison.similarity_score,
+                    &history[replace_index],
+                );
                 let complete_version = history.remove(replace_index);
+                deltas.push(HistoryDelta::Removed { index: replace_index });
+                deltas.push(HistoryDelta::Added { entry: complete_version.clone(), index: 0 });
                 history.insert(0, complete_version);
                 log::info!("已将完整版本移动到最前面");
             } else {
+                self.record_dedup_decision(
+                    "replaced",
+                    comparison.reason.clone(),
+                    comparison.similarity_score,
+                    &history[replace_index],
+                );
                 history[replace_index] = content.clone();
                 let item = history.remove(replace_index);
+                deltas.push(HistoryDelta::Removed { index: replace_index });
+                deltas.push(HistoryDelta::Added { entry: item.clone(), index: 0 });
                 history.insert(0, item);
                 log::info!("已用完整版本替换不完整版本");
             }
         } else {
             log::debug!("未找到相似版本，直接添加");
-            history.retain(|item| item != &content);
+            if self.dedup_mode != DedupMode::None {
+                let duplicates: Vec<usize> = history
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, item)| *item == &content)
+                    .map(|(index, _)| index)
+                    .collect();
+                for index in duplicates.into_iter().rev() {
+                    history.remove(index);
+                    deltas.push(HistoryDelta::Removed { index });
+                }
+            }
 
+            deltas.push(HistoryDelta::Added { entry: content.clone(), index: 0 });
             history.insert(0, content);
         }
 
         let mut categories = self.categories.lock().unwrap();
-        shrink_text_history_with_group_protection(
+        let shrunk = shrink_text_history_with_group_protection(
             &mut history,
             self.max_items,
             &mut categories,
@@ -316,11 +605,75 @@ impl ClipboardManager {
             items: history.clone(),
             categories: categories.clone(),
             category_list: category_list.clone(),
+            use_counts: self.live_use_counts(&history),
         };
 
         self.enqueue_persist(data);
         *fingerprints = build_history_fingerprints(&history);
         self.history_cache_dirty.store(false, Ordering::Relaxed);
+        deltas.extend(shrunk.into_iter().map(|index| HistoryDelta::Removed { index }));
+        self.notify_deltas(deltas);
+        self.notify_change(history.len(), "add");
+    }
+
+    /// 记录一次去重处理的决定
+    fn record_dedup_decision(&self, action: &str, reason: String, similarity_score: f64, affected: &str) {
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        *self.last_dedup_decision.lock().unwrap() = Some(DedupDecision {
+            action: action.to_string(),
+            reason,
+            similarity_score,
+            affected_preview: affected.chars().take(DEDUP_DECISION_PREVIEW_CHARS).collect(),
+            timestamp_ms,
+        });
+    }
+
+    /// 获取最近一次去重处理的决定
+    pub fn get_last_dedup_decision(&self) -> Option<DedupDecision> {
+        self.last_dedup_decision.lock().unwrap().clone()
+    }
+
+    /// 获取与历史记录一一对应的内容类型
+    pub fn get_content_types(&self) -> Vec<ContentType> {
+        let history = self.history.lock().unwrap();
+        let mut cache = self.content_type_cache.lock().unwrap();
+        let hashes: Vec<u64> = history.iter().map(|item| stable_text_hash(item)).collect();
+        let types = history
+            .iter()
+            .zip(hashes.iter())
+            .map(|(item, hash)| *cache.entry(*hash).or_insert_with(|| classify_content(item)))
+            .collect();
+        if cache.len() > hashes.len() * 2 + 16 {
+            let live: HashSet<u64> = hashes.into_iter().collect();
+            cache.retain(|hash, _| live.contains(hash));
+        }
+        types
+    }
+
+    /// 获取与历史记录一一对应的预览文本，已计算过的预览直接复用。
+    /// 预览按内容哈希缓存，相似替换或原地翻译改变内容后自动使用新内容的预览
+    pub fn get_previews(&self) -> Vec<String> {
+        let history = self.history.lock().unwrap();
+        let mut cache = self.preview_cache.lock().unwrap();
+        let hashes: Vec<u64> = history.iter().map(|item| stable_text_hash(item)).collect();
+        let previews = history
+            .iter()
+            .zip(hashes.iter())
+            .map(|(item, hash)| {
+                cache
+                    .entry(*hash)
+                    .or_insert_with(|| item.chars().take(HISTORY_PREVIEW_CHARS).collect())
+                    .clone()
+            })
+            .collect();
+        if cache.len() > hashes.len() * 2 + 16 {
+            let live: HashSet<u64> = hashes.into_iter().collect();
+            cache.retain(|hash, _| live.contains(hash));
+        }
+        previews
     }
 
     /// 清空历史记录
@@ -335,12 +688,17 @@ impl ClipboardManager {
         let mut category_list = self.category_list.lock().unwrap();
         category_list.clear();
 
+        self.use_counts.lock().unwrap().clear();
+
         self.enqueue_persist(ClipboardHistoryData {
             items: Vec::new(),
             categories: HashMap::new(),
             category_list: Vec::new(),
+            use_counts: HashMap::new(),
         });
-        
+
+        self.notify_deltas([HistoryDelta::Cleared]);
+        self.notify_change(0, "clear");
         log::info!("历史记录已清空");
         Ok(())
     }
@@ -353,7 +711,7 @@ impl ClipboardManager {
         let mut history = self.history.lock().unwrap();
         if history.len() > max_items {
             let mut categories = self.categories.lock().unwrap();
-            shrink_text_history_with_group_protection(
+            let shrunk = shrink_text_history_with_group_protection(
                 &mut history,
                 max_items,
                 &mut categories,
@@ -365,10 +723,12 @@ impl ClipboardManager {
                 items: history.clone(),
                 categories: categories.clone(),
                 category_list: category_list.clone(),
+                use_counts: self.live_use_counts(&history),
             };
 
             self.enqueue_persist(data);
             self.history_cache_dirty.store(true, Ordering::Relaxed);
+            self.notify_deltas(shrunk.into_iter().map(|index| HistoryDelta::Removed { index }));
         }
     }
 
@@ -387,15 +747,154 @@ impl ClipboardManager {
                 items: history.clone(),
                 categories: categories.clone(),
                 category_list: category_list.clone(),
+                use_counts: self.live_use_counts(&history),
             };
 
             self.enqueue_persist(data);
+            self.notify_deltas([HistoryDelta::Removed { index }]);
+            self.notify_change(history.len(), "remove");
             Ok(item)
         } else {
             Err("索引超出范围".to_string())
         }
     }
 
+    /// 批量移除历史记录（从大到小处理索引），只保存一次，返回被移除的内容
+    pub fn remove_many_from_history(&self, indices: &[usize]) -> Result<Vec<String>, String> {
+        let mut history = self.history.lock().unwrap();
+        if let Some(invalid) = indices.iter().find(|&&index| index >= history.len()) {
+            return Err(format!("索引超出范围: {}", invalid));
+        }
+        let mut sorted_indices = indices.to_vec();
+        sorted_indices.sort_unstable_by(|a, b| b.cmp(a));
+        sorted_indices.dedup();
+        if sorted_indices.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut categories = self.categories.lock().unwrap();
+        let removed_deltas: Vec<HistoryDelta> = sorted_indices
+            .iter()
+            .map(|&index| HistoryDelta::Removed { index })
+            .collect();
+        let removed: Vec<String> = sorted_indices
+            .into_iter()
+            .map(|index| {
+                let item = history.remove(index);
+                categories.remove(&item);
+                item
+            })
+            .collect();
+        self.history_cache_dirty.store(true, Ordering::Relaxed);
+
+        let category_list = self.category_list.lock().unwrap();
+        self.enqueue_persist(ClipboardHistoryData {
+            items: history.clone(),
+            categories: categories.clone(),
+            category_list: category_list.clone(),
+            use_counts: self.live_use_counts(&history),
+        });
+        self.notify_deltas(removed_deltas);
+        self.notify_change(history.len(), "remove");
+        Ok(removed)
+    }
+
+    /// 移除所有疑似包含密钥的历史记录，返回移除数量
+    pub fn remove_secret_items(&self) -> usize {
+        let mut history = self.history.lock().unwrap();
+        let secret_indices: Vec<usize> = history
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| detect_secret_pattern(item).is_some())
+            .map(|(index, _)| index)
+            .collect();
+        let removed = secret_indices.len();
+        if removed == 0 {
+            return 0;
+        }
+        let removed_items: Vec<String> = secret_indices
+            .iter()
+            .rev()
+            .map(|&index| history.remove(index))
+            .collect();
+        self.history_cache_dirty.store(true, Ordering::Relaxed);
+
+        let mut categories = self.categories.lock().unwrap();
+        for item in &removed_items {
+            categories.remove(item);
+        }
+        let category_list = self.category_list.lock().unwrap();
+        self.enqueue_persist(ClipboardHistoryData {
+            items: history.clone(),
+            categories: categories.clone(),
+            category_list: category_list.clone(),
+            use_counts: self.live_use_counts(&history),
+        });
+        self.notify_deltas(
+            secret_indices
+                .into_iter()
+                .rev()
+                .map(|index| HistoryDelta::Removed { index }),
+        );
+        self.notify_change(history.len(), "remove");
+        removed
+    }
+
+    /// 替换指定位置的记录内容并保留分类，返回被替换的原内容
+    pub fn replace_at(&self, index: usize, content: String) -> Result<String, String> {
+        let mut history = self.history.lock().unwrap();
+        if index >= history.len() {
+            return Err("索引超出范围".to_string());
+        }
+        let previous = std::mem::replace(&mut history[index], content.clone());
+        self.notify_deltas([
+            HistoryDelta::Removed { index },
+            HistoryDelta::Added { entry: content.clone(), index },
+        ]);
+        self.history_cache_dirty.store(true, Ordering::Relaxed);
+
+        let mut categories = self.categories.lock().unwrap();
+        if let Some(category) = categories.remove(&previous) {
+            categories.insert(content.clone(), category);
+        }
+        {
+            let mut use_counts = self.use_counts.lock().unwrap();
+            if let Some(count) = use_counts.remove(&previous) {
+                use_counts.insert(content, count);
+            }
+        }
+        let category_list = self.category_list.lock().unwrap();
+        self.enqueue_persist(ClipboardHistoryData {
+            items: history.clone(),
+            categories: categories.clone(),
+            category_list: category_list.clone(),
+            use_counts: self.live_use_counts(&history),
+        });
+        Ok(previous)
+    }
+
+    /// 原地替换记录并记住原文，便于撤销
+    pub fn replace_at_with_undo(&self, index: usize, content: String) -> Result<(), String> {
+        let previous = self.replace_at(index, content.clone())?;
+        self.previous_contents
+            .lock()
+            .unwrap()
+            .insert(index, (content, previous));
+        Ok(())
+    }
+
+    /// 恢复指定位置记录被替换前的原文
+    pub fn restore_previous_content(&self, index: usize) -> Result<(), String> {
+        let current = self.get_entry(index).ok_or_else(|| "索引超出范围".to_string())?;
+        let undo_entry = self.previous_contents.lock().unwrap().remove(&index);
+        let previous = match undo_entry {
+            Some((content, previous)) if content == current => previous,
+            _ => return Err("该记录没有可恢复的原文".to_string()),
+        };
+        self.replace_at(index, previous)?;
+        Ok(())
+    }
+
     pub fn promote_to_top(&self, index: usize) -> Result<String, String> {
         let (item, categories_clone, category_list_clone, history_clone) = {
             let mut history = self.history.lock().unwrap();
@@ -409,46 +908,198 @@ impl ClipboardManager {
             let item = history.remove(index);
             history.insert(0, item.clone());
             self.history_cache_dirty.store(true, Ordering::Relaxed);
+            self.notify_deltas([
+                HistoryDelta::Removed { index },
+                HistoryDelta::Added { entry: item.clone(), index: 0 },
+            ]);
 
             let categories = self.categories.lock().unwrap().clone();
             let category_list = self.category_list.lock().unwrap().clone();
             (item, categories, category_list, history.clone())
         };
 
+        let use_counts = self.live_use_counts(&history_clone);
         self.enqueue_persist(ClipboardHistoryData {
             items: history_clone,
             categories: categories_clone,
             category_list: category_list_clone,
+            use_counts,
         });
 
         Ok(item)
     }
 
-    /// 退出时保存历史记录
-    pub fn save_history_on_exit(&self) -> Result<(), String> {
+    /// 将指定记录移到历史末尾
+    pub fn move_to_end(&self, index: usize) -> Result<String, String> {
+        let (item, categories_clone, category_list_clone, history_clone) = {
+            let mut history = self.history.lock().unwrap();
+            if index >= history.len() {
+                return Err("索引超出范围".to_string());
+            }
+            let last_index = history.len() - 1;
+            if index == last_index {
+                let item = history[last_index].clone();
+                return Ok(item);
+            }
+            let item = history.remove(index);
+            history.push(item.clone());
+            self.history_cache_dirty.store(true, Ordering::Relaxed);
+            self.notify_deltas([
+                HistoryDelta::Removed { index },
+                HistoryDelta::Added { entry: item.clone(), index: last_index },
+            ]);
+
+            let categories = self.categories.lock().unwrap().clone();
+            let category_list = self.category_list.lock().unwrap().clone();
+            (item, categories, category_list, history.clone())
+        };
+
+        let use_counts = self.live_use_counts(&history_clone);
+        self.enqueue_persist(ClipboardHistoryData {
+            items: history_clone,
+            categories: categories_clone,
+            category_list: category_list_clone,
+            use_counts,
+        });
+
+        Ok(item)
+    }
+
+    /// 回填完成后对刚粘贴的记录执行操作，index 为该记录当前的位置。
+    /// CopyNextItem 返回下一条记录的位置与内容，由调用方写入剪贴板
+    pub fn apply_post_paste_action(
+        &self,
+        index: usize,
+        action: PostPasteAction,
+    ) -> Result<Option<(usize, String)>, String> {
+        match action {
+            PostPasteAction::None => Ok(None),
+            PostPasteAction::MoveToEnd => {
+                self.move_to_end(index)?;
+                Ok(None)
+            }
+            PostPasteAction::IncrementAccessCount => {
+                {
+                    let history = self.history.lock().unwrap();
+                    let item = history.get(index).ok_or_else(|| "索引超出范围".to_string())?;
+                    let mut use_counts = self.use_counts.lock().unwrap();
+                    let count = use_counts.entry(item.clone()).or_insert(0);
+                    *count = count.saturating_add(1);
+                }
+                self.enqueue_persist(self.snapshot_data());
+                Ok(None)
+            }
+            PostPasteAction::CopyNextItem => {
+                let next_index = index + 1;
+                Ok(self.get_entry(next_index).map(|item| (next_index, item)))
+            }
+        }
+    }
+
+    /// 粘贴使用指定记录：使用次数加一并移到顶部，返回记录内容
+    pub fn record_use(&self, index: usize) -> Result<String, String> {
+        {
+            let history = self.history.lock().unwrap();
+            let item = history.get(index).ok_or_else(|| "索引超出范围".to_string())?;
+            let mut use_counts = self.use_counts.lock().unwrap();
+            let count = use_counts.entry(item.clone()).or_insert(0);
+            *count = count.saturating_add(1);
+        }
+        let item = self.promote_to_top(index)?;
+        if index == 0 {
+            // 已在顶部时 promote_to_top 不会保存，需单独保存使用次数
+            self.enqueue_persist(self.snapshot_data());
+        }
+        Ok(item)
+    }
+
+    /// 获取与历史记录一一对应的使用次数
+    pub fn get_use_counts(&self) -> Vec<u32> {
         let history = self.history.lock().unwrap();
-        let categories = self.categories.lock().unwrap();
-        let category_list = self.category_list.lock().unwrap();
+        let use_counts = self.use_counts.lock().unwrap();
+        history
+            .iter()
+            .map(|item| use_counts.get(item).copied().unwrap_or(0))
+            .collect()
+    }
 
-        let data = ClipboardHistoryData {
+    /// 只保留仍在历史中的记录的使用次数，并返回副本用于保存
+    fn live_use_counts(&self, history: &[String]) -> HashMap<String, u32> {
+        let mut use_counts = self.use_counts.lock().unwrap();
+        let live: HashSet<&str> = history.iter().map(String::as_str).collect();
+        use_counts.retain(|item, _| live.contains(item.as_str()));
+        use_counts.clone()
+    }
+
+    /// 获取当前历史记录（含分类）的副本
+    pub fn snapshot_data(&self) -> ClipboardHistoryData {
+        let history = self.history.lock().unwrap();
+        ClipboardHistoryData {
+            items: history.clone(),
+            categories: self.categories.lock().unwrap().clone(),
+            category_list: self.category_list.lock().unwrap().clone(),
+            use_counts: self.live_use_counts(&history),
+        }
+    }
+
+    /// 用快照数据替换当前历史记录
+    pub fn restore_data(&self, data: ClipboardHistoryData) {
+        let mut history = self.history.lock().unwrap();
+        let mut categories = self.categories.lock().unwrap();
+        let mut category_list = self.category_list.lock().unwrap();
+        *history = data.items;
+        *categories = data.categories;
+        *category_list = data.category_list;
+        *self.use_counts.lock().unwrap() = data.use_counts;
+        shrink_text_history_with_group_protection(
+            &mut history,
+            self.max_items,
+            &mut categories,
+            self.grouped_items_protected_from_limit,
+        );
+        self.history_cache_dirty.store(true, Ordering::Relaxed);
+
+        self.enqueue_persist(ClipboardHistoryData {
             items: history.clone(),
             categories: categories.clone(),
             category_list: category_list.clone(),
-        };
-        save_history_data_with_retry(&data, 3)
+            use_counts: self.live_use_counts(&history),
+        });
+        self.notify_deltas([HistoryDelta::Reset]);
+        self.notify_change(history.len(), "restore");
+        log::info!("已从快照恢复历史记录，共 {} 条", history.len());
+    }
+
+    /// 退出时保存历史记录：提交最新快照并等待保存线程写盘，
+    /// 避免保存线程稍后用较旧的快照覆盖文件
+    pub fn save_history_on_exit(&self) -> Result<(), String> {
+        let data = self.snapshot_data();
+        match self.persist_tx.send(PersistMessage::Save(data)) {
+            Ok(()) => self.flush(),
+            Err(mpsc::SendError(PersistMessage::Save(data))) => {
+                save_history_data_with_retry(&data, 3)
+            }
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// 设置去重方式，仅影响之后新增的记录
+    pub fn set_dedup_mode(&mut self, dedup_mode: DedupMode) {
+        self.dedup_mode = dedup_mode;
     }
 
     pub fn set_grouped_items_protected_from_limit(&mut self, enabled: bool) {
         self.grouped_items_protected_from_limit = enabled;
         let mut history = self.history.lock().unwrap();
         let mut categories = self.categories.lock().unwrap();
-        shrink_text_history_with_group_protection(
+        let shrunk = shrink_text_history_with_group_protection(
             &mut history,
             self.max_items,
             &mut categories,
             self.grouped_items_protected_from_limit,
         );
         self.history_cache_dirty.store(true, Ordering::Relaxed);
+        self.notify_deltas(shrunk.into_iter().map(|index| HistoryDelta::Removed { index }));
     }
 }
 
@@ -467,20 +1118,53 @@ impl Drop for ClipboardManager {
     }
 }
 
+/// 按增量变更移动可撤销的原文：插入与移除时平移索引，被移除的记录丢弃原文；
+/// 紧接着在别处插入相同内容视为移动（如置顶），原文随记录一起移动
+fn apply_deltas_to_undo_entries(undo_entries: &mut HashMap<usize, (String, String)>, deltas: &[HistoryDelta]) {
+    let mut moving: Option<(String, String)> = None;
+    for delta in deltas {
+        match delta {
+            HistoryDelta::Added { entry, index } => {
+                *undo_entries = undo_entries
+                    .drain()
+                    .map(|(i, undo)| (if i >= *index { i + 1 } else { i }, undo))
+                    .collect();
+                if let Some(undo) = moving.take().filter(|(content, _)| content == entry) {
+                    undo_entries.insert(*index, undo);
+                }
+            }
+            HistoryDelta::Removed { index } => {
+                moving = undo_entries.remove(index);
+                *undo_entries = undo_entries
+                    .drain()
+                    .map(|(i, undo)| (if i > *index { i - 1 } else { i }, undo))
+                    .collect();
+            }
+            HistoryDelta::Cleared | HistoryDelta::Reset => {
+                undo_entries.clear();
+                moving = None;
+            }
+        }
+    }
+}
+
+/// 裁剪超出上限的记录，返回依次移除的索引（按返回顺序逐个移除即可复现裁剪结果）
 fn shrink_text_history_with_group_protection(
     history: &mut Vec<String>,
     max_items: usize,
     categories: &mut HashMap<String, String>,
     grouped_items_protected_from_limit: bool,
-) {
+) -> Vec<usize> {
+    let mut removed_indices = Vec::new();
     if !grouped_items_protected_from_limit {
         if history.len() > max_items {
+            removed_indices.extend((max_items..history.len()).rev());
             let removed = history.split_off(max_items);
             for item in removed {
                 categories.remove(&item);
             }
         }
-        return;
+        return removed_indices;
     }
     while history.len() > max_items {
         if let Some(pos) = history
@@ -489,8 +1173,198 @@ fn shrink_text_history_with_group_protection(
         {
             let removed = history.remove(pos);
             categories.remove(&removed);
+            removed_indices.push(pos);
         } else {
             break;
         }
     }
+    removed_indices
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 不读写历史文件的管理器；接收端保留在返回值中，保存任务只会排队而不会写盘
+    fn manager_with_mode(dedup_mode: DedupMode) -> (ClipboardManager, Receiver<PersistMessage>) {
+        let (persist_tx, persist_rx) = mpsc::channel();
+        let manager = ClipboardManager::from_parts(ClipboardHistoryData::default(), persist_tx, 50, false, dedup_mode);
+        (manager, persist_rx)
+    }
+
+    /// 依次复制：截断的句子、短词、完整的句子、再次复制短词两次
+    fn history_after_sequence(dedup_mode: DedupMode) -> Vec<String> {
+        let (manager, _persist_rx) = manager_with_mode(dedup_mode);
+        for text in [
+            "The quick brown fox jumps over the lazy dog",
+            "hello",
+            "The quick brown fox jumps over the lazy dog.",
+            "hello",
+            "hello",
+        ] {
+            manager.add_to_history(text.to_string());
+        }
+        manager.get_history()
+    }
+
+    #[test]
+    fn dedup_none_keeps_every_copy_except_immediate_repeats() {
+        assert_eq!(
+            history_after_sequence(DedupMode::None),
+            vec![
+                "hello",
+                "The quick brown fox jumps over the lazy dog.",
+                "hello",
+                "The quick brown fox jumps over the lazy dog",
+            ]
+        );
+    }
+
+    #[test]
+    fn dedup_exact_merges_only_identical_copies() {
+        assert_eq!(
+            history_after_sequence(DedupMode::Exact),
+            vec![
+                "hello",
+                "The quick brown fox jumps over the lazy dog.",
+                "The quick brown fox jumps over the lazy dog",
+            ]
+        );
+    }
+
+    #[test]
+    fn dedup_fuzzy_replaces_incomplete_version() {
+        assert_eq!(
+            history_after_sequence(DedupMode::Fuzzy),
+            vec!["hello", "The quick brown fox jumps over the lazy dog."]
+        );
+    }
+
+    #[test]
+    fn copy_next_item_after_promotion_uses_original_index() {
+        let (manager, _persist_rx) = manager_with_mode(DedupMode::Exact);
+        for text in ["alpha one", "beta two", "gamma three"] {
+            manager.add_to_history(text.to_string());
+        }
+        let pasted_index = 1;
+        assert_eq!(manager.record_use(pasted_index).unwrap(), "beta two");
+        assert_eq!(manager.get_history(), vec!["beta two", "gamma three", "alpha one"]);
+
+        let next = manager
+            .apply_post_paste_action(pasted_index, PostPasteAction::CopyNextItem)
+            .unwrap();
+        assert_eq!(next, Some((2, "alpha one".to_string())));
+    }
+
+    #[test]
+    fn fuzzy_search_max_distance_sets_similarity_floor() {
+        let (manager, _persist_rx) = manager_with_mode(DedupMode::Exact);
+        for text in ["goodbye", "say hello", "hello wurld", "hello world"] {
+            manager.add_to_history(text.to_string());
+        }
+        let found = |max_distance| -> Vec<String> {
+            manager
+                .fuzzy_search("hello world", max_distance)
+                .into_iter()
+                .map(|(_, _, text)| text)
+                .collect()
+        };
+        // 相似度：hello world = 1，hello wurld = 8/11，say hello = 5/11，goodbye = 0
+        assert_eq!(found(0), vec!["hello world"]);
+        assert_eq!(found(27), vec!["hello world"]);
+        assert_eq!(found(28), vec!["hello world", "hello wurld"]);
+        assert_eq!(found(60), vec!["hello world", "hello wurld", "say hello"]);
+        assert_eq!(found(100), vec!["hello world", "hello wurld", "say hello"]);
+        // 超过 100 按 100 处理
+        assert_eq!(found(250), found(100));
+    }
+
+    #[test]
+    fn filtered_contents_map_to_current_indices_after_history_changes() {
+        let (manager, _persist_rx) = manager_with_mode(DedupMode::None);
+        for text in ["hello world", "goodbye", "hello wurld", "say hello"] {
+            manager.add_to_history(text.to_string());
+        }
+        // 历史：say hello, hello wurld, goodbye, hello world
+        let filter: Vec<String> = manager
+            .fuzzy_search("hello world", 30)
+            .into_iter()
+            .map(|(_, _, text)| text)
+            .collect();
+        assert_eq!(filter, vec!["hello world", "hello wurld"]);
+        assert_eq!(manager.current_indices_of(&filter), vec![3, 1]);
+
+        // 过滤期间新复制的内容插到最前，原记录整体后移
+        manager.add_to_history("new entry".to_string());
+        assert_eq!(manager.current_indices_of(&filter), vec![4, 2]);
+
+        // 已删除的记录不再对应任何位置
+        manager.remove_from_history(2).unwrap();
+        assert_eq!(manager.current_indices_of(&filter), vec![3]);
+    }
+
+    #[test]
+    fn current_indices_of_pairs_duplicate_contents_in_order() {
+        let (manager, _persist_rx) = manager_with_mode(DedupMode::None);
+        for text in ["same", "other", "same"] {
+            manager.add_to_history(text.to_string());
+        }
+        let same = "same".to_string();
+        assert_eq!(manager.current_indices_of(&[same.clone()]), vec![0]);
+        assert_eq!(manager.current_indices_of(&[same.clone(), same.clone(), same]), vec![0, 2]);
+    }
+
+    #[test]
+    fn item_categories_follow_history_order_for_long_entries() {
+        let (manager, _persist_rx) = manager_with_mode(DedupMode::Exact);
+        let long_text = "长文本".repeat(100);
+        manager.add_to_history(long_text.clone());
+        manager.add_to_history("short".to_string());
+        manager.set_category(long_text.clone(), "工作".to_string()).unwrap();
+
+        // 预览被截断后无法再按内容查到分类，按索引对应则不受影响
+        let previews = manager.get_previews();
+        assert_ne!(previews[1], long_text);
+        assert_eq!(manager.get_item_categories(), vec![None, Some("工作".to_string())]);
+
+        manager.add_to_history("newest".to_string());
+        assert_eq!(manager.get_item_categories(), vec![None, None, Some("工作".to_string())]);
+    }
+
+    #[test]
+    fn undo_follows_entry_as_history_shifts() {
+        let (manager, _persist_rx) = manager_with_mode(DedupMode::Exact);
+        for text in ["original", "other"] {
+            manager.add_to_history(text.to_string());
+        }
+        // 历史：other, original
+        manager.replace_at_with_undo(1, "translated".to_string()).unwrap();
+
+        manager.add_to_history("newest".to_string());
+        assert!(manager.restore_previous_content(1).is_err());
+        assert_eq!(manager.get_entry(2).as_deref(), Some("translated"));
+
+        // 置顶后原文随记录移动
+        manager.promote_to_top(2).unwrap();
+        assert_eq!(manager.get_history(), vec!["translated", "newest", "other"]);
+        manager.restore_previous_content(0).unwrap();
+        assert_eq!(manager.get_history(), vec!["original", "newest", "other"]);
+        assert!(manager.restore_previous_content(0).is_err());
+    }
+
+    #[test]
+    fn undo_is_dropped_when_entry_is_removed_or_replaced() {
+        let (manager, _persist_rx) = manager_with_mode(DedupMode::Exact);
+        manager.add_to_history("original".to_string());
+        manager.replace_at_with_undo(0, "translated".to_string()).unwrap();
+        manager.remove_from_history(0).unwrap();
+
+        // 相同内容的新记录不继承已删除记录的原文
+        manager.add_to_history("translated".to_string());
+        assert!(manager.restore_previous_content(0).is_err());
+
+        manager.replace_at_with_undo(0, "again".to_string()).unwrap();
+        manager.replace_at(0, "edited".to_string()).unwrap();
+        assert!(manager.restore_previous_content(0).is_err());
+    }
 }
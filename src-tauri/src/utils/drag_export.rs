@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 拖拽导出文件所在的临时子目录
+const DRAG_EXPORT_DIR_NAME: &str = "fuyun_tools_drag";
+/// 导出文件保留时长，超过后在清理时删除
+const DRAG_EXPORT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+/// 文件名中取自内容的词数与字符数上限
+const SLUG_MAX_WORDS: usize = 5;
+const SLUG_MAX_CHARS: usize = 40;
+
+lazy_static::lazy_static! {
+    /// 本次运行中导出的文件及其导出时间
+    static ref EXPORTED_FILES: Mutex<HashMap<PathBuf, SystemTime>> = Mutex::new(HashMap::new());
+}
+
+fn export_dir() -> PathBuf {
+    std::env::temp_dir().join(DRAG_EXPORT_DIR_NAME)
+}
+
+/// 取内容开头的几个词作为文件名，只保留字母、数字和连字符
+fn slug_from_text(text: &str) -> String {
+    let slug = text
+        .split_whitespace()
+        .take(SLUG_MAX_WORDS)
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric() || *c == '-')
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug: String = slug.chars().take(SLUG_MAX_CHARS).collect();
+    if slug.is_empty() {
+        "clipboard".to_string()
+    } else {
+        slug
+    }
+}
+
+/// 规范化扩展名，去掉前导点，非法或为空时使用 txt
+fn sanitize_extension(extension: &str) -> String {
+    let extension = extension.trim().trim_start_matches('.').to_ascii_lowercase();
+    let valid = !extension.is_empty()
+        && extension.len() <= 10
+        && extension.chars().all(|c| c.is_ascii_alphanumeric());
+    if valid {
+        extension
+    } else {
+        "txt".to_string()
+    }
+}
+
+/// 生成不与已有文件冲突的导出路径，并登记以便到期清理
+fn prepare_export_path(slug: &str, extension: &str) -> Result<PathBuf, String> {
+    cleanup_expired_exports();
+    let dir = export_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("创建拖拽导出目录失败: {}", e))?;
+    let mut path = dir.join(format!("{}.{}", slug, extension));
+    if path.exists() {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+        path = dir.join(format!("{}_{}.{}", slug, timestamp, extension));
+    }
+    EXPORTED_FILES
+        .lock()
+        .unwrap()
+        .insert(path.clone(), SystemTime::now());
+    Ok(path)
+}
+
+/// 将文本写入临时文件，返回文件路径
+pub fn materialize_text(text: &str, extension: &str) -> Result<PathBuf, String> {
+    let path = prepare_export_path(&slug_from_text(text), &sanitize_extension(extension))?;
+    fs::write(&path, text).map_err(|e| format!("写入拖拽导出文件失败: {}", e))?;
+    log::info!("已导出历史条目到 {}", path.display());
+    Ok(path)
+}
+
+/// 将 RGBA 图片编码为 PNG 写入临时文件，返回文件路径
+pub fn materialize_png(rgba: &[u8], width: u32, height: u32) -> Result<PathBuf, String> {
+    let image = ::image::RgbaImage::from_raw(width, height, rgba.to_vec())
+        .ok_or_else(|| "图片数据长度与尺寸不符".to_string())?;
+    let path = prepare_export_path(&format!("image-{}x{}", width, height), "png")?;
+    image
+        .save_with_format(&path, ::image::ImageFormat::Png)
+        .map_err(|e| format!("写入拖拽导出图片失败: {}", e))?;
+    log::info!("已导出图片历史条目到 {}", path.display());
+    Ok(path)
+}
+
+/// 文件最近一次被写入或读取距今的时长，拖拽中的文件会被目标程序读取，据此尽量避免误删
+fn file_idle_time(path: &Path, now: SystemTime) -> Option<Duration> {
+    let metadata = fs::metadata(path).ok()?;
+    let last_used = [metadata.modified().ok(), metadata.accessed().ok()]
+        .into_iter()
+        .flatten()
+        .max()?;
+    Some(now.duration_since(last_used).unwrap_or_default())
+}
+
+/// 删除超过保留时长的导出文件，启动时与每次导出前调用，返回删除的文件数
+pub fn cleanup_expired_exports() -> usize {
+    let Ok(entries) = fs::read_dir(export_dir()) else {
+        return 0;
+    };
+    let now = SystemTime::now();
+    let mut exported = EXPORTED_FILES.lock().unwrap();
+    let mut removed = 0;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let exported_recently = exported
+            .get(&path)
+            .is_some_and(|time| now.duration_since(*time).unwrap_or_default() < DRAG_EXPORT_MAX_AGE);
+        let expired = file_idle_time(&path, now).is_some_and(|idle| idle >= DRAG_EXPORT_MAX_AGE);
+        if exported_recently || !expired {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => {
+                exported.remove(&path);
+                removed += 1;
+            }
+            Err(e) => log::warn!("删除过期拖拽导出文件失败 {}: {}", path.display(), e),
+        }
+    }
+    if removed > 0 {
+        log::info!("已清理 {} 个过期拖拽导出文件", removed);
+    }
+    removed
+}
@@ -0,0 +1,115 @@
+use crate::utils::utils_helpers::{atomic_write_with_backup, get_history_file_path, ClipboardHistoryData};
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 快照文件名前缀，文件与 history.json 位于同一目录
+const SNAPSHOT_FILE_PREFIX: &str = "history_snapshot_";
+
+/// 快照标识，即去掉 .json 后缀的文件名
+pub type SnapshotId = String;
+
+/// 快照文件的元数据
+#[derive(Debug, Clone, Serialize)]
+pub struct SnapshotInfo {
+    pub id: SnapshotId,
+    pub label: String,
+    pub created_ms: u64,
+    pub item_count: usize,
+    pub size_bytes: u64,
+}
+
+fn snapshot_dir() -> PathBuf {
+    let mut dir = get_history_file_path();
+    dir.pop();
+    dir
+}
+
+/// 只保留字母、数字、下划线和连字符，避免标签影响文件路径
+fn sanitize_label(label: &str) -> String {
+    let sanitized: String = label
+        .trim()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' { c } else { '_' })
+        .take(40)
+        .collect();
+    if sanitized.is_empty() {
+        "snapshot".to_string()
+    } else {
+        sanitized
+    }
+}
+
+/// 校验快照标识并返回对应的文件路径
+fn snapshot_path(id: &str) -> Result<PathBuf, String> {
+    let valid = id.starts_with(SNAPSHOT_FILE_PREFIX)
+        && !id.contains(['/', '\\'])
+        && !id.contains("..");
+    if !valid {
+        return Err(format!("无效的快照标识: {}", id));
+    }
+    Ok(snapshot_dir().join(format!("{}.json", id)))
+}
+
+/// 从标识中解析标签与创建时间（history_snapshot_{label}_{timestamp}）
+fn parse_snapshot_id(id: &str) -> Option<(String, u64)> {
+    let rest = id.strip_prefix(SNAPSHOT_FILE_PREFIX)?;
+    let (label, timestamp) = rest.rsplit_once('_')?;
+    Some((label.to_string(), timestamp.parse().ok()?))
+}
+
+/// 将历史数据写入新的快照文件
+pub fn write_snapshot(label: &str, data: &ClipboardHistoryData) -> Result<SnapshotId, String> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    let id = format!("{}{}_{}", SNAPSHOT_FILE_PREFIX, sanitize_label(label), timestamp);
+    let json = serde_json::to_string_pretty(data).map_err(|e| format!("序列化历史快照失败: {}", e))?;
+    atomic_write_with_backup(&snapshot_path(&id)?, json.as_bytes())
+        .map_err(|e| format!("写入历史快照失败: {}", e))?;
+    log::info!("已创建历史快照 {}，共 {} 条", id, data.items.len());
+    Ok(id)
+}
+
+/// 列出所有快照，按创建时间从新到旧排序
+pub fn list_snapshots() -> Result<Vec<SnapshotInfo>, String> {
+    let dir = snapshot_dir();
+    let entries = fs::read_dir(&dir).map_err(|e| format!("读取快照目录失败: {}", e))?;
+    let mut snapshots: Vec<SnapshotInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                return None;
+            }
+            let id = path.file_stem()?.to_str()?.to_string();
+            let (label, created_ms) = parse_snapshot_id(&id)?;
+            let size_bytes = entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+            let item_count = read_snapshot(&id).map(|data| data.items.len()).unwrap_or(0);
+            Some(SnapshotInfo {
+                id,
+                label,
+                created_ms,
+                item_count,
+                size_bytes,
+            })
+        })
+        .collect();
+    snapshots.sort_by_key(|snapshot| std::cmp::Reverse(snapshot.created_ms));
+    Ok(snapshots)
+}
+
+/// 读取快照中的历史数据
+pub fn read_snapshot(id: &str) -> Result<ClipboardHistoryData, String> {
+    let contents = fs::read_to_string(snapshot_path(id)?).map_err(|e| format!("读取历史快照失败: {}", e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("解析历史快照失败: {}", e))
+}
+
+/// 删除快照文件
+pub fn delete_snapshot(id: &str) -> Result<(), String> {
+    fs::remove_file(snapshot_path(id)?).map_err(|e| format!("删除历史快照失败: {}", e))?;
+    log::info!("已删除历史快照 {}", id);
+    Ok(())
+}
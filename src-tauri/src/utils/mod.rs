@@ -1,3 +1,6 @@
 pub mod clipboard;
+pub mod drag_export;
+pub mod history_snapshot;
 pub mod image_clipboard;
+pub mod paste_transforms;
 pub mod utils_helpers;
@@ -0,0 +1,208 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+lazy_static::lazy_static! {
+    static ref URL_REGEX: Regex = Regex::new(r#"https?://[^\s<>"'`]+"#).unwrap();
+}
+
+/// 除 utm_* 以外同样视为跟踪参数的查询参数名
+const TRACKING_PARAMS: [&str; 6] = ["fbclid", "gclid", "dclid", "msclkid", "yclid", "mc_eid"];
+
+/// 回填粘贴前对文本做的转换，历史记录本身保持不变
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteTransform {
+    /// 去掉首尾空白
+    Trim,
+    /// 删除空行，连续的多个换行合并为一个
+    CollapseNewlines,
+    /// 删除链接中的 utm_* 等跟踪参数
+    StripUrlTracking,
+}
+
+/// 转换按固定顺序执行，与设置中的排列顺序无关
+const TRANSFORM_ORDER: [PasteTransform; 3] = [
+    PasteTransform::StripUrlTracking,
+    PasteTransform::CollapseNewlines,
+    PasteTransform::Trim,
+];
+
+/// 依次执行已启用的转换，未启用任何转换时原样返回
+pub fn apply_paste_transforms(text: &str, enabled: &[PasteTransform]) -> String {
+    TRANSFORM_ORDER
+        .iter()
+        .filter(|transform| enabled.contains(transform))
+        .fold(text.to_string(), |text, transform| match transform {
+            PasteTransform::Trim => text.trim().to_string(),
+            PasteTransform::CollapseNewlines => collapse_newlines(&text),
+            PasteTransform::StripUrlTracking => strip_url_tracking(&text),
+        })
+}
+
+/// 删除空行，保留原文使用的换行符风格
+fn collapse_newlines(text: &str) -> String {
+    let line_ending = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join(line_ending)
+}
+
+fn is_tracking_param(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    name.starts_with("utm_") || TRACKING_PARAMS.contains(&name.as_str())
+}
+
+/// 删除文本中所有链接的跟踪参数，其余参数与锚点保持原样
+fn strip_url_tracking(text: &str) -> String {
+    URL_REGEX
+        .replace_all(text, |caps: &regex::Captures| strip_tracking_from_url(&caps[0]))
+        .into_owned()
+}
+
+fn strip_tracking_from_url(url: &str) -> String {
+    let (without_fragment, fragment) = match url.split_once('#') {
+        Some((head, fragment)) => (head, Some(fragment)),
+        None => (url, None),
+    };
+    let Some((base, query)) = without_fragment.split_once('?') else {
+        return url.to_string();
+    };
+    let kept: Vec<&str> = query
+        .split('&')
+        .filter(|param| {
+            let name = param.split('=').next().unwrap_or_default();
+            !param.is_empty() && !is_tracking_param(name)
+        })
+        .collect();
+
+    let mut result = base.to_string();
+    if !kept.is_empty() {
+        result.push('?');
+        result.push_str(&kept.join("&"));
+    }
+    if let Some(fragment) = fragment {
+        result.push('#');
+        result.push_str(fragment);
+    }
+    result
+}
+
+/// 对历史条目执行的一次性文本转换，结果原地替换条目（可撤销）
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TransformOp {
+    /// 合并段落内的硬换行后按指定宽度（字符数）重新换行
+    ReWrapAtWidth(usize),
+    /// 将所有换行替换为空格
+    StripLineBreaks,
+}
+
+/// 执行文本转换
+pub fn apply_transform_op(text: &str, op: TransformOp) -> Result<String, String> {
+    match op {
+        TransformOp::ReWrapAtWidth(0) => Err("换行宽度必须大于0".to_string()),
+        TransformOp::ReWrapAtWidth(width) => Ok(rewrap_at_width(text, width)),
+        TransformOp::StripLineBreaks => Ok(text.replace("\r\n", " ").replace('\n', " ")),
+    }
+}
+
+/// 以空行分段，段内各行合并后按宽度在空格处换行；超过宽度的单词不拆开
+fn rewrap_at_width(text: &str, width: usize) -> String {
+    let line_ending = if text.contains("\r\n") { "\r\n" } else { "\n" };
+    let mut paragraphs: Vec<Vec<&str>> = vec![Vec::new()];
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            if paragraphs.last().is_some_and(|paragraph| !paragraph.is_empty()) {
+                paragraphs.push(Vec::new());
+            }
+        } else {
+            paragraphs.last_mut().unwrap().extend(line.split_whitespace());
+        }
+    }
+    paragraphs
+        .iter()
+        .filter(|words| !words.is_empty())
+        .map(|words| wrap_words(words, width).join(line_ending))
+        .collect::<Vec<_>>()
+        .join(&line_ending.repeat(2))
+}
+
+fn wrap_words(words: &[&str], width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut current_len = 0;
+    for word in words {
+        let word_len = word.chars().count();
+        if current_len > 0 && current_len + 1 + word_len > width {
+            lines.push(std::mem::take(&mut current));
+            current_len = 0;
+        }
+        if current_len > 0 {
+            current.push(' ');
+            current_len += 1;
+        }
+        current.push_str(word);
+        current_len += word_len;
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trim_removes_surrounding_whitespace_only() {
+        assert_eq!(
+            apply_paste_transforms("  \n hello  world \t\n", &[PasteTransform::Trim]),
+            "hello  world"
+        );
+    }
+
+    #[test]
+    fn collapse_newlines_drops_blank_lines() {
+        assert_eq!(collapse_newlines("a\n\n\nb\n  \nc\n"), "a\nb\nc");
+    }
+
+    #[test]
+    fn collapse_newlines_keeps_crlf_line_endings() {
+        assert_eq!(collapse_newlines("a\r\n\r\n\r\nb\r\n \r\nc"), "a\r\nb\r\nc");
+    }
+
+    #[test]
+    fn strip_url_tracking_removes_tracking_params() {
+        assert_eq!(
+            strip_url_tracking("see https://example.com/page?utm_source=x&UTM_Medium=y&fbclid=z now"),
+            "see https://example.com/page now"
+        );
+    }
+
+    #[test]
+    fn strip_url_tracking_keeps_fragment_and_other_params() {
+        assert_eq!(
+            strip_url_tracking("https://example.com/a?id=7&utm_campaign=spring&lang=en#section-2"),
+            "https://example.com/a?id=7&lang=en#section-2"
+        );
+        assert_eq!(
+            strip_url_tracking("https://example.com/a#top?utm_source=x"),
+            "https://example.com/a#top?utm_source=x"
+        );
+        assert_eq!(strip_url_tracking("plain text, no links"), "plain text, no links");
+    }
+
+    #[test]
+    fn transforms_run_in_fixed_order() {
+        let text = "\n https://example.com/?utm_source=x\n\n\nnext line \n";
+        let enabled = [
+            PasteTransform::Trim,
+            PasteTransform::CollapseNewlines,
+            PasteTransform::StripUrlTracking,
+        ];
+        assert_eq!(apply_paste_transforms(text, &enabled), "https://example.com/\nnext line");
+        assert_eq!(apply_paste_transforms(text, &[]), text);
+    }
+}
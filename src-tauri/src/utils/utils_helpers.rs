@@ -1,15 +1,28 @@
 use crate::core::config::{
-    ProviderConfig, DEFAULT_CLIPBOARD_POLL_IDLE_INTERVAL_MS, DEFAULT_CLIPBOARD_POLL_MAX_INTERVAL_MS,
-    DEFAULT_CLIPBOARD_POLL_METRICS_ENABLED, DEFAULT_CLIPBOARD_POLL_METRICS_LOG_LEVEL,
-    DEFAULT_CLIPBOARD_POLL_MIN_INTERVAL_MS, DEFAULT_CLIPBOARD_POLL_REPORT_INTERVAL_SECS,
-    DEFAULT_CLIPBOARD_POLL_WARM_INTERVAL_MS, DEFAULT_IMAGE_TOGGLE_SHORTCUT, DEFAULT_TOGGLE_SHORTCUT,
+    validate_custom_headers, ProviderConfig, ResultWindowGeometry,
+    DEFAULT_AUTOSTART_LISTENER_DELAY_SECS, DEFAULT_CLIPBOARD_POLL_IDLE_INTERVAL_MS,
+    DEFAULT_CLIPBOARD_POLL_MAX_INTERVAL_MS, DEFAULT_CLIPBOARD_POLL_METRICS_ENABLED,
+    DEFAULT_CLIPBOARD_POLL_METRICS_LOG_LEVEL, DEFAULT_CLIPBOARD_POLL_MIN_INTERVAL_MS,
+    DEFAULT_CLIPBOARD_POLL_REPORT_INTERVAL_SECS, DEFAULT_CLIPBOARD_POLL_WARM_INTERVAL_MS,
+    DEFAULT_IMAGE_TOGGLE_SHORTCUT, DEFAULT_LARGE_INPUT_CONFIRM_TOKENS,
+    DEFAULT_MAX_CONCURRENT_AI_REQUESTS, DEFAULT_RESULT_WINDOW_HEIGHT, DEFAULT_RESULT_WINDOW_WIDTH,
+    DEFAULT_SELECTION_CAPTURE_INTERVAL_MS, DEFAULT_SELECTION_CAPTURE_TIMEOUT_MS,
+    DEFAULT_SELECTION_UIA_MAX_CHARS,
+    DEFAULT_TOGGLE_SHORTCUT, DEFAULT_TRANSLATION_CHUNK_CHARS, MAX_SELECTION_CAPTURE_TIMEOUT_MS,
+    SETTINGS_SAVE_RETRY_INTERVAL,
 };
+use crate::core::diagnostics::spawn_named;
+use crate::core::chord_shortcuts::ChordShortcut;
+use crate::features::custom_actions::{validate_custom_actions, CustomAction};
+use crate::utils::paste_transforms::PasteTransform;
 use keyring::Entry;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use semver::{BuildMetadata, Prerelease, Version};
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
 use std::thread;
 use std::time::{Duration, Instant};
 use std::fs;
@@ -21,7 +34,196 @@ pub fn get_default_app_version() -> String {
     env!("CARGO_PKG_VERSION").to_string()
 }
 
+/// 设置文件格式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SettingsFormat {
+    #[default]
+    Json,
+    Toml,
+}
+
+/// 剪贴板历史去重方式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupMode {
+    /// 保留所有记录（仅忽略与最新一条完全相同的重复复制）
+    None,
+    /// 仅合并完全相同的记录
+    Exact,
+    /// 合并完全相同及相似的不完整版本
+    #[default]
+    Fuzzy,
+}
+
+/// 剪贴板历史的展示顺序
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HistorySortMode {
+    /// 按最近使用排序（历史记录原始顺序）
+    #[default]
+    Recent,
+    /// 按粘贴使用次数从多到少排序，次数相同时保持原始顺序
+    Frequency,
+}
+
+/// 剪贴板窗口显示在哪个显示器上
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowPositionStrategy {
+    /// 始终显示在主显示器
+    PrimaryMonitor,
+    /// 显示在当前前台应用所在的显示器
+    #[default]
+    ActiveMonitor,
+    /// 显示在鼠标所在的显示器
+    MouseMonitor,
+}
+
+/// 选择剪贴板记录后写入目标应用的方式
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PasteMethod {
+    /// 写入系统剪贴板后模拟 Ctrl+V
+    #[default]
+    #[serde(alias = "ctrl_v")]
+    ClipboardPaste,
+    /// 直接模拟键入文本，不改动剪贴板；适用于禁止粘贴的输入框、远程桌面与虚拟机，
+    /// 但长文本较慢，部分应用中非 ASCII 字符可能出错
+    #[serde(alias = "type_out")]
+    TypeText,
+    /// 只写入剪贴板并发送通知，由用户手动粘贴
+    CopyOnly,
+}
+
+/// 按目标窗口强制使用的回填方式
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PasteMethodRule {
+    /// 窗口标题包含的文本，不区分大小写
+    pub window_title: String,
+    pub method: PasteMethod,
+}
+
+impl PasteMethodRule {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.window_title.trim().is_empty() {
+            return Err("回填方式规则的窗口标题不能为空".to_string());
+        }
+        Ok(())
+    }
+
+    fn matches(&self, title: &str) -> bool {
+        title.to_lowercase().contains(&self.window_title.trim().to_lowercase())
+    }
+}
+
+/// 返回第一条匹配窗口标题的规则指定的回填方式
+pub fn paste_method_for_window(rules: &[PasteMethodRule], title: &str) -> Option<PasteMethod> {
+    rules.iter().find(|rule| rule.matches(title)).map(|rule| rule.method)
+}
+
+/// 划词工具栏上的快捷翻译按钮，语言名称原样传给翻译请求
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TranslationPreset {
+    /// 按钮上显示的名称，例如“日→中”
+    pub name: String,
+    pub source_language: String,
+    pub target_language: String,
+}
+
+impl TranslationPreset {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty()
+            || self.source_language.trim().is_empty()
+            || self.target_language.trim().is_empty()
+        {
+            return Err("翻译预设的名称、源语言和目标语言都不能为空".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 提示词库中的一条自定义提示词，template 的占位符与翻译/解释提示词模板相同
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CustomPrompt {
+    pub name: String,
+    pub template: String,
+}
+
+impl CustomPrompt {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("自定义提示词名称不能为空".to_string());
+        }
+        if self.template.trim().is_empty() {
+            return Err(format!("自定义提示词 {} 的内容不能为空", self.name));
+        }
+        Ok(())
+    }
+}
+
+/// 检查提示词列表，名称不能重复
+pub fn validate_custom_prompts(prompts: &[CustomPrompt]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for prompt in prompts {
+        prompt.validate()?;
+        if !names.insert(prompt.name.trim()) {
+            return Err(format!("自定义提示词名称重复: {}", prompt.name));
+        }
+    }
+    Ok(())
+}
+
+/// 文本记录回填完成后执行的操作
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PostPasteAction {
+    /// 不做额外操作（记录照常移到顶部并累加使用次数）
+    #[default]
+    None,
+    /// 粘贴后将记录移到历史末尾
+    MoveToEnd,
+    /// 只累加使用次数，记录保持原位置不移到顶部
+    IncrementAccessCount,
+    /// 粘贴后将下一条记录写入剪贴板并选中，便于连续粘贴
+    CopyNextItem,
+}
+
+/// 文本完整性判断使用的标点集合，每个字符为一个成员
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct SentencePunctuation {
+    /// 句子结束符
+    pub sentence_endings: String,
+    /// 截断提示符，文本以这些字符结尾时视为被截断
+    pub truncation_indicators: String,
+    /// 比较句子结构时计入的标点
+    pub counted_punctuation: String,
+}
+
+impl Default for SentencePunctuation {
+    fn default() -> Self {
+        Self {
+            sentence_endings: "。！？.!?".to_string(),
+            truncation_indicators: "，,、([{\"'".to_string(),
+            counted_punctuation: "。！？.!?；;，,".to_string(),
+        }
+    }
+}
+
+impl SentencePunctuation {
+    /// 检查标点集合是否有效
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sentence_endings.trim().is_empty() {
+            return Err("句子结束符不能为空".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// 所有字段缺失时使用默认值；未知字段保存在 extra 中，旧版本程序回写时不会丢失新版本的设置
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
 pub struct AppSettingsData {
     pub version: String,
     pub max_items: usize,
@@ -57,6 +259,148 @@ pub struct AppSettingsData {
     pub clipboard_poll_metrics_enabled: bool,
     #[serde(default = "default_clipboard_poll_metrics_log_level")]
     pub clipboard_poll_metrics_log_level: String,
+    #[serde(default = "default_result_window_width")]
+    pub result_window_width: u32,
+    #[serde(default = "default_result_window_height")]
+    pub result_window_height: u32,
+    /// 各类结果窗口上次的位置与尺寸，键为窗口类型
+    #[serde(default)]
+    pub result_window_geometries: HashMap<String, ResultWindowGeometry>,
+    /// 各类结果窗口是否置顶，键为窗口类型
+    #[serde(default)]
+    pub result_window_always_on_top: HashMap<String, bool>,
+    /// 是否记录划词文本（默认关闭）
+    #[serde(default)]
+    pub record_selection_history: bool,
+    /// 手动划词快捷键，为空表示不启用
+    #[serde(default)]
+    pub capture_selection_hot_key: String,
+    /// 免打扰：暂停剪贴板记录、划词检测与划词工具栏
+    #[serde(default)]
+    pub do_not_disturb: bool,
+    /// 前台为全屏应用时自动进入免打扰
+    #[serde(default)]
+    pub auto_dnd_fullscreen: bool,
+    /// 切换免打扰的快捷键，为空表示不启用
+    #[serde(default)]
+    pub dnd_hot_key: String,
+    /// 打开全局搜索窗口的快捷键，为空表示不启用
+    #[serde(default)]
+    pub global_search_hot_key: String,
+    /// 更新通道：stable / beta
+    #[serde(default = "default_update_channel")]
+    pub update_channel: String,
+    /// 是否在后台自动检查更新
+    #[serde(default = "default_auto_check_updates")]
+    pub auto_check_updates: bool,
+    /// 已通知过的最新版本号，避免重复提醒
+    #[serde(default)]
+    pub last_notified_update_version: String,
+    /// 剪贴板历史去重方式
+    #[serde(default)]
+    pub dedup_mode: DedupMode,
+    /// 剪贴板窗口所在显示器的选择策略
+    #[serde(default)]
+    pub window_position_strategy: WindowPositionStrategy,
+    /// 文本记录回填到目标应用的方式
+    #[serde(default)]
+    pub paste_method: PasteMethod,
+    /// 按目标窗口标题强制使用的回填方式，优先于 paste_method
+    #[serde(default)]
+    pub paste_method_rules: Vec<PasteMethodRule>,
+    /// 键入回填时每个字符之间的间隔（毫秒），0 表示一次性键入
+    #[serde(default)]
+    pub type_text_key_delay_ms: u64,
+    /// 剪贴板窗口移动选中位置到两端时是否循环到另一端
+    #[serde(default = "default_selection_wrap_around")]
+    pub selection_wrap_around: bool,
+    /// 用户定义的快捷翻译语言组合
+    #[serde(default)]
+    pub translation_presets: Vec<TranslationPreset>,
+    /// 试运行：翻译与解释只在结果窗口展示提示词和请求体，不调用AI接口
+    #[serde(default)]
+    pub ai_dry_run: bool,
+    /// 自定义提示词库，可导出分享
+    #[serde(default)]
+    pub custom_prompts: Vec<CustomPrompt>,
+    /// 输入估算超过该 token 数时先请用户确认再发送，0 表示不确认
+    #[serde(default = "default_large_input_confirm_tokens")]
+    pub large_input_confirm_tokens: usize,
+    /// 文本记录回填完成后执行的操作
+    #[serde(default)]
+    pub post_paste_action: PostPasteAction,
+    /// 回填粘贴前对文本做的转换，只影响粘贴内容，不修改历史记录
+    #[serde(default)]
+    pub paste_transforms: Vec<PasteTransform>,
+    /// 离线翻译接口地址（LibreTranslate 兼容），为空时不启用；主 AI 服务网络不可达时使用
+    #[serde(default)]
+    pub fallback_translate_url: String,
+    /// 划词时不弹出工具栏的内容类型
+    #[serde(default = "default_suppress_toolbar_for")]
+    pub suppress_toolbar_for: Vec<ContentType>,
+    /// 日志级别：trace / debug / info / warn / error
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    /// 开机自启后延迟启动剪贴板与划词监听的秒数，等待桌面加载完成
+    #[serde(default = "default_autostart_listener_delay_secs")]
+    pub autostart_listener_delay_secs: u64,
+    /// 划词捕获等待剪贴板更新的最长时间（毫秒），远程桌面等较慢环境可调大
+    #[serde(default = "default_selection_capture_timeout_ms")]
+    pub selection_capture_timeout_ms: u64,
+    /// 划词捕获轮询剪贴板的间隔（毫秒）
+    #[serde(default = "default_selection_capture_interval_ms")]
+    pub selection_capture_interval_ms: u64,
+    /// 日志文件保留天数
+    #[serde(default = "default_log_retention_days")]
+    pub log_retention_days: u32,
+    /// 全局允许同时进行的AI请求数
+    #[serde(default = "default_max_concurrent_ai_requests")]
+    pub max_concurrent_ai_requests: u8,
+    /// 是否启用仅监听 127.0.0.1 的本地 HTTP 接口
+    #[serde(default)]
+    pub http_api_enabled: bool,
+    /// 本地 HTTP 接口端口，0 表示由系统随机分配
+    #[serde(default)]
+    pub http_api_port: u16,
+    /// 本地 HTTP 接口的访问令牌，启用时自动生成
+    #[serde(default)]
+    pub http_api_token: String,
+    /// 长文本分段翻译的每段最大字符数，超过时按段落依次翻译；0 表示不分段
+    #[serde(default = "default_translation_chunk_chars")]
+    pub translation_chunk_chars: usize,
+    /// UI Automation 兜底读取选中文本的最大字符数，超出部分截断
+    #[serde(default = "default_selection_uia_max_chars")]
+    pub selection_uia_max_chars: usize,
+    /// 是否发送相似度调试事件，用于排查去重结果
+    #[serde(default)]
+    pub debug_show_similarity: bool,
+    /// 是否将 AI 请求与响应记录到 ai_transcript.log
+    #[serde(default)]
+    pub log_ai_transcripts: bool,
+    /// 两段式组合快捷键
+    #[serde(default)]
+    pub chord_shortcuts: Vec<ChordShortcut>,
+    /// 调用外部命令处理文本的自定义动作，只能在设置文件中编辑
+    #[serde(default)]
+    pub custom_actions: Vec<CustomAction>,
+    /// 去重时判断文本完整性使用的标点集合
+    #[serde(default)]
+    pub sentence_punctuation: SentencePunctuation,
+    /// 旧版扁平结构中的 API 地址，仅用于迁移到 provider_configs，不写入文件
+    #[serde(rename = "ai_api_url", default, skip_serializing)]
+    pub legacy_ai_api_url: String,
+    /// 旧版扁平结构中的模型名称，仅用于迁移
+    #[serde(rename = "ai_model_name", default, skip_serializing)]
+    pub legacy_ai_model_name: String,
+    /// 旧版扁平结构中的加密 API 密钥，仅用于迁移
+    #[serde(rename = "ai_api_key", alias = "encrypted_api_key", default, skip_serializing)]
+    pub legacy_encrypted_api_key: String,
+    /// 设置文件格式，由加载时检测得到，不写入文件
+    #[serde(skip)]
+    pub settings_format: SettingsFormat,
+    /// 当前版本无法识别的字段，原样保留
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl Default for AppSettingsData {
@@ -80,6 +424,54 @@ impl Default for AppSettingsData {
             clipboard_poll_report_interval_secs: default_clipboard_poll_report_interval_secs(),
             clipboard_poll_metrics_enabled: default_clipboard_poll_metrics_enabled(),
             clipboard_poll_metrics_log_level: default_clipboard_poll_metrics_log_level(),
+            result_window_width: default_result_window_width(),
+            result_window_height: default_result_window_height(),
+            result_window_geometries: HashMap::new(),
+            result_window_always_on_top: HashMap::new(),
+            record_selection_history: false,
+            capture_selection_hot_key: String::new(),
+            do_not_disturb: false,
+            auto_dnd_fullscreen: false,
+            dnd_hot_key: String::new(),
+            global_search_hot_key: String::new(),
+            update_channel: default_update_channel(),
+            auto_check_updates: default_auto_check_updates(),
+            last_notified_update_version: String::new(),
+            dedup_mode: DedupMode::Fuzzy,
+            window_position_strategy: WindowPositionStrategy::ActiveMonitor,
+            paste_method: PasteMethod::ClipboardPaste,
+            paste_method_rules: Vec::new(),
+            type_text_key_delay_ms: 0,
+            selection_wrap_around: default_selection_wrap_around(),
+            translation_presets: Vec::new(),
+            ai_dry_run: false,
+            custom_prompts: Vec::new(),
+            large_input_confirm_tokens: default_large_input_confirm_tokens(),
+            post_paste_action: PostPasteAction::None,
+            paste_transforms: Vec::new(),
+            fallback_translate_url: String::new(),
+            suppress_toolbar_for: default_suppress_toolbar_for(),
+            log_level: default_log_level(),
+            autostart_listener_delay_secs: default_autostart_listener_delay_secs(),
+            selection_capture_timeout_ms: default_selection_capture_timeout_ms(),
+            selection_capture_interval_ms: default_selection_capture_interval_ms(),
+            log_retention_days: default_log_retention_days(),
+            max_concurrent_ai_requests: default_max_concurrent_ai_requests(),
+            http_api_enabled: false,
+            http_api_port: 0,
+            http_api_token: String::new(),
+            translation_chunk_chars: default_translation_chunk_chars(),
+            selection_uia_max_chars: default_selection_uia_max_chars(),
+            debug_show_similarity: false,
+            log_ai_transcripts: false,
+            chord_shortcuts: Vec::new(),
+            custom_actions: Vec::new(),
+            sentence_punctuation: SentencePunctuation::default(),
+            legacy_ai_api_url: String::new(),
+            legacy_ai_model_name: String::new(),
+            legacy_encrypted_api_key: String::new(),
+            settings_format: SettingsFormat::Json,
+            extra: serde_json::Map::new(),
         }
     }
 }
@@ -128,51 +520,217 @@ fn default_clipboard_poll_metrics_log_level() -> String {
     DEFAULT_CLIPBOARD_POLL_METRICS_LOG_LEVEL.to_string()
 }
 
-pub fn default_translation_prompt_template() -> String {
-    "你是专业翻译助手。任务：将用户文本翻译为{target_language}。\n要求：\n1) 自动识别源语言（如已提供{source_language}且不是“自动识别”，按其处理）。\n2) 忠实原意，不遗漏、不杜撰。\n3) 保留专有名词、代码、变量、URL、邮箱、数字与单位。\n4) 保持原文段落与换行结构。\n5) 只输出译文，不要任何说明。\n\n待翻译文本：\n{text}".to_string()
+fn default_result_window_width() -> u32 {
+    DEFAULT_RESULT_WINDOW_WIDTH
 }
 
-pub fn default_explanation_prompt_template() -> String {
-    "你是清晰易懂的讲解助手。请使用{target_language}解释下列内容。\n要求：\n1) 先给一句话总结，再分点说明关键点。\n2) 面向普通用户，术语给简短释义。\n3) 保持准确，不编造；不确定时直接说明。\n4) 控制在180字以内。\n5) 仅输出解释内容。\n\n待解释文本：\n{text}".to_string()
+fn default_result_window_height() -> u32 {
+    DEFAULT_RESULT_WINDOW_HEIGHT
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_suppress_toolbar_for() -> Vec<ContentType> {
+    vec![ContentType::Url, ContentType::Email, ContentType::PhoneNumber]
+}
+
+/// 离线翻译接口地址为空或以 http(s):// 开头
+pub fn is_valid_fallback_translate_url(url: &str) -> bool {
+    let url = url.trim();
+    url.is_empty() || url.starts_with("http://") || url.starts_with("https://")
+}
+
+fn default_max_concurrent_ai_requests() -> u8 {
+    DEFAULT_MAX_CONCURRENT_AI_REQUESTS
+}
+
+fn default_translation_chunk_chars() -> usize {
+    DEFAULT_TRANSLATION_CHUNK_CHARS
+}
+
+/// 分段翻译的每段字符数是否有效（0 表示关闭分段）
+pub fn is_valid_translation_chunk_chars(chars: usize) -> bool {
+    chars == 0 || (200..=20000).contains(&chars)
+}
+
+fn default_selection_uia_max_chars() -> usize {
+    DEFAULT_SELECTION_UIA_MAX_CHARS
+}
+
+/// UI Automation 读取选中文本的字符上限是否有效
+pub fn is_valid_selection_uia_max_chars(chars: usize) -> bool {
+    (100..=100_000).contains(&chars)
+}
+
+fn default_autostart_listener_delay_secs() -> u64 {
+    DEFAULT_AUTOSTART_LISTENER_DELAY_SECS
+}
+
+fn default_selection_capture_timeout_ms() -> u64 {
+    DEFAULT_SELECTION_CAPTURE_TIMEOUT_MS
+}
+
+fn default_selection_capture_interval_ms() -> u64 {
+    DEFAULT_SELECTION_CAPTURE_INTERVAL_MS
+}
+
+/// 划词捕获时间设置是否有效：最长时间 100ms 至上限，轮询间隔 5-200ms 且不超过最长时间
+pub fn is_valid_selection_capture_timing(timeout_ms: u64, interval_ms: u64) -> bool {
+    (100..=MAX_SELECTION_CAPTURE_TIMEOUT_MS).contains(&timeout_ms)
+        && (5..=200).contains(&interval_ms)
+        && interval_ms <= timeout_ms
+}
+
+/// token 价格是否有效：有限的非负数
+pub fn is_valid_token_price(price: f64) -> bool {
+    price.is_finite() && price >= 0.0
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-struct MigrationVersion {
-    major: u32,
-    minor: u32,
-    patch: u32,
+/// 粗略估算文本的 token 数：中日韩字符每字约 1 个 token，其余字符约每 4 个 1 个 token
+pub fn estimate_tokens(text: &str) -> usize {
+    let (cjk, other) = text.chars().fold((0usize, 0usize), |(cjk, other), c| {
+        if is_cjk_char(c) {
+            (cjk + 1, other)
+        } else {
+            (cjk, other + 1)
+        }
+    });
+    cjk + other.div_ceil(4)
 }
 
-impl MigrationVersion {
-    const fn new(major: u32, minor: u32, patch: u32) -> Self {
-        Self { major, minor, patch }
+/// 规范化快捷键：去掉首尾空白，并确认能被解析为全局快捷键
+pub fn normalize_hot_key(hot_key: &str) -> Result<String, String> {
+    let hot_key = hot_key.trim();
+    if hot_key.is_empty() {
+        return Err("快捷键不能为空".to_string());
     }
+    hot_key
+        .parse::<tauri_plugin_global_shortcut::Shortcut>()
+        .map_err(|e| format!("无效的快捷键 {}: {}", hot_key, e))?;
+    Ok(hot_key.to_string())
+}
+
+fn default_log_retention_days() -> u32 {
+    14
+}
+
+fn default_update_channel() -> String {
+    "stable".to_string()
+}
+
+fn default_auto_check_updates() -> bool {
+    true
+}
+
+fn default_selection_wrap_around() -> bool {
+    true
+}
+
+fn default_large_input_confirm_tokens() -> usize {
+    DEFAULT_LARGE_INPUT_CONFIRM_TOKENS
+}
+
+pub fn default_translation_prompt_template() -> String {
+    "你是专业翻译助手。任务：将用户文本翻译为{target_language}。\n\
+    要求：\n\
+    1) 自动识别源语言（如已提供{source_language}且不是“自动识别”，按其处理）。\n\
+    2) 忠实原意，不遗漏、不杜撰。\n\
+    3) 保留专有名词、代码、变量、URL、邮箱、数字与单位。\n\
+    4) 保持原文段落与换行结构。\n\
+    5) 只输出译文，不要任何说明。\n\
+    \n\
+    待翻译文本：\n\
+    {text}".to_string()
+}
+
+pub fn default_explanation_prompt_template() -> String {
+    "你是清晰易懂的讲解助手。请使用{target_language}解释下列内容。\n\
+    要求：\n\
+    1) 先给一句话总结，再分点说明关键点。\n\
+    2) 面向普通用户，术语给简短释义。\n\
+    3) 保持准确，不编造；不确定时直接说明。\n\
+    4) 控制在180字以内。\n\
+    5) 仅输出解释内容。\n\
+    \n\
+    待解释文本：\n\
+    {text}".to_string()
 }
 
-fn parse_migration_version(raw: &str) -> Option<MigrationVersion> {
+/// 单个设置迁移步骤：目标版本、说明与迁移函数
+type MigrationStep = (Version, &'static str, fn(&mut AppSettingsData));
+
+/// 按版本排序的设置迁移步骤：版本号位于 (旧版本, 当前版本] 区间内的步骤会依次执行
+const MIGRATION_STEPS: [MigrationStep; 2] = [
+    (
+        Version::new(0, 2, 0),
+        "确保基础配置完整性",
+        AppSettingsData::ensure_basic_config_integrity,
+    ),
+    (
+        Version::new(0, 3, 0),
+        "初始化AI提供商配置",
+        AppSettingsData::initialize_ai_provider_configs_if_needed,
+    ),
+];
+
+/// 解析设置文件中的版本号，兼容 "v0.3.1"、"0.3" 以及早期的纯数字版本 "3"
+fn parse_migration_version(raw: &str) -> Option<Version> {
     let trimmed = raw.trim().trim_start_matches('v');
     if trimmed.is_empty() {
         return None;
     }
 
     if !trimmed.contains('.') {
-        if let Ok(legacy) = trimmed.parse::<u32>() {
-            return Some(MigrationVersion::new(0, legacy, 0));
+        if let Ok(legacy) = trimmed.parse::<u64>() {
+            return Some(Version::new(0, legacy, 0));
+        }
+    }
+
+    let mut version = Version::parse(trimmed).ok().or_else(|| {
+        let core = trimmed.split(['-', '+']).next().unwrap_or(trimmed);
+        let mut parts: Vec<&str> = core.split('.').collect();
+        while parts.len() < 3 {
+            parts.push("0");
         }
+        Version::parse(&parts.join(".")).ok()
+    })?;
+    // 预发布与构建标记不影响迁移判断
+    version.pre = Prerelease::EMPTY;
+    version.build = BuildMetadata::EMPTY;
+    Some(version)
+}
+
+/// 设置文件中的版本号对应的迁移方式
+#[derive(Debug, PartialEq)]
+enum MigrationPlan {
+    /// 执行版本号位于 (起始版本, 当前版本] 区间内的步骤
+    Steps(Version),
+    /// 版本号无法解析，执行通用迁移
+    Generic,
+    /// 不低于当前版本，无需迁移
+    UpToDate,
+}
+
+/// 根据设置文件中的版本号决定迁移方式，缺少版本号的早期设置文件从头执行全部步骤
+fn migration_plan(stored_version: &str, current_version: &Version) -> MigrationPlan {
+    if stored_version.trim().is_empty() {
+        return MigrationPlan::Steps(Version::new(0, 0, 0));
     }
+    match parse_migration_version(stored_version) {
+        Some(old_version) if &old_version < current_version => MigrationPlan::Steps(old_version),
+        Some(_) => MigrationPlan::UpToDate,
+        None => MigrationPlan::Generic,
+    }
+}
 
-    let core = trimmed
-        .split_once('-')
-        .map(|(left, _)| left)
-        .unwrap_or(trimmed)
-        .split_once('+')
-        .map(|(left, _)| left)
-        .unwrap_or(trimmed);
-    let mut parts = core.split('.');
-    let major = parts.next()?.parse::<u32>().ok()?;
-    let minor = parts.next().unwrap_or("0").parse::<u32>().ok()?;
-    let patch = parts.next().unwrap_or("0").parse::<u32>().ok()?;
-    Some(MigrationVersion::new(major, minor, patch))
+/// 版本号位于 (old_version, new_version] 区间内的迁移步骤，按版本顺序排列
+fn pending_migration_steps(old_version: &Version, new_version: &Version) -> Vec<MigrationStep> {
+    MIGRATION_STEPS
+        .into_iter()
+        .filter(|(step_version, _, _)| old_version < step_version && step_version <= new_version)
+        .collect()
 }
 
 impl AppSettingsData {
@@ -254,6 +812,47 @@ impl AppSettingsData {
         Err(format!("获取API密钥失败: {}", last_error))
     }
 
+    /// 将旧版扁平结构的 AI 配置迁移为当前提供商的 provider_configs 条目（保留加密密钥）
+    /// 返回是否发生了迁移
+    pub fn migrate_flat_provider_fields(&mut self) -> bool {
+        if self.legacy_ai_api_url.is_empty()
+            && self.legacy_ai_model_name.is_empty()
+            && self.legacy_encrypted_api_key.is_empty()
+        {
+            return false;
+        }
+        if self.ai_provider.is_empty() {
+            self.ai_provider = "deepseek".to_string();
+        }
+        let api_url = std::mem::take(&mut self.legacy_ai_api_url);
+        let model_name = std::mem::take(&mut self.legacy_ai_model_name);
+        let encrypted_api_key = std::mem::take(&mut self.legacy_encrypted_api_key);
+
+        let config = self
+            .provider_configs
+            .entry(self.ai_provider.clone())
+            .or_insert_with(|| ProviderConfig {
+                api_url: String::new(),
+                model_name: String::new(),
+                encrypted_api_key: String::new(),
+                timeout_secs: None,
+                custom_headers: HashMap::new(),
+                price_per_million_tokens: None,
+                extra: serde_json::Map::new(),
+            });
+        if config.api_url.is_empty() {
+            config.api_url = api_url;
+        }
+        if config.model_name.is_empty() {
+            config.model_name = model_name;
+        }
+        if config.encrypted_api_key.is_empty() {
+            config.encrypted_api_key = encrypted_api_key;
+        }
+        log::info!("已将旧版扁平 AI 配置迁移到提供商: {}", self.ai_provider);
+        true
+    }
+
     /// 迁移旧版加密的API密钥到系统凭据管理器
     /// 返回是否发生了迁移
     pub fn migrate_legacy_api_keys(&mut self) -> bool {
@@ -339,6 +938,10 @@ impl AppSettingsData {
                 api_url: default_url,
                 model_name: default_model,
                 encrypted_api_key: String::new(),
+                timeout_secs: None,
+                custom_headers: HashMap::new(),
+                price_per_million_tokens: None,
+                extra: serde_json::Map::new(),
             }
         };
 
@@ -394,6 +997,71 @@ impl AppSettingsData {
         if level != "trace" && level != "debug" && level != "info" && level != "warn" {
             return Err("clipboard_poll_metrics_log_level仅支持trace/debug/info/warn".to_string());
         }
+        if !(240..=3840).contains(&self.result_window_width) {
+            return Err("result_window_width必须在240-3840之间".to_string());
+        }
+        if !(160..=2160).contains(&self.result_window_height) {
+            return Err("result_window_height必须在160-2160之间".to_string());
+        }
+        if !matches!(self.log_level.as_str(), "trace" | "debug" | "info" | "warn" | "error") {
+            return Err("log_level仅支持trace/debug/info/warn/error".to_string());
+        }
+        if !is_valid_fallback_translate_url(&self.fallback_translate_url) {
+            return Err("fallback_translate_url必须以http://或https://开头".to_string());
+        }
+        for (provider, config) in &self.provider_configs {
+            validate_custom_headers(&config.custom_headers)
+                .map_err(|e| format!("提供商 {} 的{}", provider, e))?;
+        }
+        if !(1..=10).contains(&self.max_concurrent_ai_requests) {
+            return Err("max_concurrent_ai_requests必须在1-10之间".to_string());
+        }
+        if !is_valid_translation_chunk_chars(self.translation_chunk_chars) {
+            return Err("translation_chunk_chars必须为0或在200-20000之间".to_string());
+        }
+        if !is_valid_selection_uia_max_chars(self.selection_uia_max_chars) {
+            return Err("selection_uia_max_chars必须在100-100000之间".to_string());
+        }
+        if !(1..=365).contains(&self.log_retention_days) {
+            return Err("log_retention_days必须在1-365之间".to_string());
+        }
+        if self.autostart_listener_delay_secs > 300 {
+            return Err("autostart_listener_delay_secs必须在0-300之间".to_string());
+        }
+        if !is_valid_selection_capture_timing(self.selection_capture_timeout_ms, self.selection_capture_interval_ms) {
+            return Err(format!(
+                "selection_capture_timeout_ms必须在100-{}之间，selection_capture_interval_ms必须在5-200之间",
+                MAX_SELECTION_CAPTURE_TIMEOUT_MS
+            ));
+        }
+        for chord in &self.chord_shortcuts {
+            chord.validate()?;
+        }
+        validate_custom_actions(&self.custom_actions)?;
+        for rule in &self.paste_method_rules {
+            rule.validate()?;
+        }
+        for preset in &self.translation_presets {
+            preset.validate()?;
+        }
+        validate_custom_prompts(&self.custom_prompts)?;
+        if self.type_text_key_delay_ms > 200 {
+            return Err("type_text_key_delay_ms必须在0-200之间".to_string());
+        }
+        self.sentence_punctuation.validate()?;
+        if self.update_channel != "stable" && self.update_channel != "beta" {
+            return Err("update_channel仅支持stable/beta".to_string());
+        }
+        for (provider, config) in &self.provider_configs {
+            if let Some(timeout) = config.timeout_secs {
+                if !(5..=600).contains(&timeout) {
+                    return Err(format!("提供商 {} 的 timeout_secs 必须在5-600之间", provider));
+                }
+            }
+            if matches!(config.price_per_million_tokens, Some(price) if !is_valid_token_price(price)) {
+                return Err(format!("提供商 {} 的 price_per_million_tokens 必须是非负数", provider));
+            }
+        }
 
         Ok(())
     }
@@ -430,41 +1098,38 @@ impl AppSettingsData {
             return;
         }
 
-        match (
-            parse_migration_version(&self.version),
-            parse_migration_version(&current_version),
-        ) {
-            (Some(old_ver), Some(new_ver)) => {
-                if old_ver < new_ver {
-                    log::debug!("执行版本 {} 到 {} 的迁移", self.version, current_version);
-                    self.perform_version_migration(old_ver, new_ver);
-                }
+        let new_ver = match parse_migration_version(&current_version) {
+            Some(version) => version,
+            None => {
+                log::warn!("无法解析当前版本号 {}，执行通用迁移", current_version);
+                self.perform_generic_migration();
+                self.version = current_version;
+                return;
             }
-            _ => {
+        };
+
+        match migration_plan(&self.version, &new_ver) {
+            MigrationPlan::Steps(old_ver) => {
+                log::debug!("执行版本 {} 到 {} 的迁移", self.version, current_version);
+                self.perform_version_migration(&old_ver, &new_ver);
+            }
+            MigrationPlan::Generic => {
                 log::debug!("无法解析版本号格式，执行通用迁移");
                 self.perform_generic_migration();
             }
+            MigrationPlan::UpToDate => {}
         }
 
         self.version = current_version;
         log::debug!("版本迁移完成，当前版本: {}", self.version);
     }
 
-    /// 执行具体的版本迁移逻辑
-    fn perform_version_migration(&mut self, old_version: MigrationVersion, new_version: MigrationVersion) {
-        log::info!("执行版本迁移: {:?} -> {:?}", old_version, new_version);
-        if old_version < MigrationVersion::new(0, 3, 0)
-            && new_version >= MigrationVersion::new(0, 3, 0)
-        {
-            log::info!("迁移至版本 3: 初始化AI提供商配置");
-            self.initialize_ai_provider_configs_if_needed();
-        }
-
-        if old_version < MigrationVersion::new(0, 2, 0)
-            && new_version >= MigrationVersion::new(0, 2, 0)
-        {
-            log::info!("迁移至版本 2: 确保基础配置完整性");
-            self.ensure_basic_config_integrity();
+    /// 按顺序执行旧版本与当前版本之间的迁移步骤
+    fn perform_version_migration(&mut self, old_version: &Version, new_version: &Version) {
+        log::info!("执行版本迁移: {} -> {}", old_version, new_version);
+        for (step_version, description, step) in pending_migration_steps(old_version, new_version) {
+            log::info!("迁移至版本 {}: {}", step_version, description);
+            step(self);
         }
     }
 
@@ -538,6 +1203,68 @@ impl AppSettingsData {
         if !valid_level {
             self.clipboard_poll_metrics_log_level = default_clipboard_poll_metrics_log_level();
         }
+        if !(240..=3840).contains(&self.result_window_width) {
+            self.result_window_width = default_result_window_width();
+        }
+        if !(160..=2160).contains(&self.result_window_height) {
+            self.result_window_height = default_result_window_height();
+        }
+        if !matches!(self.log_level.as_str(), "trace" | "debug" | "info" | "warn" | "error") {
+            self.log_level = default_log_level();
+        }
+        if !is_valid_fallback_translate_url(&self.fallback_translate_url) {
+            self.fallback_translate_url.clear();
+        }
+        for config in self.provider_configs.values_mut() {
+            if validate_custom_headers(&config.custom_headers).is_err() {
+                config.custom_headers.clear();
+            }
+        }
+        if !(1..=10).contains(&self.max_concurrent_ai_requests) {
+            self.max_concurrent_ai_requests = default_max_concurrent_ai_requests();
+        }
+        if !is_valid_translation_chunk_chars(self.translation_chunk_chars) {
+            self.translation_chunk_chars = default_translation_chunk_chars();
+        }
+        if !is_valid_selection_uia_max_chars(self.selection_uia_max_chars) {
+            self.selection_uia_max_chars = default_selection_uia_max_chars();
+        }
+        if !(1..=365).contains(&self.log_retention_days) {
+            self.log_retention_days = default_log_retention_days();
+        }
+        if self.autostart_listener_delay_secs > 300 {
+            self.autostart_listener_delay_secs = default_autostart_listener_delay_secs();
+        }
+        if !is_valid_selection_capture_timing(self.selection_capture_timeout_ms, self.selection_capture_interval_ms) {
+            self.selection_capture_timeout_ms = default_selection_capture_timeout_ms();
+            self.selection_capture_interval_ms = default_selection_capture_interval_ms();
+        }
+        self.chord_shortcuts.retain(|chord| chord.validate().is_ok());
+        self.paste_method_rules.retain(|rule| rule.validate().is_ok());
+        self.translation_presets.retain(|preset| preset.validate().is_ok());
+        let mut prompt_names = HashSet::new();
+        self.custom_prompts
+            .retain(|prompt| prompt.validate().is_ok() && prompt_names.insert(prompt.name.trim().to_string()));
+        if self.type_text_key_delay_ms > 200 {
+            self.type_text_key_delay_ms = 0;
+        }
+        let mut action_ids = HashSet::new();
+        self.custom_actions
+            .retain(|action| action.validate().is_ok() && action_ids.insert(action.id.clone()));
+        if self.sentence_punctuation.validate().is_err() {
+            self.sentence_punctuation = SentencePunctuation::default();
+        }
+        if self.update_channel != "stable" && self.update_channel != "beta" {
+            self.update_channel = default_update_channel();
+        }
+        for config in self.provider_configs.values_mut() {
+            if matches!(config.timeout_secs, Some(timeout) if !(5..=600).contains(&timeout)) {
+                config.timeout_secs = None;
+            }
+            if matches!(config.price_per_million_tokens, Some(price) if !is_valid_token_price(price)) {
+                config.price_per_million_tokens = None;
+            }
+        }
 
         log::debug!("迁移后 max_items: {}", self.max_items);
     }
@@ -557,6 +1284,10 @@ impl AppSettingsData {
                 api_url: default_url,
                 model_name: default_model,
                 encrypted_api_key: String::new(),
+                timeout_secs: None,
+                custom_headers: HashMap::new(),
+                price_per_million_tokens: None,
+                extra: serde_json::Map::new(),
             };
 
             self.provider_configs.insert(self.ai_provider.clone(), config);
@@ -593,12 +1324,23 @@ pub struct ClipboardHistoryData {
     pub categories: HashMap<String, String>,
     #[serde(default)]
     pub category_list: Vec<String>,
+    /// 记录被粘贴使用的次数，键为记录内容
+    #[serde(default)]
+    pub use_counts: HashMap<String, u32>,
 }
 /// 获取设置文件路径
 pub fn get_settings_file_path() -> PathBuf {
+    get_settings_file_path_for(SettingsFormat::Json)
+}
+
+/// 获取指定格式的设置文件路径
+pub fn get_settings_file_path_for(format: SettingsFormat) -> PathBuf {
     let mut settings_dir = env::current_exe().unwrap_or_else(|_| PathBuf::from("."));
     settings_dir.pop();
-    settings_dir.push("settings.json");
+    settings_dir.push(match format {
+        SettingsFormat::Json => "settings.json",
+        SettingsFormat::Toml => "settings.toml",
+    });
     settings_dir
 }
 
@@ -673,19 +1415,143 @@ pub fn read_text_with_backup(path: &Path) -> Result<String, String> {
     }
 }
 
-/// 保存设置到文件
+/// 保存设置到文件，格式由 settings_format 决定。
+/// 写入失败或设置文件无法读取时返回错误，调用方不应再更新内存中的设置
 pub fn save_settings(settings: &AppSettingsData) -> Result<(), String> {
-    let settings_path = get_settings_file_path();
-    let json =
-        serde_json::to_string_pretty(settings).map_err(|e| format!("序列化设置失败: {}", e))?;
-    atomic_write_with_backup(&settings_path, json.as_bytes())
+    if SETTINGS_FILE_UNREADABLE.load(Ordering::Relaxed) {
+        return Err("设置文件无法读取，为避免覆盖原有设置，本次修改未保存".to_string());
+    }
+    write_settings_file(settings)?;
+    // 最新设置已写入，等待重试的旧设置不能再覆盖它
+    PENDING_SETTINGS_SAVE.lock().unwrap().take();
+    Ok(())
+}
+
+/// 加载设置时回写文件：写入失败（文件被占用、路径是目录等）不影响启动，
+/// 设置保留在内存中并在后台定时重试保存
+fn save_settings_or_retry(settings: &AppSettingsData) {
+    if SETTINGS_FILE_UNREADABLE.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Err(e) = write_settings_file(settings) {
+        log::warn!("{}，将在 {:?} 后重试保存", e, SETTINGS_SAVE_RETRY_INTERVAL);
+        schedule_settings_save_retry(settings.clone());
+    }
+}
+
+/// 序列化并写入设置文件，失败时直接返回错误
+fn write_settings_file(settings: &AppSettingsData) -> Result<(), String> {
+    let settings_path = get_settings_file_path_for(settings.settings_format);
+    if settings_path.is_dir() {
+        return Err(format!("设置文件路径是一个目录: {}", settings_path.display()));
+    }
+    let contents = match settings.settings_format {
+        SettingsFormat::Json => serde_json::to_string_pretty(settings)
+            .map_err(|e| format!("序列化设置失败: {}", e))?,
+        SettingsFormat::Toml => {
+            toml::to_string_pretty(settings).map_err(|e| format!("序列化设置失败: {}", e))?
+        }
+    };
+    atomic_write_with_backup(&settings_path, contents.as_bytes())
         .map_err(|e| format!("写入设置文件失败: {}", e))?;
     Ok(())
 }
 
+lazy_static::lazy_static! {
+    /// 等待重试保存的设置，只保留最新一份
+    static ref PENDING_SETTINGS_SAVE: Mutex<Option<AppSettingsData>> = Mutex::new(None);
+}
+
+/// 设置文件存在但无法读取时置位，此后不再写入该文件，避免用默认值覆盖用户设置
+static SETTINGS_FILE_UNREADABLE: AtomicBool = AtomicBool::new(false);
+
+/// 记录待保存的设置；没有重试线程在运行时启动一个，直到保存成功为止
+fn schedule_settings_save_retry(settings: AppSettingsData) {
+    let retry_running = PENDING_SETTINGS_SAVE.lock().unwrap().replace(settings).is_some();
+    if retry_running {
+        return;
+    }
+    spawn_named("settings-save-retry", || loop {
+        thread::sleep(SETTINGS_SAVE_RETRY_INTERVAL);
+        let mut pending = PENDING_SETTINGS_SAVE.lock().unwrap();
+        let Some(settings) = pending.as_ref() else {
+            return;
+        };
+        match write_settings_file(settings) {
+            Ok(()) => {
+                pending.take();
+                log::info!("设置文件重试保存成功");
+                return;
+            }
+            Err(e) => log::debug!("设置文件重试保存失败: {}", e),
+        }
+    });
+}
+
+/// 设置文件无法读取时使用内存中的默认设置，并在启动后提示用户
+fn fall_back_to_memory_settings(reason: &str) -> AppSettingsData {
+    log::error!("{}，本次使用默认设置", reason);
+    SETTINGS_FILE_UNREADABLE.store(true, Ordering::Relaxed);
+    *SETTINGS_RECOVERY_NOTICE.lock().unwrap() = Some(format!(
+        "{}。本次使用默认设置运行，修改不会写入该文件，请检查文件权限或是否被其他程序占用后重启",
+        reason
+    ));
+    let mut settings = AppSettingsData::default();
+    initialize_builtin_providers(&mut settings);
+    settings
+}
+
+/// 将设置切换为指定格式保存，并删除旧格式的设置文件
+pub fn convert_settings_to_format(
+    settings: &mut AppSettingsData,
+    target: SettingsFormat,
+) -> Result<(), String> {
+    let previous = settings.settings_format;
+    if previous == target {
+        return Ok(());
+    }
+    settings.settings_format = target;
+    // 转换成功后会删除旧文件，必须确认新文件已写入
+    if let Err(e) = write_settings_file(settings) {
+        settings.settings_format = previous;
+        return Err(e);
+    }
+    let old_path = get_settings_file_path_for(previous);
+    if old_path.exists() {
+        fs::remove_file(&old_path).map_err(|e| format!("删除旧设置文件失败: {}", e))?;
+    }
+    log::info!("设置文件格式已切换为 {:?}", target);
+    Ok(())
+}
+
 /// 从文件加载设置
 pub fn load_settings() -> Result<AppSettingsData, String> {
     let settings_path = get_settings_file_path();
+    let toml_settings_path = get_settings_file_path_for(SettingsFormat::Toml);
+
+    for path in [&settings_path, &toml_settings_path] {
+        if path.is_dir() {
+            return Ok(fall_back_to_memory_settings(&format!(
+                "设置文件路径是一个目录: {}",
+                path.display()
+            )));
+        }
+    }
+
+    if settings_path.exists() && toml_settings_path.exists() {
+        log::warn!("同时存在 settings.json 与 settings.toml，优先使用 settings.json");
+    }
+
+    if !settings_path.exists() && toml_settings_path.exists() {
+        let contents = match read_text_with_backup(&toml_settings_path) {
+            Ok(contents) => contents,
+            Err(e) => return Ok(fall_back_to_memory_settings(&format!("读取设置文件失败: {}", e))),
+        };
+        let mut settings: AppSettingsData =
+            toml::from_str(&contents).map_err(|e| format!("解析设置文件失败: {}", e))?;
+        settings.settings_format = SettingsFormat::Toml;
+        return finish_loaded_settings(settings);
+    }
 
     if !settings_path.exists() {
         log::info!("首次运行，创建默认设置文件");
@@ -693,28 +1559,191 @@ pub fn load_settings() -> Result<AppSettingsData, String> {
 
         initialize_builtin_providers(&mut default_settings);
 
-        let json = serde_json::to_string_pretty(&default_settings)
-            .map_err(|e| format!("序列化默认设置失败: {}", e))?;
-        atomic_write_with_backup(&settings_path, json.as_bytes())
-            .map_err(|e| format!("创建设置文件失败: {}", e))?;
+        save_settings_or_retry(&default_settings);
         return Ok(default_settings);
     }
 
-    let contents = read_text_with_backup(&settings_path).map_err(|e| format!("读取设置文件失败: {}", e))?;
-
-    let mut settings: AppSettingsData =
-        serde_json::from_str(&contents).map_err(|e| format!("解析设置文件失败: {}", e))?;
-
-    let keys_migrated = settings.migrate_legacy_api_keys();
-    let old_version = settings.version.clone();
-    settings.migrate_from_old();
-
-    if old_version != settings.version || keys_migrated {
-        log::info!("配置已更新，保存到文件");
-        save_settings(&settings)?;
+    // 文件被占用或没有读取权限时不能当作损坏处理，否则会把原文件改名或覆盖
+    let contents = match read_text_with_backup(&settings_path) {
+        Ok(contents) => contents,
+        Err(e) => return Ok(fall_back_to_memory_settings(&format!("读取设置文件失败: {}", e))),
+    };
+    let parsed = serde_json::from_str::<AppSettingsData>(&contents)
+        .map_err(|e| format!("解析设置文件失败: {}", e));
+    match parsed {
+        Ok(settings) => finish_loaded_settings(settings),
+        Err(e) => {
+            log::error!("{}，尝试修复设置文件", e);
+            let settings = recover_corrupted_settings(&settings_path)?;
+            finish_loaded_settings(settings)
+        }
     }
+}
 
-    let _provider_key = settings.ai_provider.to_string();
+lazy_static::lazy_static! {
+    /// 设置文件修复提示，启动后向用户展示一次
+    static ref SETTINGS_RECOVERY_NOTICE: Mutex<Option<String>> = Mutex::new(None);
+}
+
+/// 取出设置文件修复提示（只返回一次）
+pub fn take_settings_recovery_notice() -> Option<String> {
+    SETTINGS_RECOVERY_NOTICE.lock().unwrap().take()
+}
+
+/// 修复损坏的设置文件：先备份损坏文件；文件仍是合法 JSON 对象时逐字段恢复，
+/// 只丢弃无效字段；否则优先使用上次的备份，最后才使用默认值
+fn recover_corrupted_settings(settings_path: &Path) -> Result<AppSettingsData, String> {
+    let raw = fs::read(settings_path).unwrap_or_default();
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let corrupt_path = settings_path.with_file_name(format!("settings.json.corrupt-{}", timestamp));
+    fs::rename(settings_path, &corrupt_path).map_err(|e| format!("备份损坏的设置文件失败: {}", e))?;
+    log::warn!("已将损坏的设置文件备份到 {:?}", corrupt_path);
+
+    let contents = String::from_utf8_lossy(&raw);
+    let is_json_object = matches!(
+        serde_json::from_str::<serde_json::Value>(&contents),
+        Ok(serde_json::Value::Object(_))
+    );
+    // 文件整体损坏时，上次成功写入前的备份完整可用则直接使用
+    let backup_settings = if is_json_object {
+        None
+    } else {
+        fs::read_to_string(get_backup_file_path(settings_path))
+            .ok()
+            .and_then(|contents| serde_json::from_str::<AppSettingsData>(&contents).ok())
+    };
+    let (settings, summary) = match backup_settings {
+        Some(settings) => (settings, "已从上次的备份恢复全部设置".to_string()),
+        None => {
+            let (settings, recovered, reset) = recover_settings_fields(&contents);
+            let summary = if recovered.is_empty() {
+                "未能恢复任何设置，已使用默认设置".to_string()
+            } else if reset.is_empty() {
+                format!("已恢复 {} 项设置", recovered.len())
+            } else {
+                format!("已恢复 {} 项设置，以下无效项已重置为默认值: {}", recovered.len(), reset.join(", "))
+            };
+            log::info!("从损坏的设置文件中恢复的字段: {:?}", recovered);
+            if !reset.is_empty() {
+                log::warn!("设置文件中以下字段无效，已重置为默认值: {:?}", reset);
+            }
+            (settings, summary)
+        }
+    };
+
+    save_settings_or_retry(&settings);
+    *SETTINGS_RECOVERY_NOTICE.lock().unwrap() = Some(format!(
+        "设置文件已损坏，{}。原文件备份在 {}",
+        summary,
+        corrupt_path.display()
+    ));
+    Ok(settings)
+}
+
+/// 判断合并后的设置能否完整解析
+fn settings_parse_ok(merged: &serde_json::Map<String, serde_json::Value>) -> bool {
+    serde_json::from_value::<AppSettingsData>(serde_json::Value::Object(merged.clone())).is_ok()
+}
+
+/// 逐个尝试将损坏文件中的顶层字段合并到默认设置；对象类型的字段整体无效时再逐个子项合并，
+/// 只丢弃其中无效的子项。返回合并结果、恢复成功的字段名与被重置的字段名（子项为 "字段.子项"）
+fn recover_settings_fields(contents: &str) -> (AppSettingsData, Vec<String>, Vec<String>) {
+    let mut default_settings = AppSettingsData::default();
+    initialize_builtin_providers(&mut default_settings);
+    let Ok(serde_json::Value::Object(fields)) = serde_json::from_str::<serde_json::Value>(contents) else {
+        return (default_settings, Vec::new(), Vec::new());
+    };
+    let Ok(serde_json::Value::Object(mut merged)) = serde_json::to_value(&default_settings) else {
+        return (default_settings, Vec::new(), Vec::new());
+    };
+
+    let mut recovered = Vec::new();
+    let mut reset = Vec::new();
+    for (key, value) in fields {
+        let children = value.as_object().cloned();
+        let previous = merged.insert(key.clone(), value);
+        if settings_parse_ok(&merged) {
+            recovered.push(key);
+            continue;
+        }
+
+        if let Some(children) = children {
+            let base = match &previous {
+                Some(serde_json::Value::Object(base)) => base.clone(),
+                _ => serde_json::Map::new(),
+            };
+            merged.insert(key.clone(), serde_json::Value::Object(base));
+            if settings_parse_ok(&merged) {
+                for (child_key, child_value) in children {
+                    let Some(serde_json::Value::Object(object)) = merged.get_mut(&key) else {
+                        break;
+                    };
+                    let child_previous = object.insert(child_key.clone(), child_value);
+                    if settings_parse_ok(&merged) {
+                        continue;
+                    }
+                    if let Some(serde_json::Value::Object(object)) = merged.get_mut(&key) {
+                        match child_previous {
+                            Some(child_previous) => object.insert(child_key.clone(), child_previous),
+                            None => object.remove(&child_key),
+                        };
+                    }
+                    reset.push(format!("{}.{}", key, child_key));
+                }
+                recovered.push(key);
+                continue;
+            }
+        }
+
+        match previous {
+            Some(previous) => merged.insert(key.clone(), previous),
+            None => merged.remove(&key),
+        };
+        reset.push(key);
+    }
+
+    let settings = serde_json::from_value(serde_json::Value::Object(merged)).unwrap_or(default_settings);
+    (settings, recovered, reset)
+}
+
+/// 字段能解析但取值不合法时修复为默认值，返回被重置的字段名
+fn repair_invalid_setting_values(settings: &mut AppSettingsData) -> Vec<String> {
+    if settings.validate().is_ok() {
+        return Vec::new();
+    }
+    let before = serde_json::to_value(&*settings).ok();
+    settings.ensure_basic_config_integrity();
+    let after = serde_json::to_value(&*settings).ok();
+    match (before, after) {
+        (Some(serde_json::Value::Object(before)), Some(serde_json::Value::Object(after))) => after
+            .iter()
+            .filter(|(key, value)| before.get(*key) != Some(*value))
+            .map(|(key, _)| key.clone())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// 对读取到的设置执行迁移，必要时回写
+fn finish_loaded_settings(mut settings: AppSettingsData) -> Result<AppSettingsData, String> {
+    let flat_migrated = settings.migrate_flat_provider_fields();
+    let keys_migrated = settings.migrate_legacy_api_keys() || flat_migrated;
+    let old_version = settings.version.clone();
+    settings.migrate_from_old();
+    let reset = repair_invalid_setting_values(&mut settings);
+    if !reset.is_empty() {
+        log::warn!("以下设置项的值无效，已重置为默认值: {:?}", reset);
+    }
+
+    if old_version != settings.version || keys_migrated || !reset.is_empty() {
+        log::info!("配置已更新，保存到文件");
+        save_settings_or_retry(&settings);
+    }
+
+    let _provider_key = settings.ai_provider.to_string();
 
     Ok(settings)
 }
@@ -727,6 +1756,7 @@ pub fn save_history(history: &[String]) -> Result<(), String> {
         items: history.to_vec(),
         categories: HashMap::new(),
         category_list: Vec::new(),
+        use_counts: HashMap::new(),
     };
 
     let json = serde_json::to_string_pretty(&history_data)
@@ -745,6 +1775,7 @@ pub fn save_history_with_retry(history: &Vec<String>, max_retries: u32) -> Resul
             items: history.clone(),
             categories: HashMap::new(),
             category_list: Vec::new(),
+            use_counts: HashMap::new(),
         },
         max_retries,
     )
@@ -810,6 +1841,7 @@ pub fn load_history_data() -> Result<ClipboardHistoryData, String> {
                 items,
                 categories: HashMap::new(),
                 category_list: Vec::new(),
+                use_counts: HashMap::new(),
             }),
             Err(_) => {
                 // 如果既不是新结构也不是旧结构，可能是文件损坏，或者是一个空的 JSON 对象
@@ -836,10 +1868,15 @@ pub fn load_history_data() -> Result<ClipboardHistoryData, String> {
                             category_list = unique.into_iter().filter(|c| c != "未分类" && c != "全部").collect();
                         }
 
+                        let use_counts = obj.get("use_counts")
+                            .and_then(|v| serde_json::from_value::<HashMap<String, u32>>(v.clone()).ok())
+                            .unwrap_or_default();
+
                         return Ok(ClipboardHistoryData {
                             items,
                             categories,
                             category_list,
+                            use_counts,
                         });
                     }
                 }
@@ -884,6 +1921,10 @@ fn initialize_builtin_providers(settings: &mut AppSettingsData) {
             api_url: default_url,
             model_name: default_model,
             encrypted_api_key: String::new(),
+            timeout_secs: None,
+            custom_headers: HashMap::new(),
+            price_per_million_tokens: None,
+            extra: serde_json::Map::new(),
         };
 
         settings.provider_configs.insert(provider_key, config);
@@ -920,15 +1961,95 @@ pub struct VersionComparison {
     pub reason: String,
 }
 
+/// 最近一次去重处理的决定，用于向用户解释条目为何被替换或前移
+#[derive(Debug, Clone, Serialize)]
+pub struct DedupDecision {
+    /// 处理方式：exact_promoted / complete_promoted / replaced
+    pub action: String,
+    /// 处理原因
+    pub reason: String,
+    /// 相似度分数 (0.0 - 1.0)
+    pub similarity_score: f64,
+    /// 被替换或前移的原条目预览
+    pub affected_preview: String,
+    pub timestamp_ms: u64,
+}
+
+/// 相似度调试事件：记录添加历史时每一次版本比较的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct SimilarityDebugEvent {
+    pub old_text_preview: String,
+    pub new_text_preview: String,
+    pub similarity: f64,
+    /// 新文本的完整性判断
+    pub completeness: String,
+    pub should_replace: bool,
+    pub reason: String,
+}
+
+/// 剪贴板历史变更事件
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardHistoryEvent {
+    /// 变更后的记录数量
+    pub new_count: usize,
+    /// 变更类型：add / remove / clear
+    pub operation: String,
+}
+
+/// 剪贴板历史的增量变更，按发送顺序依次应用即可与后端历史保持一致
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum HistoryDelta {
+    /// 在 index 处插入记录
+    Added { entry: String, index: usize },
+    /// 移除 index 处的记录
+    Removed { index: usize },
+    /// 历史已清空
+    Cleared,
+    /// 变更无法用增量表示（如从快照恢复），需重新拉取完整历史
+    Reset,
+}
+
+impl HistoryDelta {
+    /// 发送给前端的事件名
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            HistoryDelta::Added { .. } => "history-added",
+            HistoryDelta::Removed { .. } => "history-removed",
+            HistoryDelta::Cleared => "history-cleared",
+            HistoryDelta::Reset => "history-reset",
+        }
+    }
+}
+
 const LCS_MAX_CHARS_EACH: usize = 1400;
 const LCS_MAX_PRODUCT: usize = 1_600_000;
+/// 超长文本近似比较时首尾各取的字符数
+const LCS_WINDOW_CHARS: usize = 600;
+/// 超长文本近似比较时首尾窗口相似度所占权重，其余为长度比例
+const LCS_WINDOW_WEIGHT: f64 = 0.7;
 const FIND_BEST_CANDIDATE_BUDGET_MS: u64 = 18;
 const FIND_BEST_CANDIDATE_BUDGET_MIN_MS: u64 = 12;
 const FIND_BEST_CANDIDATE_BUDGET_MAX_MS: u64 = 30;
 const CANDIDATE_LEN_RATIO_MIN: f64 = 0.22;
 const CANDIDATE_EDGE_MATCH_MIN: f64 = 0.06;
+/// 视为子集的最短字符数，更短的文本按独立内容保留
+const SUBSET_MIN_CHARS: usize = 8;
+/// 基于子集关系替换或前移时要求的最低相似度
+const SUBSET_REPLACE_MIN_SIMILARITY: f64 = 0.9;
 static FIND_BEST_CANDIDATE_DYNAMIC_BUDGET_MS: AtomicU64 =
     AtomicU64::new(FIND_BEST_CANDIDATE_BUDGET_MS);
+lazy_static::lazy_static! {
+    /// 当前生效的标点集合，由设置加载或修改时更新
+    static ref SENTENCE_PUNCTUATION: RwLock<SentencePunctuation> =
+        RwLock::new(SentencePunctuation::default());
+}
+
+/// 应用文本完整性判断使用的标点集合
+pub fn apply_sentence_punctuation(punctuation: &SentencePunctuation) {
+    *SENTENCE_PUNCTUATION.write().unwrap() = punctuation.clone();
+}
+
 static DEDUP_SCAN_TOTAL: AtomicU64 = AtomicU64::new(0);
 static DEDUP_SCAN_TIMEOUTS: AtomicU64 = AtomicU64::new(0);
 static DEDUP_SCAN_ELAPSED_TOTAL_MS: AtomicU64 = AtomicU64::new(0);
@@ -1017,6 +2138,31 @@ fn suffix_match_ratio(text1: &str, text2: &str, sample_len: usize) -> f64 {
     same as f64 / n as f64
 }
 
+/// 两行滚动数组计算最长公共子序列长度，内存占用 O(较短文本长度)
+fn lcs_length(chars1: &[char], chars2: &[char]) -> usize {
+    let (outer, inner) = if chars1.len() >= chars2.len() {
+        (chars1, chars2)
+    } else {
+        (chars2, chars1)
+    };
+    let mut prev = vec![0usize; inner.len() + 1];
+    let mut curr = vec![0usize; inner.len() + 1];
+    for &a in outer {
+        for (j, &b) in inner.iter().enumerate() {
+            curr[j + 1] = if a == b {
+                prev[j] + 1
+            } else {
+                prev[j + 1].max(curr[j])
+            };
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[inner.len()]
+}
+
+/// 超长文本的近似相似度：只对首尾各 LCS_WINDOW_CHARS 个字符计算 LCS，再按长度比例加权。
+/// 精度取舍：中间部分的差异不会被发现，首尾相同而中间被改写的文本会被高估；
+/// 换来的是内存与耗时只取决于窗口大小，与文本总长度无关
 fn calculate_text_similarity_fast(text1: &str, text2: &str, len1: usize, len2: usize) -> f64 {
     if text1 == text2 {
         return 1.0;
@@ -1027,9 +2173,20 @@ fn calculate_text_similarity_fast(text1: &str, text2: &str, len1: usize, len2: u
     if text1.contains(text2) || text2.contains(text1) {
         return length_ratio.max(0.85);
     }
-    let head = prefix_match_ratio(text1, text2, 256);
-    let tail = suffix_match_ratio(text1, text2, 256);
-    (head * 0.35 + tail * 0.35 + length_ratio * 0.30).min(1.0)
+
+    let chars1: Vec<char> = text1.chars().collect();
+    let chars2: Vec<char> = text2.chars().collect();
+    let head1 = &chars1[..len1.min(LCS_WINDOW_CHARS)];
+    let head2 = &chars2[..len2.min(LCS_WINDOW_CHARS)];
+    let tail1 = &chars1[len1.saturating_sub(LCS_WINDOW_CHARS)..];
+    let tail2 = &chars2[len2.saturating_sub(LCS_WINDOW_CHARS)..];
+    let window_total = head1.len().max(head2.len()) + tail1.len().max(tail2.len());
+    if window_total == 0 {
+        return 0.0;
+    }
+    let window_similarity =
+        (lcs_length(head1, head2) + lcs_length(tail1, tail2)) as f64 / window_total as f64;
+    (window_similarity * LCS_WINDOW_WEIGHT + length_ratio * (1.0 - LCS_WINDOW_WEIGHT)).clamp(0.0, 1.0)
 }
 
 /// 计算两个文本的相似度
@@ -1057,36 +2214,83 @@ pub fn calculate_text_similarity(text1: &str, text2: &str) -> f64 {
         return calculate_text_similarity_fast(text1, text2, len1, len2);
     }
 
-    // 创建DP表
-    let mut dp = vec![vec![0; len2 + 1]; len1 + 1];
-
-    // 填充DP表
-    for i in 1..=len1 {
-        for j in 1..=len2 {
-            if chars1[i - 1] == chars2[j - 1] {
-                dp[i][j] = dp[i - 1][j - 1] + 1;
-            } else {
-                dp[i][j] = dp[i - 1][j].max(dp[i][j - 1]);
-            }
-        }
-    }
-
-    // 计算相似度
-    let lcs_length = dp[len1][len2];
+    let lcs = lcs_length(&chars1, &chars2);
     let max_len = len1.max(len2);
 
     let similarity = if max_len == 0 {
         0.0
     } else {
-        lcs_length as f64 / max_len as f64
+        lcs as f64 / max_len as f64
     };
 
     log::debug!("LCS长度: {}, 最大长度: {}, 相似度: {:.4}", 
-                lcs_length, max_len, similarity);
+                lcs, max_len, similarity);
 
     similarity
 }
 
+/// 与 calculate_text_similarity 结果一致的相似度计算，但在可证明相似度低于 min_similarity 时
+/// 提前返回 None：依次检查长度比例上界、字符频次上界，并在 LCS 计算中途按剩余行数估算上界
+pub fn bounded_text_similarity(text1: &str, text2: &str, min_similarity: f64) -> Option<f64> {
+    if text1.is_empty() || text2.is_empty() {
+        return Some(calculate_text_similarity(text1, text2));
+    }
+
+    let chars1: Vec<char> = text1.chars().collect();
+    let chars2: Vec<char> = text2.chars().collect();
+    let len1 = chars1.len();
+    let len2 = chars2.len();
+    if len1 > LCS_MAX_CHARS_EACH
+        || len2 > LCS_MAX_CHARS_EACH
+        || len1.saturating_mul(len2) > LCS_MAX_PRODUCT
+    {
+        return Some(calculate_text_similarity_fast(text1, text2, len1, len2));
+    }
+
+    let max_len = len1.max(len2);
+    let below = |lcs_upper: usize| (lcs_upper as f64 / max_len as f64) < min_similarity;
+    if below(len1.min(len2)) {
+        return None;
+    }
+
+    // LCS 不会超过两段文本中各字符出现次数较小值之和
+    let mut counts: HashMap<char, i64> = HashMap::new();
+    for &ch in &chars1 {
+        *counts.entry(ch).or_insert(0) += 1;
+    }
+    let mut shared = 0usize;
+    for &ch in &chars2 {
+        if let Some(count) = counts.get_mut(&ch) {
+            if *count > 0 {
+                *count -= 1;
+                shared += 1;
+            }
+        }
+    }
+    if below(shared) {
+        return None;
+    }
+
+    let mut prev = vec![0usize; len2 + 1];
+    let mut curr = vec![0usize; len2 + 1];
+    for i in 1..=len1 {
+        for j in 1..=len2 {
+            curr[j] = if chars1[i - 1] == chars2[j - 1] {
+                prev[j - 1] + 1
+            } else {
+                prev[j].max(curr[j - 1])
+            };
+        }
+        std::mem::swap(&mut prev, &mut curr);
+        // 剩余每一行最多让 LCS 增加 1
+        if below((prev[len2] + (len1 - i)).min(len2)) {
+            return None;
+        }
+    }
+
+    Some(prev[len2] as f64 / max_len as f64)
+}
+
 fn candidate_prefilter(old_text: &str, new_text: &str) -> bool {
     if old_text.is_empty() || new_text.is_empty() {
         return true;
@@ -1189,8 +2393,10 @@ fn detect_text_completeness_with_similarity(
 
 /// 统计文本中标点符号数量
 fn count_punctuation(text: &str) -> usize {
-    let punctuation_chars = ['。', '！', '？', '.', '!', '?', '；', ';', '，', ','];
-    text.chars().filter(|&c| punctuation_chars.contains(&c)).count()
+    let punctuation = SENTENCE_PUNCTUATION.read().unwrap();
+    text.chars()
+        .filter(|&c| punctuation.counted_punctuation.contains(c))
+        .count()
 }
 
 /// 判断文本是否具有更完整的句子结构
@@ -1204,8 +2410,93 @@ fn is_more_complete_sentence(new_text: &str, old_text: &str) -> bool {
 
 /// 判断文本是否以句子结束符结尾
 fn has_sentence_endings(text: &str) -> bool {
-    let ending_chars = ['。', '！', '？', '.', '!', '?'];
-    text.trim_end().chars().last().map_or(false, |c| ending_chars.contains(&c))
+    let punctuation = SENTENCE_PUNCTUATION.read().unwrap();
+    text.trim_end().chars().last().map_or(false, |c| punctuation.sentence_endings.contains(c))
+}
+
+/// 长文本分段翻译中的一段，separator 为拼接译文时放在该段之前的分隔符
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranslationChunk {
+    pub text: String,
+    pub separator: &'static str,
+}
+
+/// 将长文本切分为不超过 max_chars 个字符的段：优先合并完整段落，
+/// 超长段落按句子结束符切分，找不到句子边界时按字符数硬切分
+pub fn split_translation_chunks(text: &str, max_chars: usize) -> Vec<TranslationChunk> {
+    let max_chars = max_chars.max(1);
+    let normalized = text.replace("\r\n", "\n");
+    let mut chunks: Vec<TranslationChunk> = Vec::new();
+    let mut current = String::new();
+    let mut current_separator = "";
+
+    for paragraph in normalized.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        let paragraph_chars = paragraph.chars().count();
+        if !current.is_empty() && current.chars().count() + 2 + paragraph_chars <= max_chars {
+            current.push_str("\n\n");
+            current.push_str(paragraph);
+            continue;
+        }
+        if !current.is_empty() {
+            chunks.push(TranslationChunk {
+                text: std::mem::take(&mut current),
+                separator: current_separator,
+            });
+        }
+        let separator = if chunks.is_empty() { "" } else { "\n\n" };
+        if paragraph_chars <= max_chars {
+            current = paragraph.to_string();
+            current_separator = separator;
+            continue;
+        }
+        for (i, piece) in split_long_paragraph(paragraph, max_chars).into_iter().enumerate() {
+            chunks.push(TranslationChunk {
+                text: piece,
+                separator: if i == 0 { separator } else { " " },
+            });
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(TranslationChunk {
+            text: current,
+            separator: current_separator,
+        });
+    }
+    chunks
+}
+
+/// 按句子边界切分超长段落，每块不超过 max_chars 个字符
+fn split_long_paragraph(paragraph: &str, max_chars: usize) -> Vec<String> {
+    let mut pieces = Vec::new();
+    let mut piece = String::new();
+    let mut piece_chars = 0;
+    // 当前块中最后一个句子结束处的字节长度与字符数
+    let mut boundary: Option<(usize, usize)> = None;
+    for c in paragraph.chars() {
+        piece.push(c);
+        piece_chars += 1;
+        if has_sentence_endings(&piece) {
+            boundary = Some((piece.len(), piece_chars));
+        }
+        if piece_chars < max_chars {
+            continue;
+        }
+        match boundary.take() {
+            Some((byte_len, char_count)) if byte_len < piece.len() => {
+                let rest = piece.split_off(byte_len);
+                pieces.push(piece.trim().to_string());
+                piece = rest;
+                piece_chars -= char_count;
+            }
+            _ => {
+                pieces.push(std::mem::take(&mut piece).trim().to_string());
+                piece_chars = 0;
+            }
+        }
+    }
+    pieces.push(piece.trim().to_string());
+    pieces.retain(|piece| !piece.is_empty());
+    pieces
 }
 
 /// 判断文本是否像是被截断的句子
@@ -1217,9 +2508,13 @@ fn is_truncated_sentence(text: &str) -> bool {
 
     // 如果文本以某些字符结尾，可能是被截断的
     let last_char = trimmed.chars().last().unwrap();
-    let truncation_indicators = ['，', ',', '、', '(', '[', '{', '"', '\''];
+    let is_truncation_indicator = SENTENCE_PUNCTUATION
+        .read()
+        .unwrap()
+        .truncation_indicators
+        .contains(last_char);
 
-    truncation_indicators.contains(&last_char) ||
+    is_truncation_indicator ||
         // 或者以常见词汇结尾但没有句子结束符
         (!has_sentence_endings(trimmed) &&
             (trimmed.ends_with("但非") ||
@@ -1230,7 +2525,7 @@ fn is_truncated_sentence(text: &str) -> bool {
 
 /// 判断new_text是否是old_text的子集（前缀或后缀）
 fn is_subset_of(new_text: &str, old_text: &str) -> bool {
-    if new_text.is_empty() || old_text.is_empty() {
+    if new_text.chars().count() < SUBSET_MIN_CHARS || old_text.is_empty() {
         return false;
     }
 
@@ -1255,6 +2550,16 @@ fn is_subset_of(new_text: &str, old_text: &str) -> bool {
 /// 比较两个版本并决定是否应该替换
 pub fn compare_versions(old_text: &str, new_text: &str, similarity_threshold: f64) -> VersionComparison {
     let similarity = calculate_text_similarity(old_text, new_text);
+    compare_versions_with_similarity(old_text, new_text, similarity_threshold, similarity)
+}
+
+/// 使用已算好的相似度比较两个版本
+fn compare_versions_with_similarity(
+    old_text: &str,
+    new_text: &str,
+    similarity_threshold: f64,
+    similarity: f64,
+) -> VersionComparison {
     let completeness = detect_text_completeness_with_similarity(new_text, old_text, similarity);
 
     log::debug!(
@@ -1282,7 +2587,7 @@ pub fn compare_versions(old_text: &str, new_text: &str, similarity_threshold: f6
                     }
                 } else {
                     // 新版本更短的情况 - 检查是否是已有完整版本的子集
-                    if is_subset_of(new_text, old_text) {
+                    if similarity >= SUBSET_REPLACE_MIN_SIMILARITY && is_subset_of(new_text, old_text) {
                         (true, "新版本是已有完整版本的子集，移动完整版本到前面".to_string())
                     } else {
                         // 即使新版本稍短，但如果它更完整（如句子结束符），也可以考虑替换
@@ -1299,7 +2604,10 @@ pub fn compare_versions(old_text: &str, new_text: &str, similarity_threshold: f6
             },
             TextCompleteness::MissingPrefix | TextCompleteness::MissingSuffix | TextCompleteness::MissingBoth => {
                 // 对于不完整版本，检查是否存在对应的完整版本
-                if new_text.len() < old_text.len() && is_subset_of(new_text, old_text) {
+                if new_text.len() < old_text.len()
+                    && similarity >= SUBSET_REPLACE_MIN_SIMILARITY
+                    && is_subset_of(new_text, old_text)
+                {
                     // 新版本是旧版本的子集，说明是找回完整版本的情况
                     (true, "找回完整版本，将完整版本移动到前面".to_string())
                 } else if new_text.len() > old_text.len() && has_sentence_endings(new_text) {
@@ -1337,6 +2645,16 @@ pub fn find_best_replacement_candidate(
     new_text: &str,
     history: &[String],
     similarity_threshold: f64,
+) -> Option<(usize, VersionComparison)> {
+    find_best_replacement_candidate_observed(new_text, history, similarity_threshold, &mut |_, _| {})
+}
+
+/// 与 find_best_replacement_candidate 相同，每完成一次版本比较都会回调 observer（旧文本, 比较结果）
+pub fn find_best_replacement_candidate_observed(
+    new_text: &str,
+    history: &[String],
+    similarity_threshold: f64,
+    observer: &mut dyn FnMut(&str, &VersionComparison),
 ) -> Option<(usize, VersionComparison)> {
     let mut best_candidate: Option<(usize, VersionComparison)> = None;
     let started = Instant::now();
@@ -1365,7 +2683,13 @@ pub fn find_best_replacement_candidate(
             continue;
         }
         scanned += 1;
-        let comparison = compare_versions(old_text, new_text, similarity_threshold);
+        // 相似度达不到阈值时不可能替换，跳过完整的版本比较
+        let Some(similarity) = bounded_text_similarity(old_text, new_text, similarity_threshold) else {
+            continue;
+        };
+        let comparison =
+            compare_versions_with_similarity(old_text, new_text, similarity_threshold, similarity);
+        observer(old_text, &comparison);
 
         if comparison.should_replace {
             match &best_candidate {
@@ -1410,3 +2734,834 @@ pub fn find_best_replacement_candidate(
 
     best_candidate
 }
+
+/// 剪贴板内容类型
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentType {
+    PlainText,
+    Code,
+    Url,
+    Email,
+    PhoneNumber,
+    Json,
+    Csv,
+    Numeric,
+    CjkText,
+    Unknown,
+}
+
+lazy_static::lazy_static! {
+    static ref PHONE_PATTERNS: Vec<regex::Regex> = [
+        r"^\+?[\d\s\-\(\)]{10,}$",
+        r"^\d{3}-\d{3}-\d{4}$",
+        r"^\d{3}\.\d{3}\.\d{4}$",
+        r"^\(\d{3}\)\s*\d{3}-\d{4}$",
+        r"^\+1\s*\d{3}\s*\d{3}\s*\d{4}$",
+    ]
+    .iter()
+    .filter_map(|pattern| regex::Regex::new(pattern).ok())
+    .collect();
+    static ref EMAIL_PATTERN: Option<regex::Regex> =
+        regex::Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$").ok();
+    static ref URL_PATTERN: Option<regex::Regex> =
+        regex::Regex::new(r"^https?://[^\s/$.?#].\S*$|^www\.\S+$").ok();
+    static ref NUMERIC_PATTERN: Option<regex::Regex> =
+        regex::Regex::new(r"^[+-]?(\d{1,3}(,\d{3})+|\d+)(\.\d+)?%?$").ok();
+    static ref SECRET_PATTERNS: Vec<(&'static str, regex::Regex)> = [
+        ("AWS Access Key", r"AKIA[0-9A-Z]{16}"),
+        ("GitHub Token", r"ghp_[A-Za-z0-9]{36}"),
+        ("JWT", r"eyJ[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+\.[A-Za-z0-9_-]+"),
+    ]
+    .iter()
+    .filter_map(|(name, pattern)| regex::Regex::new(pattern).ok().map(|re| (*name, re)))
+    .collect();
+    static ref HIGH_ENTROPY_TOKEN_PATTERN: Option<regex::Regex> =
+        regex::Regex::new(r"[A-Za-z0-9+/_-]{41,}={0,2}").ok();
+}
+
+/// 高熵字符串的最低香农熵（比特/字符）
+const SECRET_MIN_ENTROPY: f64 = 4.2;
+
+/// 计算字符串的香农熵（比特/字符）
+fn shannon_entropy(text: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    let mut total = 0usize;
+    for ch in text.chars() {
+        *counts.entry(ch).or_insert(0) += 1;
+        total += 1;
+    }
+    if total == 0 {
+        return 0.0;
+    }
+    counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// 比较两个密钥或令牌，耗时与不匹配的位置无关
+pub fn constant_time_eq(provided: &str, expected: &str) -> bool {
+    let provided = provided.as_bytes();
+    let expected = expected.as_bytes();
+    provided.len() == expected.len()
+        && provided
+            .iter()
+            .zip(expected)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// 检测文本中是否包含疑似密钥，返回命中的规则名称
+pub fn detect_secret_pattern(text: &str) -> Option<&'static str> {
+    if let Some((name, _)) = SECRET_PATTERNS.iter().find(|(_, re)| re.is_match(text)) {
+        return Some(name);
+    }
+    let re = HIGH_ENTROPY_TOKEN_PATTERN.as_ref()?;
+    re.find_iter(text)
+        .map(|m| m.as_str())
+        .any(|token| {
+            token.chars().any(|c| c.is_ascii_digit())
+                && token.chars().any(|c| c.is_ascii_alphabetic())
+                && shannon_entropy(token) >= SECRET_MIN_ENTROPY
+        })
+        .then_some("High Entropy String")
+}
+
+/// 将文本中疑似密钥的片段替换为掩码，其余内容保持原样
+pub fn mask_secrets(text: &str) -> String {
+    let mut masked = text.to_string();
+    for (name, re) in SECRET_PATTERNS.iter() {
+        masked = re
+            .replace_all(&masked, format!("<redacted:{}>", name).as_str())
+            .into_owned();
+    }
+    if let Some(re) = HIGH_ENTROPY_TOKEN_PATTERN.as_ref() {
+        masked = re
+            .replace_all(&masked, |caps: &regex::Captures| {
+                let token = &caps[0];
+                let looks_secret = token.chars().any(|c| c.is_ascii_digit())
+                    && token.chars().any(|c| c.is_ascii_alphabetic())
+                    && shannon_entropy(token) >= SECRET_MIN_ENTROPY;
+                if looks_secret {
+                    "<redacted>".to_string()
+                } else {
+                    token.to_string()
+                }
+            })
+            .into_owned();
+    }
+    masked
+}
+
+/// 检查是否为电话号码
+pub fn is_phone_number(text: &str) -> bool {
+    PHONE_PATTERNS.iter().any(|regex| regex.is_match(text))
+}
+
+/// 检查是否为邮箱地址
+pub fn is_email_address(text: &str) -> bool {
+    EMAIL_PATTERN.as_ref().is_some_and(|regex| regex.is_match(text))
+}
+
+/// 检查是否为URL
+pub fn is_url(text: &str) -> bool {
+    URL_PATTERN.as_ref().is_some_and(|regex| regex.is_match(text))
+}
+
+/// 检查是否为JSON对象或数组
+pub fn is_json(text: &str) -> bool {
+    let trimmed = text.trim();
+    let looks_like_json = (trimmed.starts_with('{') && trimmed.ends_with('}'))
+        || (trimmed.starts_with('[') && trimmed.ends_with(']'));
+    looks_like_json && serde_json::from_str::<serde_json::Value>(trimmed).is_ok()
+}
+
+fn is_numeric(text: &str) -> bool {
+    NUMERIC_PATTERN.as_ref().is_some_and(|regex| regex.is_match(text))
+}
+
+/// 检查是否为CSV/TSV表格：至少两行且每行分隔符数量一致
+fn is_csv(text: &str) -> bool {
+    let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).collect();
+    if lines.len() < 2 {
+        return false;
+    }
+    [',', '\t'].iter().any(|&separator| {
+        let first = lines[0].matches(separator).count();
+        first > 0 && lines.iter().all(|line| line.matches(separator).count() == first)
+    })
+}
+
+/// 检查是否像代码片段
+pub fn is_code_snippet(text: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "fn ", "let ", "const ", "var ", "function ", "def ", "class ", "import ", "return ",
+        "pub ", "#include", "public ", "private ", "struct ", "=> ", "select ", "#!/",
+    ];
+    let lines: Vec<&str> = text.lines().filter(|line| !line.trim().is_empty()).collect();
+    if lines.is_empty() {
+        return false;
+    }
+    let code_lines = lines
+        .iter()
+        .filter(|line| {
+            let trimmed = line.trim();
+            let lower = trimmed.to_lowercase();
+            trimmed.ends_with(';')
+                || trimmed.ends_with('{')
+                || trimmed == "}"
+                || trimmed.starts_with("//")
+                || KEYWORDS.iter().any(|keyword| lower.starts_with(keyword))
+        })
+        .count();
+    if lines.len() == 1 {
+        return code_lines == 1 && text.chars().any(|c| matches!(c, '(' | '{' | '=' | ';'));
+    }
+    code_lines * 10 >= lines.len() * 3
+}
+
+fn is_cjk_char(c: char) -> bool {
+    matches!(c as u32,
+        0x4E00..=0x9FFF | 0x3400..=0x4DBF | 0x3040..=0x30FF | 0xAC00..=0xD7AF | 0xF900..=0xFAFF)
+}
+
+/// 计算文本与搜索词的匹配等级（忽略大小写）：0 完全匹配，1 前缀匹配，2 包含，None 不匹配
+pub fn rank_text_match(text: &str, query_lower: &str) -> Option<u8> {
+    let text_lower = text.to_lowercase();
+    let trimmed = text_lower.trim();
+    if trimmed == query_lower {
+        Some(0)
+    } else if trimmed.starts_with(query_lower) {
+        Some(1)
+    } else if text_lower.contains(query_lower) {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// 搜索结果条目，offsets 为匹配片段的 [start, end) 字符下标（非字节下标）
+#[derive(Debug, Clone, Serialize)]
+pub struct TextSearchMatch {
+    pub index: usize,
+    pub text: String,
+    pub offsets: Vec<(usize, usize)>,
+}
+
+/// 单个条目最多返回的匹配位置数
+const MAX_MATCH_OFFSETS_PER_ENTRY: usize = 20;
+
+/// 查找搜索词在文本中不重叠出现的位置（忽略大小写），返回字符下标
+pub fn find_match_offsets(text: &str, query: &str, max_offsets: usize) -> Vec<(usize, usize)> {
+    // 逐字符转小写且只取首个字符，保证下标与原文字符一一对应
+    let fold = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let query: Vec<char> = query.trim().chars().map(fold).collect();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let text: Vec<char> = text.chars().map(fold).collect();
+    let mut offsets = Vec::new();
+    let mut start = 0;
+    while start + query.len() <= text.len() && offsets.len() < max_offsets {
+        if text[start..start + query.len()] == query[..] {
+            offsets.push((start, start + query.len()));
+            start += query.len();
+        } else {
+            start += 1;
+        }
+    }
+    offsets
+}
+
+/// 为排序后的搜索结果附加匹配位置
+pub fn with_match_offsets(matches: Vec<(usize, String)>, query: &str) -> Vec<TextSearchMatch> {
+    matches
+        .into_iter()
+        .map(|(index, text)| {
+            let offsets = find_match_offsets(&text, query, MAX_MATCH_OFFSETS_PER_ENTRY);
+            TextSearchMatch { index, text, offsets }
+        })
+        .collect()
+}
+
+/// 按匹配等级排序并截取结果，同等级保持原有顺序
+pub fn rank_text_matches<'a, I>(items: I, query: &str, limit: usize) -> Vec<(usize, String)>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    let query_lower = query.trim().to_lowercase();
+    if query_lower.is_empty() || limit == 0 {
+        return Vec::new();
+    }
+    let mut matches: Vec<(u8, usize, &String)> = items
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, item)| rank_text_match(item, &query_lower).map(|rank| (rank, index, item)))
+        .collect();
+    matches.sort_by_key(|(rank, index, _)| (*rank, *index));
+    matches
+        .into_iter()
+        .take(limit)
+        .map(|(_, index, item)| (index, item.clone()))
+        .collect()
+}
+
+/// 模糊搜索结果条目，score 为 0~1 的三元组相似度
+#[derive(Debug, Clone, Serialize)]
+pub struct FuzzyMatch {
+    pub index: usize,
+    pub score: f64,
+    pub preview: String,
+}
+
+/// 模糊搜索结果预览的最大字符数
+const FUZZY_MATCH_PREVIEW_CHARS: usize = 150;
+
+/// 提取小写字符三元组，首尾补空格使短词也能产生三元组
+fn char_trigrams(text: &str) -> HashSet<[char; 3]> {
+    let chars: Vec<char> = std::iter::once(' ')
+        .chain(text.trim().to_lowercase().chars())
+        .chain(std::iter::once(' '))
+        .collect();
+    chars.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+/// 查询的三元组在文本中出现的比例，记错个别字词时仍有较高得分
+pub fn trigram_similarity(query_trigrams: &HashSet<[char; 3]>, text: &str) -> f64 {
+    if query_trigrams.is_empty() {
+        return 0.0;
+    }
+    let text_trigrams = char_trigrams(text);
+    let shared = query_trigrams.intersection(&text_trigrams).count();
+    shared as f64 / query_trigrams.len() as f64
+}
+
+/// 按三元组相似度模糊匹配，返回相似度不低于 min_score 的 (索引, 相似度, 内容)，按相似度从高到低排序
+pub fn rank_fuzzy_matches<'a, I>(items: I, query: &str, min_score: f64) -> Vec<(usize, f64, String)>
+where
+    I: IntoIterator<Item = &'a String>,
+{
+    if query.trim().is_empty() {
+        return Vec::new();
+    }
+    let query_trigrams = char_trigrams(query);
+    let mut matches: Vec<(usize, f64, String)> = items
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, item)| {
+            let score = trigram_similarity(&query_trigrams, item);
+            (score > 0.0 && score >= min_score).then(|| (index, score, item.clone()))
+        })
+        .collect();
+    matches.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    matches
+}
+
+/// 将模糊匹配结果转换为带预览的条目
+pub fn to_fuzzy_matches(matches: Vec<(usize, f64, String)>) -> Vec<FuzzyMatch> {
+    matches
+        .into_iter()
+        .map(|(index, score, text)| FuzzyMatch {
+            index,
+            score,
+            preview: text.chars().take(FUZZY_MATCH_PREVIEW_CHARS).collect(),
+        })
+        .collect()
+}
+
+/// 判断剪贴板内容类型
+pub fn classify_content(text: &str) -> ContentType {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return ContentType::Unknown;
+    }
+    if is_json(trimmed) {
+        return ContentType::Json;
+    }
+    if is_url(trimmed) {
+        return ContentType::Url;
+    }
+    if is_email_address(trimmed) {
+        return ContentType::Email;
+    }
+    if is_phone_number(trimmed) {
+        return ContentType::PhoneNumber;
+    }
+    if is_numeric(trimmed) {
+        return ContentType::Numeric;
+    }
+    if is_csv(trimmed) {
+        return ContentType::Csv;
+    }
+    if is_code_snippet(trimmed) {
+        return ContentType::Code;
+    }
+
+    let letters = trimmed.chars().filter(|c| c.is_alphanumeric()).count();
+    let cjk = trimmed.chars().filter(|&c| is_cjk_char(c)).count();
+    if letters > 0 && cjk * 2 >= letters {
+        return ContentType::CjkText;
+    }
+    ContentType::PlainText
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 去重判断中常见的文本对：截断、补全、改写、无关内容与超长文本
+    fn similarity_corpus() -> Vec<(String, String)> {
+        let long_text = "The quick brown fox jumps over the lazy dog. ".repeat(40);
+        let mut long_edited = long_text.clone();
+        long_edited.replace_range(900..903, "cat");
+        [
+            ("The quick brown fox jumps over the lazy dog.", "The quick brown fox jumps over the lazy dog"),
+            ("hello world", "hello world!"),
+            ("今天天气很好，我们去公园散步吧。", "今天天气很好，我们去公园散步"),
+            ("error", "An unexpected error occurred while saving the file."),
+            ("fn main() { println!(\"hi\"); }", "fn main() { println!(\"hello\"); }"),
+            ("https://example.com/path?a=1", "https://example.com/path?a=2"),
+            ("completely different text about cats", "another sentence regarding dogs and birds"),
+            ("abc", "xyz"),
+            ("", "non-empty"),
+            ("", ""),
+        ]
+        .into_iter()
+        .map(|(a, b)| (a.to_string(), b.to_string()))
+        .chain([(long_text.clone(), long_edited), (long_text.clone(), long_text[..1200].to_string())])
+        .collect()
+    }
+
+    /// 提前退出优化前的候选扫描：对每条通过预筛选的记录做完整的版本比较
+    fn reference_best_candidate(new_text: &str, history: &[String], threshold: f64) -> Option<usize> {
+        let mut best: Option<(usize, VersionComparison)> = None;
+        for (index, old_text) in history.iter().enumerate() {
+            if !candidate_prefilter(old_text, new_text) {
+                continue;
+            }
+            let comparison = compare_versions(old_text, new_text, threshold);
+            if !comparison.should_replace {
+                continue;
+            }
+            let better = match &best {
+                None => true,
+                Some((_, existing)) => {
+                    comparison.similarity_score > existing.similarity_score
+                        || (comparison.similarity_score == existing.similarity_score
+                            && (matches!(comparison.new_completeness, TextCompleteness::Complete)
+                                || comparison.reason.contains("更完整")))
+                }
+            };
+            if better {
+                best = Some((index, comparison));
+            }
+        }
+        best.map(|(index, _)| index)
+    }
+
+    #[test]
+    fn bounded_similarity_matches_full_similarity_above_threshold() {
+        for (a, b) in similarity_corpus() {
+            let full = calculate_text_similarity(&a, &b);
+            for threshold in [0.0, 0.3, 0.5, 0.7, 0.85, 0.9, 1.0] {
+                match bounded_text_similarity(&a, &b, threshold) {
+                    Some(similarity) => assert_eq!(similarity, full, "{:?} / {:?}", a, b),
+                    None => assert!(full < threshold, "{:?} / {:?} 在阈值 {} 下被误判", a, b, threshold),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn best_replacement_candidate_unchanged_by_early_exit() {
+        // 超长文本的比较较慢，可能触发扫描耗时预算而提前结束，这里只用短文本
+        let history: Vec<String> = similarity_corpus()
+            .into_iter()
+            .flat_map(|(a, b)| [a, b])
+            .filter(|text| text.chars().count() <= LCS_WINDOW_CHARS)
+            .collect();
+        for new_text in &history {
+            for threshold in [0.5, 0.7, 0.85, 0.95] {
+                let expected = reference_best_candidate(new_text, &history, threshold);
+                let actual = find_best_replacement_candidate(new_text, &history, threshold).map(|(index, _)| index);
+                assert_eq!(actual, expected, "{:?} 在阈值 {} 下的替换候选不一致", new_text, threshold);
+            }
+        }
+    }
+
+    /// 确定性的伪随机文本（xorshift），字符取自 alphabet，长度在 0..=max_len 之间
+    fn pseudo_random_texts(seed: u64, count: usize, max_len: usize, alphabet: &[char]) -> Vec<String> {
+        let mut state = seed.max(1);
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        (0..count)
+            .map(|_| {
+                let len = (next() % (max_len as u64 + 1)) as usize;
+                (0..len).map(|_| alphabet[(next() % alphabet.len() as u64) as usize]).collect()
+            })
+            .collect()
+    }
+
+    /// 完整二维表的 LCS，作为滚动数组实现的对照
+    fn full_table_lcs(a: &[char], b: &[char]) -> usize {
+        let mut table = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+        for i in 1..=a.len() {
+            for j in 1..=b.len() {
+                table[i][j] = if a[i - 1] == b[j - 1] {
+                    table[i - 1][j - 1] + 1
+                } else {
+                    table[i - 1][j].max(table[i][j - 1])
+                };
+            }
+        }
+        table[a.len()][b.len()]
+    }
+
+    #[test]
+    fn lcs_length_equals_full_table_for_short_inputs() {
+        let texts = pseudo_random_texts(7, 60, 40, &['a', 'b', 'c', 'd', '你', '好']);
+        for a in &texts {
+            for b in &texts {
+                let a: Vec<char> = a.chars().collect();
+                let b: Vec<char> = b.chars().collect();
+                assert_eq!(lcs_length(&a, &b), full_table_lcs(&a, &b));
+            }
+        }
+    }
+
+    #[test]
+    fn text_similarity_equals_exact_lcs_ratio_for_short_inputs() {
+        let texts = pseudo_random_texts(11, 40, 60, &['x', 'y', 'z', ' ', '.']);
+        for a in texts.iter().filter(|text| !text.is_empty()) {
+            for b in texts.iter().filter(|text| !text.is_empty()) {
+                let chars_a: Vec<char> = a.chars().collect();
+                let chars_b: Vec<char> = b.chars().collect();
+                let expected =
+                    full_table_lcs(&chars_a, &chars_b) as f64 / chars_a.len().max(chars_b.len()) as f64;
+                assert_eq!(calculate_text_similarity(a, b), expected, "{:?} / {:?}", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn text_similarity_stays_within_unit_interval() {
+        // 包含超过 LCS_MAX_CHARS_EACH 的文本，覆盖首尾窗口近似比较
+        let mut texts = pseudo_random_texts(23, 12, 40, &['a', 'b', 'c']);
+        texts.extend(pseudo_random_texts(29, 8, LCS_MAX_CHARS_EACH * 3, &['a', 'b', 'c', 'd']));
+        texts.push(String::new());
+        for a in &texts {
+            for b in &texts {
+                let similarity = calculate_text_similarity(a, b);
+                assert!((0.0..=1.0).contains(&similarity), "相似度越界: {}", similarity);
+            }
+        }
+    }
+
+    #[test]
+    fn short_substrings_do_not_replace_longer_entries() {
+        let cases = [
+            ("An unexpected error occurred while saving the file.", "error"),
+            ("error: failed to compile the crate", "error"),
+            ("error:", "error"),
+            ("Request failed with status 500", "failed"),
+            ("value was null after the migration", "null"),
+            ("TODO: handle the empty case", "TODO"),
+            ("保存文件时发生错误，请稍后重试", "错误"),
+        ];
+        // 阈值为 0 时同样不替换，确认拦截来自长度下限与子集相似度要求，而不只是相似度阈值
+        for threshold in [0.0, 0.5, 0.8] {
+            for (old_text, new_text) in cases {
+                let comparison = compare_versions(old_text, new_text, threshold);
+                assert!(
+                    !comparison.should_replace,
+                    "{:?} 不应替换 {:?}（阈值 {}，原因: {}）",
+                    new_text,
+                    old_text,
+                    threshold,
+                    comparison.reason
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn long_truncated_prefix_still_restores_complete_entry() {
+        let old_text = "The deployment finished without any errors today.";
+        let new_text = "The deployment finished without any errors tod";
+        assert!(compare_versions(old_text, new_text, 0.8).should_replace);
+    }
+
+    fn planned_step_descriptions(stored_version: &str, current_version: &Version) -> Option<Vec<&'static str>> {
+        match migration_plan(stored_version, current_version) {
+            MigrationPlan::Steps(old_version) => Some(
+                pending_migration_steps(&old_version, current_version)
+                    .into_iter()
+                    .map(|(_, description, _)| description)
+                    .collect(),
+            ),
+            MigrationPlan::UpToDate => Some(Vec::new()),
+            MigrationPlan::Generic => None,
+        }
+    }
+
+    #[test]
+    fn parses_legacy_and_partial_versions() {
+        assert_eq!(parse_migration_version("0.2"), Some(Version::new(0, 2, 0)));
+        assert_eq!(parse_migration_version("3"), Some(Version::new(0, 3, 0)));
+        assert_eq!(parse_migration_version("v0.3.1-beta.2"), Some(Version::new(0, 3, 1)));
+        assert_eq!(parse_migration_version(""), None);
+        assert_eq!(parse_migration_version("not-a-version"), None);
+    }
+
+    #[test]
+    fn migration_steps_follow_stored_version() {
+        let current = Version::new(0, 3, 0);
+        assert_eq!(
+            planned_step_descriptions("0.2", &current),
+            Some(vec!["初始化AI提供商配置"])
+        );
+        assert_eq!(planned_step_descriptions("3", &current), Some(Vec::new()));
+        assert_eq!(
+            planned_step_descriptions("", &current),
+            Some(vec!["确保基础配置完整性", "初始化AI提供商配置"])
+        );
+        // 无法解析的版本号不按步骤迁移，改为执行通用迁移
+        assert_eq!(planned_step_descriptions("not-a-version", &current), None);
+        assert_eq!(migration_plan("not-a-version", &current), MigrationPlan::Generic);
+    }
+
+    #[test]
+    fn unknown_settings_keys_survive_round_trip() {
+        // 较新版本写入的设置文件，包含当前版本不认识的顶层字段与提供商字段
+        let newer = serde_json::json!({
+            "version": "9.0.0",
+            "max_items": 80,
+            "future_top_level": {"enabled": true, "level": 3},
+            "ai_provider": "deepseek",
+            "provider_configs": {
+                "deepseek": {
+                    "api_url": "https://api.deepseek.com",
+                    "model_name": "deepseek-chat",
+                    "future_provider_field": ["a", "b"]
+                }
+            }
+        });
+        let settings: AppSettingsData = serde_json::from_value(newer.clone()).unwrap();
+        assert_eq!(settings.max_items, 80);
+        assert!(settings.extra.contains_key("future_top_level"));
+        assert!(settings.provider_configs["deepseek"].extra.contains_key("future_provider_field"));
+
+        let written = serde_json::to_value(&settings).unwrap();
+        assert_eq!(written["future_top_level"], newer["future_top_level"]);
+        assert_eq!(
+            written["provider_configs"]["deepseek"]["future_provider_field"],
+            newer["provider_configs"]["deepseek"]["future_provider_field"]
+        );
+
+        let reread: AppSettingsData = serde_json::from_value(written.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&reread).unwrap(), written);
+    }
+
+    #[test]
+    fn flat_legacy_settings_migrate_into_provider_configs() {
+        // 旧版扁平结构：AI 配置直接位于顶层
+        let flat = r#"{
+            "version": "0.1.0",
+            "max_items": 50,
+            "ai_provider": "deepseek",
+            "ai_api_url": "https://api.deepseek.com",
+            "ai_model_name": "deepseek-chat",
+            "ai_api_key": "ZW5jcnlwdGVkLWtleQ=="
+        }"#;
+        let mut settings: AppSettingsData = serde_json::from_str(flat).unwrap();
+        assert!(settings.migrate_flat_provider_fields());
+        let config = &settings.provider_configs["deepseek"];
+        assert_eq!(config.api_url, "https://api.deepseek.com");
+        assert_eq!(config.model_name, "deepseek-chat");
+        assert_eq!(config.encrypted_api_key, "ZW5jcnlwdGVkLWtleQ==");
+
+        // 回写后不再包含扁平字段，再次读取时无需迁移且配置不变
+        let written = serde_json::to_value(&settings).unwrap();
+        for legacy_key in ["ai_api_url", "ai_model_name", "ai_api_key"] {
+            assert!(written.get(legacy_key).is_none(), "{} 不应写回", legacy_key);
+        }
+        let mut reread: AppSettingsData = serde_json::from_value(written).unwrap();
+        assert!(!reread.migrate_flat_provider_fields());
+        assert_eq!(reread.provider_configs["deepseek"].encrypted_api_key, "ZW5jcnlwdGVkLWtleQ==");
+    }
+
+    #[test]
+    fn flat_legacy_key_alias_and_missing_provider_use_default_provider() {
+        let flat = r#"{"ai_api_url": "https://api.deepseek.com", "encrypted_api_key": "b2xkLWtleQ=="}"#;
+        let mut settings: AppSettingsData = serde_json::from_str(flat).unwrap();
+        settings.ai_provider.clear();
+        assert!(settings.migrate_flat_provider_fields());
+        assert_eq!(settings.ai_provider, "deepseek");
+        assert_eq!(settings.provider_configs["deepseek"].encrypted_api_key, "b2xkLWtleQ==");
+    }
+
+    #[test]
+    fn provider_schema_settings_round_trip_unchanged() {
+        // 当前的提供商结构：每个提供商一份独立配置
+        let provider_based = serde_json::json!({
+            "version": "0.2.9",
+            "ai_provider": "openai",
+            "provider_configs": {
+                "openai": {
+                    "api_url": "https://api.openai.com/v1",
+                    "model_name": "gpt-4o-mini",
+                    "encrypted_api_key": "cHJvdmlkZXIta2V5"
+                }
+            }
+        });
+        let mut settings: AppSettingsData = serde_json::from_value(provider_based).unwrap();
+        let before = serde_json::to_value(&settings).unwrap();
+        assert!(!settings.migrate_flat_provider_fields());
+        assert_eq!(settings.provider_configs["openai"].encrypted_api_key, "cHJvdmlkZXIta2V5");
+        assert_eq!(serde_json::to_value(&settings).unwrap(), before);
+    }
+
+    #[test]
+    fn char_trigrams_pad_trim_and_lowercase() {
+        let expected: HashSet<[char; 3]> = [[' ', 'a', 'b'], ['a', 'b', ' ']].into_iter().collect();
+        assert_eq!(char_trigrams("ab"), expected);
+        assert_eq!(char_trigrams("  AB \n"), expected);
+        assert_eq!(char_trigrams("x").len(), 1);
+        assert!(char_trigrams("").is_empty());
+        assert!(char_trigrams("   ").is_empty());
+
+        let cjk: HashSet<[char; 3]> = [[' ', '中', '文'], ['中', '文', ' ']].into_iter().collect();
+        assert_eq!(char_trigrams("中文"), cjk);
+    }
+
+    #[test]
+    fn trigram_similarity_is_share_of_query_trigrams() {
+        let query = char_trigrams("hello world");
+        assert_eq!(query.len(), 11);
+        assert_eq!(trigram_similarity(&query, "hello world"), 1.0);
+        assert_eq!(trigram_similarity(&query, "HELLO WORLD"), 1.0);
+        assert_eq!(trigram_similarity(&query, "hello wurld"), 8.0 / 11.0);
+        assert_eq!(trigram_similarity(&query, "say hello"), 5.0 / 11.0);
+        assert_eq!(trigram_similarity(&query, "goodbye"), 0.0);
+        // 只看查询的三元组覆盖率，文本中多出的内容不扣分
+        assert_eq!(trigram_similarity(&query, "well, hello world again"), 1.0);
+        assert_eq!(trigram_similarity(&HashSet::new(), "hello"), 0.0);
+    }
+
+    #[test]
+    fn rank_fuzzy_matches_filters_by_min_score_and_sorts() {
+        let items: Vec<String> = ["say hello", "goodbye", "hello wurld", "hello world"]
+            .into_iter()
+            .map(String::from)
+            .collect();
+        let ranked: Vec<(usize, String)> = rank_fuzzy_matches(&items, "hello world", 0.4)
+            .into_iter()
+            .map(|(index, _, text)| (index, text))
+            .collect();
+        assert_eq!(
+            ranked,
+            vec![
+                (3, "hello world".to_string()),
+                (2, "hello wurld".to_string()),
+                (0, "say hello".to_string()),
+            ]
+        );
+        // 下限为 0 时仍排除完全不相关的内容
+        assert_eq!(rank_fuzzy_matches(&items, "hello world", 0.0).len(), 3);
+        assert!(rank_fuzzy_matches(&items, "  ", 0.0).is_empty());
+    }
+
+    #[test]
+    fn recover_settings_resets_only_corrupt_scalar_field() {
+        let contents = r#"{
+            "max_items": "很多",
+            "clipboard_bottom_offset": 120,
+            "hot_key": "Alt+K"
+        }"#;
+        let (settings, recovered, reset) = recover_settings_fields(contents);
+        assert_eq!(reset, vec!["max_items"]);
+        assert!(recovered.contains(&"clipboard_bottom_offset".to_string()));
+        assert!(recovered.contains(&"hot_key".to_string()));
+        assert_eq!(settings.max_items, AppSettingsData::default().max_items);
+        assert_eq!(settings.clipboard_bottom_offset, 120);
+        assert_eq!(settings.hot_key, "Alt+K");
+    }
+
+    #[test]
+    fn recover_settings_drops_only_corrupt_provider_entry() {
+        let contents = r#"{
+            "provider_configs": {
+                "deepseek": {"api_url": "https://gateway.example.com", "model_name": "deepseek-chat"},
+                "broken": {"api_url": 5}
+            }
+        }"#;
+        let (settings, recovered, reset) = recover_settings_fields(contents);
+        assert_eq!(recovered, vec!["provider_configs"]);
+        assert_eq!(reset, vec!["provider_configs.broken"]);
+        assert_eq!(settings.provider_configs["deepseek"].api_url, "https://gateway.example.com");
+        assert!(!settings.provider_configs.contains_key("broken"));
+        // 未出现在文件中的内置提供商保留默认配置
+        assert!(settings.provider_configs.contains_key("qwen"));
+    }
+
+    #[test]
+    fn recover_settings_falls_back_to_defaults_for_unparseable_file() {
+        let (settings, recovered, reset) = recover_settings_fields("{ not json");
+        assert!(recovered.is_empty());
+        assert!(reset.is_empty());
+        assert_eq!(settings.max_items, AppSettingsData::default().max_items);
+    }
+
+    #[test]
+    fn repair_resets_out_of_range_values_only() {
+        let mut settings = AppSettingsData::default();
+        assert!(repair_invalid_setting_values(&mut settings).is_empty());
+
+        settings.max_items = 5000;
+        settings.clipboard_bottom_offset = -3;
+        settings.hot_key = "Alt+K".to_string();
+        let mut reset = repair_invalid_setting_values(&mut settings);
+        reset.sort();
+        assert_eq!(reset, vec!["clipboard_bottom_offset", "max_items"]);
+        assert_eq!(settings.max_items, 50);
+        assert_eq!(settings.clipboard_bottom_offset, default_clipboard_bottom_offset());
+        assert_eq!(settings.hot_key, "Alt+K");
+        assert!(settings.validate().is_ok());
+    }
+
+    #[test]
+    fn match_offsets_are_char_indices_for_cjk_and_emoji() {
+        let text = "剪贴板历史，剪贴板";
+        let offsets = find_match_offsets(text, "剪贴板", 10);
+        assert_eq!(offsets, vec![(0, 3), (6, 9)]);
+        let chars: Vec<char> = text.chars().collect();
+        for (start, end) in offsets {
+            assert_eq!(chars[start..end].iter().collect::<String>(), "剪贴板");
+        }
+
+        assert_eq!(find_match_offsets("👍 ok 👍OK", " ok ", 10), vec![(2, 4), (6, 8)]);
+        assert_eq!(find_match_offsets("a👍👍b", "👍", 10), vec![(1, 2), (2, 3)]);
+        // 小写后变为多个字符的字母只取首个字符，下标仍与原文对应
+        assert_eq!(find_match_offsets("İstanbul 伊斯坦布尔", "istanbul", 10), vec![(0, 8)]);
+        assert_eq!(find_match_offsets("aaaa", "aa", 10), vec![(0, 2), (2, 4)]);
+        assert!(find_match_offsets("剪贴板", "  ", 10).is_empty());
+    }
+
+    #[test]
+    fn match_offsets_are_capped_per_entry() {
+        let text = "中文ab".repeat(50);
+        let matches = with_match_offsets(vec![(0, text)], "AB");
+        let offsets = &matches[0].offsets;
+        assert_eq!(offsets.len(), MAX_MATCH_OFFSETS_PER_ENTRY);
+        assert_eq!(offsets[0], (2, 4));
+        assert_eq!(offsets[MAX_MATCH_OFFSETS_PER_ENTRY - 1], (78, 80));
+    }
+}
@@ -39,6 +39,7 @@ export function useAIProvider(form) {
                 form.apiUrl = config.api_url || ''
                 form.modelName = config.model_name || ''
                 form.apiKey = config.api_key || ''
+                form.timeoutSecs = config.timeout_secs || 120
             } else {
                 const configResult = await AISettingsService.getProviderConfig(provider)
                 if (Array.isArray(configResult) && configResult.length >= 2) {
@@ -64,6 +65,7 @@ export function useAIProvider(form) {
             form.apiUrl = config.api_url || ''
             form.modelName = config.model_name || ''
             form.apiKey = config.api_key || ''
+            form.timeoutSecs = config.timeout_secs || 120
         } else {
             form.apiUrl = ''
             form.modelName = ''
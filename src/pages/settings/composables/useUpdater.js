@@ -2,12 +2,20 @@ import {ref} from 'vue'
 import {ElMessageBox} from 'element-plus'
 import {check} from '@tauri-apps/plugin-updater'
 import {relaunch} from '@tauri-apps/plugin-process'
+import {UpdateService} from '../../../services/ipc'
+
+const formatBytes = (bytes) => {
+    if (bytes >= 1024 * 1024) return `${(bytes / 1024 / 1024).toFixed(1)} MB`
+    if (bytes >= 1024) return `${(bytes / 1024).toFixed(1)} KB`
+    return `${bytes} B`
+}
 
 export function useUpdater(currentVersion) {
     const checkingUpdate = ref(false)
     const updateStatus = ref(null)
     const updateProgress = ref(0)
     const showUpdateProgress = ref(false)
+    const progressText = ref('')
 
     const checkUpdate = async () => {
         checkingUpdate.value = true
@@ -36,19 +44,32 @@ export function useUpdater(currentVersion) {
 
                     let contentLength = 0
                     let downloaded = 0
+                    let halfwayNotified = false
 
                     await update.downloadAndInstall((event) => {
                         if (event.event === 'Started') {
                             contentLength = event.data.contentLength || 0
                             downloaded = 0
                             updateProgress.value = 0
+                            progressText.value = '正在更新... 0%'
+                            UpdateService.notifyMilestone(`开始下载新版本 ${update.version}`)
                         } else if (event.event === 'Progress') {
                             downloaded += event.data.chunkLength
                             if (contentLength > 0) {
                                 updateProgress.value = Math.round((downloaded / contentLength) * 100)
+                                progressText.value = `正在更新... ${updateProgress.value}%`
+                                if (!halfwayNotified && updateProgress.value >= 50) {
+                                    halfwayNotified = true
+                                    UpdateService.notifyMilestone(`新版本 ${update.version} 已下载 50%`)
+                                }
+                            } else {
+                                // 服务器未返回总大小时显示已下载字节数，避免一直显示 0%
+                                progressText.value = `正在更新... 已下载 ${formatBytes(downloaded)}`
                             }
                         } else if (event.event === 'Finished') {
                             updateProgress.value = 100
+                            progressText.value = '正在更新... 100%'
+                            UpdateService.notifyMilestone(`新版本 ${update.version} 下载完成`)
                         }
                     })
 
@@ -88,6 +109,7 @@ export function useUpdater(currentVersion) {
         updateStatus,
         updateProgress,
         showUpdateProgress,
+        progressText,
         checkUpdate
     }
 }
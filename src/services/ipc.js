@@ -1,6 +1,6 @@
 import {invoke} from '@tauri-apps/api/core';
 
-const buildSelectAndFillRequest = (index, opId) => ({index, opId});
+const buildSelectAndFillRequest = (index, opId, pasteMethod) => ({index, opId, pasteMethod});
 const buildSelectAndFillImageRequest = (index, opId) => ({index, opId});
 const buildStreamTranslateRequest = (text, sourceLanguage, targetLanguage, opId, sceneHint) => ({
     text,
@@ -9,11 +9,12 @@ const buildStreamTranslateRequest = (text, sourceLanguage, targetLanguage, opId,
     opId,
     sceneHint
 });
-const buildStreamExplainRequest = (text, targetLanguage, opId, sceneHint) => ({
+const buildStreamExplainRequest = (text, targetLanguage, opId, sceneHint, depth) => ({
     text,
     targetLanguage,
     opId,
-    sceneHint
+    sceneHint,
+    depth
 });
 
 /**
@@ -23,7 +24,17 @@ const buildStreamExplainRequest = (text, targetLanguage, opId, sceneHint) => ({
 export const IPC_COMMANDS = {
     // 剪贴板管理
     GET_CLIPBOARD_HISTORY: 'get_clipboard_history',
+    GET_CLIPBOARD_HISTORY_FULL: 'get_clipboard_history_full',
+    GET_CLIPBOARD_ENTRY: 'get_clipboard_entry',
+    ADVANCE_SELECTION_INDEX: 'advance_selection_index',
+    RETREAT_SELECTION_INDEX: 'retreat_selection_index',
+    SET_SELECTED_INDEX: 'set_selected_index',
+    MOVE_SELECTION: 'move_selection',
+    CONFIRM_SELECTION: 'confirm_selection',
+    FILTER_HISTORY: 'filter_history',
+    SET_SELECTION_WRAP_AROUND: 'set_selection_wrap_around',
     REMOVE_CLIPBOARD_ITEM: 'remove_clipboard_item',
+    REMOVE_CLIPBOARD_ITEMS: 'remove_clipboard_items',
     SELECT_AND_FILL: 'select_and_fill',
     GET_IMAGE_CLIPBOARD_HISTORY: 'get_image_clipboard_history',
     REMOVE_IMAGE_CLIPBOARD_ITEM: 'remove_image_clipboard_item',
@@ -33,6 +44,10 @@ export const IPC_COMMANDS = {
     CLOSE_IMAGE_PREVIEW_WINDOW: 'close_image_preview_window',
     COPY_TEXT: 'copy_text',
     COPY_AND_PASTE_TEXT: 'copy_and_paste_text',
+    RUN_CUSTOM_ACTION: 'run_custom_action',
+    COPY_RESULT_PAIR: 'copy_result_pair',
+    COPY_RESULT_ONLY: 'copy_result_only',
+    NAVIGATE_RESULT: 'navigate_result',
 
     // 分类管理
     SET_ITEM_CATEGORY: 'set_item_category',
@@ -46,6 +61,10 @@ export const IPC_COMMANDS = {
     GET_CLIPBOARD_BOTTOM_OFFSET: 'get_clipboard_bottom_offset',
     PREVIEW_CLIPBOARD_BOTTOM_OFFSET: 'preview_clipboard_bottom_offset',
     SAVE_CLIPBOARD_BOTTOM_OFFSET: 'save_clipboard_bottom_offset',
+    GET_RESULT_WINDOW_DEFAULT_SIZE: 'get_result_window_default_size',
+    SAVE_RESULT_WINDOW_DEFAULT_SIZE: 'save_result_window_default_size',
+    RESET_RESULT_WINDOW_GEOMETRY: 'reset_result_window_geometry',
+    TOGGLE_RESULT_WINDOW_ALWAYS_ON_TOP: 'toggle_result_window_always_on_top',
     WINDOW_BLUR: 'window_blur',
     IMAGE_WINDOW_BLUR: 'image_window_blur',
     SELECTION_TOOLBAR_BLUR: 'selection_toolbar_blur',
@@ -54,8 +73,64 @@ export const IPC_COMMANDS = {
     GET_AI_SETTINGS: 'get_ai_settings',
     SAVE_APP_SETTINGS: 'save_app_settings',
     TEST_AI_CONNECTION: 'test_ai_connection',
+    TEST_CURRENT_PROVIDER: 'test_current_provider',
     GET_PROVIDER_CONFIG: 'get_provider_config',
     REMOVE_AI_PROVIDER: 'remove_ai_provider',
+    CONVERT_SETTINGS_FORMAT: 'convert_settings_format',
+    SAVE_UPDATE_SETTINGS: 'save_update_settings',
+    SET_DEDUP_MODE: 'set_dedup_mode',
+    SET_DEBUG_SHOW_SIMILARITY: 'set_debug_show_similarity',
+    SET_LOG_LEVEL: 'set_log_level',
+    SET_CHORD_SHORTCUTS: 'set_chord_shortcuts',
+    SET_SENTENCE_PUNCTUATION: 'set_sentence_punctuation',
+    SET_WINDOW_POSITION_STRATEGY: 'set_window_position_strategy',
+    SET_PASTE_METHOD: 'set_paste_method',
+    SET_POST_PASTE_ACTION: 'set_post_paste_action',
+    SET_PASTE_METHOD_RULES: 'set_paste_method_rules',
+    SET_TRANSLATION_PRESETS: 'set_translation_presets',
+    SET_AI_DRY_RUN: 'set_ai_dry_run',
+    ESTIMATE_INPUT_TOKENS: 'estimate_input_tokens',
+    SET_PROVIDER_TOKEN_PRICE: 'set_provider_token_price',
+    SET_LARGE_INPUT_CONFIRM_TOKENS: 'set_large_input_confirm_tokens',
+    SET_CUSTOM_PROMPTS: 'set_custom_prompts',
+    EXPORT_CUSTOM_PROMPTS: 'export_custom_prompts',
+    IMPORT_CUSTOM_PROMPTS: 'import_custom_prompts',
+    SET_TYPE_TEXT_KEY_DELAY: 'set_type_text_key_delay',
+    SET_PASTE_TRANSFORMS: 'set_paste_transforms',
+    SET_DO_NOT_DISTURB: 'set_do_not_disturb',
+    SET_TEXT_SELECTION_ENABLED: 'set_text_selection_enabled',
+    CHECK_SELECTION_VALID: 'check_selection_valid',
+    SET_AUTO_DND_FULLSCREEN: 'set_auto_dnd_fullscreen',
+    SET_DND_HOT_KEY: 'set_dnd_hot_key',
+    SET_GLOBAL_SEARCH_HOT_KEY: 'set_global_search_hot_key',
+    ADD_SUPPRESSED_CONTENT_TYPE: 'add_suppressed_content_type',
+    REMOVE_SUPPRESSED_CONTENT_TYPE: 'remove_suppressed_content_type',
+    SET_FALLBACK_TRANSLATE_URL: 'set_fallback_translate_url',
+    SET_LOG_RETENTION_DAYS: 'set_log_retention_days',
+    SET_AUTOSTART_LISTENER_DELAY: 'set_autostart_listener_delay',
+    SET_SELECTION_CAPTURE_TIMING: 'set_selection_capture_timing',
+    SET_TRANSLATION_CHUNK_CHARS: 'set_translation_chunk_chars',
+    SET_SELECTION_UIA_MAX_CHARS: 'set_selection_uia_max_chars',
+    SET_HTTP_API_ENABLED: 'set_http_api_enabled',
+    REGENERATE_HTTP_API_TOKEN: 'regenerate_http_api_token',
+    GET_HTTP_API_STATUS: 'get_http_api_status',
+    SET_LOG_AI_TRANSCRIPTS: 'set_log_ai_transcripts',
+    SET_MAX_CONCURRENT_AI_REQUESTS: 'set_max_concurrent_ai_requests',
+    GET_ACTIVE_AI_REQUEST_COUNT: 'get_active_ai_request_count',
+    NOTIFY_UPDATE_MILESTONE: 'notify_update_milestone',
+    SCAN_CLIPBOARD_FOR_SECRETS: 'scan_clipboard_for_secrets',
+    REMOVE_SECRETS_FROM_HISTORY: 'remove_secrets_from_history',
+    GET_LAST_DEDUP_DECISION: 'get_last_dedup_decision',
+    CREATE_CLIPBOARD_SNAPSHOT: 'create_clipboard_snapshot',
+    LIST_CLIPBOARD_SNAPSHOTS: 'list_clipboard_snapshots',
+    RESTORE_CLIPBOARD_SNAPSHOT: 'restore_clipboard_snapshot',
+    DELETE_CLIPBOARD_SNAPSHOT: 'delete_clipboard_snapshot',
+    MATERIALIZE_HISTORY_ITEM: 'materialize_history_item',
+    MATERIALIZE_IMAGE_HISTORY_ITEM: 'materialize_image_history_item',
+    FUZZY_SEARCH_CLIPBOARD: 'fuzzy_search_clipboard',
+    SUBSCRIBE_CLIPBOARD_HISTORY_UPDATES: 'subscribe_clipboard_history_updates',
+    UNSUBSCRIBE_CLIPBOARD_HISTORY_UPDATES: 'unsubscribe_clipboard_history_updates',
+    GET_DIAGNOSTICS_BUNDLE: 'get_diagnostics_bundle',
     GET_ALL_CONFIGURED_PROVIDERS: 'get_all_configured_providers',
     GET_POLL_METRICS_HISTORY: 'get_poll_metrics_history',
     GET_POLL_METRICS_MINUTE_AGGREGATES: 'get_poll_metrics_minute_aggregates',
@@ -63,9 +138,26 @@ export const IPC_COMMANDS = {
     EXPORT_POLL_METRICS_TO_FILE: 'export_poll_metrics_to_file',
     GET_TEXT_DEDUP_METRICS: 'get_text_dedup_metrics',
 
+    // 划词记录
+    GET_SELECTION_HISTORY: 'get_selection_history',
+    CLEAR_SELECTION_HISTORY: 'clear_selection_history',
+    SET_RECORD_SELECTION_HISTORY: 'set_record_selection_history',
+    REOPEN_SELECTION_HISTORY_ITEM: 'reopen_selection_history_item',
+    CAPTURE_SELECTION_NOW: 'capture_selection_now',
+    GLOBAL_SEARCH: 'global_search',
+
     // AI 功能
     STREAM_TRANSLATE_TEXT: 'stream_translate_text',
+    STREAM_TRANSLATE_MULTI_TARGET: 'stream_translate_multi_target',
     STREAM_EXPLAIN_TEXT: 'stream_explain_text',
+    TRANSLATE_CLIPBOARD: 'translate_clipboard',
+    OPEN_SELECTION_CHAT: 'open_selection_chat',
+    SEND_CHAT_MESSAGE: 'send_chat_message',
+    RETRY_LAST_REQUEST: 'retry_last_request',
+    GET_LAST_EXPLAIN_DEPTH: 'get_last_explain_depth',
+    TRANSLATE_CLIPBOARD_HISTORY_ITEM_INPLACE: 'translate_clipboard_history_item_inplace',
+    UNDO_CLIPBOARD_ITEM_TRANSLATION: 'undo_clipboard_item_translation',
+    TRANSFORM_CLIPBOARD_ITEM: 'transform_clipboard_item',
 };
 
 /**
@@ -73,10 +165,65 @@ export const IPC_COMMANDS = {
  */
 export const ClipboardService = {
     /**
-     * 获取剪贴板历史记录
-     * @returns {Promise<{history: string[], categories: Object, category_list: string[]}>}
+     * 获取剪贴板历史记录，history 中为每条记录的前 150 个字符预览；categories 与 history 一一对应，未分类为 null
+     * @param {'recent'|'frequency'} [sortBy] 展示顺序，frequency 按粘贴次数排序，结果见 display_order
+     * @returns {Promise<{history: string[], categories: (string|null)[], category_list: string[], use_counts: number[], display_order: number[]}>}
+     */
+    getHistory: (sortBy) => invoke(IPC_COMMANDS.GET_CLIPBOARD_HISTORY, {sortBy}),
+
+    /**
+     * 获取完整的剪贴板历史内容
+     * @returns {Promise<string[]>}
+     */
+    getHistoryFull: () => invoke(IPC_COMMANDS.GET_CLIPBOARD_HISTORY_FULL),
+
+    /**
+     * 获取单条历史记录
+     * @param {number} index
+     * @returns {Promise<string|null>} 索引越界时为 null
+     */
+    getEntry: (index) => invoke(IPC_COMMANDS.GET_CLIPBOARD_ENTRY, {index}),
+
+    /**
+     * 选中下一条 / 上一条记录，不改变记录顺序；到两端时是否循环由设置决定
+     * @returns {Promise<number>} 新的选中索引
+     */
+    advanceSelection: () => invoke(IPC_COMMANDS.ADVANCE_SELECTION_INDEX),
+    retreatSelection: () => invoke(IPC_COMMANDS.RETREAT_SELECTION_INDEX),
+
+    /**
+     * 设置后端保存的选中位置，超出范围时取最后一条；会发送 selection-changed 事件
+     * @param {number} index
+     * @returns {Promise<number>} 实际选中索引
+     */
+    setSelectedIndex: (index) => invoke(IPC_COMMANDS.SET_SELECTED_INDEX, {index}),
+
+    /**
+     * 将选中位置移动 delta 位；会发送 selection-changed 事件
+     * @param {number} delta
+     * @returns {Promise<number>} 新的选中索引
      */
-    getHistory: () => invoke(IPC_COMMANDS.GET_CLIPBOARD_HISTORY),
+    moveSelection: (delta) => invoke(IPC_COMMANDS.MOVE_SELECTION, {delta}),
+
+    /**
+     * 回填当前选中的记录
+     * @returns {Promise<string>}
+     */
+    confirmSelection: () => invoke(IPC_COMMANDS.CONFIRM_SELECTION),
+
+    /**
+     * 输入过滤历史记录并选中最匹配的一条，之后的移动与回填只在匹配结果间进行；query 为空时恢复全部记录
+     * @param {string} query
+     * @returns {Promise<Array<{index: number, score: number, preview: string}>>} index 为历史中的实际位置
+     */
+    filterHistory: (query) => invoke(IPC_COMMANDS.FILTER_HISTORY, {query}),
+
+    /**
+     * 设置选中位置移动到两端时是否循环
+     * @param {boolean} enabled
+     * @returns {Promise<void>}
+     */
+    setSelectionWrapAround: (enabled) => invoke(IPC_COMMANDS.SET_SELECTION_WRAP_AROUND, {enabled}),
 
     /**
      * 删除剪贴板条目
@@ -85,13 +232,132 @@ export const ClipboardService = {
      */
     removeItem: (index) => invoke(IPC_COMMANDS.REMOVE_CLIPBOARD_ITEM, {index}),
 
+    /**
+     * 批量删除剪贴板条目
+     * @param {number[]} indices
+     * @returns {Promise<number>} 删除数量
+     */
+    removeItems: (indices) => invoke(IPC_COMMANDS.REMOVE_CLIPBOARD_ITEMS, {indices}),
+
+    /**
+     * 扫描历史记录中疑似误复制的密钥
+     * @returns {Promise<Array<{index: number, content_preview: string, pattern_matched: string}>>}
+     */
+    scanForSecrets: () => invoke(IPC_COMMANDS.SCAN_CLIPBOARD_FOR_SECRETS),
+
+    /**
+     * 移除所有疑似密钥条目
+     * @returns {Promise<number>} 移除的条目数量
+     */
+    removeSecrets: () => invoke(IPC_COMMANDS.REMOVE_SECRETS_FROM_HISTORY),
+
+    /**
+     * 获取最近一次去重处理的决定（为何某条记录被替换或前移）
+     * @returns {Promise<Object|null>}
+     */
+    getLastDedupDecision: () => invoke(IPC_COMMANDS.GET_LAST_DEDUP_DECISION),
+
+    /**
+     * 将当前历史记录保存为快照
+     * @param {string} label
+     * @returns {Promise<string>} 快照标识
+     */
+    createSnapshot: (label) => invoke(IPC_COMMANDS.CREATE_CLIPBOARD_SNAPSHOT, {label}),
+
+    /**
+     * 列出所有历史快照（从新到旧）
+     * @returns {Promise<Array<{id: string, label: string, created_ms: number, item_count: number, size_bytes: number}>>}
+     */
+    listSnapshots: () => invoke(IPC_COMMANDS.LIST_CLIPBOARD_SNAPSHOTS),
+
+    /**
+     * 用快照替换当前历史记录
+     * @param {string} id
+     * @returns {Promise<void>}
+     */
+    restoreSnapshot: (id) => invoke(IPC_COMMANDS.RESTORE_CLIPBOARD_SNAPSHOT, {id}),
+
+    /**
+     * 删除历史快照
+     * @param {string} id
+     * @returns {Promise<void>}
+     */
+    deleteSnapshot: (id) => invoke(IPC_COMMANDS.DELETE_CLIPBOARD_SNAPSHOT, {id}),
+
+    /**
+     * 将条目写入临时文件，用于开始原生拖拽；文件 24 小时后清理
+     * @param {number} index
+     * @param {string} [extension] 默认 txt
+     * @returns {Promise<string>} 文件路径
+     */
+    materializeItem: (index, extension = 'txt') =>
+        invoke(IPC_COMMANDS.MATERIALIZE_HISTORY_ITEM, {index, extension}),
+
+    /**
+     * 按三元组相似度模糊搜索历史记录，结果按相似度从高到低排序
+     * @param {string} query
+     * @param {number} [threshold] 0~1 的相似度下限
+     * @returns {Promise<Array<{index: number, score: number, preview: string}>>}
+     */
+    fuzzySearch: (query, threshold = 0.5) =>
+        invoke(IPC_COMMANDS.FUZZY_SEARCH_CLIPBOARD, {query, threshold}),
+
+    /**
+     * 订阅历史记录变更，之后会收到 clipboard-count-changed 事件；
+     * 剪贴板窗口可见时还会收到 history-added {entry, index}、history-removed {index}、
+     * history-cleared、history-reset（需重新调用 getHistory）增量事件，按顺序应用即可
+     * @returns {Promise<void>}
+     */
+    subscribeHistoryUpdates: () => invoke(IPC_COMMANDS.SUBSCRIBE_CLIPBOARD_HISTORY_UPDATES),
+
+    /**
+     * 取消订阅历史记录变更
+     * @returns {Promise<void>}
+     */
+    unsubscribeHistoryUpdates: () => invoke(IPC_COMMANDS.UNSUBSCRIBE_CLIPBOARD_HISTORY_UPDATES),
+
+    /**
+     * 翻译指定条目并原地替换
+     * @param {number} index
+     * @param {string} targetLanguage
+     * @returns {Promise<void>}
+     */
+    translateItemInPlace: (index, targetLanguage) =>
+        invoke(IPC_COMMANDS.TRANSLATE_CLIPBOARD_HISTORY_ITEM_INPLACE, {index, targetLanguage}),
+
+    /**
+     * 撤销条目的原地翻译
+     * @param {number} index
+     * @returns {Promise<void>}
+     */
+    undoItemTranslation: (index) => invoke(IPC_COMMANDS.UNDO_CLIPBOARD_ITEM_TRANSLATION, {index}),
+
+    /**
+     * 合并条目段落内的硬换行，再按宽度重新换行并原地替换（可用 undoItemTranslation 撤销）
+     * @param {number} index
+     * @param {number} [width=80] 每行最多字符数
+     * @returns {Promise<string>} 转换后的内容
+     */
+    rewrapItem: (index, width = 80) =>
+        invoke(IPC_COMMANDS.TRANSFORM_CLIPBOARD_ITEM, {index, op: {re_wrap_at_width: width}}),
+
+    /**
+     * 将条目中的换行全部替换为空格并原地替换（可用 undoItemTranslation 撤销）
+     * @param {number} index
+     * @returns {Promise<string>} 转换后的内容
+     */
+    stripItemLineBreaks: (index) =>
+        invoke(IPC_COMMANDS.TRANSFORM_CLIPBOARD_ITEM, {index, op: 'strip_line_breaks'}),
+
     /**
      * 选择并填充内容
      * @param {number} index
+     * @param {number} [opId]
+     * @param {'clipboard_paste' | 'type_text' | 'copy_only'} [pasteMethod] 本次使用的回填方式，省略时按窗口规则与设置决定
      * @returns {Promise<void>}
      */
-    selectAndFill: (index, opId) =>
-        invoke(IPC_COMMANDS.SELECT_AND_FILL, {request: buildSelectAndFillRequest(index, opId)}),
+    selectAndFill: (index, opId, pasteMethod) =>
+        invoke(IPC_COMMANDS.SELECT_AND_FILL, {request: buildSelectAndFillRequest(index, opId, pasteMethod)}),
 
     /**
      * 复制文本到剪贴板
@@ -100,6 +366,25 @@ export const ClipboardService = {
      */
     copyText: (text) => invoke(IPC_COMMANDS.COPY_TEXT, {text}),
     copyAndPasteText: (text) => invoke(IPC_COMMANDS.COPY_AND_PASTE_TEXT, {text}),
+    copyResultPair: (windowType) => invoke(IPC_COMMANDS.COPY_RESULT_PAIR, {windowType}),
+    copyResultOnly: (windowType) => invoke(IPC_COMMANDS.COPY_RESULT_ONLY, {windowType}),
+
+    /**
+     * 在结果窗口中前后翻看最近的结果；result-update 事件的 index/total 为当前位置与总数
+     * @param {string} windowType
+     * @param {number} direction 负数向前，正数向后
+     * @returns {Promise<{index: number, total: number, original: string}>}
+     */
+    navigateResult: (windowType, direction) =>
+        invoke(IPC_COMMANDS.NAVIGATE_RESULT, {windowType, direction}),
+
+    /**
+     * 执行设置文件中定义的自定义动作，输出按动作配置送往剪贴板、结果窗口或直接粘贴
+     * @param {string} actionId
+     * @param {string} text
+     * @returns {Promise<{stdout: string, stderr: string, truncated: boolean}>}
+     */
+    runCustomAction: (actionId, text) => invoke(IPC_COMMANDS.RUN_CUSTOM_ACTION, {actionId, text}),
 };
 
 export const ImageClipboardService = {
@@ -110,6 +395,7 @@ export const ImageClipboardService = {
     warmupItem: (index) => invoke(IPC_COMMANDS.WARMUP_IMAGE_CLIPBOARD_ITEM, {index}),
     openPreviewWindow: (index) => invoke(IPC_COMMANDS.OPEN_IMAGE_PREVIEW_WINDOW, {index}),
     closePreviewWindow: () => invoke(IPC_COMMANDS.CLOSE_IMAGE_PREVIEW_WINDOW),
+    materializeItem: (index) => invoke(IPC_COMMANDS.MATERIALIZE_IMAGE_HISTORY_ITEM, {index}),
 };
 
 /**
@@ -183,6 +469,60 @@ export const WindowService = {
     selectionToolbarBlur: () => invoke(IPC_COMMANDS.SELECTION_TOOLBAR_BLUR),
 };
 
+/**
+ * 划词记录相关的 IPC 服务
+ */
+export const SelectionHistoryService = {
+    /**
+     * 获取划词记录（最新在前）
+     * @returns {Promise<Array<{text: string, timestamp_ms: number}>>}
+     */
+    getHistory: () => invoke(IPC_COMMANDS.GET_SELECTION_HISTORY),
+    clearHistory: () => invoke(IPC_COMMANDS.CLEAR_SELECTION_HISTORY),
+    setRecordEnabled: (enabled) => invoke(IPC_COMMANDS.SET_RECORD_SELECTION_HISTORY, {enabled}),
+    /**
+     * 用划词记录中的文本重新打开划词工具栏，以便再次翻译或解释
+     * @param {number} index 划词记录索引
+     * @returns {Promise<string>} 重新打开的文本
+     */
+    reopen: (index) => invoke(IPC_COMMANDS.REOPEN_SELECTION_HISTORY_ITEM, {index}),
+    /**
+     * 立即捕获前台应用的选中文本
+     * @param {number} [delayMs] 延迟毫秒数，便于切换到目标窗口
+     * @returns {Promise<string>}
+     */
+    captureNow: (delayMs) => invoke(IPC_COMMANDS.CAPTURE_SELECTION_NOW, {delayMs}),
+    /**
+     * 同时搜索剪贴板历史与划词记录
+     * @param {string} query
+     * @param {number} [maxResultsPerSource]
+     * 每条结果的 offsets 为匹配片段的 [start, end) 字符（码点）下标，高亮时配合 Array.from(text) 使用
+     * @returns {Promise<{clipboard_matches: Array<{index: number, text: string, offsets: Array<[number, number]>}>, selection_matches: Array<{index: number, text: string, offsets: Array<[number, number]>}>}>}
+     */
+    globalSearch: (query, maxResultsPerSource) =>
+        invoke(IPC_COMMANDS.GLOBAL_SEARCH, {query, maxResultsPerSource}),
+};
+
+/**
+ * 更新相关的 IPC 服务
+ */
+export const UpdateService = {
+    /**
+     * 发送更新下载阶段的系统通知（开始 / 50% / 完成）
+     * @param {string} message
+     * @returns {Promise<void>}
+     */
+    notifyMilestone: (message) =>
+        invoke(IPC_COMMANDS.NOTIFY_UPDATE_MILESTONE, {message}).catch(() => {}),
+
+    /**
+     * 生成诊断包（最近日志与脱敏设置）
+     * @param {string} path 保存路径
+     * @returns {Promise<string>}
+     */
+    getDiagnosticsBundle: (path) => invoke(IPC_COMMANDS.GET_DIAGNOSTICS_BUNDLE, {path}),
+};
+
 /**
  * AI 设置相关的 IPC 服务
  */
@@ -214,6 +554,9 @@ export const AISettingsService = {
      * @param {number} params.clipboardPollReportIntervalSecs
      * @param {boolean} params.clipboardPollMetricsEnabled
      * @param {string} params.clipboardPollMetricsLogLevel
+     * @param {number} [params.aiRequestTimeoutSecs]
+     * @param {string} [params.logLevel]
+     * @param {Object<string, string>} [params.customHeaders] 当前提供商的自定义请求头，省略时保持不变
      * @returns {Promise<void>}
      */
     saveSettings: ({
@@ -234,7 +577,10 @@ export const AISettingsService = {
                        clipboardPollMaxIntervalMs,
                        clipboardPollReportIntervalSecs,
                        clipboardPollMetricsEnabled,
-                       clipboardPollMetricsLogLevel
+                       clipboardPollMetricsLogLevel,
+                       aiRequestTimeoutSecs,
+                       logLevel,
+                       customHeaders
                    }) =>
         invoke(IPC_COMMANDS.SAVE_APP_SETTINGS, {
             maxItems,
@@ -254,7 +600,10 @@ export const AISettingsService = {
             clipboardPollMaxIntervalMs,
             clipboardPollReportIntervalSecs,
             clipboardPollMetricsEnabled,
-            clipboardPollMetricsLogLevel
+            clipboardPollMetricsLogLevel,
+            aiRequestTimeoutSecs,
+            logLevel,
+            customHeaders
         }),
 
     /**
@@ -263,10 +612,17 @@ export const AISettingsService = {
      * @param {string} params.aiApiUrl
      * @param {string} params.aiModelName
      * @param {string} params.aiApiKey
+     * @param {Object<string, string>} [params.customHeaders]
      * @returns {Promise<string>}
      */
-    testConnection: ({aiApiUrl, aiModelName, aiApiKey}) =>
-        invoke(IPC_COMMANDS.TEST_AI_CONNECTION, {aiApiUrl, aiModelName, aiApiKey}),
+    testConnection: ({aiApiUrl, aiModelName, aiApiKey, customHeaders}) =>
+        invoke(IPC_COMMANDS.TEST_AI_CONNECTION, {aiApiUrl, aiModelName, aiApiKey, customHeaders}),
+
+    /**
+     * 使用已保存的当前提供商配置测试连接
+     * @returns {Promise<{provider: string, model: string, latency_ms: number}>}
+     */
+    testCurrentProvider: () => invoke(IPC_COMMANDS.TEST_CURRENT_PROVIDER),
 
     /**
      * 获取提供商配置
@@ -287,6 +643,293 @@ export const AISettingsService = {
      * @returns {Promise<Array<[string, string]>>}
      */
     getAllConfiguredProviders: () => invoke(IPC_COMMANDS.GET_ALL_CONFIGURED_PROVIDERS),
+
+    /**
+     * 切换设置文件格式
+     * @param {'json' | 'toml'} target
+     * @returns {Promise<void>}
+     */
+    convertSettingsFormat: (target) => invoke(IPC_COMMANDS.CONVERT_SETTINGS_FORMAT, {target}),
+
+    /**
+     * 设置剪贴板历史去重方式
+     * @param {'none' | 'exact' | 'fuzzy'} mode
+     * @returns {Promise<void>}
+     */
+    setDedupMode: (mode) => invoke(IPC_COMMANDS.SET_DEDUP_MODE, {mode}),
+
+    /**
+     * 开启或关闭相似度调试，开启后后端会发送 similarity-debug 事件
+     * @param {boolean} enabled
+     * @returns {Promise<void>}
+     */
+    setDebugShowSimilarity: (enabled) => invoke(IPC_COMMANDS.SET_DEBUG_SHOW_SIMILARITY, {enabled}),
+
+    /**
+     * 设置日志级别（立即生效）
+     * @param {'trace' | 'debug' | 'info' | 'warn' | 'error'} level
+     * @returns {Promise<void>}
+     */
+    setLogLevel: (level) => invoke(IPC_COMMANDS.SET_LOG_LEVEL, {level}),
+    setLogRetentionDays: (days) => invoke(IPC_COMMANDS.SET_LOG_RETENTION_DAYS, {days}),
+
+    /**
+     * 设置开机自启后延迟启动剪贴板与划词监听的秒数（0-300）
+     * @param {number} seconds
+     * @returns {Promise<void>}
+     */
+    setAutostartListenerDelay: (seconds) => invoke(IPC_COMMANDS.SET_AUTOSTART_LISTENER_DELAY, {seconds}),
+
+    /**
+     * 设置划词捕获等待剪贴板更新的最长时间（100-3000 毫秒）与轮询间隔（5-200 毫秒）
+     * @param {number} timeoutMs
+     * @param {number} intervalMs
+     * @returns {Promise<void>}
+     */
+    setSelectionCaptureTiming: (timeoutMs, intervalMs) =>
+        invoke(IPC_COMMANDS.SET_SELECTION_CAPTURE_TIMING, {timeoutMs, intervalMs}),
+
+    /**
+     * 设置长文本分段翻译的每段最大字符数（0 表示不分段，否则 200-20000）
+     * @param {number} chars
+     * @returns {Promise<void>}
+     */
+    setTranslationChunkChars: (chars) => invoke(IPC_COMMANDS.SET_TRANSLATION_CHUNK_CHARS, {chars}),
+
+    /**
+     * 设置 Windows 下通过 UI Automation 读取选中文本的最大字符数
+     * @param {number} chars 100-100000
+     * @returns {Promise<void>}
+     */
+    setSelectionUiaMaxChars: (chars) => invoke(IPC_COMMANDS.SET_SELECTION_UIA_MAX_CHARS, {chars}),
+
+    /**
+     * 开启或关闭本地 HTTP 接口（仅监听 127.0.0.1，需携带 Bearer 令牌）
+     * @param {boolean} enabled
+     * @param {number} [port] 端口，0 表示随机分配；不传时沿用已保存的端口
+     * @returns {Promise<{enabled: boolean, running: boolean, port: number | null, token: string}>}
+     */
+    setHttpApiEnabled: (enabled, port) => invoke(IPC_COMMANDS.SET_HTTP_API_ENABLED, {enabled, port}),
+
+    /**
+     * 重新生成本地 HTTP 接口访问令牌，旧令牌立即失效
+     * @returns {Promise<{enabled: boolean, running: boolean, port: number | null, token: string}>}
+     */
+    regenerateHttpApiToken: () => invoke(IPC_COMMANDS.REGENERATE_HTTP_API_TOKEN),
+
+    /**
+     * 获取本地 HTTP 接口状态
+     * @returns {Promise<{enabled: boolean, running: boolean, port: number | null, token: string}>}
+     */
+    getHttpApiStatus: () => invoke(IPC_COMMANDS.GET_HTTP_API_STATUS),
+
+    /**
+     * 开启或关闭 AI 请求记录（ai_transcript.log，密钥会被掩码）
+     * @param {boolean} enabled
+     * @returns {Promise<void>}
+     */
+    setLogAiTranscripts: (enabled) => invoke(IPC_COMMANDS.SET_LOG_AI_TRANSCRIPTS, {enabled}),
+
+    /**
+     * 设置全局允许同时进行的AI请求数（1-10）
+     * @param {number} limit
+     * @returns {Promise<void>}
+     */
+    setMaxConcurrentAiRequests: (limit) => invoke(IPC_COMMANDS.SET_MAX_CONCURRENT_AI_REQUESTS, {limit}),
+
+    /**
+     * 获取当前正在进行的AI请求数
+     * @returns {Promise<number>}
+     */
+    getActiveAiRequestCount: () => invoke(IPC_COMMANDS.GET_ACTIVE_AI_REQUEST_COUNT),
+
+    /**
+     * 保存两段式组合快捷键并重新注册
+     * @param {Array<{first: string, second: string, action: string}>} chords
+     * @returns {Promise<void>}
+     */
+    setChordShortcuts: (chords) => invoke(IPC_COMMANDS.SET_CHORD_SHORTCUTS, {chords}),
+
+    /**
+     * 设置去重时判断文本完整性使用的标点集合
+     * @param {{sentence_endings: string, truncation_indicators: string, counted_punctuation: string}} punctuation
+     * @returns {Promise<void>}
+     */
+    setSentencePunctuation: (punctuation) =>
+        invoke(IPC_COMMANDS.SET_SENTENCE_PUNCTUATION, {punctuation}),
+
+    /**
+     * 设置剪贴板窗口所在显示器的选择策略
+     * @param {'primary_monitor' | 'active_monitor' | 'mouse_monitor'} strategy
+     * @returns {Promise<void>}
+     */
+    setWindowPositionStrategy: (strategy) =>
+        invoke(IPC_COMMANDS.SET_WINDOW_POSITION_STRATEGY, {strategy}),
+
+    /**
+     * 设置文本记录回填到目标应用的方式
+     * @param {'clipboard_paste' | 'type_text' | 'copy_only'} method
+     * @returns {Promise<void>}
+     */
+    setPasteMethod: (method) => invoke(IPC_COMMANDS.SET_PASTE_METHOD, {method}),
+
+    /**
+     * 保存按目标窗口标题强制使用的回填方式规则，按顺序匹配第一条
+     * @param {Array<{window_title: string, method: 'clipboard_paste' | 'type_text' | 'copy_only'}>} rules
+     * @returns {Promise<void>}
+     */
+    setPasteMethodRules: (rules) => invoke(IPC_COMMANDS.SET_PASTE_METHOD_RULES, {rules}),
+
+    /**
+     * 保存划词工具栏的快捷翻译语言组合，get_ai_settings 的 translation_presets 字段返回当前值
+     * @param {Array<{name: string, source_language: string, target_language: string}>} presets
+     * @returns {Promise<void>}
+     */
+    setTranslationPresets: (presets) => invoke(IPC_COMMANDS.SET_TRANSLATION_PRESETS, {presets}),
+
+    /**
+     * 开关AI试运行：开启后翻译与解释只在结果窗口展示提示词和请求体，不调用接口
+     * @param {boolean} enabled
+     * @returns {Promise<void>}
+     */
+    setAiDryRun: (enabled) => invoke(IPC_COMMANDS.SET_AI_DRY_RUN, {enabled}),
+
+    /**
+     * 估算文本作为输入时的 token 数与费用；needs_confirmation 为 true 时应先请用户确认再发送
+     * @param {string} text
+     * @returns {Promise<{tokens: number, estimated_cost: number | null, needs_confirmation: boolean}>}
+     */
+    estimateInputTokens: (text) => invoke(IPC_COMMANDS.ESTIMATE_INPUT_TOKENS, {text}),
+
+    /**
+     * 设置当前提供商每百万输入 token 的价格，传 null 表示不估算费用
+     * @param {number | null} price
+     * @returns {Promise<void>}
+     */
+    setProviderTokenPrice: (price) => invoke(IPC_COMMANDS.SET_PROVIDER_TOKEN_PRICE, {price}),
+
+    /**
+     * 设置需要确认的输入 token 数，0 表示不确认
+     * @param {number} tokens
+     * @returns {Promise<void>}
+     */
+    setLargeInputConfirmTokens: (tokens) => invoke(IPC_COMMANDS.SET_LARGE_INPUT_CONFIRM_TOKENS, {tokens}),
+
+    /**
+     * 保存自定义提示词库，名称不能重复
+     * @param {Array<{name: string, template: string}>} prompts
+     * @returns {Promise<void>}
+     */
+    setCustomPrompts: (prompts) => invoke(IPC_COMMANDS.SET_CUSTOM_PROMPTS, {prompts}),
+
+    /**
+     * 将自定义提示词库导出为 JSON 文件
+     * @param {string} path
+     * @returns {Promise<number>} 导出条数
+     */
+    exportCustomPrompts: (path) => invoke(IPC_COMMANDS.EXPORT_CUSTOM_PROMPTS, {path}),
+
+    /**
+     * 从 JSON 文件导入自定义提示词；merge 时同名但内容不同的条目保留现有版本，
+     * 并为每条发送 prompt-import-conflict 事件（负载为导入文件中的 {name, template}）
+     * @param {string} path
+     * @param {boolean} merge
+     * @returns {Promise<number>} 导入条数
+     */
+    importCustomPrompts: (path, merge) => invoke(IPC_COMMANDS.IMPORT_CUSTOM_PROMPTS, {path, merge}),
+
+    /**
+     * 设置键入回填时每个字符之间的间隔（0-200 毫秒，0 表示一次性键入）
+     * @param {number} delayMs
+     * @returns {Promise<void>}
+     */
+    setTypeTextKeyDelay: (delayMs) => invoke(IPC_COMMANDS.SET_TYPE_TEXT_KEY_DELAY, {delayMs}),
+
+    /**
+     * 设置文本记录回填完成后执行的操作；copy_next_item 会发送 clipboard-selection-advanced 事件
+     * @param {'none' | 'move_to_end' | 'increment_access_count' | 'copy_next_item'} action
+     * @returns {Promise<void>}
+     */
+    setPostPasteAction: (action) => invoke(IPC_COMMANDS.SET_POST_PASTE_ACTION, {action}),
+
+    /**
+     * 设置回填粘贴前启用的文本转换，只影响粘贴内容，历史记录保持不变
+     * @param {Array<'trim' | 'collapse_newlines' | 'strip_url_tracking'>} transforms
+     * @returns {Promise<void>}
+     */
+    setPasteTransforms: (transforms) => invoke(IPC_COMMANDS.SET_PASTE_TRANSFORMS, {transforms}),
+
+    /**
+     * 开启或关闭免打扰（暂停剪贴板记录、划词检测与划词工具栏），状态变化时发送 do-not-disturb-changed 事件
+     * @param {boolean} enabled
+     * @returns {Promise<void>}
+     */
+    setDoNotDisturb: (enabled) => invoke(IPC_COMMANDS.SET_DO_NOT_DISTURB, {enabled}),
+
+    /**
+     * 开启或关闭划词（关闭时隐藏划词工具栏），状态变化时发送 text-selection-changed 事件
+     * @param {boolean} enabled
+     * @returns {Promise<void>}
+     */
+    setTextSelectionEnabled: (enabled) => invoke(IPC_COMMANDS.SET_TEXT_SELECTION_ENABLED, {enabled}),
+
+    /**
+     * 按当前设置检查文本是否会被划词过滤
+     * @param {string} text
+     * @returns {Promise<{valid: boolean, reason: 'empty' | 'suppressed_type' | 'error' | null, content_type: string | null}>}
+     */
+    checkSelectionValid: (text) => invoke(IPC_COMMANDS.CHECK_SELECTION_VALID, {text}),
+
+    /**
+     * 设置前台为全屏应用时是否自动进入免打扰
+     * @param {boolean} enabled
+     * @returns {Promise<void>}
+     */
+    setAutoDndFullscreen: (enabled) => invoke(IPC_COMMANDS.SET_AUTO_DND_FULLSCREEN, {enabled}),
+
+    /**
+     * 设置切换免打扰的快捷键，空字符串表示不启用
+     * @param {string} hotKey
+     * @returns {Promise<void>}
+     */
+    setDndHotKey: (hotKey) => invoke(IPC_COMMANDS.SET_DND_HOT_KEY, {hotKey}),
+
+    /**
+     * 设置打开全局搜索窗口的快捷键，空字符串表示不启用
+     * @param {string} hotKey
+     * @returns {Promise<void>}
+     */
+    setGlobalSearchHotKey: (hotKey) => invoke(IPC_COMMANDS.SET_GLOBAL_SEARCH_HOT_KEY, {hotKey}),
+
+    /**
+     * 将内容类型加入划词工具栏屏蔽列表
+     * @param {'plain_text'|'code'|'url'|'email'|'phone_number'|'json'|'csv'|'numeric'|'cjk_text'|'unknown'} contentType
+     * @returns {Promise<string[]>} 更新后的屏蔽列表
+     */
+    addSuppressedContentType: (contentType) => invoke(IPC_COMMANDS.ADD_SUPPRESSED_CONTENT_TYPE, {contentType}),
+
+    /**
+     * 将内容类型移出划词工具栏屏蔽列表
+     * @param {string} contentType
+     * @returns {Promise<string[]>} 更新后的屏蔽列表
+     */
+    removeSuppressedContentType: (contentType) => invoke(IPC_COMMANDS.REMOVE_SUPPRESSED_CONTENT_TYPE, {contentType}),
+
+    /**
+     * 设置离线翻译接口地址（LibreTranslate 兼容），空字符串表示关闭
+     * @param {string} url
+     * @returns {Promise<void>}
+     */
+    setFallbackTranslateUrl: (url) => invoke(IPC_COMMANDS.SET_FALLBACK_TRANSLATE_URL, {url}),
+
+    /**
+     * 保存更新通道与后台自动检查设置
+     * @param {'stable' | 'beta'} updateChannel
+     * @param {boolean} autoCheckUpdates
+     * @returns {Promise<void>}
+     */
+    saveUpdateSettings: (updateChannel, autoCheckUpdates) =>
+        invoke(IPC_COMMANDS.SAVE_UPDATE_SETTINGS, {updateChannel, autoCheckUpdates}),
     getPollMetricsHistory: (limit = 120) =>
         invoke(IPC_COMMANDS.GET_POLL_METRICS_HISTORY, {limit}),
     getPollMetricsMinuteAggregates: (limitMinutes = 60) =>
@@ -315,14 +958,65 @@ export const AIService = {
             request: buildStreamTranslateRequest(text, sourceLanguage, targetLanguage, opId, sceneHint)
         }),
 
+    /**
+     * 同时翻译为多种目标语言，每种语言在独立的结果窗口中显示
+     * @param {string} text
+     * @param {string} sourceLanguage
+     * @param {string[]} targetLanguages
+     * @returns {Promise<void>} 任务启动后即返回
+     */
+    streamTranslateMultiTarget: (text, sourceLanguage, targetLanguages) =>
+        invoke(IPC_COMMANDS.STREAM_TRANSLATE_MULTI_TARGET, {text, sourceLanguage, targetLanguages}),
+
     /**
      * 流式解释文本
      * @param {string} text
      * @param {string} targetLanguage
+     * @param {number} [opId]
+     * @param {string} [sceneHint]
+     * @param {'brief' | 'standard' | 'detailed' | 'academic'} [depth] 解释深度，不传则沿用上次
      * @returns {Promise<void>}
      */
-    streamExplain: (text, targetLanguage, opId, sceneHint) =>
+    streamExplain: (text, targetLanguage, opId, sceneHint, depth) =>
         invoke(IPC_COMMANDS.STREAM_EXPLAIN_TEXT, {
-            request: buildStreamExplainRequest(text, targetLanguage, opId, sceneHint)
+            request: buildStreamExplainRequest(text, targetLanguage, opId, sceneHint, depth)
         }),
+
+    /**
+     * 翻译当前剪贴板中的文本，结果显示在翻译结果窗口
+     * @param {string} [source] 源语言，默认自动识别
+     * @param {string} [target] 目标语言，默认简体中文
+     * @returns {Promise<void>}
+     */
+    translateClipboard: (source, target) => invoke(IPC_COMMANDS.TRANSLATE_CLIPBOARD, {source, target}),
+
+    /**
+     * 获取本次运行中最近使用的解释深度
+     * @returns {Promise<string>}
+     */
+    getLastExplainDepth: () => invoke(IPC_COMMANDS.GET_LAST_EXPLAIN_DEPTH),
+
+    /**
+     * 使用上次的参数重试请求
+     * @param {string} windowType translation | explanation
+     * @returns {Promise<void>}
+     */
+    retryLastRequest: (windowType) => invoke(IPC_COMMANDS.RETRY_LAST_REQUEST, {windowType}),
+
+    /**
+     * 打开划词对话窗口
+     * @param {string} initialText
+     * @returns {Promise<void>}
+     */
+    openSelectionChat: (initialText) =>
+        invoke(IPC_COMMANDS.OPEN_SELECTION_CHAT, {initialText}),
+
+    /**
+     * 发送对话消息，回复通过 chat-stream 事件返回
+     * @param {string} sessionId
+     * @param {string} userMessage
+     * @returns {Promise<void>}
+     */
+    sendChatMessage: (sessionId, userMessage) =>
+        invoke(IPC_COMMANDS.SEND_CHAT_MESSAGE, {sessionId, userMessage}),
 };